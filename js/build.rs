@@ -0,0 +1,35 @@
+//! Captures the startup snapshot that `js` restores its VMs from, so the
+//! builtins are not set up again on every start.
+
+/// The globals each builtin feature adds, with the `typeof` they have when
+/// the feature is on.
+const FEATURE_GLOBALS: [(&str, bool, &str, &str); 4] = [
+    ("intl", cfg!(feature = "intl"), "Intl", "object"),
+    ("temporal", cfg!(feature = "temporal"), "Temporal", "object"),
+    ("shared-memory", cfg!(feature = "shared-memory"), "SharedArrayBuffer", "function"),
+    ("typed-arrays", cfg!(feature = "typed-arrays"), "DataView", "function"),
+];
+
+fn main() {
+    // The snapshot only loads in a build with the same builtin features, and
+    // the build-dependency is compiled separately from the dependency `js`
+    // links, so check that `js`'s feature list reached both.
+    for (feature, enabled, global, type_of) in FEATURE_GLOBALS {
+        let found =
+            javascript::evaluate_script(format!("typeof {global}"), false, None::<&std::path::Path>).expect("evaluate a typeof probe");
+        let expected = if enabled {
+            format!("\"{type_of}\"")
+        } else {
+            "\"undefined\"".to_string()
+        };
+        assert_eq!(
+            found, expected,
+            "the `javascript` build-dependency was built with different features than `js` (`{feature}`)"
+        );
+    }
+
+    let out_dir = std::path::PathBuf::from(std::env::var_os("OUT_DIR").expect("OUT_DIR is set by cargo"));
+    let snapshot = javascript::Snapshot::new();
+    std::fs::write(out_dir.join("startup.snapshot"), snapshot.to_bytes()).expect("write the startup snapshot");
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -11,13 +11,124 @@ struct Cli {
     /// JavaScript file to execute
     file: Option<std::path::PathBuf>,
 
-    /// Milliseconds threshold for short timers which `evaluate_script` will wait for
+    /// Longest timer delay, in milliseconds, waited for with `-e` and in the REPL
     #[arg(long, default_value_t = 20)]
     timer_wait_ms: u64,
 
     /// Execute as an ES module (enables import/export handling)
     #[arg(long, default_value_t = false)]
     module: bool,
+
+    /// Disable ANSI colors in REPL results and console output
+    #[arg(long, default_value_t = false)]
+    no_color: bool,
+
+    /// Extra package.json "exports"/"imports" condition to match (repeatable)
+    #[arg(short = 'C', long = "conditions", value_name = "NAME")]
+    conditions: Vec<String>,
+
+    /// Run reproducibly: seeded Math.random, a virtual clock and UTC as the local time zone
+    #[arg(long, default_value_t = false)]
+    deterministic: bool,
+
+    /// Seed for Math.random with `--deterministic`
+    #[arg(long, value_name = "N", default_value_t = 0, requires = "deterministic")]
+    seed: u64,
+
+    /// Start time of the virtual clock with `--deterministic` (e.g. 2024-01-01T00:00:00Z; default: the Unix epoch)
+    #[arg(long, value_name = "ISO", requires = "deterministic")]
+    now: Option<String>,
+
+    /// Compile the script to bytecode in OUT instead of running it (run OUT later as the FILE)
+    #[arg(long, value_name = "OUT")]
+    compile: Option<std::path::PathBuf>,
+
+    /// Allow reading files, only under the comma-separated PATHS if given
+    #[arg(long, value_name = "PATHS", num_args = 0..=1, require_equals = true, value_delimiter = ',')]
+    allow_read: Option<Vec<std::path::PathBuf>>,
+
+    /// Allow writing files, only under the comma-separated PATHS if given
+    #[arg(long, value_name = "PATHS", num_args = 0..=1, require_equals = true, value_delimiter = ',')]
+    allow_write: Option<Vec<std::path::PathBuf>>,
+
+    /// Allow reading environment variables, only the comma-separated NAMES if given
+    #[arg(long, value_name = "NAMES", num_args = 0..=1, require_equals = true, value_delimiter = ',')]
+    allow_env: Option<Vec<String>>,
+
+    /// Allow reading process information (os.getcwd, os.getpid, os.getppid)
+    #[arg(long, default_value_t = false)]
+    allow_sys: bool,
+
+    /// Allow creating temporary files with std.tmpfile
+    #[arg(long, default_value_t = false)]
+    allow_tmpfile: bool,
+
+    /// Allow everything the os and std namespaces can do
+    #[arg(short = 'A', long, default_value_t = false)]
+    allow_all: bool,
+}
+
+/// Colors are used when stdout is a terminal, unless disabled by `--no-color` or `NO_COLOR`.
+fn use_colors(cli: &Cli) -> bool {
+    use std::io::IsTerminal;
+    !cli.no_color && std::env::var_os("NO_COLOR").is_none() && std::io::stdout().is_terminal()
+}
+
+/// The `--deterministic` settings, if requested.
+fn deterministic_options(cli: &Cli) -> Result<Option<DeterministicOptions>, JSError> {
+    if !cli.deterministic {
+        return Ok(None);
+    }
+    let options = DeterministicOptions::new().seed(cli.seed);
+    match &cli.now {
+        Some(now) => options.start_time_iso(now).map(Some),
+        None => Ok(Some(options)),
+    }
+}
+
+/// What scripts may do through `os` and `std`: nothing beyond what the
+/// `--allow-*` flags grant, as in Deno.
+fn permissions(cli: &Cli) -> Permissions {
+    if cli.allow_all {
+        return Permissions::all();
+    }
+    let mut permissions = Permissions::none();
+    permissions = match cli.allow_read.as_deref() {
+        Some([]) => permissions.allow_read_all(),
+        Some(paths) => paths.iter().fold(permissions, Permissions::allow_read),
+        None => permissions,
+    };
+    permissions = match cli.allow_write.as_deref() {
+        Some([]) => permissions.allow_write_all(),
+        Some(paths) => paths.iter().fold(permissions, Permissions::allow_write),
+        None => permissions,
+    };
+    permissions = match cli.allow_env.as_deref() {
+        Some([]) => permissions.allow_env_all(),
+        Some(names) => names.iter().fold(permissions, Permissions::allow_env),
+        None => permissions,
+    };
+    if cli.allow_sys {
+        permissions = permissions.allow_sys();
+    }
+    if cli.allow_tmpfile {
+        permissions = permissions.allow_tmpfile();
+    }
+    permissions
+}
+
+/// The builtins as set up by the engine this binary was built with, captured
+/// by the build script so VMs are restored instead of set up on every start.
+static STARTUP_SNAPSHOT: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/startup.snapshot"));
+
+/// Whether the file at `path` holds bytecode from `--compile` rather than source.
+fn is_bytecode_file(path: &std::path::Path) -> bool {
+    use std::io::Read;
+    let mut head = Vec::new();
+    std::fs::File::open(path)
+        .and_then(|file| file.take(4).read_to_end(&mut head))
+        .is_ok()
+        && is_bytecode(&head)
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
@@ -68,17 +179,36 @@ mod prompt_helper {
 fn run_main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
     let cli = <Cli as clap::Parser>::parse();
 
-    // Apply configured short-timer threshold so evaluate_script can decide which
-    // timers to wait for before returning (default 20 ms).
-    // set_short_timer_threshold_ms(cli.timer_wait_ms);
+    let snapshot = match Snapshot::from_bytes(STARTUP_SNAPSHOT) {
+        Ok(snapshot) => Some(snapshot),
+        Err(e) => {
+            eprintln!("warning: startup snapshot rejected, setting up builtins instead: {}", e.message());
+            None
+        }
+    };
 
-    // If executing a file, mirror Node semantics by keeping the event loop alive
-    // while there are active handles (timers, intervals). This allows scripts
-    // that set intervals or long timeouts to keep the process running like Node.
-    // set_wait_for_active_handles(cli.file.is_some());
+    let deterministic = match deterministic_options(&cli) {
+        Ok(deterministic) => deterministic,
+        Err(e) => {
+            eprintln!("Invalid --now: {}", e.user_message());
+            std::process::exit(1);
+        }
+    };
 
+    let mut bytecode = None;
     let script_content = if let Some(ref script) = cli.eval {
         script.clone()
+    } else if let Some(ref file) = cli.file
+        && is_bytecode_file(file)
+    {
+        match std::fs::read(file) {
+            Ok(bytes) => bytecode = Some(bytes),
+            Err(e) => {
+                eprintln!("Error reading file {}: {e}", file.display());
+                std::process::exit(1);
+            }
+        }
+        String::new()
     } else if let Some(ref file) = cli.file {
         match read_script_file(file) {
             Ok(content) => content,
@@ -87,9 +217,18 @@ fn run_main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>>
                 std::process::exit(1);
             }
         }
+    } else if cli.compile.is_some() {
+        eprintln!("--compile needs a FILE or --eval script");
+        std::process::exit(1);
     } else {
         // No script argument -> start the interactive REPL (VM-backed)
-        return Ok(run_persistent_repl()?);
+        return Ok(run_persistent_repl(
+            use_colors(&cli),
+            deterministic,
+            snapshot.as_ref(),
+            permissions(&cli),
+            cli.timer_wait_ms,
+        )?);
     };
 
     // If we got here we have a script to execute. Prefer the safe evaluate_script
@@ -104,7 +243,54 @@ fn run_main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>>
         .unwrap_or(false);
     let run_as_module = cli.module || file_ext_is_mjs;
 
-    let result = evaluate_script(&script_content, run_as_module, script_path.as_ref());
+    if let Some(ref out) = cli.compile {
+        if bytecode.is_some() {
+            eprintln!("{} is already compiled", cli.file.as_ref().unwrap().display());
+            std::process::exit(1);
+        }
+        if run_as_module {
+            eprintln!("--compile supports classic scripts only, not ES modules");
+            std::process::exit(1);
+        }
+        let bytes = match compile_to_bytes(&script_content) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                eprintln!("{}", e.message());
+                std::process::exit(1);
+            }
+        };
+        if let Err(e) = std::fs::write(out, bytes) {
+            eprintln!("Error writing {}: {e}", out.display());
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let mut options = EvalOptions::new()
+        .inspect_options(InspectOptions::new().colors(use_colors(&cli)))
+        .rejection_tracker(throw_unhandled_rejection)
+        .permissions(permissions(&cli))
+        .short_timer_threshold_ms(cli.timer_wait_ms)
+        // When executing a file, mirror Node by keeping the event loop alive
+        // while there are active handles (timers, intervals).
+        .wait_for_active_handles(cli.file.is_some());
+    if let Some(deterministic) = deterministic {
+        options = options.deterministic(deterministic);
+    }
+    if let Some(snapshot) = snapshot {
+        options = options.snapshot(snapshot);
+    }
+    if !cli.conditions.is_empty() {
+        let loader = cli
+            .conditions
+            .iter()
+            .fold(FsModuleLoader::new(), |loader, name| loader.condition(name));
+        options = options.module_loader(loader);
+    }
+    let result = match &bytecode {
+        Some(bytes) => evaluate_bytecode(bytes, script_path.as_ref(), &options),
+        None => evaluate_script_with_options(&script_content, run_as_module, script_path.as_ref(), &options),
+    };
 
     match result {
         Ok(result) => {
@@ -148,9 +334,24 @@ fn run_main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>>
     Ok(())
 }
 
+// Like Node's default `--unhandled-rejections=throw`: a rejection nobody handled
+// fails the run, so it is reported with its stack and the process exits non-zero.
+fn throw_unhandled_rejection(rejection: PromiseRejection) -> Result<(), JSError> {
+    match rejection.event() {
+        RejectionEvent::Unhandled => Err(rejection.into_error()),
+        RejectionEvent::Handled => Ok(()),
+    }
+}
+
 // Persistent rustyline-powered REPL loop extracted into a helper to keep `main()` small.
 #[allow(clippy::println_empty_string)]
-fn run_persistent_repl() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+fn run_persistent_repl(
+    colors: bool,
+    deterministic: Option<DeterministicOptions>,
+    snapshot: Option<&Snapshot>,
+    permissions: Permissions,
+    timer_wait_ms: u64,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
     let ver = clap::crate_version!();
     println!("JavaScript Interpreter REPL (VM mode) v{ver}. Type '.exit' or Ctrl-D to quit.");
 
@@ -171,7 +372,17 @@ fn run_persistent_repl() -> Result<(), Box<dyn std::error::Error + Send + Sync +
         rl.load_history(p)?;
     }
 
-    let mut repl = Repl::new();
+    let mut repl = match snapshot {
+        Some(snapshot) => Repl::from_snapshot(snapshot),
+        None => Repl::new(),
+    };
+    repl.set_inspect_options(InspectOptions::new().colors(colors));
+    repl.set_rejection_tracker(throw_unhandled_rejection);
+    repl.set_permissions(permissions);
+    repl.set_short_timer_threshold_ms(timer_wait_ms);
+    if let Some(deterministic) = deterministic {
+        repl.set_deterministic(deterministic);
+    }
 
     let mut buffer = String::new();
 
@@ -206,7 +417,7 @@ fn run_persistent_repl() -> Result<(), Box<dyn std::error::Error + Send + Sync +
 
                 rl.add_history_entry(buffer.clone())?;
 
-                match repl.eval(&buffer) {
+                match repl.eval_inspect(&buffer) {
                     Ok(val) => println!("{val}"),
                     Err(e) => {
                         eprintln!("{}", e.user_message());
@@ -0,0 +1,72 @@
+use crate::JSError;
+use crate::core::JsOwnedValue;
+use crossbeam_channel::Sender;
+use std::rc::Rc;
+
+/// Starts an async host operation; settle the promise through the resolver.
+pub(crate) type AsyncHostCallback = Rc<dyn Fn(&[JsOwnedValue], PromiseResolver) -> Result<(), JSError>>;
+
+/// The outcome of an async host operation, sent back to the VM that started it.
+pub(crate) enum Settlement {
+    Fulfilled(JsOwnedValue),
+    Rejected(JSError),
+}
+
+/// Settlements tagged with the id of the promise they belong to.
+pub(crate) type SettlementSender = Sender<(u64, Settlement)>;
+
+/// Settles the promise returned by an async host function; see
+/// [`Repl::register_async_function`](crate::Repl::register_async_function).
+///
+/// The resolver is `Send`, so it can be moved to a worker thread or into a
+/// channel and settled whenever the operation completes. The settlement is
+/// queued and applied by the VM's event loop, which keeps the evaluation
+/// alive while any resolver is outstanding. Dropping a resolver without
+/// settling it rejects the promise with an `Error`.
+pub struct PromiseResolver {
+    id: u64,
+    sender: Option<SettlementSender>,
+}
+
+impl PromiseResolver {
+    pub(crate) fn new(id: u64, sender: SettlementSender) -> Self {
+        PromiseResolver { id, sender: Some(sender) }
+    }
+
+    /// Fulfill the promise with `value`. A [`JsOwnedValue::Handle`] to a
+    /// promise or thenable is adopted, as with `resolve()` in scripts.
+    pub fn resolve(mut self, value: impl Into<JsOwnedValue>) {
+        self.send(Settlement::Fulfilled(value.into()));
+    }
+
+    /// Reject the promise with `error`, thrown into scripts as the matching JS error.
+    pub fn reject(mut self, error: JSError) {
+        self.send(Settlement::Rejected(error));
+    }
+
+    fn send(&mut self, settlement: Settlement) {
+        if let Some(sender) = self.sender.take() {
+            // The VM may already be gone, in which case nobody is waiting.
+            let _ = sender.send((self.id, settlement));
+        }
+    }
+}
+
+impl Drop for PromiseResolver {
+    fn drop(&mut self) {
+        if self.sender.is_some() {
+            self.send(Settlement::Rejected(crate::raise_runtime_error!(
+                "PromiseResolver was dropped without settling its promise"
+            )));
+        }
+    }
+}
+
+impl std::fmt::Debug for PromiseResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PromiseResolver")
+            .field("id", &self.id)
+            .field("settled", &self.sender.is_none())
+            .finish()
+    }
+}
@@ -0,0 +1,631 @@
+//! Binary form of a compiled script, so it can be written to disk and run
+//! again without being tokenized, parsed and compiled on every start.
+//!
+//! A file is the magic `JSBC`, the format version (u32), the version of the
+//! engine that wrote it, a flags byte, every [`Chunk`] field in declaration
+//! order and an FNV-1a checksum of all the bytes before it. Integers are
+//! LEB128 and numbers their little-endian bits; hash maps and sets are written
+//! sorted so a script always encodes to the same bytes.
+//!
+//! Loading rejects files from another format or engine version, files whose
+//! checksum does not match and chunks whose instructions do not decode or
+//! refer outside the code and constant pool.
+
+use crate::JSError;
+use crate::core::{Chunk, Opcode, Value};
+use num_bigint::BigInt;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+const MAGIC: &[u8; 4] = b"JSBC";
+const FORMAT_VERSION: u32 = 1;
+const ENGINE_VERSION: &str = env!("CARGO_PKG_VERSION");
+pub(crate) const CHECKSUM_LEN: usize = 8;
+
+/// Flag bit: the script's directive prologue has `"use strict"`.
+const FLAG_STRICT: u8 = 1;
+
+const CONST_UNDEFINED: u8 = 0;
+const CONST_NULL: u8 = 1;
+const CONST_UNINITIALIZED: u8 = 2;
+const CONST_BOOLEAN: u8 = 3;
+const CONST_NUMBER: u8 = 4;
+const CONST_STRING: u8 = 5;
+const CONST_BIGINT: u8 = 6;
+const CONST_FUNCTION: u8 = 7;
+const CONST_NATIVE_FUNCTION: u8 = 8;
+
+/// Whether `bytes` starts like a bytecode file rather than source text.
+pub fn is_bytecode(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// A compiled classic script: its chunk and whether it runs in strict mode.
+pub(crate) struct CompiledScript<'gc> {
+    pub chunk: Chunk<'gc>,
+    pub strict: bool,
+}
+
+impl<'gc> CompiledScript<'gc> {
+    pub(crate) fn to_bytes(&self) -> Result<Vec<u8>, JSError> {
+        let mut w = Writer::default();
+        w.out.extend_from_slice(MAGIC);
+        w.out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        ENGINE_VERSION.to_string().encode(&mut w);
+        w.byte(if self.strict { FLAG_STRICT } else { 0 });
+
+        let Chunk {
+            code,
+            constants,
+            fn_names,
+            fn_lengths,
+            class_constructor_ips,
+            derived_constructor_ips,
+            fn_strictness,
+            fn_simple_parameter_list,
+            async_function_ips,
+            arrow_function_ips,
+            fn_local_names,
+            fn_const_local_names,
+            call_callee_names,
+            generator_function_ips,
+            method_function_ips,
+            line_map,
+            fn_private_name_context,
+            fn_eval_context,
+            fn_brand_upvalue,
+            fn_upvalue_names,
+            fn_source_paths,
+            fn_source_texts,
+            declared_globals,
+            lexical_declared_globals,
+            fn_declared_globals,
+            block_alias_to_original,
+            is_eval_code,
+            named_fn_self_ips,
+            const_import_bindings,
+            self_namespace_imports,
+            self_deferred_namespace_imports,
+            self_import_aliases,
+            loaded_module_vars,
+            live_import_bindings,
+            top_level_locals_at_ip,
+        } = &self.chunk;
+
+        w.bytes(code);
+        w.usize(constants.len());
+        for constant in constants {
+            w.constant(constant)?;
+        }
+        fn_names.encode(&mut w);
+        fn_lengths.encode(&mut w);
+        class_constructor_ips.encode(&mut w);
+        derived_constructor_ips.encode(&mut w);
+        fn_strictness.encode(&mut w);
+        fn_simple_parameter_list.encode(&mut w);
+        async_function_ips.encode(&mut w);
+        arrow_function_ips.encode(&mut w);
+        fn_local_names.encode(&mut w);
+        fn_const_local_names.encode(&mut w);
+        call_callee_names.encode(&mut w);
+        generator_function_ips.encode(&mut w);
+        method_function_ips.encode(&mut w);
+        line_map.encode(&mut w);
+        fn_private_name_context.encode(&mut w);
+        fn_eval_context.encode(&mut w);
+        fn_brand_upvalue.encode(&mut w);
+        fn_upvalue_names.encode(&mut w);
+        fn_source_paths.encode(&mut w);
+        fn_source_texts.encode(&mut w);
+        declared_globals.encode(&mut w);
+        lexical_declared_globals.encode(&mut w);
+        fn_declared_globals.encode(&mut w);
+        block_alias_to_original.encode(&mut w);
+        is_eval_code.encode(&mut w);
+        named_fn_self_ips.encode(&mut w);
+        const_import_bindings.encode(&mut w);
+        self_namespace_imports.encode(&mut w);
+        self_deferred_namespace_imports.encode(&mut w);
+        self_import_aliases.encode(&mut w);
+        loaded_module_vars.encode(&mut w);
+        live_import_bindings.encode(&mut w);
+        top_level_locals_at_ip.encode(&mut w);
+
+        let checksum = fnv1a(&w.out);
+        w.out.extend_from_slice(&checksum.to_le_bytes());
+        Ok(w.out)
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self, JSError> {
+        if !is_bytecode(bytes) {
+            return Err(invalid("not a bytecode file"));
+        }
+        let mut r = Reader { bytes, pos: MAGIC.len() };
+        let format = u32::from_le_bytes(r.take(4)?.try_into().unwrap());
+        if format != FORMAT_VERSION {
+            return Err(invalid(format!(
+                "format version {format} is not supported (expected {FORMAT_VERSION})"
+            )));
+        }
+        let engine = String::decode(&mut r)?;
+        if engine != ENGINE_VERSION {
+            return Err(invalid(format!(
+                "compiled by engine version {engine}, but this is {ENGINE_VERSION}; recompile the script"
+            )));
+        }
+        let Some(body_len) = bytes.len().checked_sub(CHECKSUM_LEN).filter(|&len| len >= r.pos) else {
+            return Err(invalid("file is truncated"));
+        };
+        let (body, checksum) = bytes.split_at(body_len);
+        if fnv1a(body).to_le_bytes() != checksum {
+            return Err(invalid("checksum mismatch; the file is corrupt"));
+        }
+        r.bytes = body;
+        let strict = r.byte()? & FLAG_STRICT != 0;
+
+        let code = r.bytes()?.to_vec();
+        let constant_count = r.usize()?;
+        let mut constants = Vec::with_capacity(constant_count.min(r.remaining()));
+        for _ in 0..constant_count {
+            constants.push(r.constant()?);
+        }
+        let chunk = Chunk {
+            code,
+            constants,
+            fn_names: Decode::decode(&mut r)?,
+            fn_lengths: Decode::decode(&mut r)?,
+            class_constructor_ips: Decode::decode(&mut r)?,
+            derived_constructor_ips: Decode::decode(&mut r)?,
+            fn_strictness: Decode::decode(&mut r)?,
+            fn_simple_parameter_list: Decode::decode(&mut r)?,
+            async_function_ips: Decode::decode(&mut r)?,
+            arrow_function_ips: Decode::decode(&mut r)?,
+            fn_local_names: Decode::decode(&mut r)?,
+            fn_const_local_names: Decode::decode(&mut r)?,
+            call_callee_names: Decode::decode(&mut r)?,
+            generator_function_ips: Decode::decode(&mut r)?,
+            method_function_ips: Decode::decode(&mut r)?,
+            line_map: Decode::decode(&mut r)?,
+            fn_private_name_context: Decode::decode(&mut r)?,
+            fn_eval_context: Decode::decode(&mut r)?,
+            fn_brand_upvalue: Decode::decode(&mut r)?,
+            fn_upvalue_names: Decode::decode(&mut r)?,
+            fn_source_paths: Decode::decode(&mut r)?,
+            fn_source_texts: Decode::decode(&mut r)?,
+            declared_globals: Decode::decode(&mut r)?,
+            lexical_declared_globals: Decode::decode(&mut r)?,
+            fn_declared_globals: Decode::decode(&mut r)?,
+            block_alias_to_original: Decode::decode(&mut r)?,
+            is_eval_code: Decode::decode(&mut r)?,
+            named_fn_self_ips: Decode::decode(&mut r)?,
+            const_import_bindings: Decode::decode(&mut r)?,
+            self_namespace_imports: Decode::decode(&mut r)?,
+            self_deferred_namespace_imports: Decode::decode(&mut r)?,
+            self_import_aliases: Decode::decode(&mut r)?,
+            loaded_module_vars: Decode::decode(&mut r)?,
+            live_import_bindings: Decode::decode(&mut r)?,
+            top_level_locals_at_ip: Decode::decode(&mut r)?,
+        };
+        if r.remaining() != 0 {
+            return Err(invalid("unexpected data after the chunk"));
+        }
+        validate(&chunk)?;
+        Ok(CompiledScript { chunk, strict })
+    }
+}
+
+/// Check that every instruction decodes, constant indices are in the pool,
+/// and jumps and function entry points land on instructions, so the VM never
+/// reads past the code or the constants.
+fn validate(chunk: &Chunk<'_>) -> Result<(), JSError> {
+    let code = &chunk.code;
+    let mut starts = vec![false; code.len()];
+    let mut jumps = Vec::new();
+    let mut ip = 0;
+    let mut last = None;
+    while ip < code.len() {
+        let operands = Chunk::decode_operands(code, ip).ok_or_else(|| invalid(format!("bad instruction at offset {ip}")))?;
+        if let Some(pos) = operands.constant_at {
+            let index = u16::from_le_bytes([code[pos], code[pos + 1]]);
+            // SetupTry uses 0xffff for "no catch binding"
+            let sentinel = code[ip] == Opcode::SetupTry as u8 && index == 0xffff;
+            if !sentinel && index as usize >= chunk.constants.len() {
+                return Err(invalid(format!("constant index {index} out of range at offset {ip}")));
+            }
+        }
+        if let Some(pos) = operands.jump_at {
+            jumps.push((ip, u32::from_le_bytes(code[pos..pos + 4].try_into().unwrap()) as usize));
+        }
+        starts[ip] = true;
+        last = Some(ip);
+        ip = operands.next;
+    }
+    if last.map(|ip| code[ip]) != Some(Opcode::Return as u8) {
+        return Err(invalid("code does not end with a return"));
+    }
+    let is_start = |target: usize| starts.get(target).copied().unwrap_or(false);
+    if let Some((ip, target)) = jumps.into_iter().find(|&(_, target)| !is_start(target)) {
+        return Err(invalid(format!("jump at offset {ip} to invalid target {target}")));
+    }
+    for constant in &chunk.constants {
+        if let Value::Function(entry, _) = constant
+            && !is_start(*entry)
+        {
+            return Err(invalid(format!("function entry {entry} is not an instruction")));
+        }
+    }
+    Ok(())
+}
+
+fn invalid(reason: impl std::fmt::Display) -> JSError {
+    crate::raise_eval_error!(format!("Invalid bytecode: {reason}"))
+}
+
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[derive(Default)]
+pub(crate) struct Writer {
+    pub(crate) out: Vec<u8>,
+}
+
+impl Writer {
+    pub(crate) fn byte(&mut self, byte: u8) {
+        self.out.push(byte);
+    }
+
+    fn uint(&mut self, mut value: u64) {
+        loop {
+            let low = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                self.out.push(low);
+                return;
+            }
+            self.out.push(low | 0x80);
+        }
+    }
+
+    pub(crate) fn usize(&mut self, value: usize) {
+        self.uint(value as u64);
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.usize(bytes.len());
+        self.out.extend_from_slice(bytes);
+    }
+
+    fn constant(&mut self, value: &Value<'_>) -> Result<(), JSError> {
+        match value {
+            Value::Undefined => self.byte(CONST_UNDEFINED),
+            Value::Null => self.byte(CONST_NULL),
+            Value::Uninitialized => self.byte(CONST_UNINITIALIZED),
+            Value::Boolean(b) => {
+                self.byte(CONST_BOOLEAN);
+                b.encode(self);
+            }
+            Value::Number(n) => {
+                self.byte(CONST_NUMBER);
+                self.out.extend_from_slice(&n.to_bits().to_le_bytes());
+            }
+            Value::String(units) => {
+                self.byte(CONST_STRING);
+                self.usize(units.len());
+                for unit in units {
+                    self.out.extend_from_slice(&unit.to_le_bytes());
+                }
+            }
+            Value::BigInt(n) => {
+                self.byte(CONST_BIGINT);
+                self.bytes(&n.to_signed_bytes_le());
+            }
+            Value::Function(ip, arity) => {
+                self.byte(CONST_FUNCTION);
+                self.usize(*ip);
+                self.byte(*arity);
+            }
+            Value::NativeFunction(id) => {
+                self.byte(CONST_NATIVE_FUNCTION);
+                self.usize(*id);
+            }
+            _ => {
+                return Err(crate::raise_eval_error!(
+                    "Cannot serialize a constant that is not a primitive or function template"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+pub(crate) struct Reader<'a> {
+    pub(crate) bytes: &'a [u8],
+    pub(crate) pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn remaining(&self) -> usize {
+        self.bytes.len() - self.pos
+    }
+
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8], JSError> {
+        if len > self.remaining() {
+            return Err(invalid("file is truncated"));
+        }
+        let slice = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(slice)
+    }
+
+    pub(crate) fn byte(&mut self) -> Result<u8, JSError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn uint(&mut self) -> Result<u64, JSError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            let bits = (byte & 0x7f) as u64;
+            if shift == 63 && bits > 1 {
+                break;
+            }
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid(format!("integer overflow at offset {}", self.pos)))
+    }
+
+    pub(crate) fn usize(&mut self) -> Result<usize, JSError> {
+        let value = self.uint()?;
+        usize::try_from(value).map_err(|_| invalid(format!("integer overflow at offset {}", self.pos)))
+    }
+
+    /// A length-prefixed collection is never longer than the bytes left,
+    /// since every element takes at least one.
+    fn len(&mut self) -> Result<usize, JSError> {
+        let len = self.usize()?;
+        if len > self.remaining() {
+            return Err(invalid("file is truncated"));
+        }
+        Ok(len)
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], JSError> {
+        let len = self.len()?;
+        self.take(len)
+    }
+
+    fn constant<'gc>(&mut self) -> Result<Value<'gc>, JSError> {
+        let tag = self.byte()?;
+        Ok(match tag {
+            CONST_UNDEFINED => Value::Undefined,
+            CONST_NULL => Value::Null,
+            CONST_UNINITIALIZED => Value::Uninitialized,
+            CONST_BOOLEAN => Value::Boolean(bool::decode(self)?),
+            CONST_NUMBER => Value::Number(f64::from_bits(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))),
+            CONST_STRING => {
+                let len = self.usize()?;
+                let bytes = self.take(len.checked_mul(2).ok_or_else(|| invalid("file is truncated"))?)?;
+                Value::String(bytes.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect())
+            }
+            CONST_BIGINT => Value::BigInt(Box::new(BigInt::from_signed_bytes_le(self.bytes()?))),
+            CONST_FUNCTION => Value::Function(self.usize()?, self.byte()?),
+            CONST_NATIVE_FUNCTION => Value::NativeFunction(self.usize()?),
+            _ => return Err(invalid(format!("unknown constant tag {tag}"))),
+        })
+    }
+}
+
+pub(crate) trait Encode {
+    fn encode(&self, w: &mut Writer);
+}
+
+pub(crate) trait Decode: Sized {
+    fn decode(r: &mut Reader<'_>) -> Result<Self, JSError>;
+}
+
+impl Encode for bool {
+    fn encode(&self, w: &mut Writer) {
+        w.byte(*self as u8);
+    }
+}
+
+impl Decode for bool {
+    fn decode(r: &mut Reader<'_>) -> Result<Self, JSError> {
+        match r.byte()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            other => Err(invalid(format!("bad boolean {other}"))),
+        }
+    }
+}
+
+impl Encode for u8 {
+    fn encode(&self, w: &mut Writer) {
+        w.byte(*self);
+    }
+}
+
+impl Decode for u8 {
+    fn decode(r: &mut Reader<'_>) -> Result<Self, JSError> {
+        r.byte()
+    }
+}
+
+impl Encode for usize {
+    fn encode(&self, w: &mut Writer) {
+        w.usize(*self);
+    }
+}
+
+impl Decode for usize {
+    fn decode(r: &mut Reader<'_>) -> Result<Self, JSError> {
+        r.usize()
+    }
+}
+
+impl Encode for u16 {
+    fn encode(&self, w: &mut Writer) {
+        w.out.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+impl Decode for u16 {
+    fn decode(r: &mut Reader<'_>) -> Result<Self, JSError> {
+        Ok(u16::from_le_bytes(r.take(2)?.try_into().unwrap()))
+    }
+}
+
+impl Encode for u64 {
+    fn encode(&self, w: &mut Writer) {
+        w.uint(*self);
+    }
+}
+
+impl Decode for u64 {
+    fn decode(r: &mut Reader<'_>) -> Result<Self, JSError> {
+        r.uint()
+    }
+}
+
+impl Encode for f64 {
+    fn encode(&self, w: &mut Writer) {
+        w.out.extend_from_slice(&self.to_bits().to_le_bytes());
+    }
+}
+
+impl Decode for f64 {
+    fn decode(r: &mut Reader<'_>) -> Result<Self, JSError> {
+        Ok(f64::from_bits(u64::from_le_bytes(r.take(8)?.try_into().unwrap())))
+    }
+}
+
+impl Encode for String {
+    fn encode(&self, w: &mut Writer) {
+        w.bytes(self.as_bytes());
+    }
+}
+
+impl Decode for String {
+    fn decode(r: &mut Reader<'_>) -> Result<Self, JSError> {
+        let bytes = r.bytes()?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| invalid("string is not valid UTF-8"))
+    }
+}
+
+impl<A: Encode, B: Encode> Encode for (A, B) {
+    fn encode(&self, w: &mut Writer) {
+        self.0.encode(w);
+        self.1.encode(w);
+    }
+}
+
+impl<A: Decode, B: Decode> Decode for (A, B) {
+    fn decode(r: &mut Reader<'_>) -> Result<Self, JSError> {
+        Ok((A::decode(r)?, B::decode(r)?))
+    }
+}
+
+impl<A: Encode, B: Encode, C: Encode> Encode for (A, B, C) {
+    fn encode(&self, w: &mut Writer) {
+        self.0.encode(w);
+        self.1.encode(w);
+        self.2.encode(w);
+    }
+}
+
+impl<A: Decode, B: Decode, C: Decode> Decode for (A, B, C) {
+    fn decode(r: &mut Reader<'_>) -> Result<Self, JSError> {
+        Ok((A::decode(r)?, B::decode(r)?, C::decode(r)?))
+    }
+}
+
+impl<T: Encode> Encode for Box<T> {
+    fn encode(&self, w: &mut Writer) {
+        (**self).encode(w);
+    }
+}
+
+impl<T: Decode> Decode for Box<T> {
+    fn decode(r: &mut Reader<'_>) -> Result<Self, JSError> {
+        Ok(Box::new(T::decode(r)?))
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode(&self, w: &mut Writer) {
+        match self {
+            None => w.byte(0),
+            Some(value) => {
+                w.byte(1);
+                value.encode(w);
+            }
+        }
+    }
+}
+
+impl<T: Decode> Decode for Option<T> {
+    fn decode(r: &mut Reader<'_>) -> Result<Self, JSError> {
+        Ok(if bool::decode(r)? { Some(T::decode(r)?) } else { None })
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode(&self, w: &mut Writer) {
+        w.usize(self.len());
+        for item in self {
+            item.encode(w);
+        }
+    }
+}
+
+impl<T: Decode> Decode for Vec<T> {
+    fn decode(r: &mut Reader<'_>) -> Result<Self, JSError> {
+        let len = r.len()?;
+        let mut items = Vec::with_capacity(len);
+        for _ in 0..len {
+            items.push(T::decode(r)?);
+        }
+        Ok(items)
+    }
+}
+
+impl<T: Encode + Ord> Encode for HashSet<T> {
+    fn encode(&self, w: &mut Writer) {
+        let mut items: Vec<&T> = self.iter().collect();
+        items.sort();
+        w.usize(items.len());
+        for item in items {
+            item.encode(w);
+        }
+    }
+}
+
+impl<T: Decode + Eq + Hash> Decode for HashSet<T> {
+    fn decode(r: &mut Reader<'_>) -> Result<Self, JSError> {
+        Ok(Vec::<T>::decode(r)?.into_iter().collect())
+    }
+}
+
+impl<K: Encode + Ord, V: Encode> Encode for HashMap<K, V> {
+    fn encode(&self, w: &mut Writer) {
+        let mut entries: Vec<(&K, &V)> = self.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        w.usize(entries.len());
+        for (key, value) in entries {
+            key.encode(w);
+            value.encode(w);
+        }
+    }
+}
+
+impl<K: Decode + Eq + Hash, V: Decode> Decode for HashMap<K, V> {
+    fn decode(r: &mut Reader<'_>) -> Result<Self, JSError> {
+        Ok(Vec::<(K, V)>::decode(r)?.into_iter().collect())
+    }
+}
@@ -0,0 +1,38 @@
+use crate::core::JsHandle;
+
+/// The function invoked by [`Repl::call`](crate::Repl::call).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CallTarget<'a> {
+    /// A global binding, or a dotted path through globals (`"app.handlers.onEvent"`).
+    Global(&'a str),
+    /// A function held by a [`JsHandle`].
+    Handle(&'a JsHandle),
+}
+
+impl<'a> From<&'a str> for CallTarget<'a> {
+    fn from(path: &'a str) -> Self {
+        CallTarget::Global(path)
+    }
+}
+
+impl<'a> From<&'a String> for CallTarget<'a> {
+    fn from(path: &'a String) -> Self {
+        CallTarget::Global(path)
+    }
+}
+
+impl<'a> From<&'a JsHandle> for CallTarget<'a> {
+    fn from(handle: &'a JsHandle) -> Self {
+        CallTarget::Handle(handle)
+    }
+}
+
+impl std::fmt::Display for CallTarget<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CallTarget::Global(path) => f.write_str(path),
+            CallTarget::Handle(_) => f.write_str("handle target"),
+        }
+    }
+}
@@ -91,6 +91,8 @@ pub struct Compiler<'gc> {
     /// True when compiling a function body that has using/await using declarations
     /// so that Return statements emit disposal code before returning.
     fn_body_has_disposables: bool,
+    /// Resolves import specifiers the same way the VM's module loader does.
+    module_loader: Option<std::rc::Rc<crate::core::ModuleLoaderState>>,
 }
 
 #[derive(Debug, Clone)]
@@ -159,6 +161,11 @@ impl<'gc> Compiler<'gc> {
         self.chunk.fn_source_texts.insert(func_ip, source_text);
     }
 
+    /// Resolve import specifiers through `loader` instead of as filesystem paths.
+    pub(crate) fn set_module_loader(&mut self, loader: std::rc::Rc<crate::core::ModuleLoaderState>) {
+        self.module_loader = Some(loader);
+    }
+
     /// Register exports from a loaded external module so the compiler can resolve imports.
     pub fn set_loaded_module_exports(&mut self, resolved_path: String, export_info: std::collections::HashMap<String, String>) {
         self.loaded_module_exports.insert(resolved_path, export_info);
@@ -734,7 +741,10 @@ impl<'gc> Compiler<'gc> {
     fn resolve_import_path(&self, source: &str, import_type: Option<&str>) -> Option<String> {
         if let Some(ref fname) = self.script_filename {
             let base_path = std::path::Path::new(fname);
-            let resolved_str = crate::core::resolve_module_request_key(source, base_path, import_type);
+            let resolved_str = match &self.module_loader {
+                Some(loader) => loader.request_key(source, base_path, import_type),
+                None => crate::core::resolve_module_request_key(source, base_path, import_type),
+            };
             if self.loaded_module_exports.contains_key(&resolved_str) {
                 Some(resolved_str)
             } else {
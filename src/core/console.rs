@@ -0,0 +1,85 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Severity of a `console.*` call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ConsoleLevel {
+    Log,
+    Info,
+    Debug,
+    Warn,
+    Error,
+    Trace,
+}
+
+impl ConsoleLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConsoleLevel::Log => "log",
+            ConsoleLevel::Info => "info",
+            ConsoleLevel::Debug => "debug",
+            ConsoleLevel::Warn => "warn",
+            ConsoleLevel::Error => "error",
+            ConsoleLevel::Trace => "trace",
+        }
+    }
+
+    /// Whether the default sink writes this level to stderr (as Node does).
+    pub fn is_diagnostic(&self) -> bool {
+        matches!(self, ConsoleLevel::Warn | ConsoleLevel::Error | ConsoleLevel::Trace)
+    }
+}
+
+/// Destination for `console.*` output.
+///
+/// Install one with [`Repl::set_console_sink`](crate::Repl::set_console_sink) or
+/// [`EvalOptions::console_sink`](crate::EvalOptions::console_sink). `message`
+/// is the fully formatted line, without a trailing newline.
+pub trait ConsoleSink {
+    fn write(&self, level: ConsoleLevel, message: &str);
+}
+
+/// The default sink: warnings, errors and traces go to stderr, everything else to stdout.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StdioConsoleSink;
+
+impl ConsoleSink for StdioConsoleSink {
+    fn write(&self, level: ConsoleLevel, message: &str) {
+        if level.is_diagnostic() {
+            eprintln!("{message}");
+        } else {
+            println!("{message}");
+        }
+    }
+}
+
+/// A sink that buffers every line in memory instead of printing it.
+///
+/// Clones share the same buffer, so keep one clone to read what a script
+/// printed after handing another to the VM.
+#[derive(Clone, Debug, Default)]
+pub struct CapturedConsole {
+    lines: Rc<RefCell<Vec<(ConsoleLevel, String)>>>,
+}
+
+impl CapturedConsole {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remove and return everything captured so far.
+    pub fn take(&self) -> Vec<(ConsoleLevel, String)> {
+        std::mem::take(&mut *self.lines.borrow_mut())
+    }
+
+    /// The captured messages, without their levels.
+    pub fn messages(&self) -> Vec<String> {
+        self.lines.borrow().iter().map(|(_, message)| message.clone()).collect()
+    }
+}
+
+impl ConsoleSink for CapturedConsole {
+    fn write(&self, level: ConsoleLevel, message: &str) {
+        self.lines.borrow_mut().push((level, message.to_string()));
+    }
+}
@@ -0,0 +1,59 @@
+use crate::JSError;
+
+/// Settings for a reproducible run, applied with
+/// [`Repl::set_deterministic`](crate::Repl::set_deterministic) or
+/// [`EvalOptions::deterministic`](crate::EvalOptions::deterministic).
+///
+/// In a deterministic run `Math.random` draws from a generator seeded with
+/// [`seed`](Self::seed), and `Date.now()`, `new Date()`, `Temporal.Now` and
+/// `performance.now()` read a virtual clock that starts at
+/// [`start_time`](Self::start_time) and only moves when the event loop jumps
+/// to the next timer deadline or the host advances it with
+/// [`Repl::advance_clock`](crate::Repl::advance_clock). Local time is pinned
+/// to UTC; the default locale is always `en-US`.
+///
+/// ```
+/// use javascript::{DeterministicOptions, Repl};
+///
+/// let mut repl = Repl::new();
+/// repl.set_deterministic(DeterministicOptions::new().seed(7).start_time_iso("2024-01-01T00:00:00Z").unwrap());
+/// assert_eq!(repl.eval("new Date().toISOString()").unwrap(), "\"2024-01-01T00:00:00.000Z\"");
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DeterministicOptions {
+    pub(crate) seed: u64,
+    pub(crate) start_ms: f64,
+}
+
+impl DeterministicOptions {
+    /// Seed 0, with the clock starting at the Unix epoch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Seed for `Math.random`; equal seeds give equal sequences.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Start the virtual clock at `epoch_ms` milliseconds since the Unix epoch.
+    pub fn start_time(mut self, epoch_ms: f64) -> Self {
+        self.start_ms = epoch_ms.trunc();
+        self
+    }
+
+    /// Start the virtual clock at an RFC 3339 date-time such as
+    /// `2024-01-01T00:00:00Z`, or at midnight UTC of a `YYYY-MM-DD` date.
+    pub fn start_time_iso(self, text: &str) -> Result<Self, JSError> {
+        let text = text.trim();
+        let epoch_ms = if let Ok(date_time) = chrono::DateTime::parse_from_rfc3339(text) {
+            date_time.timestamp_millis()
+        } else if let Ok(date) = chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+            date.and_time(chrono::NaiveTime::MIN).and_utc().timestamp_millis()
+        } else {
+            return Err(crate::raise_range_error!(format!("Invalid start time '{text}'")));
+        };
+        Ok(self.start_time(epoch_ms as f64))
+    }
+}
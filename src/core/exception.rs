@@ -0,0 +1,79 @@
+use crate::core::JsOwnedValue;
+
+/// What a script threw, kept on a [`JSError`](crate::JSError) when the
+/// exception reaches Rust. Read it with [`JSError::exception`](crate::JSError::exception).
+///
+/// ```
+/// use javascript::{JsOwnedValue, evaluate_script};
+///
+/// let script = "function load() { throw new TypeError('bad port', { cause: new RangeError('out of range') }); }\nload();";
+/// let err = evaluate_script(script, false, Some("config.js")).unwrap_err();
+/// let exception = err.exception().unwrap();
+/// assert_eq!(exception.name, "TypeError");
+/// assert_eq!(exception.message, "bad port");
+/// assert_eq!((exception.stack[0].function.as_str(), exception.stack[0].file.as_str()), ("load", "config.js"));
+/// assert_eq!(exception.cause.as_ref().unwrap().name, "RangeError");
+///
+/// let err = evaluate_script("throw { code: 42 }", false, None::<&str>).unwrap_err();
+/// let value = &err.exception().unwrap().value;
+/// assert_eq!(value.get("code"), Some(&JsOwnedValue::Number(42.0)));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct JsException {
+    /// The error's `name`, such as `TypeError`, or the name of its
+    /// constructor. Empty when the thrown value is not an object.
+    pub name: String,
+    /// The error's `message`, or the string form of any other thrown value.
+    pub message: String,
+    /// The frames of the error's `stack`, innermost first.
+    pub stack: Vec<JsStackFrame>,
+    /// The error's `cause`, if it has one.
+    pub cause: Option<Box<JsException>>,
+    /// The `errors` of an `AggregateError`.
+    pub errors: Vec<JsException>,
+    /// An owned copy of the thrown value. Like other [`JsOwnedValue`]s it
+    /// holds own enumerable properties, so for an `Error` it keeps custom
+    /// fields such as `code` but not `message` or `stack`.
+    pub value: JsOwnedValue,
+}
+
+/// One line of a JS stack trace, such as `at parse (config.js:3:11)`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct JsStackFrame {
+    /// The function name, `<anonymous>` for top-level code.
+    pub function: String,
+    /// The script path or module key, or the whole location text when it
+    /// has no `:line:column` suffix.
+    pub file: String,
+    /// The 1-based line, if the frame has a location.
+    pub line: Option<usize>,
+    /// The 1-based column, if the frame has a location.
+    pub column: Option<usize>,
+}
+
+impl JsStackFrame {
+    /// Parse an `at function (file:line:column)` or `at file:line:column` line.
+    pub(crate) fn parse(line: &str) -> Option<Self> {
+        let frame = line.trim().strip_prefix("at ")?;
+        let (function, location) = match frame.strip_suffix(')').and_then(|rest| rest.split_once(" (")) {
+            Some((function, location)) => (function.to_string(), location),
+            None => ("<anonymous>".to_string(), frame),
+        };
+        let mut parts = location.rsplitn(3, ':');
+        let (column, line, file) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(column), Some(line), Some(file)) => match (line.parse().ok(), column.parse().ok()) {
+                (Some(line), Some(column)) => (Some(column), Some(line), file),
+                _ => (None, None, location),
+            },
+            _ => (None, None, location),
+        };
+        Some(JsStackFrame {
+            function,
+            file: file.to_string(),
+            line,
+            column,
+        })
+    }
+}
@@ -0,0 +1,143 @@
+use crate::core::Value;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Dynamic root keeping a handle's value alive inside the arena.
+pub(crate) type HandleRoot = gc_arena::DynamicRoot<gc_arena::Rootable!['gc => Value<'gc>]>;
+
+/// Ids of handles whose last clone was dropped, waiting for the VM to unroot them.
+type ReleaseQueue = Arc<Mutex<Vec<u64>>>;
+
+/// A persistent reference to a JS object or function owned by a [`Repl`](crate::Repl).
+///
+/// Handles are created by [`Repl::eval_handle`](crate::Repl::eval_handle) and
+/// [`Repl::get_handle`](crate::Repl::get_handle) and keep their object alive
+/// across evaluations. Use them with the `Repl` that created them to read
+/// and write properties, call the object or its methods, or pass the object
+/// back to scripts as a [`JsOwnedValue::Handle`](crate::JsOwnedValue::Handle).
+///
+/// Once every clone is dropped the object is unrooted (at the start of the
+/// Repl's next operation) and can be garbage collected. Handles compare equal
+/// when one is a clone of the other.
+#[derive(Clone)]
+pub struct JsHandle {
+    inner: Arc<HandleToken>,
+    callable: bool,
+}
+
+struct HandleToken {
+    id: u64,
+    released: ReleaseQueue,
+}
+
+impl Drop for HandleToken {
+    fn drop(&mut self) {
+        if let Ok(mut released) = self.released.lock() {
+            released.push(self.id);
+        }
+    }
+}
+
+impl JsHandle {
+    /// Whether the referenced value is callable (`typeof` is `"function"`).
+    pub fn is_callable(&self) -> bool {
+        self.callable
+    }
+}
+
+impl PartialEq for JsHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl Eq for JsHandle {}
+
+impl std::fmt::Debug for JsHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JsHandle")
+            .field("id", &self.inner.id)
+            .field("callable", &self.callable)
+            .finish()
+    }
+}
+
+/// The dynamic roots behind a VM's outstanding [`JsHandle`]s, shared with helper VMs.
+#[derive(Default)]
+pub(crate) struct HandleTable {
+    roots: RefCell<HashMap<u64, HandleRoot>>,
+    next_id: Cell<u64>,
+    released: ReleaseQueue,
+}
+
+impl HandleTable {
+    pub(crate) fn insert(&self, root: HandleRoot, callable: bool) -> JsHandle {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        self.roots.borrow_mut().insert(id, root);
+        JsHandle {
+            inner: Arc::new(HandleToken {
+                id,
+                released: self.released.clone(),
+            }),
+            callable,
+        }
+    }
+
+    /// The root behind `handle`, or `None` when it was created by another VM.
+    pub(crate) fn root(&self, handle: &JsHandle) -> Option<std::cell::Ref<'_, HandleRoot>> {
+        if !Arc::ptr_eq(&handle.inner.released, &self.released) {
+            return None;
+        }
+        std::cell::Ref::filter_map(self.roots.borrow(), |roots| roots.get(&handle.inner.id)).ok()
+    }
+
+    /// Unroot the values of handles that have been dropped.
+    pub(crate) fn release_dropped(&self) {
+        let released = match self.released.lock() {
+            Ok(mut released) => std::mem::take(&mut *released),
+            Err(_) => return,
+        };
+        let mut roots = self.roots.borrow_mut();
+        for id in released {
+            roots.remove(&id);
+        }
+    }
+}
+
+/// The namespace object of a module imported with
+/// [`Repl::import_module`](crate::Repl::import_module).
+///
+/// Read exports with [`Repl::get_export`](crate::Repl::get_export) and call
+/// exported functions with [`Repl::call_export`](crate::Repl::call_export);
+/// bindings are live, so a read sees the export's current value. The
+/// namespace is also an ordinary [`JsHandle`] for the other `Repl` methods.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ModuleNamespaceHandle {
+    handle: JsHandle,
+    key: String,
+    export_names: Vec<String>,
+}
+
+impl ModuleNamespaceHandle {
+    pub(crate) fn new(handle: JsHandle, key: String, export_names: Vec<String>) -> Self {
+        ModuleNamespaceHandle { handle, key, export_names }
+    }
+
+    /// The module key the import resolved to: an absolute path with the
+    /// default loader, or the key chosen by a custom [`ModuleLoader`](crate::ModuleLoader).
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// The names the module exports, sorted like `Object.keys` of the namespace.
+    pub fn export_names(&self) -> &[String] {
+        &self.export_names
+    }
+
+    /// The namespace object itself.
+    pub fn as_handle(&self) -> &JsHandle {
+        &self.handle
+    }
+}
@@ -0,0 +1,15 @@
+/// A point-in-time view of the VM heap, from [`Repl::heap_stats`](crate::Repl::heap_stats).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HeapStats {
+    /// Bytes currently accounted by the garbage collector, including garbage
+    /// that has not been collected yet.
+    pub allocated_bytes: usize,
+    /// The configured heap ceiling, if any.
+    pub heap_limit: Option<usize>,
+    /// Plain objects (including function objects) reachable from the global scope.
+    pub object_count: usize,
+    /// Arrays reachable from the global scope.
+    pub array_count: usize,
+    /// `Map`/`Set` instances reachable from the global scope.
+    pub collection_count: usize,
+}
@@ -0,0 +1,139 @@
+use crate::core::JsOwnedValue;
+use crate::{JSError, Repl};
+use std::any::Any;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+/// Builds the payload of a new instance from the `new` arguments.
+pub(crate) type ConstructCallback = Rc<dyn Fn(&[JsOwnedValue]) -> Result<Box<dyn Any>, JSError>>;
+
+/// Runs an instance member against the payload of its receiver.
+pub(crate) type InstanceCallback = Rc<dyn Fn(&mut dyn Any, &[JsOwnedValue]) -> Result<JsOwnedValue, JSError>>;
+
+/// A static method; dispatched like a function registered with `Repl::register_function`.
+pub(crate) type StaticCallback = Rc<dyn Fn(&[JsOwnedValue]) -> Result<JsOwnedValue, JSError>>;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HostMemberKind {
+    Method,
+    Getter,
+    Setter,
+}
+
+/// A host class with its payload type erased, ready to be installed in a VM.
+pub(crate) struct HostClassSpec {
+    pub(crate) name: String,
+    pub(crate) constructor: Option<ConstructCallback>,
+    pub(crate) members: Vec<(String, HostMemberKind, InstanceCallback)>,
+    pub(crate) static_methods: Vec<(String, StaticCallback)>,
+    pub(crate) static_values: Vec<(String, JsOwnedValue)>,
+}
+
+/// Defines a JavaScript class backed by a Rust type `T`; see [`Repl::define_class`].
+///
+/// Every instance constructed from scripts owns a `T` built by the
+/// [`constructor`](ClassBuilder::constructor) callback. Methods, getters and
+/// setters receive that payload, and it is dropped once the JS object has
+/// been garbage collected. Calling a member on an object that is not an
+/// instance of the class throws a `TypeError`.
+///
+/// Nothing is installed until [`build`](ClassBuilder::build) is called.
+#[must_use = "the class is only defined once `build` is called"]
+pub struct ClassBuilder<'r, T: 'static> {
+    repl: &'r mut Repl,
+    spec: HostClassSpec,
+    payload: PhantomData<fn() -> T>,
+}
+
+impl<'r, T: 'static> ClassBuilder<'r, T> {
+    pub(crate) fn new(repl: &'r mut Repl, name: &str) -> Self {
+        ClassBuilder {
+            repl,
+            spec: HostClassSpec {
+                name: name.to_string(),
+                constructor: None,
+                members: Vec::new(),
+                static_methods: Vec::new(),
+                static_values: Vec::new(),
+            },
+            payload: PhantomData,
+        }
+    }
+
+    /// Build the payload for `new Class(...args)`. Returning `Err` throws into
+    /// the script. Without a constructor, `new Class()` throws a `TypeError`.
+    pub fn constructor<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&[JsOwnedValue]) -> Result<T, JSError> + 'static,
+    {
+        let construct: ConstructCallback = Rc::new(move |args: &[JsOwnedValue]| f(args).map(|payload| Box::new(payload) as Box<dyn Any>));
+        self.spec.constructor = Some(construct);
+        self
+    }
+
+    /// Add a prototype method.
+    pub fn method<F>(self, name: &str, f: F) -> Self
+    where
+        F: Fn(&mut T, &[JsOwnedValue]) -> Result<JsOwnedValue, JSError> + 'static,
+    {
+        self.member(name, HostMemberKind::Method, f)
+    }
+
+    /// Add a prototype getter.
+    pub fn getter<F>(self, name: &str, f: F) -> Self
+    where
+        F: Fn(&T) -> Result<JsOwnedValue, JSError> + 'static,
+    {
+        self.member(name, HostMemberKind::Getter, move |payload, _| f(payload))
+    }
+
+    /// Add a prototype setter; it receives the assigned value.
+    pub fn setter<F>(self, name: &str, f: F) -> Self
+    where
+        F: Fn(&mut T, JsOwnedValue) -> Result<(), JSError> + 'static,
+    {
+        self.member(name, HostMemberKind::Setter, move |payload, args| {
+            f(payload, args.first().cloned().unwrap_or_default())?;
+            Ok(JsOwnedValue::Undefined)
+        })
+    }
+
+    /// Add a static method on the class itself.
+    pub fn static_method<F>(mut self, name: &str, f: F) -> Self
+    where
+        F: Fn(&[JsOwnedValue]) -> Result<JsOwnedValue, JSError> + 'static,
+    {
+        let callback: StaticCallback = Rc::new(f);
+        self.spec.static_methods.push((name.to_string(), callback));
+        self
+    }
+
+    /// Add a static data property on the class itself.
+    pub fn static_value(mut self, name: &str, value: impl Into<JsOwnedValue>) -> Self {
+        self.spec.static_values.push((name.to_string(), value.into()));
+        self
+    }
+
+    /// Install the class as a global binding named after the class.
+    ///
+    /// Fails with a `TypeError` when the name is not a valid identifier or is
+    /// a reserved word such as `class`.
+    pub fn build(self) -> Result<(), JSError> {
+        self.repl.install_host_class(self.spec)
+    }
+
+    fn member<F>(mut self, name: &str, kind: HostMemberKind, f: F) -> Self
+    where
+        F: Fn(&mut T, &[JsOwnedValue]) -> Result<JsOwnedValue, JSError> + 'static,
+    {
+        let class_name = self.spec.name.clone();
+        let callback: InstanceCallback = Rc::new(
+            move |payload: &mut dyn Any, args: &[JsOwnedValue]| match payload.downcast_mut::<T>() {
+                Some(payload) => f(payload, args),
+                None => Err(crate::raise_type_error!(format!("Receiver is not a {class_name} instance"))),
+            },
+        );
+        self.spec.members.push((name.to_string(), kind, callback));
+        self
+    }
+}
@@ -0,0 +1,59 @@
+/// How `console.log` and [`Repl::eval_inspect`](crate::Repl::eval_inspect)
+/// render values, in the spirit of Node's `util.inspect`.
+///
+/// ```
+/// use javascript::{InspectOptions, Repl};
+///
+/// let mut repl = Repl::new();
+/// repl.set_inspect_options(InspectOptions::new().depth(Some(0)));
+/// assert_eq!(repl.eval_inspect("({ a: { b: 1 } })").unwrap(), "{ a: [Object] }");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InspectOptions {
+    pub(crate) depth: Option<usize>,
+    pub(crate) colors: bool,
+    pub(crate) break_length: usize,
+    pub(crate) max_array_length: usize,
+}
+
+impl Default for InspectOptions {
+    fn default() -> Self {
+        InspectOptions {
+            depth: Some(2),
+            colors: false,
+            break_length: 80,
+            max_array_length: 100,
+        }
+    }
+}
+
+impl InspectOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How many levels of nesting to expand before printing `[Object]`
+    /// (default 2). `None` expands everything.
+    pub fn depth(mut self, depth: Option<usize>) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Style the output with ANSI color escapes.
+    pub fn colors(mut self, colors: bool) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    /// Width above which an object or array is split over several lines (default 80).
+    pub fn break_length(mut self, columns: usize) -> Self {
+        self.break_length = columns;
+        self
+    }
+
+    /// Maximum number of array, Set and Map entries shown (default 100).
+    pub fn max_array_length(mut self, entries: usize) -> Self {
+        self.max_array_length = entries;
+        self
+    }
+}
@@ -0,0 +1,55 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A thread-safe handle that cancels a running evaluation.
+///
+/// Obtain one from [`Repl::interrupt_handle`](crate::Repl::interrupt_handle)
+/// or create one and pass it through
+/// [`EvalOptions::interrupt_handle`](crate::EvalOptions::interrupt_handle).
+/// Calling [`interrupt`](InterruptHandle::interrupt) from any thread makes the
+/// VM abort at its next poll point with an uncatchable
+/// `JSErrorKind::Interrupted` error. A request applies to the evaluation
+/// that is running when it is made: one made while nothing runs, or after
+/// the evaluation has passed its last poll point, does not reach the next
+/// evaluation.
+#[derive(Clone, Debug, Default)]
+pub struct InterruptHandle {
+    state: Arc<InterruptState>,
+}
+
+#[derive(Debug, Default)]
+struct InterruptState {
+    /// How many runs have started with this handle, which is also the id of
+    /// the latest one.
+    run: AtomicU64,
+    /// The id of the run a pending request is for, or 0 when none is pending.
+    requested: AtomicU64,
+}
+
+impl InterruptHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request that the current evaluation stop as soon as possible.
+    pub fn interrupt(&self) {
+        let run = self.state.run.load(Ordering::Acquire);
+        self.state.requested.store(run, Ordering::Release);
+    }
+
+    /// Whether a request for the current evaluation is pending.
+    pub fn is_interrupted(&self) -> bool {
+        let requested = self.state.requested.load(Ordering::Acquire);
+        requested != 0 && requested == self.state.run.load(Ordering::Acquire)
+    }
+
+    /// Withdraw a pending interrupt request.
+    pub fn reset(&self) {
+        self.state.requested.store(0, Ordering::Release);
+    }
+
+    /// Start a new run; requests made before it no longer apply.
+    pub(crate) fn begin_run(&self) {
+        self.state.run.fetch_add(1, Ordering::AcqRel);
+    }
+}
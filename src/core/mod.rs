@@ -18,6 +18,64 @@ mod gc;
 mod value;
 pub use value::*;
 
+mod owned_value;
+pub use owned_value::*;
+
+mod exception;
+pub use exception::*;
+
+mod call_target;
+pub use call_target::*;
+
+mod handle;
+pub use handle::*;
+
+mod value_serde;
+pub use value_serde::*;
+
+mod host_class;
+pub use host_class::*;
+
+mod async_host;
+pub use async_host::*;
+
+mod rejection;
+pub use rejection::*;
+
+mod deterministic;
+pub use deterministic::*;
+
+mod permissions;
+pub use permissions::Permissions;
+pub(crate) use permissions::{Access, resolve_path};
+
+mod bytecode;
+pub use bytecode::is_bytecode;
+
+mod snapshot;
+pub use snapshot::*;
+
+mod options;
+pub use options::*;
+
+mod interrupt;
+pub use interrupt::*;
+
+mod heap_stats;
+pub use heap_stats::*;
+
+mod console;
+pub use console::*;
+
+mod inspect;
+pub use inspect::*;
+
+mod module_loader;
+pub use module_loader::*;
+
+mod node_resolve;
+pub(crate) use node_resolve::{is_bare_specifier, resolve_package_specifier};
+
 pub mod property_descriptor;
 #[allow(unused_imports)]
 pub use property_descriptor::{PropAttrs, PropDesc};
@@ -136,6 +194,11 @@ pub(crate) fn parse_program_statements(script: &str, run_as_module: bool) -> Res
 pub fn read_script_file<P: AsRef<std::path::Path>>(path: P) -> Result<String, JSError> {
     let path = path.as_ref();
     let bytes = std::fs::read(path).map_err(|e| raise_eval_error!(format!("Failed to read script file '{}': {e}", path.display())))?;
+    decode_script_bytes(&bytes)
+}
+
+/// Decode script file contents: UTF-8 (with optional BOM) or UTF-16 (LE/BE) with BOM.
+pub(crate) fn decode_script_bytes(bytes: &[u8]) -> Result<String, JSError> {
     if bytes.len() >= 3 && bytes[0] == 0xEF && bytes[1] == 0xBB && bytes[2] == 0xBF {
         // UTF-8 with BOM
         let s = std::str::from_utf8(&bytes[3..]).map_err(|e| raise_eval_error!(format!("Script file contains invalid UTF-8: {e}")))?;
@@ -168,7 +231,7 @@ pub fn read_script_file<P: AsRef<std::path::Path>>(path: P) -> Result<String, JS
         return String::from_utf16(&u16s).map_err(|e| raise_eval_error!(format!("Invalid UTF-16BE script file contents: {e}")));
     }
     // Otherwise assume UTF-8 without BOM
-    std::str::from_utf8(&bytes)
+    std::str::from_utf8(bytes)
         .map(|s| s.to_string())
         .map_err(|e| raise_eval_error!(format!("Script file contains invalid UTF-8: {e}")))
 }
@@ -312,6 +375,20 @@ fn push_unique_or_throw(names: &mut std::collections::HashSet<String>, name: &st
     }
 }
 
+/// Whether `name` is spelled like an identifier: a letter, `_` or `$`
+/// followed by letters, digits, `_` or `$`. Reserved words count.
+pub(crate) fn is_identifier_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Whether `name` can be bound in strict code, such as a class body: an
+/// identifier name that is not a reserved word, `eval` or `arguments`.
+pub(crate) fn is_strict_binding_identifier(name: &str) -> bool {
+    is_identifier_name(name) && !is_reserved_identifier_name(name) && !matches!(name, "eval" | "arguments")
+}
+
 fn is_reserved_identifier_name(name: &str) -> bool {
     matches!(
         name,
@@ -2927,16 +3004,283 @@ pub fn evaluate_script_with_unwrap<T: AsRef<str>, P: AsRef<std::path::Path>>(
     script_path: Option<P>,
     unwrap_top_level_promise: bool,
 ) -> Result<String, JSError> {
+    let options = EvalOptions::default();
+    evaluate_script_in_new_vm(
+        script,
+        run_as_module,
+        script_path,
+        unwrap_top_level_promise,
+        &options,
+        format_completion_value,
+    )
+}
+
+/// Evaluate a script like [`evaluate_script`], applying the limits in `options`.
+pub fn evaluate_script_with_options<T: AsRef<str>, P: AsRef<std::path::Path>>(
+    script: T,
+    run_as_module: bool,
+    script_path: Option<P>,
+    options: &EvalOptions,
+) -> Result<String, JSError> {
+    let unwrap_top_level_promise = script_path.is_none();
+    evaluate_script_in_new_vm(
+        script,
+        run_as_module,
+        script_path,
+        unwrap_top_level_promise,
+        options,
+        format_completion_value,
+    )
+}
+
+pub(crate) fn format_completion_value<'gc>(_ctx: &GcContext<'gc>, _vm: &mut VM<'gc>, v: Value<'gc>) -> Result<String, JSError> {
+    match v {
+        Value::String(s) => {
+            let s_utf8 = crate::unicode::utf16_to_utf8(&s);
+            match serde_json::to_string(&s_utf8) {
+                Ok(quoted) => Ok(quoted),
+                Err(_) => Ok(format!("\"{}\"", s_utf8)),
+            }
+        }
+        Value::Array(_) | Value::Object(_) => Ok(value_to_compact_result_string(&v)),
+        _ => Ok(value_to_string(&v)),
+    }
+}
+
+/// Evaluate a script like [`evaluate_script`], but return the completion value
+/// as an owned [`JsOwnedValue`] instead of its string rendering.
+pub fn evaluate_script_value<T: AsRef<str>, P: AsRef<std::path::Path>>(
+    script: T,
+    run_as_module: bool,
+    script_path: Option<P>,
+) -> Result<JsOwnedValue, JSError> {
+    evaluate_script_value_with_options(script, run_as_module, script_path, &EvalOptions::default())
+}
+
+/// Evaluate a script like [`evaluate_script_value`], applying the limits in `options`.
+pub fn evaluate_script_value_with_options<T: AsRef<str>, P: AsRef<std::path::Path>>(
+    script: T,
+    run_as_module: bool,
+    script_path: Option<P>,
+    options: &EvalOptions,
+) -> Result<JsOwnedValue, JSError> {
+    let unwrap_top_level_promise = script_path.is_none();
+    evaluate_script_in_new_vm(
+        script,
+        run_as_module,
+        script_path,
+        unwrap_top_level_promise,
+        options,
+        |ctx, vm, v| vm.value_to_owned(ctx, &v),
+    )
+}
+
+/// Compile a classic script to bytecode that [`evaluate_bytecode`] runs later
+/// without parsing it again. The bytes only load in the same engine version.
+///
+/// ES modules are not supported yet: the format has no place for a module's
+/// import and export records, so `import` and `export` declarations are a
+/// `SyntaxError` here, as in any classic script. Module bytecode is planned
+/// as a follow-up; until then modules are compiled from source when loaded.
+///
+/// ```
+/// use javascript::{EvalOptions, compile_to_bytes, evaluate_bytecode};
+///
+/// let bytes = compile_to_bytes("const square = x => x * x; square(12)").unwrap();
+/// let result = evaluate_bytecode(&bytes, None::<&std::path::Path>, &EvalOptions::default()).unwrap();
+/// assert_eq!(result, "144");
+/// ```
+pub fn compile_to_bytes<T: AsRef<str>>(script: T) -> Result<Vec<u8>, JSError> {
     let script_str = script.as_ref();
-    let statements = parse_program_statements(script_str, run_as_module)?;
+    let statements = parse_program_statements(script_str, false)?;
+    let mut compiler = Compiler::new();
+    compiler.set_source_text(script_str.to_string());
+    let chunk = compiler.compile(&statements)?;
+    let strict = body_contains_use_strict_directive(&statements);
+    bytecode::CompiledScript { chunk, strict }.to_bytes()
+}
+
+/// Run bytecode from [`compile_to_bytes`] like [`evaluate_script_with_options`]
+/// runs the source. Files from another engine version, corrupt files and
+/// anything else that is not valid bytecode are rejected with an error
+/// before any of it runs.
+pub fn evaluate_bytecode<P: AsRef<std::path::Path>>(
+    bytes: &[u8],
+    script_path: Option<P>,
+    options: &EvalOptions,
+) -> Result<String, JSError> {
+    let unwrap_top_level_promise = script_path.is_none();
     let script_path_buf = script_path.as_ref().map(|p| p.as_ref().to_path_buf());
 
-    let mut arena = JsArenaVm::new(|ctx| VM::new(Chunk::new(), ctx));
+    let mut arena = new_vm_arena(options);
+    arena.mutate_root(|_, vm| -> Result<(), JSError> {
+        let compiled = bytecode::CompiledScript::from_bytes(bytes)?;
+        apply_eval_options(vm, options);
+        install_script_chunk(vm, compiled.chunk);
+        vm.set_bytecode_context(compiled.strict, script_path_buf.as_deref());
+        Ok(())
+    })?;
+    let result = run_installed_script(&mut arena, |ctx, vm, mut v| {
+        if unwrap_top_level_promise {
+            v = unwrap_settled_promise(ctx, vm, v)?;
+        }
+        format_completion_value(ctx, vm, v)
+    });
+    arena.collect_debt();
+    result
+}
 
-    let result = arena.mutate_root(|ctx, vm| {
-        if !crate::js_agent::is_agent_thread() {
-            crate::js_agent::reset_agent_state();
+/// Run the code installed in the arena's VM and hand its completion value to
+/// `finish`. Whenever the run pauses at the heap limit, garbage is collected
+/// and the run goes on unless the heap is still over the limit.
+fn run_installed_script<R>(
+    arena: &mut JsArenaVm,
+    finish: impl for<'gc> FnOnce(&GcContext<'gc>, &mut VM<'gc>, Value<'gc>) -> Result<R, JSError>,
+) -> Result<R, JSError> {
+    let mut finish = Some(finish);
+    loop {
+        let done = arena.mutate_root(|ctx, vm| match vm.run_slice(ctx) {
+            RunSlice::Paused => None,
+            RunSlice::Done(result) => {
+                let finish = finish.take().expect("a run finishes once");
+                Some(result.and_then(|v| finish(ctx, vm, v)))
+            }
+        });
+        if let Some(result) = done {
+            return result;
         }
+        arena.finish_cycle();
+        arena.mutate_root(|ctx, vm| vm.check_heap_after_collection(ctx));
+    }
+}
+
+/// A fresh VM, restored from the snapshot in `options` if there is one.
+fn new_vm_arena(options: &EvalOptions) -> JsArenaVm {
+    JsArenaVm::new(|ctx| match &options.snapshot {
+        Some(snapshot) => VM::from_heap_image(snapshot.image(), ctx),
+        None => VM::new(Chunk::new(), ctx),
+    })
+}
+
+/// Make `chunk` the classic script the VM runs next. A VM restored from a
+/// snapshot with a prelude already holds the prelude's code, which its
+/// functions point into, so the script is appended after it instead.
+fn install_script_chunk<'gc>(vm: &mut VM<'gc>, chunk: Chunk<'gc>) {
+    if vm.chunk.code.is_empty() {
+        vm.chunk = chunk;
+        return;
+    }
+    let top_level_locals = chunk.top_level_locals_at_ip.clone();
+    let start = vm.chunk.merge_dependency_chunk(chunk);
+    vm.chunk.top_level_locals_at_ip = top_level_locals
+        .into_iter()
+        .map(|(ip, locals, consts)| (ip + start, locals, consts))
+        .collect();
+    vm.ip = start;
+}
+
+/// Apply the per-run settings in `options` to a fresh VM and start its limits.
+fn apply_eval_options(vm: &mut VM<'_>, options: &EvalOptions) {
+    #[cfg(feature = "shared-memory")]
+    if !crate::js_agent::is_agent_thread() {
+        crate::js_agent::reset_agent_state();
+    }
+    vm.set_instruction_budget(options.instruction_budget);
+    vm.set_heap_limit(options.heap_limit);
+    if let Some(sink) = &options.console_sink {
+        vm.set_console_sink(sink.clone());
+    }
+    if let Some(inspect) = &options.inspect {
+        vm.set_inspect_options(inspect.clone());
+    }
+    if let Some(loader) = &options.module_loader {
+        vm.set_module_loader(loader.clone());
+    }
+    if let Some(handle) = &options.interrupt {
+        vm.set_interrupt_handle(handle.clone());
+    }
+    if let Some(tracker) = &options.rejection_tracker {
+        vm.set_rejection_tracker(Some(tracker.clone()));
+    }
+    if let Some(deterministic) = options.deterministic {
+        vm.set_deterministic(deterministic);
+    }
+    if let Some(permissions) = &options.permissions {
+        vm.set_permissions(permissions.clone());
+    }
+    if let Some(ms) = options.short_timer_threshold_ms {
+        vm.set_short_timer_threshold_ms(ms);
+    }
+    if let Some(enabled) = options.wait_for_active_handles {
+        vm.set_wait_for_active_handles(enabled);
+    }
+    vm.begin_limited_run();
+}
+
+/// Follow a chain of settled promises to the value at its end, turning a
+/// rejection with an Error into `Err`.
+fn unwrap_settled_promise<'gc>(ctx: &GcContext<'gc>, vm: &mut VM<'gc>, mut v: Value<'gc>) -> Result<Value<'gc>, JSError> {
+    for _ in 0..8 {
+        let step = if let Value::Object(obj) = &v {
+            let b = obj.borrow();
+            let is_promise = matches!(b.get("__type__"), Some(Value::String(s)) if crate::unicode::utf16_to_utf8(s) == "Promise");
+            if is_promise {
+                let rejected = matches!(b.get("__promise_rejected__"), Some(Value::Boolean(true)));
+                let next = b.get("__promise_value__").cloned();
+                Some((rejected, next))
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        let Some((rejected, next)) = step else {
+            break;
+        };
+        let Some(next) = next else {
+            break;
+        };
+
+        if rejected && let Value::Object(obj) = &next {
+            let b = obj.borrow();
+            if let Some(Value::String(t)) = b.get("__type__") {
+                let tn = crate::unicode::utf16_to_utf8(t);
+                if tn == "Error" || tn.ends_with("Error") {
+                    drop(b);
+                    return Err(vm.vm_error_to_js_error(ctx, &next));
+                }
+            }
+        }
+        v = next;
+    }
+    Ok(v)
+}
+
+/// Shared driver for the `evaluate_script*` entry points: compiles and runs the
+/// script in a fresh VM, then hands the completion value to `finish` while the
+/// arena is still mutable.
+pub(crate) fn evaluate_script_in_new_vm<T, P, R, F>(
+    script: T,
+    run_as_module: bool,
+    script_path: Option<P>,
+    unwrap_top_level_promise: bool,
+    options: &EvalOptions,
+    finish: F,
+) -> Result<R, JSError>
+where
+    T: AsRef<str>,
+    P: AsRef<std::path::Path>,
+    F: for<'gc> FnOnce(&GcContext<'gc>, &mut VM<'gc>, Value<'gc>) -> Result<R, JSError>,
+{
+    let script_str = script.as_ref();
+    let statements = parse_program_statements(script_str, run_as_module)?;
+    let script_path_buf = script_path.as_ref().map(|p| p.as_ref().to_path_buf());
+
+    let mut arena = new_vm_arena(options);
+
+    let main_module = arena.mutate_root(|ctx, vm| -> Result<Option<(String, Vec<String>)>, JSError> {
+        apply_eval_options(vm, options);
 
         let script_path_buf = if let Some(p) = script_path_buf.as_ref() {
             let mut p_str = p.to_string_lossy().to_string();
@@ -2951,6 +3295,7 @@ pub fn evaluate_script_with_unwrap<T: AsRef<str>, P: AsRef<std::path::Path>>(
 
         let mut compiler = Compiler::new();
         compiler.set_source_text(script_str.to_string());
+        compiler.set_module_loader(vm.module_loader_state());
         if run_as_module && let Some(ref p) = script_path_buf {
             compiler.set_script_filename(p.to_string_lossy().to_string());
         }
@@ -3063,7 +3408,7 @@ pub fn evaluate_script_with_unwrap<T: AsRef<str>, P: AsRef<std::path::Path>>(
             vm.chunk.self_import_aliases = main_self_import_aliases;
             vm.chunk.const_import_bindings = main_const_import_bindings;
         } else {
-            vm.chunk = chunk;
+            install_script_chunk(vm, chunk);
         }
 
         // In module mode, top-level `this` is undefined (not globalThis)
@@ -3126,8 +3471,11 @@ pub fn evaluate_script_with_unwrap<T: AsRef<str>, P: AsRef<std::path::Path>>(
 
         // let mut vm = VM::new(chunk, ctx);
         vm.set_source_context(script_str, script_path_buf.as_deref());
-        let mut v = vm.run(ctx)?;
-        if let Some((main_key, main_export_names, ..)) = &main_module_record {
+        Ok(main_module_record.map(|(main_key, main_export_names, ..)| (main_key, main_export_names)))
+    })?;
+
+    let result = run_installed_script(&mut arena, |ctx, vm, mut v| {
+        if let Some((main_key, main_export_names)) = &main_module {
             vm.finalize_active_module_record(ctx, main_key, main_export_names);
         }
 
@@ -3136,53 +3484,10 @@ pub fn evaluate_script_with_unwrap<T: AsRef<str>, P: AsRef<std::path::Path>>(
         // File execution should preserve normal script semantics and must not turn
         // a bare `import()` completion value into a process-level failure.
         if unwrap_top_level_promise {
-            for _ in 0..8 {
-                let step = if let Value::Object(obj) = &v {
-                    let b = obj.borrow();
-                    let is_promise = matches!(b.get("__type__"), Some(Value::String(s)) if crate::unicode::utf16_to_utf8(s) == "Promise");
-                    if is_promise {
-                        let rejected = matches!(b.get("__promise_rejected__"), Some(Value::Boolean(true)));
-                        let next = b.get("__promise_value__").cloned();
-                        Some((rejected, next))
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                };
-
-                let Some((rejected, next)) = step else {
-                    break;
-                };
-                let Some(next) = next else {
-                    break;
-                };
-
-                if rejected && let Value::Object(obj) = &next {
-                    let b = obj.borrow();
-                    if let Some(Value::String(t)) = b.get("__type__") {
-                        let tn = crate::unicode::utf16_to_utf8(t);
-                        if tn == "Error" || tn.ends_with("Error") {
-                            drop(b);
-                            return Err(vm.vm_error_to_js_error(ctx, &next));
-                        }
-                    }
-                }
-                v = next;
-            }
+            v = unwrap_settled_promise(ctx, vm, v)?;
         }
 
-        match v {
-            Value::String(s) => {
-                let s_utf8 = crate::unicode::utf16_to_utf8(&s);
-                match serde_json::to_string(&s_utf8) {
-                    Ok(quoted) => Ok(quoted),
-                    Err(_) => Ok(format!("\"{}\"", s_utf8)),
-                }
-            }
-            Value::Array(_) | Value::Object(_) => Ok(value_to_compact_result_string(&v)),
-            _ => Ok(value_to_string(&v)),
-        }
+        finish(ctx, vm, v)
     });
 
     // Run incremental GC to reclaim unreachable objects before returning.
@@ -0,0 +1,197 @@
+use crate::error::JSError;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// A module's contents, as returned by [`ModuleLoader::load`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ModuleSource {
+    /// JavaScript source, evaluated as an ES module.
+    JavaScript(String),
+    /// JSON text; the module's default export is the parsed value.
+    Json(String),
+    /// Raw contents. Decoded like a script file (UTF-8, or UTF-16 with a BOM)
+    /// when text is needed, and parsed as JSON when the key ends in `.json`.
+    Bytes(Vec<u8>),
+}
+
+impl ModuleSource {
+    pub(crate) fn into_text(self) -> Result<String, JSError> {
+        match self {
+            ModuleSource::JavaScript(text) | ModuleSource::Json(text) => Ok(text),
+            ModuleSource::Bytes(bytes) => crate::core::decode_script_bytes(&bytes),
+        }
+    }
+
+    pub(crate) fn into_bytes(self) -> Vec<u8> {
+        match self {
+            ModuleSource::JavaScript(text) | ModuleSource::Json(text) => text.into_bytes(),
+            ModuleSource::Bytes(bytes) => bytes,
+        }
+    }
+}
+
+/// Resolves and fetches ES modules for `import` declarations, `import()`,
+/// `import defer` and `import source`.
+///
+/// Module keys are opaque strings chosen by the loader; each key is loaded
+/// and evaluated at most once per VM, and serves as the referrer when the
+/// module imports others. The entry script's referrer is its script path.
+///
+/// ```
+/// use javascript::{JSError, ModuleLoader, ModuleSource, evaluate_script_with_options, EvalOptions};
+///
+/// struct Memory;
+///
+/// impl ModuleLoader for Memory {
+///     fn resolve(&self, specifier: &str, _referrer: &str) -> Result<String, JSError> {
+///         Ok(format!("mem:{specifier}"))
+///     }
+///
+///     fn load(&self, key: &str) -> Result<ModuleSource, JSError> {
+///         match key {
+///             "mem:answer" => Ok(ModuleSource::JavaScript("export default 42;".to_string())),
+///             _ => Err(javascript::raise_eval_error!(format!("no module {key}"))),
+///         }
+///     }
+/// }
+///
+/// let options = EvalOptions::new().module_loader(Memory);
+/// let script = "import answer from 'answer'; answer";
+/// let out = evaluate_script_with_options(script, true, Some("main.js"), &options).unwrap();
+/// assert_eq!(out, "42");
+/// ```
+pub trait ModuleLoader {
+    /// Map `specifier`, as written in the module identified by `referrer`,
+    /// to a module key.
+    fn resolve(&self, specifier: &str, referrer: &str) -> Result<String, JSError>;
+
+    /// Fetch the module identified by `key`.
+    fn load(&self, key: &str) -> Result<ModuleSource, JSError>;
+}
+
+/// The default loader: relative and absolute specifiers are paths, bare
+/// specifiers and `#imports` follow Node's ESM package resolution
+/// (`node_modules` lookup, `package.json` `exports`/`imports`/`main`), and
+/// keys are filesystem paths.
+///
+/// `node`, `import` and `default` conditions always match; add others with
+/// [`FsModuleLoader::condition`].
+#[derive(Clone, Debug, Default)]
+pub struct FsModuleLoader {
+    conditions: Vec<String>,
+}
+
+impl FsModuleLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also match `name` in `package.json` `exports`/`imports` condition objects.
+    pub fn condition(mut self, name: impl Into<String>) -> Self {
+        self.conditions.push(name.into());
+        self
+    }
+}
+
+impl ModuleLoader for FsModuleLoader {
+    fn resolve(&self, specifier: &str, referrer: &str) -> Result<String, JSError> {
+        let referrer = std::path::Path::new(referrer);
+        let resolved = if crate::core::is_bare_specifier(specifier) {
+            crate::core::resolve_package_specifier(specifier, referrer, &self.conditions)?
+        } else {
+            crate::core::resolve_module_path(specifier, referrer)
+        };
+        Ok(resolved.to_string_lossy().to_string())
+    }
+
+    fn load(&self, key: &str) -> Result<ModuleSource, JSError> {
+        std::fs::read(key)
+            .map(ModuleSource::Bytes)
+            .map_err(|e| crate::raise_eval_error!(format!("Failed to read script file '{key}': {e}")))
+    }
+}
+
+/// Prefix of the placeholder keys given to specifiers the loader failed to resolve.
+const UNRESOLVED_KEY_PREFIX: &str = "\0unresolved\0";
+
+/// The installed [`ModuleLoader`] plus a cache of its resolutions, shared
+/// with helper VMs.
+///
+/// Module bookkeeping looks keys up from many places that cannot fail, so a
+/// failed resolution is mapped to a placeholder key; loading that key
+/// records the resolution error as the module's load error.
+pub(crate) struct ModuleLoaderState {
+    loader: RefCell<Rc<dyn ModuleLoader>>,
+    custom: Cell<bool>,
+    resolutions: RefCell<HashMap<(String, String), String>>,
+    failures: RefCell<HashMap<String, JSError>>,
+}
+
+impl Default for ModuleLoaderState {
+    fn default() -> Self {
+        ModuleLoaderState {
+            loader: RefCell::new(Rc::new(FsModuleLoader::new())),
+            custom: Cell::new(false),
+            resolutions: RefCell::new(HashMap::new()),
+            failures: RefCell::new(HashMap::new()),
+        }
+    }
+}
+
+impl ModuleLoaderState {
+    /// Resolve `specifier` imported from the module at `base_path` to a module key.
+    pub(crate) fn resolve_key(&self, specifier: &str, base_path: &std::path::Path) -> String {
+        let referrer = base_path.to_string_lossy().to_string();
+        let cache_key = (specifier.to_string(), referrer);
+        if let Some(key) = self.resolutions.borrow().get(&cache_key) {
+            return key.clone();
+        }
+        let loader = self.loader.borrow().clone();
+        let key = match loader.resolve(specifier, &cache_key.1) {
+            Ok(key) => key,
+            Err(err) => {
+                let placeholder = format!("{UNRESOLVED_KEY_PREFIX}{}\0{specifier}", cache_key.1);
+                self.failures.borrow_mut().insert(placeholder.clone(), err);
+                placeholder
+            }
+        };
+        self.resolutions.borrow_mut().insert(cache_key, key.clone());
+        key
+    }
+
+    /// Like [`Self::resolve_key`], as the path-shaped key module records use.
+    pub(crate) fn resolve_path(&self, specifier: &str, base_path: &std::path::Path) -> std::path::PathBuf {
+        std::path::PathBuf::from(self.resolve_key(specifier, base_path))
+    }
+
+    /// Module record key for a request, including its `with { type }` attribute.
+    pub(crate) fn request_key(&self, specifier: &str, base_path: &std::path::Path, import_type: Option<&str>) -> String {
+        let resolved = self.resolve_path(specifier, base_path);
+        crate::core::module_request_key_from_resolved_path(&resolved, import_type)
+    }
+
+    /// Fetch the module at `resolved_path`, reporting a failed resolution first.
+    ///
+    /// Callers load each key at most once, so the resolution error is handed over.
+    pub(crate) fn load(&self, resolved_path: &std::path::Path) -> Result<ModuleSource, JSError> {
+        let key = resolved_path.to_string_lossy();
+        if let Some(err) = self.failures.borrow_mut().remove(key.as_ref()) {
+            return Err(err);
+        }
+        let loader = self.loader.borrow().clone();
+        loader.load(&key)
+    }
+
+    pub(crate) fn install(&self, loader: Rc<dyn ModuleLoader>) {
+        self.loader.replace(loader);
+        self.custom.set(true);
+        self.resolutions.borrow_mut().clear();
+        self.failures.borrow_mut().clear();
+    }
+
+    /// Whether an embedder installed its own loader (so imports need no script path).
+    pub(crate) fn is_custom(&self) -> bool {
+        self.custom.get()
+    }
+}
@@ -0,0 +1,388 @@
+//! Node.js ESM resolution for bare specifiers (`lodash-es`, `@scope/pkg/sub`)
+//! and package-internal `#imports`, following the algorithm in Node's
+//! `esm` resolver documentation.
+
+use crate::error::JSError;
+use crate::{raise_eval_error, raise_type_error};
+use serde_json::{Map, Value as Json};
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+/// Modules provided by the engine itself; never looked up on disk.
+const BUILTIN_MODULES: [&str; 4] = ["math", "console", "os", "std"];
+
+/// Whether `specifier` names a package (or `#import`) rather than a file path.
+pub(crate) fn is_bare_specifier(specifier: &str) -> bool {
+    !(specifier.is_empty()
+        || specifier.starts_with("./")
+        || specifier.starts_with("../")
+        || specifier == "."
+        || specifier == ".."
+        || Path::new(specifier).is_absolute()
+        || specifier.starts_with('/')
+        || BUILTIN_MODULES.contains(&specifier))
+}
+
+/// Resolve a bare or `#` specifier imported from the file at `referrer` to
+/// the path of an existing file.
+///
+/// `node`, `import` and `default` always match in `exports`/`imports`
+/// condition objects; `conditions` lists any additional names to accept.
+pub(crate) fn resolve_package_specifier(specifier: &str, referrer: &Path, conditions: &[String]) -> Result<PathBuf, JSError> {
+    let parent = referrer.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let parent = std::path::absolute(parent).unwrap_or_else(|_| parent.to_path_buf());
+    let resolver = Resolver { referrer, conditions };
+    let resolved = if specifier.starts_with('#') {
+        resolver.imports_resolve(specifier, &parent)?
+    } else {
+        resolver.package_resolve(specifier, &parent)?
+    };
+    if resolved.is_dir() {
+        return Err(raise_eval_error!(format!(
+            "Directory import '{}' is not supported resolving ES modules imported from {}",
+            resolved.display(),
+            referrer.display()
+        )));
+    }
+    if !resolved.is_file() {
+        return Err(raise_eval_error!(format!(
+            "Cannot find module '{}' imported from {}",
+            resolved.display(),
+            referrer.display()
+        )));
+    }
+    Ok(resolved)
+}
+
+/// Conditions that match in every `exports`/`imports` condition object.
+const DEFAULT_CONDITIONS: [&str; 3] = ["default", "import", "node"];
+
+/// What an `exports`/`imports` target resolved to.
+enum Target {
+    Path(PathBuf),
+    /// A `null` target: the path is deliberately not exported.
+    Excluded,
+    /// A condition object none of whose conditions matched.
+    Unmatched,
+}
+
+impl Target {
+    fn into_path(self) -> Option<PathBuf> {
+        match self {
+            Target::Path(path) => Some(path),
+            Target::Excluded | Target::Unmatched => None,
+        }
+    }
+}
+
+struct Resolver<'a> {
+    referrer: &'a Path,
+    conditions: &'a [String],
+}
+
+impl Resolver<'_> {
+    fn package_resolve(&self, specifier: &str, parent: &Path) -> Result<PathBuf, JSError> {
+        if specifier.starts_with("node:") {
+            return Err(raise_eval_error!(format!(
+                "No such built-in module: {specifier} (imported from {})",
+                self.referrer.display()
+            )));
+        }
+        let (name, subpath) = self.parse_package_specifier(specifier)?;
+
+        // A package may import itself by name when it declares "exports".
+        if let Some((scope, pjson)) = find_package_scope(parent)?
+            && pjson.get("name").and_then(Json::as_str) == Some(name)
+            && let Some(exports) = pjson.get("exports").filter(|exports| !exports.is_null())
+        {
+            return self.exports_resolve(&scope, &subpath, exports);
+        }
+
+        for dir in parent.ancestors() {
+            if dir.file_name().is_some_and(|n| n == "node_modules") {
+                continue;
+            }
+            let package_dir = dir.join("node_modules").join(name);
+            if !package_dir.is_dir() {
+                continue;
+            }
+            let pjson_path = package_dir.join("package.json");
+            let pjson = if pjson_path.is_file() {
+                Some(read_package_json(&pjson_path)?)
+            } else {
+                None
+            };
+            if let Some(exports) = pjson.as_ref().and_then(|p| p.get("exports")).filter(|exports| !exports.is_null()) {
+                return self.exports_resolve(&package_dir, &subpath, exports);
+            }
+            if subpath == "." {
+                return self.legacy_main_resolve(&package_dir, pjson.as_ref());
+            }
+            return Ok(package_dir.join(&subpath[2..]));
+        }
+
+        Err(raise_eval_error!(format!(
+            "Cannot find package '{name}' imported from {}",
+            self.referrer.display()
+        )))
+    }
+
+    /// Split `@scope/name/sub/path` into the package name and a `./sub/path` subpath.
+    fn parse_package_specifier<'s>(&self, specifier: &'s str) -> Result<(&'s str, String), JSError> {
+        let name_end = if specifier.starts_with('@') {
+            match specifier.find('/') {
+                Some(slash) if slash > 1 => specifier[slash + 1..].find('/').map_or(specifier.len(), |i| slash + 1 + i),
+                _ => 0,
+            }
+        } else {
+            specifier.find('/').unwrap_or(specifier.len())
+        };
+        let name = &specifier[..name_end];
+        let valid = !name.is_empty()
+            && !name.ends_with('/')
+            && !name.starts_with('.')
+            && !name.contains('\\')
+            && !name.contains('%')
+            && !specifier.ends_with('/');
+        if !valid {
+            return Err(raise_type_error!(format!(
+                "Invalid module \"{specifier}\" is not a valid package name imported from {}",
+                self.referrer.display()
+            )));
+        }
+        Ok((name, format!(".{}", &specifier[name_end..])))
+    }
+
+    fn exports_resolve(&self, package_dir: &Path, subpath: &str, exports: &Json) -> Result<PathBuf, JSError> {
+        let pjson_path = package_dir.join("package.json");
+        let subpath_map = match exports {
+            Json::Object(map) => {
+                let dotted = map.keys().filter(|k| k.starts_with('.')).count();
+                if dotted != 0 && dotted != map.len() {
+                    return Err(raise_type_error!(format!(
+                        "Invalid package config {}: \"exports\" cannot contain some keys starting with '.' and some not",
+                        pjson_path.display()
+                    )));
+                }
+                (dotted != 0).then_some(map)
+            }
+            _ => None,
+        };
+
+        let resolved = match (subpath, subpath_map) {
+            (".", None) => self.target_resolve(package_dir, exports, None, false)?.into_path(),
+            (".", Some(map)) => match map.get(".") {
+                Some(target) => self.target_resolve(package_dir, target, None, false)?.into_path(),
+                None => None,
+            },
+            (_, Some(map)) => self.imports_exports_resolve(subpath, map, package_dir, false)?,
+            (_, None) => None,
+        };
+        resolved.ok_or_else(|| {
+            if subpath == "." {
+                raise_eval_error!(format!(
+                    "No \"exports\" main defined in {} imported from {}",
+                    pjson_path.display(),
+                    self.referrer.display()
+                ))
+            } else {
+                raise_eval_error!(format!(
+                    "Package subpath '{subpath}' is not defined by \"exports\" in {} imported from {}",
+                    pjson_path.display(),
+                    self.referrer.display()
+                ))
+            }
+        })
+    }
+
+    fn imports_resolve(&self, specifier: &str, parent: &Path) -> Result<PathBuf, JSError> {
+        if specifier == "#" || specifier.starts_with("#/") {
+            return Err(raise_type_error!(format!(
+                "Invalid module \"{specifier}\" is not a valid internal imports specifier name imported from {}",
+                self.referrer.display()
+            )));
+        }
+        let scope = find_package_scope(parent)?;
+        if let Some((scope_dir, pjson)) = &scope
+            && let Some(Json::Object(imports)) = pjson.get("imports")
+            && let Some(resolved) = self.imports_exports_resolve(specifier, imports, scope_dir, true)?
+        {
+            return Ok(resolved);
+        }
+        let package = match &scope {
+            Some((scope_dir, _)) => format!(" in package {}", scope_dir.join("package.json").display()),
+            None => String::new(),
+        };
+        Err(raise_eval_error!(format!(
+            "Package import specifier \"{specifier}\" is not defined{package} imported from {}",
+            self.referrer.display()
+        )))
+    }
+
+    /// Match `match_key` against the keys of an `exports` or `imports` map,
+    /// trying exact keys first and then `*` patterns, most specific first.
+    fn imports_exports_resolve(
+        &self,
+        match_key: &str,
+        map: &Map<String, Json>,
+        package_dir: &Path,
+        is_imports: bool,
+    ) -> Result<Option<PathBuf>, JSError> {
+        if !match_key.contains('*')
+            && let Some(target) = map.get(match_key)
+        {
+            return Ok(self.target_resolve(package_dir, target, None, is_imports)?.into_path());
+        }
+
+        let mut pattern_keys: Vec<&String> = map.keys().filter(|k| k.matches('*').count() == 1).collect();
+        pattern_keys.sort_by(|a, b| pattern_key_compare(a, b));
+        for key in pattern_keys {
+            let Some((base, trailer)) = key.split_once('*') else {
+                continue;
+            };
+            if match_key.starts_with(base)
+                && match_key != base
+                && (trailer.is_empty() || (match_key.ends_with(trailer) && match_key.len() >= key.len()))
+            {
+                let pattern_match = &match_key[base.len()..match_key.len() - trailer.len()];
+                return Ok(self
+                    .target_resolve(package_dir, &map[key.as_str()], Some(pattern_match), is_imports)?
+                    .into_path());
+            }
+        }
+        Ok(None)
+    }
+
+    fn target_resolve(&self, package_dir: &Path, target: &Json, pattern_match: Option<&str>, is_imports: bool) -> Result<Target, JSError> {
+        match target {
+            Json::String(target) => self
+                .string_target_resolve(package_dir, target, pattern_match, is_imports)
+                .map(Target::Path),
+            Json::Object(conditions) => {
+                // The first matching condition decides, unless nothing under
+                // it matched; a `null` under it excludes the path outright.
+                for (condition, value) in conditions {
+                    if DEFAULT_CONDITIONS.contains(&condition.as_str()) || self.conditions.iter().any(|c| c == condition) {
+                        match self.target_resolve(package_dir, value, pattern_match, is_imports)? {
+                            Target::Unmatched => continue,
+                            resolved => return Ok(resolved),
+                        }
+                    }
+                }
+                Ok(Target::Unmatched)
+            }
+            Json::Array(targets) => {
+                let mut last_error = None;
+                for target in targets {
+                    match self.target_resolve(package_dir, target, pattern_match, is_imports) {
+                        Ok(Target::Path(resolved)) => return Ok(Target::Path(resolved)),
+                        Ok(_) => last_error = None,
+                        Err(err) => last_error = Some(err),
+                    }
+                }
+                last_error.map_or(Ok(Target::Excluded), Err)
+            }
+            Json::Null => Ok(Target::Excluded),
+            _ => Err(self.invalid_target(package_dir, &target.to_string(), is_imports)),
+        }
+    }
+
+    fn string_target_resolve(
+        &self,
+        package_dir: &Path,
+        target: &str,
+        pattern_match: Option<&str>,
+        is_imports: bool,
+    ) -> Result<PathBuf, JSError> {
+        let Some(relative) = target.strip_prefix("./") else {
+            // `imports` may map to another package: "#dep": "some-package/sub".
+            if is_imports && !target.starts_with("../") && !target.starts_with('/') && !target.contains("://") {
+                let specifier = pattern_match.map_or_else(|| target.to_string(), |m| target.replace('*', m));
+                return self.package_resolve(&specifier, package_dir);
+            }
+            return Err(self.invalid_target(package_dir, target, is_imports));
+        };
+        if has_invalid_segment(relative) {
+            return Err(self.invalid_target(package_dir, target, is_imports));
+        }
+        let Some(pattern_match) = pattern_match else {
+            return Ok(package_dir.join(relative));
+        };
+        if has_invalid_segment(pattern_match) {
+            return Err(raise_type_error!(format!(
+                "Invalid module \"{pattern_match}\" request is not a valid match in pattern \"{target}\" for the {} in {} imported from {}",
+                if is_imports {
+                    "\"imports\" resolution"
+                } else {
+                    "\"exports\" resolution"
+                },
+                package_dir.join("package.json").display(),
+                self.referrer.display()
+            )));
+        }
+        Ok(package_dir.join(relative.replace('*', pattern_match)))
+    }
+
+    fn invalid_target(&self, package_dir: &Path, target: &str, is_imports: bool) -> JSError {
+        raise_type_error!(format!(
+            "Invalid \"{}\" target \"{target}\" in the package config {} imported from {}",
+            if is_imports { "imports" } else { "exports" },
+            package_dir.join("package.json").display(),
+            self.referrer.display()
+        ))
+    }
+
+    /// Pre-`exports` entry point lookup: `main` with common extensions, then `index`.
+    fn legacy_main_resolve(&self, package_dir: &Path, pjson: Option<&Json>) -> Result<PathBuf, JSError> {
+        let mut candidates = Vec::new();
+        if let Some(main) = pjson.and_then(|p| p.get("main")).and_then(Json::as_str) {
+            for suffix in ["", ".js", ".json", "/index.js", "/index.json"] {
+                candidates.push(package_dir.join(format!("{main}{suffix}")));
+            }
+        }
+        candidates.push(package_dir.join("index.js"));
+        candidates.push(package_dir.join("index.json"));
+        candidates.into_iter().find(|path| path.is_file()).ok_or_else(|| {
+            raise_eval_error!(format!(
+                "Cannot find package '{}' imported from {}",
+                package_dir.display(),
+                self.referrer.display()
+            ))
+        })
+    }
+}
+
+/// Node's PATTERN_KEY_COMPARE: longer prefix before `*` first, then longer key.
+fn pattern_key_compare(a: &str, b: &str) -> Ordering {
+    let base_a = a.find('*').map_or(a.len(), |i| i + 1);
+    let base_b = b.find('*').map_or(b.len(), |i| i + 1);
+    base_b.cmp(&base_a).then_with(|| b.len().cmp(&a.len()))
+}
+
+/// Whether a target path contains an empty, `.`, `..` or `node_modules` segment.
+fn has_invalid_segment(path: &str) -> bool {
+    let trimmed = path.strip_suffix('/').unwrap_or(path);
+    trimmed
+        .split(['/', '\\'])
+        .any(|segment| segment.is_empty() || segment == "." || segment == ".." || segment.eq_ignore_ascii_case("node_modules"))
+}
+
+/// The nearest directory at or above `dir` holding a `package.json`, stopping
+/// at `node_modules` boundaries.
+fn find_package_scope(dir: &Path) -> Result<Option<(PathBuf, Json)>, JSError> {
+    for scope in dir.ancestors() {
+        if scope.file_name().is_some_and(|n| n == "node_modules") {
+            break;
+        }
+        let pjson_path = scope.join("package.json");
+        if pjson_path.is_file() {
+            return Ok(Some((scope.to_path_buf(), read_package_json(&pjson_path)?)));
+        }
+    }
+    Ok(None)
+}
+
+fn read_package_json(path: &Path) -> Result<Json, JSError> {
+    let text = std::fs::read_to_string(path).map_err(|e| raise_type_error!(format!("Invalid package config {}: {e}", path.display())))?;
+    serde_json::from_str(&text).map_err(|e| raise_type_error!(format!("Invalid package config {}: {e}", path.display())))
+}
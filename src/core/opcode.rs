@@ -252,6 +252,17 @@ impl TryFrom<u8> for Opcode {
     }
 }
 
+/// Where the operands of one instruction that refer to other parts of the
+/// chunk live, as found by [`Chunk::decode_operands`].
+pub(crate) struct InstructionOperands {
+    /// Offset of the next instruction.
+    pub next: usize,
+    /// Position of a u16 constant-pool index.
+    pub constant_at: Option<usize>,
+    /// Position of a u32 absolute jump target.
+    pub jump_at: Option<usize>,
+}
+
 /// Bytecode chunk (stores instruction array and constant pool)
 #[derive(Debug, Clone, Default)]
 pub struct Chunk<'gc> {
@@ -512,101 +523,115 @@ impl<'gc> Chunk<'gc> {
     }
 
     /// Walk through bytecode and adjust constant indices and jump targets.
+    pub(crate) fn adjust_bytecode_offsets(code: &mut [u8], ip_offset: usize, const_offset: usize) {
+        let mut i = 0;
+        while i < code.len() {
+            let Some(operands) = Self::decode_operands(code, i) else {
+                panic!("Unhandled opcode {} while adjusting merged bytecode at offset {i}", code[i]);
+            };
+            if let Some(pos) = operands.constant_at {
+                Self::adjust_u16_at(code, pos, const_offset);
+            }
+            if let Some(pos) = operands.jump_at {
+                Self::adjust_u32_at(code, pos, ip_offset);
+            }
+            i = operands.next;
+        }
+    }
+
+    /// Decode the operand layout of the instruction at `ip`. Returns `None`
+    /// for an unknown opcode or an instruction cut short by the end of `code`.
     ///
     /// Operand layout for each opcode:
-    /// - Constant index (u16, needs const_offset): Constant, DefineGlobal,
-    ///   DefineGlobalConst, DefineGlobalSoft, GetGlobal, SetGlobal, GetProperty,
-    ///   SetProperty, GetMethod, DeleteProperty, TypeOfGlobal, DeleteGlobal,
+    /// - Constant index (u16): Constant, DefineGlobal, DefineGlobalConst,
+    ///   DefineGlobalSoft, GetGlobal, SetGlobal, GetProperty, SetProperty,
+    ///   GetMethod, DeleteProperty, TypeOfGlobal, DeleteGlobal,
     ///   GetSuperProperty, SetSuperProperty, InitProperty
-    /// - Jump target (u32, needs ip_offset): Jump, JumpIfFalse, JumpIfTrue
+    /// - Jump target (u32): Jump, JumpIfFalse, JumpIfTrue
     /// - SetupTry: u32 jump + u16 const + u8 flags
     /// - MakeClosure: u16 const + u8 count + count×2 bytes
-    /// - Call: u8 flags, conditionally +u16 (arg count, no adjustment)
-    /// - ThrowIfNotConstructor: u8 stack depth, no adjustment
-    pub(crate) fn adjust_bytecode_offsets(code: &mut [u8], ip_offset: usize, const_offset: usize) {
-        let len = code.len();
-        let mut i = 0;
-        while i < len {
-            let opcode = code[i];
-            i += 1;
-            match opcode {
-                // No operands
-                0
-                | 2..=6
-                | 12..=14
-                | 18..=25
-                | 31..=35
-                | 37..=39
-                | 41..=44
-                | 47..=49
-                | 51
-                | 56..=65
-                | 67..=68
-                | 72
-                | 74..=77
-                | 79..=95
-                | 97..=100
-                | 102..=103
-                | 105..=107
-                | 117 => {}
-
-                // u8 operand, no adjustment needed
-                16 | 17 | 27 | 28 | 50 | 69 | 70 | 96 | 108 | 109 | 110 => {
-                    i += 1;
-                }
-
-                // Call (15): u8 flags, conditionally +u16 arg count
-                15 => {
-                    let flags = code[i];
-                    i += 1;
-                    if (flags & 0x3f) == 0x3f {
-                        i += 2; // extended arg count, no adjustment
-                    }
-                }
-
-                // NewCall (46): u8 arg count
-                46 => {
-                    i += 1;
-                }
+    /// - Call: u8 flags, conditionally +u16 (arg count)
+    /// - ThrowIfNotConstructor: u8 stack depth
+    pub(crate) fn decode_operands(code: &[u8], ip: usize) -> Option<InstructionOperands> {
+        let opcode = *code.get(ip)?;
+        let mut i = ip + 1;
+        let mut constant_at = None;
+        let mut jump_at = None;
+        match opcode {
+            // No operands (DisposeResources* are never emitted and the runner
+            // reads nothing for them)
+            0
+            | 2..=6
+            | 12..=14
+            | 18..=25
+            | 31..=35
+            | 37..=39
+            | 41..=44
+            | 47..=49
+            | 51
+            | 56..=65
+            | 67..=68
+            | 72
+            | 74..=77
+            | 79..=95
+            | 97..=100
+            | 102..=103
+            | 105..=107
+            | 111
+            | 115..=117 => {}
 
-                // CallSpread (66): u8 flags
-                66 => {
-                    i += 1;
-                }
+            // u8 operand
+            16 | 17 | 27 | 28 | 50 | 69 | 70 | 96 | 108 | 109 | 110 => {
+                i += 1;
+            }
 
-                // u16 constant index — add const_offset
-                1 | 7 | 8 | 9 | 29 | 30 | 40 | 45 | 52 | 53 | 54 | 55 | 73 | 78 | 101 | 104 | 112 | 113 | 114 => {
-                    Self::adjust_u16_at(code, i, const_offset);
+            // Call (15): u8 flags, conditionally +u16 arg count
+            15 => {
+                let flags = *code.get(i)?;
+                i += 1;
+                if (flags & 0x3f) == 0x3f {
                     i += 2;
                 }
+            }
 
-                // u32 jump target — add ip_offset
-                10 | 11 | 26 => {
-                    Self::adjust_u32_at(code, i, ip_offset);
-                    i += 4;
-                }
+            // NewCall (46): u8 arg count; CallSpread (66): u8 flags
+            46 | 66 => {
+                i += 1;
+            }
 
-                // SetupTry (36): u32 catch_ip (jump) + u16 binding_idx (const) + u8 flags
-                36 => {
-                    Self::adjust_u32_at(code, i, ip_offset);
-                    i += 4;
-                    Self::adjust_u16_at(code, i, const_offset);
-                    i += 2;
-                    i += 1; // flags byte (no adjustment needed)
-                }
+            // u16 constant index
+            1 | 7 | 8 | 9 | 29 | 30 | 40 | 45 | 52 | 53 | 54 | 55 | 73 | 78 | 101 | 104 | 112 | 113 | 114 => {
+                constant_at = Some(i);
+                i += 2;
+            }
 
-                // MakeClosure (71): u16 const_idx + u8 capture_count + count×2 bytes
-                71 => {
-                    Self::adjust_u16_at(code, i, const_offset);
-                    i += 2;
-                    let capture_count = code[i] as usize;
-                    i += 1;
-                    i += capture_count * 2;
-                }
+            // u32 jump target
+            10 | 11 | 26 => {
+                jump_at = Some(i);
+                i += 4;
+            }
 
-                _ => panic!("Unhandled opcode {opcode} while adjusting merged bytecode at offset {}", i - 1),
+            // SetupTry (36): u32 catch_ip (jump) + u16 binding_idx (const) + u8 flags
+            36 => {
+                jump_at = Some(i);
+                constant_at = Some(i + 4);
+                i += 7;
             }
+
+            // MakeClosure (71): u16 const_idx + u8 capture_count + count×2 bytes
+            71 => {
+                constant_at = Some(i);
+                let capture_count = *code.get(i + 2)? as usize;
+                i += 3 + capture_count * 2;
+            }
+
+            _ => return None,
         }
+        (i <= code.len()).then_some(InstructionOperands {
+            next: i,
+            constant_at,
+            jump_at,
+        })
     }
 
     /// Read a little-endian u16 at position `pos`, add `offset`, write back.
@@ -0,0 +1,160 @@
+use crate::JSError;
+use crate::core::{
+    ConsoleSink, DeterministicOptions, InspectOptions, InterruptHandle, ModuleLoader, Permissions, PromiseRejection, RejectionCallback,
+    Snapshot,
+};
+use std::rc::Rc;
+
+/// Per-evaluation settings for [`evaluate_script_with_options`](crate::evaluate_script_with_options)
+/// and [`evaluate_script_value_with_options`](crate::evaluate_script_value_with_options).
+///
+/// ```
+/// use javascript::{EvalOptions, evaluate_script_with_options};
+///
+/// let options = EvalOptions::new().instruction_budget(10_000);
+/// let err = evaluate_script_with_options("while (true) {}", false, None::<&std::path::Path>, &options).unwrap_err();
+/// assert!(err.message().contains("Infinite loop"));
+/// ```
+#[derive(Clone, Default)]
+pub struct EvalOptions {
+    pub(crate) instruction_budget: Option<u64>,
+    pub(crate) interrupt: Option<InterruptHandle>,
+    pub(crate) heap_limit: Option<usize>,
+    pub(crate) console_sink: Option<Rc<dyn ConsoleSink>>,
+    pub(crate) inspect: Option<InspectOptions>,
+    pub(crate) module_loader: Option<Rc<dyn ModuleLoader>>,
+    pub(crate) rejection_tracker: Option<RejectionCallback>,
+    pub(crate) deterministic: Option<DeterministicOptions>,
+    pub(crate) snapshot: Option<Snapshot>,
+    pub(crate) permissions: Option<Permissions>,
+    pub(crate) short_timer_threshold_ms: Option<u64>,
+    pub(crate) wait_for_active_handles: Option<bool>,
+}
+
+impl std::fmt::Debug for EvalOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EvalOptions")
+            .field("instruction_budget", &self.instruction_budget)
+            .field("interrupt", &self.interrupt)
+            .field("heap_limit", &self.heap_limit)
+            .field("console_sink", &self.console_sink.is_some())
+            .field("inspect", &self.inspect)
+            .field("module_loader", &self.module_loader.is_some())
+            .field("rejection_tracker", &self.rejection_tracker.is_some())
+            .field("deterministic", &self.deterministic)
+            .field("snapshot", &self.snapshot.is_some())
+            .field("permissions", &self.permissions)
+            .field("short_timer_threshold_ms", &self.short_timer_threshold_ms)
+            .field("wait_for_active_handles", &self.wait_for_active_handles)
+            .finish()
+    }
+}
+
+impl EvalOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Abort the evaluation once this many bytecode instructions have run.
+    ///
+    /// Timers and microtasks count toward the same budget. Exhausting it
+    /// raises an uncatchable `JSErrorKind::InfiniteLoopError`.
+    pub fn instruction_budget(mut self, budget: u64) -> Self {
+        self.instruction_budget = Some(budget);
+        self
+    }
+
+    /// Abort the evaluation with an uncatchable out-of-memory `RangeError`
+    /// once the garbage-collected heap grows past `bytes`. Crossing the limit
+    /// first forces a full collection, so only objects that are still
+    /// reachable count, together with their strings, elements and properties.
+    pub fn heap_limit(mut self, bytes: usize) -> Self {
+        self.heap_limit = Some(bytes);
+        self
+    }
+
+    /// Send `console.*` output to `sink` instead of stdout/stderr.
+    pub fn console_sink<S: ConsoleSink + 'static>(mut self, sink: S) -> Self {
+        self.console_sink = Some(Rc::new(sink));
+        self
+    }
+
+    /// How `console.*` renders objects and other non-string arguments.
+    pub fn inspect_options(mut self, options: InspectOptions) -> Self {
+        self.inspect = Some(options);
+        self
+    }
+
+    /// Resolve and fetch imported modules through `loader` instead of the filesystem.
+    pub fn module_loader<L: ModuleLoader + 'static>(mut self, loader: L) -> Self {
+        self.module_loader = Some(Rc::new(loader));
+        self
+    }
+
+    /// Report promise rejections left unhandled, and those handled later,
+    /// to `tracker`; see [`Repl::set_rejection_tracker`](crate::Repl::set_rejection_tracker).
+    /// Returning `Err` fails the evaluation with that error, as Node's
+    /// default `--unhandled-rejections=throw` mode does.
+    pub fn rejection_tracker<F>(mut self, tracker: F) -> Self
+    where
+        F: Fn(PromiseRejection) -> Result<(), JSError> + 'static,
+    {
+        self.rejection_tracker = Some(Rc::new(tracker));
+        self
+    }
+
+    /// Run on a virtual clock with a seeded `Math.random` and local time
+    /// pinned to UTC, so equal inputs give equal output. Timers that the
+    /// evaluation waits for fire as soon as nothing else is left to run, with
+    /// the clock jumped to their deadline.
+    pub fn deterministic(mut self, options: DeterministicOptions) -> Self {
+        self.deterministic = Some(options);
+        self
+    }
+
+    /// Start from `snapshot` instead of setting up the builtins, and with the
+    /// globals of its prelude if it has one.
+    pub fn snapshot(mut self, snapshot: Snapshot) -> Self {
+        self.snapshot = Some(snapshot);
+        self
+    }
+
+    /// Limit what the script may do through the `os` and `std` namespaces;
+    /// see [`Permissions`].
+    pub fn permissions(mut self, permissions: Permissions) -> Self {
+        self.permissions = Some(permissions);
+        self
+    }
+
+    /// Wait for timers with a delay of up to `ms` milliseconds before the
+    /// evaluation returns (20 by default). Longer timers stay queued unless
+    /// [`wait_for_active_handles`](Self::wait_for_active_handles) is on.
+    pub fn short_timer_threshold_ms(mut self, ms: u64) -> Self {
+        self.short_timer_threshold_ms = Some(ms);
+        self
+    }
+
+    /// Keep running the event loop, sleeping until each deadline, for as long
+    /// as any timer that was not released with `os.unrefTimer` is pending, as
+    /// Node does for a script. Off by default, since a `setInterval` that is
+    /// never cleared then keeps the evaluation from returning.
+    ///
+    /// ```
+    /// use javascript::{EvalOptions, evaluate_script_with_options};
+    ///
+    /// let script = "new Promise(resolve => setTimeout(() => resolve('done'), 50))";
+    /// let options = EvalOptions::new().wait_for_active_handles(true);
+    /// let out = evaluate_script_with_options(script, false, None::<&std::path::Path>, &options).unwrap();
+    /// assert_eq!(out, "\"done\"");
+    /// ```
+    pub fn wait_for_active_handles(mut self, enabled: bool) -> Self {
+        self.wait_for_active_handles = Some(enabled);
+        self
+    }
+
+    /// Let another thread cancel the evaluation through `handle`.
+    pub fn interrupt_handle(mut self, handle: InterruptHandle) -> Self {
+        self.interrupt = Some(handle);
+        self
+    }
+}
@@ -0,0 +1,231 @@
+use crate::core::JsHandle;
+use indexmap::IndexMap;
+use num_bigint::BigInt;
+
+/// Name and arity of a JS function captured in a [`JsOwnedValue`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JsFunctionInfo {
+    pub name: String,
+    pub length: usize,
+}
+
+/// An owned, lifetime-free snapshot of a JS value.
+///
+/// Results produced by `evaluate_script_value` and `Repl::eval_value` are
+/// converted into this type so callers can inspect them without re-parsing
+/// the string form produced by `evaluate_script`. Objects and arrays are
+/// copied recursively (own enumerable string keys only); circular references
+/// are replaced by `Undefined`. `Map`, `Date` and `Uint8Array` values keep
+/// their own variants, and [`to_js_value`](crate::to_js_value) /
+/// [`from_js_value`](crate::from_js_value) convert to and from serde types.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub enum JsOwnedValue {
+    #[default]
+    Undefined,
+    Null,
+    Boolean(bool),
+    Number(f64),
+    BigInt(BigInt),
+    String(String),
+    Symbol(Option<String>),
+    Array(Vec<JsOwnedValue>),
+    Object(IndexMap<String, JsOwnedValue>),
+    /// A `Map`, with its entries in insertion order.
+    Map(Vec<(JsOwnedValue, JsOwnedValue)>),
+    /// A `Set`, with its values in insertion order.
+    Set(Vec<JsOwnedValue>),
+    /// A `Date`, as milliseconds since the Unix epoch (`NaN` for an invalid date).
+    Date(f64),
+    /// The contents of a `Uint8Array`.
+    Bytes(Vec<u8>),
+    Function(JsFunctionInfo),
+    /// A live object held by a [`JsHandle`]. Snapshots never contain one;
+    /// embedders use it to pass an existing object back to scripts.
+    Handle(JsHandle),
+}
+
+impl JsOwnedValue {
+    /// Returns the `typeof` string for this value.
+    pub fn type_of(&self) -> &'static str {
+        match self {
+            JsOwnedValue::Undefined => "undefined",
+            JsOwnedValue::Null
+            | JsOwnedValue::Array(_)
+            | JsOwnedValue::Object(_)
+            | JsOwnedValue::Map(_)
+            | JsOwnedValue::Set(_)
+            | JsOwnedValue::Date(_)
+            | JsOwnedValue::Bytes(_) => "object",
+            JsOwnedValue::Boolean(_) => "boolean",
+            JsOwnedValue::Number(_) => "number",
+            JsOwnedValue::BigInt(_) => "bigint",
+            JsOwnedValue::String(_) => "string",
+            JsOwnedValue::Symbol(_) => "symbol",
+            JsOwnedValue::Function(_) => "function",
+            JsOwnedValue::Handle(handle) if handle.is_callable() => "function",
+            JsOwnedValue::Handle(_) => "object",
+        }
+    }
+
+    pub fn is_undefined(&self) -> bool {
+        matches!(self, JsOwnedValue::Undefined)
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, JsOwnedValue::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsOwnedValue::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsOwnedValue::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_bigint(&self) -> Option<&BigInt> {
+        match self {
+            JsOwnedValue::BigInt(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsOwnedValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[JsOwnedValue]> {
+        match self {
+            JsOwnedValue::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&IndexMap<String, JsOwnedValue>> {
+        match self {
+            JsOwnedValue::Object(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    pub fn as_map(&self) -> Option<&[(JsOwnedValue, JsOwnedValue)]> {
+        match self {
+            JsOwnedValue::Map(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    pub fn as_set(&self) -> Option<&[JsOwnedValue]> {
+        match self {
+            JsOwnedValue::Set(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            JsOwnedValue::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    pub fn as_handle(&self) -> Option<&JsHandle> {
+        match self {
+            JsOwnedValue::Handle(handle) => Some(handle),
+            _ => None,
+        }
+    }
+
+    /// Look up an own property of an `Object` value.
+    pub fn get(&self, key: &str) -> Option<&JsOwnedValue> {
+        self.as_object().and_then(|map| map.get(key))
+    }
+}
+
+impl From<bool> for JsOwnedValue {
+    fn from(b: bool) -> Self {
+        JsOwnedValue::Boolean(b)
+    }
+}
+
+impl From<f64> for JsOwnedValue {
+    fn from(n: f64) -> Self {
+        JsOwnedValue::Number(n)
+    }
+}
+
+impl From<i32> for JsOwnedValue {
+    fn from(n: i32) -> Self {
+        JsOwnedValue::Number(n as f64)
+    }
+}
+
+impl From<u32> for JsOwnedValue {
+    fn from(n: u32) -> Self {
+        JsOwnedValue::Number(n as f64)
+    }
+}
+
+impl From<BigInt> for JsOwnedValue {
+    fn from(b: BigInt) -> Self {
+        JsOwnedValue::BigInt(b)
+    }
+}
+
+impl From<&str> for JsOwnedValue {
+    fn from(s: &str) -> Self {
+        JsOwnedValue::String(s.to_string())
+    }
+}
+
+impl From<String> for JsOwnedValue {
+    fn from(s: String) -> Self {
+        JsOwnedValue::String(s)
+    }
+}
+
+impl From<()> for JsOwnedValue {
+    fn from(_: ()) -> Self {
+        JsOwnedValue::Undefined
+    }
+}
+
+impl<T: Into<JsOwnedValue>> From<Option<T>> for JsOwnedValue {
+    fn from(v: Option<T>) -> Self {
+        v.map(Into::into).unwrap_or(JsOwnedValue::Null)
+    }
+}
+
+impl<T: Into<JsOwnedValue>> From<Vec<T>> for JsOwnedValue {
+    fn from(items: Vec<T>) -> Self {
+        JsOwnedValue::Array(items.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<IndexMap<String, JsOwnedValue>> for JsOwnedValue {
+    fn from(map: IndexMap<String, JsOwnedValue>) -> Self {
+        JsOwnedValue::Object(map)
+    }
+}
+
+impl From<JsHandle> for JsOwnedValue {
+    fn from(handle: JsHandle) -> Self {
+        JsOwnedValue::Handle(handle)
+    }
+}
+
+impl From<&JsHandle> for JsOwnedValue {
+    fn from(handle: &JsHandle) -> Self {
+        JsOwnedValue::Handle(handle.clone())
+    }
+}
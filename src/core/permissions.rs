@@ -0,0 +1,216 @@
+use std::path::{Component, Path, PathBuf};
+
+/// What scripts may do through the `os` and `std` namespaces, set with
+/// [`Repl::set_permissions`](crate::Repl::set_permissions) or
+/// [`EvalOptions::permissions`](crate::EvalOptions::permissions).
+///
+/// Without either, scripts may do everything. Start from
+/// [`Permissions::none`] to run untrusted code and grant what it needs:
+/// reading and writing files under given directories, reading environment
+/// variables with `std.getenv`, process information (`os.getcwd`,
+/// `os.getpid`, `os.getppid`) and temporary files from `std.tmpfile`. A
+/// denied operation throws an `Error` named `PermissionDenied` that scripts
+/// can catch.
+///
+/// Paths are compared after resolving them against the current directory,
+/// `.` and `..` components and symbolic links, so `/data/../etc` is not
+/// under `/data`. The pure `os.path` helpers and timers need no permission;
+/// imports are read by the [`ModuleLoader`](crate::ModuleLoader), which is
+/// up to the embedder.
+///
+/// ```
+/// use javascript::{Permissions, Repl};
+///
+/// let mut repl = Repl::new();
+/// repl.set_permissions(Permissions::none().allow_read("/data"));
+/// let out = repl
+///     .eval("try { os.open('/etc/passwd', 0); } catch (e) { `${e.name}: ${e.message}` }")
+///     .unwrap();
+/// assert_eq!(out, "\"PermissionDenied: Requires read access to \\\"/etc/passwd\\\"\"");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Permissions {
+    read: Grant<PathBuf>,
+    write: Grant<PathBuf>,
+    env: Grant<String>,
+    sys: bool,
+    #[cfg(feature = "std")]
+    tmpfile: bool,
+}
+
+/// Access to a kind of resource: none, all of it, or only the listed ones.
+#[derive(Clone, Debug, PartialEq)]
+enum Grant<T> {
+    None,
+    All,
+    Only(Vec<T>),
+}
+
+impl<T> Grant<T> {
+    fn add(&mut self, item: T) {
+        match self {
+            Grant::None => *self = Grant::Only(vec![item]),
+            Grant::All => {}
+            Grant::Only(items) => items.push(item),
+        }
+    }
+}
+
+/// An operation that needs a permission, with the resource it touches.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Access<'a> {
+    Read(&'a Path),
+    Write(&'a Path),
+    Env(&'a str),
+    Sys(&'a str),
+    #[cfg(feature = "std")]
+    Tmpfile,
+}
+
+impl Default for Permissions {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl Permissions {
+    /// Allow everything, which is what scripts get unless told otherwise.
+    pub fn all() -> Self {
+        Permissions {
+            read: Grant::All,
+            write: Grant::All,
+            env: Grant::All,
+            sys: true,
+            #[cfg(feature = "std")]
+            tmpfile: true,
+        }
+    }
+
+    /// Deny everything; grant access back with the `allow_*` methods.
+    pub fn none() -> Self {
+        Permissions {
+            read: Grant::None,
+            write: Grant::None,
+            env: Grant::None,
+            sys: false,
+            #[cfg(feature = "std")]
+            tmpfile: false,
+        }
+    }
+
+    /// Allow reading files at or under `path`. Relative paths are taken
+    /// from the current directory at the time of the call.
+    pub fn allow_read<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.read.add(resolve_path(path.as_ref()));
+        self
+    }
+
+    /// Allow reading any file.
+    pub fn allow_read_all(mut self) -> Self {
+        self.read = Grant::All;
+        self
+    }
+
+    /// Allow creating and writing files at or under `path`. Relative paths
+    /// are taken from the current directory at the time of the call.
+    pub fn allow_write<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.write.add(resolve_path(path.as_ref()));
+        self
+    }
+
+    /// Allow creating and writing any file.
+    pub fn allow_write_all(mut self) -> Self {
+        self.write = Grant::All;
+        self
+    }
+
+    /// Allow reading the environment variable `name`.
+    pub fn allow_env<S: Into<String>>(mut self, name: S) -> Self {
+        self.env.add(name.into());
+        self
+    }
+
+    /// Allow reading any environment variable.
+    pub fn allow_env_all(mut self) -> Self {
+        self.env = Grant::All;
+        self
+    }
+
+    /// Allow reading process information: `os.getcwd`, `os.getpid` and `os.getppid`.
+    pub fn allow_sys(mut self) -> Self {
+        self.sys = true;
+        self
+    }
+
+    /// Allow creating temporary files with `std.tmpfile`.
+    #[cfg(feature = "std")]
+    pub fn allow_tmpfile(mut self) -> Self {
+        self.tmpfile = true;
+        self
+    }
+
+    /// `Ok` if `access` is allowed, or the message of the `PermissionDenied` error.
+    pub(crate) fn check(&self, access: Access<'_>) -> Result<(), String> {
+        let allowed = match access {
+            Access::Read(path) => path_allowed(&self.read, path),
+            Access::Write(path) => path_allowed(&self.write, path),
+            Access::Env(name) => match &self.env {
+                Grant::None => false,
+                Grant::All => true,
+                Grant::Only(names) => names.iter().any(|allowed| allowed == name),
+            },
+            Access::Sys(_) => self.sys,
+            #[cfg(feature = "std")]
+            Access::Tmpfile => self.tmpfile,
+        };
+        if allowed {
+            return Ok(());
+        }
+        Err(match access {
+            Access::Read(path) => format!("Requires read access to \"{}\"", path.display()),
+            Access::Write(path) => format!("Requires write access to \"{}\"", path.display()),
+            Access::Env(name) => format!("Requires env access to \"{name}\""),
+            Access::Sys(name) => format!("Requires sys access to \"{name}\""),
+            #[cfg(feature = "std")]
+            Access::Tmpfile => "Requires tmpfile access".to_string(),
+        })
+    }
+}
+
+fn path_allowed(grant: &Grant<PathBuf>, path: &Path) -> bool {
+    match grant {
+        Grant::None => false,
+        Grant::All => true,
+        Grant::Only(prefixes) => {
+            let path = resolve_path(path);
+            prefixes.iter().any(|prefix| path.starts_with(prefix))
+        }
+    }
+}
+
+/// Make `path` absolute and resolve it one component at a time, as the
+/// filesystem does: a symbolic link is followed as soon as it is reached, so
+/// a `..` after it leaves the directory the link points to. Components that
+/// do not exist are kept as written.
+pub(crate) fn resolve_path(path: &Path) -> PathBuf {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().unwrap_or_default().join(path)
+    };
+    let mut resolved = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::Normal(name) => {
+                let next = resolved.join(name);
+                resolved = next.canonicalize().unwrap_or(next);
+            }
+            other => resolved.push(other),
+        }
+    }
+    resolved
+}
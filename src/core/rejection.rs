@@ -0,0 +1,53 @@
+use crate::JSError;
+use std::rc::Rc;
+
+/// Receives promise rejection events; returning `Err` ends the running
+/// evaluation with that error.
+pub(crate) type RejectionCallback = Rc<dyn Fn(PromiseRejection) -> Result<(), JSError>>;
+
+/// What happened to a rejected promise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RejectionEvent {
+    /// The promise was rejected and still had no handler once the microtask
+    /// queue drained (`unhandledrejection`).
+    Unhandled,
+    /// A handler was attached to a promise previously reported as
+    /// unhandled (`rejectionhandled`).
+    Handled,
+}
+
+/// A promise rejection reported to the tracker set with
+/// [`Repl::set_rejection_tracker`](crate::Repl::set_rejection_tracker) or
+/// [`EvalOptions::rejection_tracker`](crate::EvalOptions::rejection_tracker).
+#[derive(Debug)]
+pub struct PromiseRejection {
+    event: RejectionEvent,
+    id: u64,
+    error: JSError,
+}
+
+impl PromiseRejection {
+    pub(crate) fn new(event: RejectionEvent, id: u64, error: JSError) -> Self {
+        PromiseRejection { event, id, error }
+    }
+
+    pub fn event(&self) -> RejectionEvent {
+        self.event
+    }
+
+    /// Identifies the promise, so a [`RejectionEvent::Handled`] can be
+    /// matched with the earlier [`RejectionEvent::Unhandled`].
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// The rejection reason, converted as if it had been thrown.
+    pub fn error(&self) -> &JSError {
+        &self.error
+    }
+
+    /// Take the reason, e.g. to fail the evaluation with it.
+    pub fn into_error(self) -> JSError {
+        self.error
+    }
+}
@@ -0,0 +1,86 @@
+use crate::JSError;
+use crate::core::{Chunk, Compiler, EvalOptions, HeapImage, JsArenaVm, VM, parse_program_statements};
+use std::sync::Arc;
+
+/// A fully initialized VM heap that new VMs are restored from instead of
+/// registering every builtin again. Use one with
+/// [`EvalOptions::snapshot`](crate::EvalOptions::snapshot) or
+/// [`Repl::from_snapshot`](crate::Repl::from_snapshot).
+///
+/// A snapshot can also hold the globals left behind by a prelude script, so
+/// helper libraries are set up once rather than on every run. Snapshots are
+/// cheap to clone and can be shared between threads; [`to_bytes`](Self::to_bytes)
+/// writes one out, for example at build time, and [`from_bytes`](Self::from_bytes)
+/// loads it back in the same engine version.
+///
+/// ```
+/// use javascript::{EvalOptions, Snapshot, evaluate_script_with_options};
+///
+/// let snapshot = Snapshot::with_prelude("function greet(name) { return `hello, ${name}`; }").unwrap();
+/// let snapshot = Snapshot::from_bytes(&snapshot.to_bytes()).unwrap();
+/// let options = EvalOptions::new().snapshot(snapshot);
+/// let out = evaluate_script_with_options("greet('world')", false, None::<&std::path::Path>, &options).unwrap();
+/// assert_eq!(out, "\"hello, world\"");
+/// ```
+#[derive(Clone)]
+pub struct Snapshot {
+    image: Arc<HeapImage>,
+}
+
+impl Default for Snapshot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Snapshot {
+    /// Capture a VM with just the builtins set up.
+    pub fn new() -> Self {
+        Self::capture(None).expect("a fresh VM can always be captured")
+    }
+
+    /// Run `prelude` as a classic script and capture the VM it leaves behind.
+    ///
+    /// Later scripts see what the prelude put on the global object: function
+    /// and `var` declarations and properties of `globalThis`. The prelude must
+    /// finish all its work: pending timers, suspended generators or async
+    /// functions, imported modules and new realms cannot be captured.
+    pub fn with_prelude(prelude: &str) -> Result<Self, JSError> {
+        Self::capture(Some(prelude))
+    }
+
+    fn capture(prelude: Option<&str>) -> Result<Self, JSError> {
+        let mut arena = JsArenaVm::new(|ctx| VM::new(Chunk::new(), ctx));
+        let image = arena.mutate_root(|ctx, vm| {
+            if let Some(prelude) = prelude {
+                let statements = parse_program_statements(prelude, false)?;
+                let mut compiler = Compiler::new();
+                compiler.set_source_text(prelude.to_string());
+                vm.chunk = compiler.compile(&statements)?;
+                super::apply_eval_options(vm, &EvalOptions::default());
+                vm.set_source_context(prelude, None);
+                vm.run(ctx)?;
+            }
+            vm.capture_heap_image()
+        })?;
+        Ok(Snapshot { image: Arc::new(image) })
+    }
+
+    /// Serialize the snapshot for [`Snapshot::from_bytes`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.image.to_bytes()
+    }
+
+    /// Load a snapshot written by [`Snapshot::to_bytes`]. Images from another
+    /// engine version or a build with other builtin features, and damaged
+    /// images, are rejected.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, JSError> {
+        Ok(Snapshot {
+            image: Arc::new(HeapImage::from_bytes(bytes)?),
+        })
+    }
+
+    pub(crate) fn image(&self) -> &HeapImage {
+        &self.image
+    }
+}
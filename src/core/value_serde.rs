@@ -0,0 +1,762 @@
+//! serde support for [`JsOwnedValue`]: serialize any `T: Serialize` into a JS
+//! value and deserialize JS values into Rust types, reporting failures with the
+//! JS path of the offending value (e.g. `config.servers[2].port`).
+
+use crate::JSError;
+use crate::core::JsOwnedValue;
+use indexmap::IndexMap;
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Unexpected, Visitor};
+use serde::ser::{self, Serialize};
+use std::fmt;
+
+/// Largest integer a JS number represents exactly (`Number.MAX_SAFE_INTEGER`).
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+
+/// Serialize `value` into a [`JsOwnedValue`].
+///
+/// Structs and maps with string keys become plain objects, maps with other
+/// keys become a `Map`, sequences and tuples become arrays and byte buffers a
+/// `Uint8Array`. Integers outside the safe range become `BigInt`s. Enums use
+/// serde's externally tagged form (`"Unit"` or `{ "Variant": value }`).
+pub fn to_js_value<T: Serialize + ?Sized>(value: &T) -> Result<JsOwnedValue, SerdeError> {
+    value.serialize(ValueSerializer)
+}
+
+/// Deserialize a `T` from a [`JsOwnedValue`].
+///
+/// `undefined` and `null` both read as `None`/unit, integral numbers and
+/// `BigInt`s feed integer types (range-checked), and a `Date` reads as its
+/// epoch milliseconds or, for string targets, its ISO 8601 form.
+pub fn from_js_value<'de, T: de::Deserialize<'de>>(value: &'de JsOwnedValue) -> Result<T, SerdeError> {
+    T::deserialize(ValueDeserializer { value })
+}
+
+/// A serde conversion failure, with the JS path of the value that caused it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerdeError {
+    path: Vec<PathSegment>,
+    message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+impl SerdeError {
+    /// The path of the offending value, e.g. `config.servers[2].port`; empty for the root.
+    pub fn path(&self) -> String {
+        let mut out = String::new();
+        for segment in &self.path {
+            match segment {
+                PathSegment::Index(index) => out.push_str(&format!("[{index}]")),
+                PathSegment::Key(key) if crate::core::is_identifier_name(key) => {
+                    if !out.is_empty() {
+                        out.push('.');
+                    }
+                    out.push_str(key);
+                }
+                PathSegment::Key(key) => out.push_str(&format!("[{}]", serde_json::Value::from(key.as_str()))),
+            }
+        }
+        out
+    }
+
+    /// The failure without the path.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    fn at(mut self, segment: PathSegment) -> Self {
+        self.path.insert(0, segment);
+        self
+    }
+}
+
+impl fmt::Display for SerdeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path(), self.message)
+        }
+    }
+}
+
+impl std::error::Error for SerdeError {}
+
+impl de::Error for SerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        SerdeError {
+            path: Vec::new(),
+            message: msg.to_string(),
+        }
+    }
+}
+
+impl ser::Error for SerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        <SerdeError as de::Error>::custom(msg)
+    }
+}
+
+impl From<SerdeError> for JSError {
+    fn from(err: SerdeError) -> Self {
+        crate::raise_type_error!(err.to_string())
+    }
+}
+
+fn key_segment(key: &JsOwnedValue) -> PathSegment {
+    match key {
+        JsOwnedValue::String(s) => PathSegment::Key(s.clone()),
+        JsOwnedValue::Number(n) if n.fract() == 0.0 && *n >= 0.0 && *n <= MAX_SAFE_INTEGER => PathSegment::Index(*n as usize),
+        other => PathSegment::Key(format!("{other:?}")),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Serialization
+// ---------------------------------------------------------------------------
+
+struct ValueSerializer;
+
+fn integer_value(n: impl Into<BigInt> + Copy, as_f64: f64) -> JsOwnedValue {
+    if as_f64.abs() <= MAX_SAFE_INTEGER {
+        JsOwnedValue::Number(as_f64)
+    } else {
+        JsOwnedValue::BigInt(n.into())
+    }
+}
+
+fn tagged(variant: &str, value: JsOwnedValue) -> JsOwnedValue {
+    let mut map = IndexMap::new();
+    map.insert(variant.to_string(), value);
+    JsOwnedValue::Object(map)
+}
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = JsOwnedValue;
+    type Error = SerdeError;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = SeqSerializer;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = StructSerializer;
+    type SerializeStructVariant = StructSerializer;
+
+    fn serialize_bool(self, v: bool) -> Result<JsOwnedValue, SerdeError> {
+        Ok(JsOwnedValue::Boolean(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<JsOwnedValue, SerdeError> {
+        Ok(JsOwnedValue::Number(v.into()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<JsOwnedValue, SerdeError> {
+        Ok(JsOwnedValue::Number(v.into()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<JsOwnedValue, SerdeError> {
+        Ok(JsOwnedValue::Number(v.into()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<JsOwnedValue, SerdeError> {
+        Ok(integer_value(v, v as f64))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<JsOwnedValue, SerdeError> {
+        Ok(integer_value(v, v as f64))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<JsOwnedValue, SerdeError> {
+        Ok(JsOwnedValue::Number(v.into()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<JsOwnedValue, SerdeError> {
+        Ok(JsOwnedValue::Number(v.into()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<JsOwnedValue, SerdeError> {
+        Ok(JsOwnedValue::Number(v.into()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<JsOwnedValue, SerdeError> {
+        Ok(integer_value(v, v as f64))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<JsOwnedValue, SerdeError> {
+        Ok(integer_value(v, v as f64))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<JsOwnedValue, SerdeError> {
+        Ok(JsOwnedValue::Number(v.into()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<JsOwnedValue, SerdeError> {
+        Ok(JsOwnedValue::Number(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<JsOwnedValue, SerdeError> {
+        Ok(JsOwnedValue::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<JsOwnedValue, SerdeError> {
+        Ok(JsOwnedValue::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<JsOwnedValue, SerdeError> {
+        Ok(JsOwnedValue::Bytes(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<JsOwnedValue, SerdeError> {
+        Ok(JsOwnedValue::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<JsOwnedValue, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<JsOwnedValue, SerdeError> {
+        Ok(JsOwnedValue::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<JsOwnedValue, SerdeError> {
+        Ok(JsOwnedValue::Null)
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<JsOwnedValue, SerdeError> {
+        Ok(JsOwnedValue::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<JsOwnedValue, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<JsOwnedValue, SerdeError> {
+        let value = value
+            .serialize(ValueSerializer)
+            .map_err(|err| err.at(PathSegment::Key(variant.to_string())))?;
+        Ok(tagged(variant, value))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer, SerdeError> {
+        Ok(SeqSerializer {
+            items: Vec::with_capacity(len.unwrap_or(0)),
+            variant: None,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SeqSerializer, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer, SerdeError> {
+        Ok(SeqSerializer {
+            items: Vec::with_capacity(len),
+            variant: Some(variant),
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<MapSerializer, SerdeError> {
+        Ok(MapSerializer {
+            entries: Vec::with_capacity(len.unwrap_or(0)),
+            next_key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<StructSerializer, SerdeError> {
+        Ok(StructSerializer {
+            fields: IndexMap::with_capacity(len),
+            variant: None,
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<StructSerializer, SerdeError> {
+        Ok(StructSerializer {
+            fields: IndexMap::with_capacity(len),
+            variant: Some(variant),
+        })
+    }
+}
+
+struct SeqSerializer {
+    items: Vec<JsOwnedValue>,
+    variant: Option<&'static str>,
+}
+
+impl SeqSerializer {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let index = self.items.len();
+        let value = value.serialize(ValueSerializer).map_err(|err| {
+            let err = err.at(PathSegment::Index(index));
+            match self.variant {
+                Some(variant) => err.at(PathSegment::Key(variant.to_string())),
+                None => err,
+            }
+        })?;
+        self.items.push(value);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<JsOwnedValue, SerdeError> {
+        let array = JsOwnedValue::Array(self.items);
+        Ok(match self.variant {
+            Some(variant) => tagged(variant, array),
+            None => array,
+        })
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = JsOwnedValue;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<JsOwnedValue, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = JsOwnedValue;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<JsOwnedValue, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = JsOwnedValue;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<JsOwnedValue, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SeqSerializer {
+    type Ok = JsOwnedValue;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<JsOwnedValue, SerdeError> {
+        self.finish()
+    }
+}
+
+/// Collects map entries; they become an object when every key is a string
+/// and a `Map` otherwise.
+struct MapSerializer {
+    entries: Vec<(JsOwnedValue, JsOwnedValue)>,
+    next_key: Option<JsOwnedValue>,
+}
+
+impl ser::SerializeMap for MapSerializer {
+    type Ok = JsOwnedValue;
+    type Error = SerdeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerdeError> {
+        self.next_key = Some(key.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let key = self
+            .next_key
+            .take()
+            .ok_or_else(|| <SerdeError as ser::Error>::custom("serialize_value called before serialize_key"))?;
+        let value = value.serialize(ValueSerializer).map_err(|err| err.at(key_segment(&key)))?;
+        self.entries.push((key, value));
+        Ok(())
+    }
+
+    fn end(self) -> Result<JsOwnedValue, SerdeError> {
+        if !self.entries.iter().all(|(key, _)| matches!(key, JsOwnedValue::String(_))) {
+            return Ok(JsOwnedValue::Map(self.entries));
+        }
+        let mut map = IndexMap::with_capacity(self.entries.len());
+        for (key, value) in self.entries {
+            if let JsOwnedValue::String(key) = key {
+                map.insert(key, value);
+            }
+        }
+        Ok(JsOwnedValue::Object(map))
+    }
+}
+
+struct StructSerializer {
+    fields: IndexMap<String, JsOwnedValue>,
+    variant: Option<&'static str>,
+}
+
+impl StructSerializer {
+    fn field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), SerdeError> {
+        let value = value.serialize(ValueSerializer).map_err(|err| {
+            let err = err.at(PathSegment::Key(key.to_string()));
+            match self.variant {
+                Some(variant) => err.at(PathSegment::Key(variant.to_string())),
+                None => err,
+            }
+        })?;
+        self.fields.insert(key.to_string(), value);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<JsOwnedValue, SerdeError> {
+        let object = JsOwnedValue::Object(self.fields);
+        Ok(match self.variant {
+            Some(variant) => tagged(variant, object),
+            None => object,
+        })
+    }
+}
+
+impl ser::SerializeStruct for StructSerializer {
+    type Ok = JsOwnedValue;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), SerdeError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<JsOwnedValue, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for StructSerializer {
+    type Ok = JsOwnedValue;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), SerdeError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<JsOwnedValue, SerdeError> {
+        self.finish()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Deserialization
+// ---------------------------------------------------------------------------
+
+struct ValueDeserializer<'de> {
+    value: &'de JsOwnedValue,
+}
+
+impl<'de> ValueDeserializer<'de> {
+    fn unexpected(&self) -> Unexpected<'de> {
+        match self.value {
+            JsOwnedValue::Undefined => Unexpected::Other("undefined"),
+            JsOwnedValue::Null => Unexpected::Unit,
+            JsOwnedValue::Boolean(b) => Unexpected::Bool(*b),
+            JsOwnedValue::Number(n) => Unexpected::Float(*n),
+            JsOwnedValue::BigInt(_) => Unexpected::Other("BigInt"),
+            JsOwnedValue::String(s) => Unexpected::Str(s),
+            JsOwnedValue::Symbol(_) => Unexpected::Other("symbol"),
+            JsOwnedValue::Array(_) | JsOwnedValue::Set(_) => Unexpected::Seq,
+            JsOwnedValue::Object(_) | JsOwnedValue::Map(_) => Unexpected::Map,
+            JsOwnedValue::Date(_) => Unexpected::Other("Date"),
+            JsOwnedValue::Bytes(b) => Unexpected::Bytes(b),
+            JsOwnedValue::Function(_) => Unexpected::Other("function"),
+            JsOwnedValue::Handle(_) => Unexpected::Other("JsHandle"),
+        }
+    }
+}
+
+fn visit_bigint<'de, V: Visitor<'de>>(b: &BigInt, visitor: V) -> Result<V::Value, SerdeError> {
+    if let Some(n) = b.to_u64() {
+        visitor.visit_u64(n)
+    } else if let Some(n) = b.to_i64() {
+        visitor.visit_i64(n)
+    } else if let Some(n) = b.to_u128() {
+        visitor.visit_u128(n)
+    } else if let Some(n) = b.to_i128() {
+        visitor.visit_i128(n)
+    } else {
+        Err(de::Error::custom(format!("BigInt {b} does not fit in 128 bits")))
+    }
+}
+
+fn date_to_iso_string(ms: f64) -> Option<String> {
+    if !ms.is_finite() {
+        return None;
+    }
+    chrono::DateTime::from_timestamp_millis(ms as i64).map(|dt| dt.to_rfc3339_opts(chrono::SecondsFormat::Millis, true))
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.value {
+            JsOwnedValue::Undefined | JsOwnedValue::Null => visitor.visit_unit(),
+            JsOwnedValue::Boolean(b) => visitor.visit_bool(*b),
+            JsOwnedValue::Number(n) if n.fract() == 0.0 && *n >= 0.0 && *n <= MAX_SAFE_INTEGER => visitor.visit_u64(*n as u64),
+            JsOwnedValue::Number(n) if n.fract() == 0.0 && *n < 0.0 && *n >= -MAX_SAFE_INTEGER => visitor.visit_i64(*n as i64),
+            JsOwnedValue::Number(n) | JsOwnedValue::Date(n) => visitor.visit_f64(*n),
+            JsOwnedValue::BigInt(b) => visit_bigint(b, visitor),
+            JsOwnedValue::String(s) => visitor.visit_borrowed_str(s),
+            JsOwnedValue::Array(items) | JsOwnedValue::Set(items) => visitor.visit_seq(SeqAccess {
+                items: items.iter(),
+                index: 0,
+            }),
+            JsOwnedValue::Object(map) => visitor.visit_map(MapAccess {
+                entries: Box::new(map.iter().map(|(k, v)| (MapKey::Str(k), v))),
+                value: None,
+            }),
+            JsOwnedValue::Map(entries) => visitor.visit_map(MapAccess {
+                entries: Box::new(entries.iter().map(|(k, v)| (MapKey::Value(k), v))),
+                value: None,
+            }),
+            JsOwnedValue::Bytes(bytes) => visitor.visit_borrowed_bytes(bytes),
+            JsOwnedValue::Symbol(_) | JsOwnedValue::Function(_) | JsOwnedValue::Handle(_) => {
+                Err(de::Error::invalid_type(self.unexpected(), &visitor))
+            }
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.value {
+            JsOwnedValue::Undefined | JsOwnedValue::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.value {
+            JsOwnedValue::Date(ms) => match date_to_iso_string(*ms) {
+                Some(iso) => visitor.visit_string(iso),
+                None => Err(de::Error::invalid_value(Unexpected::Other("invalid Date"), &visitor)),
+            },
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.value {
+            JsOwnedValue::Bytes(bytes) => visitor.visit_seq(de::value::SeqDeserializer::new(bytes.iter().copied())),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        match self.value {
+            JsOwnedValue::String(variant) => visitor.visit_enum(EnumAccess { variant, value: None }),
+            JsOwnedValue::Object(map) if map.len() == 1 => {
+                let (variant, value) = map.iter().next().expect("map has one entry");
+                visitor.visit_enum(EnumAccess {
+                    variant,
+                    value: Some(value),
+                })
+            }
+            _ => Err(de::Error::invalid_type(self.unexpected(), &"a variant name or a single-key object")),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char bytes byte_buf
+        unit unit_struct tuple tuple_struct map struct identifier
+    }
+}
+
+impl<'de> IntoDeserializer<'de, SerdeError> for &'de JsOwnedValue {
+    type Deserializer = ValueDeserializer<'de>;
+
+    fn into_deserializer(self) -> ValueDeserializer<'de> {
+        ValueDeserializer { value: self }
+    }
+}
+
+struct SeqAccess<'de> {
+    items: std::slice::Iter<'de, JsOwnedValue>,
+    index: usize,
+}
+
+impl<'de> de::SeqAccess<'de> for SeqAccess<'de> {
+    type Error = SerdeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, SerdeError> {
+        let Some(value) = self.items.next() else {
+            return Ok(None);
+        };
+        let index = self.index;
+        self.index += 1;
+        seed.deserialize(ValueDeserializer { value })
+            .map(Some)
+            .map_err(|err| err.at(PathSegment::Index(index)))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+enum MapKey<'de> {
+    Str(&'de str),
+    Value(&'de JsOwnedValue),
+}
+
+impl MapKey<'_> {
+    fn segment(&self) -> PathSegment {
+        match self {
+            MapKey::Str(key) => PathSegment::Key(key.to_string()),
+            MapKey::Value(key) => key_segment(key),
+        }
+    }
+}
+
+struct MapAccess<'de> {
+    entries: Box<dyn Iterator<Item = (MapKey<'de>, &'de JsOwnedValue)> + 'de>,
+    value: Option<(PathSegment, &'de JsOwnedValue)>,
+}
+
+impl<'de> de::MapAccess<'de> for MapAccess<'de> {
+    type Error = SerdeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, SerdeError> {
+        let Some((key, value)) = self.entries.next() else {
+            return Ok(None);
+        };
+        let segment = key.segment();
+        let result = match key {
+            MapKey::Str(key) => seed.deserialize(de::value::BorrowedStrDeserializer::new(key)),
+            MapKey::Value(key) => seed.deserialize(ValueDeserializer { value: key }),
+        };
+        let key = result.map_err(|err| err.at(segment.clone()))?;
+        self.value = Some((segment, value));
+        Ok(Some(key))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, SerdeError> {
+        let (segment, value) = self
+            .value
+            .take()
+            .ok_or_else(|| <SerdeError as de::Error>::custom("next_value_seed called before next_key_seed"))?;
+        seed.deserialize(ValueDeserializer { value }).map_err(|err| err.at(segment))
+    }
+}
+
+struct EnumAccess<'de> {
+    variant: &'de str,
+    value: Option<&'de JsOwnedValue>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumAccess<'de> {
+    type Error = SerdeError;
+    type Variant = VariantAccess<'de>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, VariantAccess<'de>), SerdeError> {
+        let variant = seed.deserialize(de::value::BorrowedStrDeserializer::<SerdeError>::new(self.variant))?;
+        Ok((
+            variant,
+            VariantAccess {
+                variant: self.variant,
+                value: self.value,
+            },
+        ))
+    }
+}
+
+struct VariantAccess<'de> {
+    variant: &'de str,
+    value: Option<&'de JsOwnedValue>,
+}
+
+impl<'de> VariantAccess<'de> {
+    fn payload(&self, expected: &str) -> Result<ValueDeserializer<'de>, SerdeError> {
+        match self.value {
+            Some(value) => Ok(ValueDeserializer { value }),
+            None => Err(de::Error::invalid_type(Unexpected::UnitVariant, &expected)),
+        }
+    }
+
+    fn at_variant(&self, err: SerdeError) -> SerdeError {
+        err.at(PathSegment::Key(self.variant.to_string()))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for VariantAccess<'de> {
+    type Error = SerdeError;
+
+    fn unit_variant(self) -> Result<(), SerdeError> {
+        match self.value {
+            None | Some(JsOwnedValue::Undefined | JsOwnedValue::Null) => Ok(()),
+            Some(value) => Err(self.at_variant(de::Error::invalid_type(ValueDeserializer { value }.unexpected(), &"a unit variant"))),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, SerdeError> {
+        let payload = self.payload("a newtype variant")?;
+        seed.deserialize(payload).map_err(|err| self.at_variant(err))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, SerdeError> {
+        let payload = self.payload("a tuple variant")?;
+        de::Deserializer::deserialize_seq(payload, visitor).map_err(|err| self.at_variant(err))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, SerdeError> {
+        let payload = self.payload("a struct variant")?;
+        de::Deserializer::deserialize_map(payload, visitor).map_err(|err| self.at_variant(err))
+    }
+}
@@ -8,31 +8,48 @@ use crate::core::property_descriptor::{
     unmark_nonenumerable, unmark_readonly, write_attrs_to_legacy_map,
 };
 use crate::core::value::{VmArrayData, VmMapData, VmSetData, VmSymbolData, value_to_string};
-use crate::core::{Collect, Expr, GcTrace, JSError, Value, new_gc_cell_ptr};
+use crate::core::{Access, Collect, Expr, GcTrace, JSError, Value, new_gc_cell_ptr};
 use crate::core::{Gc, GcCell, GcContext, GcWeak};
 use indexmap::IndexMap;
 use std::fmt::Write as _;
 
+mod async_host;
 mod bigint;
+mod classes;
+mod clock;
+mod console;
+#[cfg(feature = "typed-arrays")]
 mod dataview;
 mod date;
+mod embed;
+mod event_loop;
+mod inspect;
+#[cfg(feature = "intl")]
 mod intl;
+mod limits;
+mod loader;
+mod os_files;
+mod permissions;
 mod regexp;
+mod rejection;
 mod runner;
+mod snapshot;
+#[cfg(feature = "temporal")]
 mod temporal;
 mod typedarray;
 mod uri;
 
 pub(crate) use bigint::{compare_bigint_number, parse_bigint_string};
+use clock::LocalZone;
+pub(crate) use limits::RunSlice;
 pub(crate) use regexp::get_or_compile_regex;
+pub(crate) use snapshot::HeapImage;
 use typedarray::coerce_typed_array_value;
 use typedarray::{coerce_bigint_for_ta, is_bigint_typed_array};
 use typedarray::{f16_bits_to_f64, f16round, f64_to_f16_bits};
 
 use std::collections::HashMap;
-use std::fs::File;
 use std::io::{Read, Seek, SeekFrom, Write};
-use std::sync::{LazyLock, Mutex};
 use unicode_normalization::UnicodeNormalization;
 
 type VmStrong<'gc, T> = Gc<'gc, GcCell<T>>;
@@ -48,21 +65,13 @@ pub type SetHandle<'gc> = VmStrong<'gc, VmSetData<'gc>>;
 pub type SymbolHandle<'gc> = VmStrong<'gc, VmSymbolData>;
 pub type VmSymbolWeakHandle<'gc> = VmWeak<'gc, VmSymbolData>;
 
-static VM_OS_FILE_STORE: LazyLock<Mutex<HashMap<u64, File>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
-static VM_NEXT_OS_FILE_ID: LazyLock<Mutex<u64>> = LazyLock::new(|| Mutex::new(1));
 const OWN_DUNDER_PROTO_DATA_KEY: &str = "__own_data___proto__";
 const IMPORT_META_SENTINEL_KEY: &str = "__import_meta_sentinel__";
 
-fn vm_next_os_file_id() -> u64 {
-    let mut id = VM_NEXT_OS_FILE_ID.lock().unwrap();
-    let current = *id;
-    *id += 1;
-    current
-}
-
 #[derive(Debug, Clone)]
 pub struct CallFrame<'gc> {
     pub return_ip: usize,
+    pub call_ip: usize,                                  // Start of the instruction that made the call
     pub bp: usize,                                       // Base pointer
     pub is_method: bool,                                 // Pop this_stack on return
     pub arg_count: usize,                                // Actual number of arguments passed
@@ -425,12 +434,14 @@ fn js_exponential_format(s: &str) -> String {
     }
 }
 
-/// A queued timer callback (setTimeout / setInterval).
+/// A queued timer callback (setTimeout / setInterval). Ordered so the
+/// timer queue pops the earliest deadline first.
 struct PendingTimer<'gc> {
     id: usize,
     callback: Value<'gc>,
     args: Vec<Value<'gc>>,
     delay_ms: u64,
+    deadline: std::time::Instant,
     is_interval: bool,
 }
 
@@ -450,6 +461,16 @@ struct AsyncAtomicsWait<'gc> {
     times_out: bool,
 }
 
+/// Eval code compiled into a temporary VM that is ready to run.
+struct PreparedEval<'gc> {
+    vm: Box<VM<'gc>>,
+    /// The compiled eval code, merged into the caller's chunk afterwards.
+    chunk: Chunk<'gc>,
+    is_strict: bool,
+    /// The eval source itself starts with a "use strict" directive.
+    explicit_strict: bool,
+}
+
 /// Saved state for a suspended generator (used by suspendable sync generators).
 #[derive(Clone)]
 struct GeneratorState<'gc> {
@@ -563,6 +584,19 @@ unsafe impl<'gc> Collect<'gc> for Microtask<'gc> {
     }
 }
 
+unsafe impl<'gc> Collect<'gc> for AsyncAtomicsWait<'gc> {
+    fn trace<T: GcTrace<'gc>>(&self, cc: &mut T) {
+        self.promise.trace(cc);
+    }
+}
+
+unsafe impl<'gc> Collect<'gc> for PreparedEval<'gc> {
+    fn trace<T: GcTrace<'gc>>(&self, cc: &mut T) {
+        self.vm.trace(cc);
+        self.chunk.trace(cc);
+    }
+}
+
 unsafe impl<'gc> Collect<'gc> for GeneratorState<'gc> {
     fn trace<T: GcTrace<'gc>>(&self, cc: &mut T) {
         self.locals.trace(cc);
@@ -682,8 +716,11 @@ unsafe impl<'gc> Collect<'gc> for VM<'gc> {
         self.symbol_values.trace(cc);
         self.pending_throw.trace(cc);
         self.preserved_throw_value.trace(cc);
-        self.pending_timers.trace(cc);
+        for timer in self.pending_timers.iter() {
+            timer.trace(cc);
+        }
         self.microtask_queue.trace(cc);
+        self.async_atomics_waits.trace(cc);
         self.generator_states.trace(cc);
         self.async_function_states.trace(cc);
         self.generator_objects.trace(cc);
@@ -705,6 +742,17 @@ unsafe impl<'gc> Collect<'gc> for VM<'gc> {
             state.trace(cc);
         }
         self.intrinsic_promise_ctor.trace(cc);
+        self.intrinsic_map_ctor.trace(cc);
+        self.intrinsic_set_ctor.trace(cc);
+        self.intrinsic_date_ctor.trace(cc);
+        self.intrinsic_uint8array_ctor.trace(cc);
+        self.restricted_thrower_intrinsic.trace(cc);
+        self.template_cache.trace(cc);
+        self.repl_eval.trace(cc);
+        self.handle_roots.trace(cc);
+        self.host_classes.trace(cc);
+        self.async_host.trace(cc);
+        self.rejections.trace(cc);
         for error in self.module_load_errors.values() {
             error.trace(cc);
         }
@@ -741,7 +789,6 @@ pub struct VM<'gc> {
     this_stack: Vec<Value<'gc>>,       // this binding stack
     new_target_stack: Vec<Value<'gc>>, // new.target binding stack
     super_called_stack: Vec<bool>,     // tracks whether super() was called in derived ctors
-    output: Vec<String>,               // captured output for console.log etc.
     // Property storage for Function values, keyed by function IP
     fn_props: HashMap<usize, ObjectHandle<'gc>>,
     // Maps Function IPs from child realms to their realm_id so get_fn_props
@@ -764,10 +811,10 @@ pub struct VM<'gc> {
     direct_eval: bool, // true when current eval is a direct call
     script_source: Option<String>,
     script_path: Option<String>,
-    // Timer queue for setTimeout / setInterval
-    pending_timers: Vec<PendingTimer<'gc>>,
-    next_timer_id: usize,
-    cleared_timers: std::collections::HashSet<usize>,
+    // Timer queue for setTimeout / setInterval, keyed by deadline
+    pending_timers: std::collections::BinaryHeap<PendingTimer<'gc>>,
+    /// Timer ids and event loop mode; shared with helper VMs.
+    timers: std::rc::Rc<event_loop::TimerState>,
     // Microtask queue for deferred .then() on settled promises
     microtask_queue: Vec<Microtask<'gc>>,
     // Main-thread async Atomics.waitAsync registrations.
@@ -817,6 +864,9 @@ pub struct VM<'gc> {
     // When true, DefineGlobal suppresses writes to globalThis for eval var declarations.
     // Set for direct eval inside a function so vars stay in the function scope.
     eval_fn_scope: bool,
+    // Set for eval VMs: the calling VM runs the event loop, so timers they
+    // schedule are handed to it and unhandled rejections are reported there.
+    defers_event_loop: bool,
     // Runtime brand counter for unique class brands per class evaluation
     runtime_brand_counter: usize,
     // Per-closure fn_props for class constructors evaluated multiple times (factory pattern).
@@ -850,6 +900,8 @@ pub struct VM<'gc> {
     realm_parent_ptr: Option<*mut VM<'gc>>,
     // REPL compatibility mode: allow lexical declarations to persist across snippets.
     repl_lexical_persist: bool,
+    /// The REPL snippet whose run paused to let the embedder collect garbage.
+    repl_eval: Option<Box<PreparedEval<'gc>>>,
     // When handle_throw catches a throw, the target stack depth is saved here.
     // run_inner uses this to re-truncate the stack after opcode handlers that
     // continue executing (and push values) after calling handle_throw.
@@ -893,6 +945,12 @@ pub struct VM<'gc> {
     module_request_depth: usize,
     /// Cached intrinsic %Promise% constructor used by dynamic import.
     intrinsic_promise_ctor: Value<'gc>,
+    /// Cached intrinsic %Map%, %Set%, %Date% and %Uint8Array% constructors used
+    /// to build embedder-supplied values; `undefined` when left out of the build.
+    intrinsic_map_ctor: Value<'gc>,
+    intrinsic_set_ctor: Value<'gc>,
+    intrinsic_date_ctor: Value<'gc>,
+    intrinsic_uint8array_ctor: Value<'gc>,
     /// Per-realm %ThrowTypeError% intrinsic shared by restricted function properties.
     restricted_thrower_intrinsic: Value<'gc>,
     /// When true, `run_opcode_await` at module top level exits early to support
@@ -904,6 +962,30 @@ pub struct VM<'gc> {
     suspended_module_states: Vec<SuspendedModuleState<'gc>>,
     /// Template cache: site_id → frozen template array (GetTemplateObject §13.2.8.3)
     template_cache: HashMap<u64, Value<'gc>>,
+    /// Embedder-registered Rust callbacks, indexed by the `embed.<n>` host function name.
+    host_callbacks: Vec<embed::HostCallback>,
+    /// Instruction budget and termination state, shared with helper VMs.
+    limits: std::rc::Rc<limits::ExecutionLimits>,
+    /// Console sink, groups, counters and timers; shared with helper VMs.
+    console: std::rc::Rc<console::ConsoleState>,
+    /// Module loader and its resolution cache; shared with helper VMs.
+    module_loader: std::rc::Rc<crate::core::ModuleLoaderState>,
+    /// Dynamic roots for values held by embedder `JsHandle`s.
+    handle_roots: gc_arena::DynamicRootSet<'gc>,
+    /// Outstanding `JsHandle` roots by id; shared with helper VMs.
+    handles: std::rc::Rc<crate::core::HandleTable>,
+    /// Host classes and their instance payloads; shared with helper VMs.
+    host_classes: std::rc::Rc<classes::HostClassState<'gc>>,
+    /// Async host functions and their unsettled promises; shared with helper VMs.
+    async_host: std::rc::Rc<async_host::AsyncHostState<'gc>>,
+    /// Rejected promises awaiting the unhandled-rejection check; shared with helper VMs.
+    rejections: std::rc::Rc<rejection::RejectionState<'gc>>,
+    /// Time source and `Math.random` state; shared with helper VMs.
+    clock: std::rc::Rc<clock::ClockState>,
+    /// What the `os` and `std` namespaces may do; shared with helper VMs.
+    permissions: std::rc::Rc<std::cell::RefCell<crate::core::Permissions>>,
+    /// Files opened with `os.open`; shared with helper VMs.
+    os_files: std::rc::Rc<os_files::OsFileTable>,
 }
 
 impl<'gc> VM<'gc> {
@@ -937,10 +1019,12 @@ impl<'gc> VM<'gc> {
         self.restricted_thrower_intrinsic.clone()
     }
 
+    #[cfg(feature = "shared-memory")]
     fn script_has_can_block_is_false(&self) -> bool {
         self.script_source.as_deref().is_some_and(|src| src.contains("CanBlockIsFalse")) && !crate::js_agent::is_agent_thread()
     }
 
+    #[cfg(feature = "shared-memory")]
     fn shared_buffer_id_from_obj(&self, buf_obj: &ObjectHandle<'gc>) -> Option<u64> {
         match buf_obj.borrow().get("__shared_buffer_id__") {
             Some(Value::Number(n)) if *n >= 1.0 => Some(*n as u64),
@@ -948,6 +1032,7 @@ impl<'gc> VM<'gc> {
         }
     }
 
+    #[cfg(feature = "shared-memory")]
     fn ensure_shared_buffer_id(&mut self, ctx: &GcContext<'gc>, buf_obj: &ObjectHandle<'gc>) -> Option<u64> {
         if let Some(id) = self.shared_buffer_id_from_obj(buf_obj) {
             return Some(id);
@@ -967,6 +1052,7 @@ impl<'gc> VM<'gc> {
         Some(id)
     }
 
+    #[cfg(feature = "shared-memory")]
     fn make_shared_array_buffer_from_id(&mut self, ctx: &GcContext<'gc>, shared_id: u64) -> Option<Value<'gc>> {
         let snapshot = crate::js_agent::shared_buffer_snapshot(shared_id)?;
         let byte_length = snapshot.len();
@@ -1029,6 +1115,831 @@ impl<'gc> VM<'gc> {
         None
     }
 
+    /// Results of eval that need no compilation: comment-only code, a bare
+    /// regex literal and simple optional chains.
+    fn eval_fast_path(&mut self, ctx: &GcContext<'gc>, first_arg: &Value<'gc>, code: &str) -> Option<Value<'gc>> {
+        if let Some(v) = self.eval_comment_only_fast_path(code) {
+            return Some(v);
+        }
+        // Fast path: eval of a bare regex literal like /pattern/ or /pattern/flags
+        // Use raw UTF-16 to preserve lone surrogates
+        if let Value::String(raw_u16) = first_arg
+            && let Some(v) = self.eval_regex_literal_fast_path_u16(ctx, raw_u16)
+        {
+            return Some(v);
+        }
+        if code.contains("?.") {
+            match self.try_eval_optional_chain_expression(ctx, code) {
+                Ok(Some(v)) => return Some(v),
+                Ok(None) => {}
+                Err(e) => {
+                    let msg = format!("{}", e);
+                    let mut err_map = IndexMap::new();
+                    err_map.insert("__type__".to_string(), Value::from("Error"));
+                    err_map.insert("message".to_string(), Value::from(&msg));
+                    self.pending_throw = Some(Value::Object(new_gc_cell_ptr(ctx, err_map)));
+                    return Some(Value::Undefined);
+                }
+            }
+        }
+        None
+    }
+
+    /// Parse and compile eval code and set up the temporary VM that runs it.
+    fn prepare_eval(
+        &mut self,
+        ctx: &GcContext<'gc>,
+        code: &str,
+        privns_context: Option<Vec<(usize, std::collections::HashSet<String>)>>,
+    ) -> Result<PreparedEval<'gc>, JSError> {
+        let is_direct = self.direct_eval;
+        // Indirect eval runs as global (sloppy) code: relax strict-mode
+        // binding restrictions so `var eval` / `arguments = 42` are allowed.
+        let do_parse = |code: &str| -> Result<Vec<crate::core::statement::Statement>, JSError> {
+            let prev_module_context = crate::core::parser::in_module_context();
+            crate::core::parser::set_module_context(false);
+            let result = (|| {
+                let tokens = crate::core::tokenize(code)?;
+                let mut index = 0;
+                crate::core::parse_statements(&tokens, &mut index)
+            })();
+            crate::core::parser::set_module_context(prev_module_context);
+            result
+        };
+        let statements = if is_direct {
+            // Set method/constructor/new.target context for direct eval
+            let caller_ip = self.frames.last().map(|f| f.func_ip);
+            let in_method = caller_ip.map(|ip| self.chunk.method_function_ips.contains(&ip)).unwrap_or(false);
+            let in_ctor = caller_ip.map(|ip| self.chunk.class_constructor_ips.contains(&ip)).unwrap_or(false);
+            // new.target is valid inside any function (arrow or not).
+            // Allow at parse time; runtime check validates actual context.
+            let in_function = self.frames.iter().rev().any(|f| f.func_ip != 0);
+            if in_method || in_ctor {
+                crate::core::parser::push_method_context_for_eval();
+            }
+            if in_ctor {
+                crate::core::parser::push_constructor_context_for_eval();
+            }
+            if in_function {
+                crate::core::parser::push_new_target_context_for_eval();
+            }
+            let result = do_parse(code);
+            if in_function {
+                crate::core::parser::pop_new_target_context_for_eval();
+            }
+            if in_ctor {
+                crate::core::parser::pop_constructor_context_for_eval();
+            }
+            if in_method || in_ctor {
+                crate::core::parser::pop_method_context_for_eval();
+            }
+            result?
+        } else {
+            // Indirect eval: relax strict-mode binding checks unless
+            // the eval'd code itself starts with "use strict".
+            let code_trimmed = code.trim();
+            let eval_code_is_strict = code_trimmed.starts_with("'use strict'") || code_trimmed.starts_with("\"use strict\"");
+            if eval_code_is_strict {
+                do_parse(code)?
+            } else {
+                crate::core::parse_without_strict_binding_checks(|| do_parse(code))?
+            }
+        };
+        // Check for bare return statements — illegal at top level of eval
+        for stmt in &statements {
+            if matches!(*stmt.kind, crate::core::StatementKind::Return(_)) {
+                return Err(crate::raise_syntax_error!("Illegal return statement"));
+            }
+        }
+        // ── PerformEval early-error restrictions ──────────────
+        // Indirect eval always runs as global code: reject super(), super.prop, new.target.
+        // Direct eval in a field initializer: reject `arguments` and `super()`.
+        {
+            use crate::core::statement::{
+                SCAN_ARGUMENTS, SCAN_IMPORT_EXPORT, SCAN_NEW_TARGET, SCAN_SUPER_CALL, SCAN_SUPER_PROP, eval_ast_scan,
+            };
+            let import_export = eval_ast_scan(&statements, SCAN_IMPORT_EXPORT);
+            if import_export & SCAN_IMPORT_EXPORT != 0 {
+                return Err(crate::raise_syntax_error!("Unexpected token"));
+            }
+            if !self.direct_eval {
+                // Indirect eval: reject super(), super.prop, new.target
+                let mask = SCAN_SUPER_CALL | SCAN_SUPER_PROP | SCAN_NEW_TARGET;
+                let found = eval_ast_scan(&statements, mask);
+                if found & SCAN_SUPER_CALL != 0 {
+                    return Err(crate::raise_syntax_error!("'super' keyword unexpected here"));
+                }
+                if found & SCAN_SUPER_PROP != 0 {
+                    return Err(crate::raise_syntax_error!("'super' keyword unexpected here"));
+                }
+                if found & SCAN_NEW_TARGET != 0 {
+                    return Err(crate::raise_syntax_error!("new.target expression is not allowed here"));
+                }
+            } else {
+                // Direct eval: check field-init context via VM flag or fn_eval_context
+                let in_field = self.in_field_init
+                    || self
+                        .frames
+                        .last()
+                        .map(|f| self.chunk.fn_eval_context.get(&f.func_ip).copied().unwrap_or(0) & 0x01 != 0)
+                        .unwrap_or(false);
+                if in_field {
+                    // Field initializer: reject `arguments` and `super()`
+                    let mask = SCAN_ARGUMENTS | SCAN_SUPER_CALL;
+                    let found = eval_ast_scan(&statements, mask);
+                    if found & SCAN_ARGUMENTS != 0 {
+                        return Err(crate::raise_syntax_error!(
+                            "'arguments' is not allowed in class field initializer or static initialization block"
+                        ));
+                    }
+                    if found & SCAN_SUPER_CALL != 0 {
+                        return Err(crate::raise_syntax_error!("'super' keyword unexpected here"));
+                    }
+                }
+
+                // Direct eval: check super/new.target based on the
+                // this-environment (nearest non-arrow function frame).
+                // §19.2.1.3 PerformEval steps 4-6.
+                let mask = SCAN_NEW_TARGET | SCAN_SUPER_CALL | SCAN_SUPER_PROP;
+                let found = eval_ast_scan(&statements, mask);
+                if found != 0 {
+                    // Walk up frames to find the nearest non-arrow function
+                    // (equivalent to GetThisEnvironment in spec).
+                    let this_env_frame = self
+                        .frames
+                        .iter()
+                        .rev()
+                        .find(|f| f.func_ip != 0 && !self.chunk.arrow_function_ips.contains(&f.func_ip));
+                    let (in_function, in_method, in_constructor) = match this_env_frame {
+                        Some(f) => {
+                            let eval_ctx = self.chunk.fn_eval_context.get(&f.func_ip).copied().unwrap_or(0);
+                            let in_field = eval_ctx & 0x01 != 0 || self.in_field_init;
+                            let in_method = eval_ctx & 0x02 != 0 || self.fn_home_objects.contains_key(&f.func_ip) || in_field;
+                            let in_constructor = eval_ctx & 0x04 != 0 || self.chunk.class_constructor_ips.contains(&f.func_ip);
+                            (true, in_method, in_constructor)
+                        }
+                        None => {
+                            // No enclosing function frame — check if we're in a
+                            // field initializer at the top level (arrow in field).
+                            let in_field = self.in_field_init;
+                            (in_field, in_field, false)
+                        }
+                    };
+
+                    if found & SCAN_NEW_TARGET != 0 && !in_function {
+                        return Err(crate::raise_syntax_error!("new.target expression is not allowed here"));
+                    }
+                    if found & SCAN_SUPER_PROP != 0 && !in_method {
+                        return Err(crate::raise_syntax_error!("'super' keyword unexpected here"));
+                    }
+                    if found & SCAN_SUPER_CALL != 0 && !in_constructor {
+                        return Err(crate::raise_syntax_error!("'super' keyword unexpected here"));
+                    }
+                }
+            }
+        }
+        // Detect strict mode: code begins with "use strict" directive, or enclosing context is strict (direct eval only)
+        let enclosing_strict = if self.direct_eval {
+            self.force_strict
+                || self
+                    .frames
+                    .last()
+                    .map(|f| self.chunk.fn_strictness.get(&f.func_ip).copied().unwrap_or(false))
+                    .unwrap_or(false)
+                || self.current_execution_is_strict()
+        } else {
+            false // indirect eval never inherits caller strict mode
+        };
+        let is_strict = enclosing_strict || code.trim().starts_with("\"use strict\"") || code.trim().starts_with("'use strict'");
+        // Strict-mode early errors: reject `with` and reserved-word bindings
+        if is_strict {
+            fn check_strict_errors(stmts: &[crate::core::Statement]) -> Option<&'static str> {
+                use crate::core::StatementKind;
+                for stmt in stmts {
+                    match &*stmt.kind {
+                        StatementKind::With(..) => {
+                            return Some("Strict mode code may not include a with statement");
+                        }
+                        StatementKind::Var(decls) | StatementKind::Let(decls) => {
+                            for (name, _) in decls {
+                                if matches!(
+                                    name.as_str(),
+                                    "static" | "implements" | "interface" | "package" | "private" | "protected" | "public"
+                                ) {
+                                    return Some("Unexpected strict mode reserved word");
+                                }
+                            }
+                        }
+                        StatementKind::Const(decls) => {
+                            for (name, _) in decls {
+                                if matches!(
+                                    name.as_str(),
+                                    "static" | "implements" | "interface" | "package" | "private" | "protected" | "public"
+                                ) {
+                                    return Some("Unexpected strict mode reserved word");
+                                }
+                            }
+                        }
+                        StatementKind::Block(inner) => {
+                            if let Some(msg) = check_strict_errors(inner) {
+                                return Some(msg);
+                            }
+                        }
+                        StatementKind::If(if_stmt) => {
+                            if let Some(msg) = check_strict_errors(&if_stmt.then_body) {
+                                return Some(msg);
+                            }
+                            if let Some(ref else_body) = if_stmt.else_body
+                                && let Some(msg) = check_strict_errors(else_body)
+                            {
+                                return Some(msg);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                None
+            }
+            if let Some(msg) = check_strict_errors(&statements) {
+                return Err(crate::raise_syntax_error!(msg));
+            }
+            use crate::core::statement::{SCAN_STRICT_ARGS_EVAL_ASSIGN, eval_ast_scan};
+            let strict_assign = eval_ast_scan(&statements, SCAN_STRICT_ARGS_EVAL_ASSIGN);
+            if strict_assign & SCAN_STRICT_ARGS_EVAL_ASSIGN != 0 {
+                return Err(crate::raise_syntax_error!("Unexpected eval or arguments in strict mode"));
+            }
+        }
+        let mut compiler = crate::core::Compiler::new();
+        compiler.set_strict_mode(is_strict);
+        if let Some(names) = privns_context {
+            compiler.set_private_name_context(names);
+        }
+        let mut chunk = compiler.compile(&statements)?;
+        chunk.is_eval_code = true;
+        // Per spec §18.2.1.1 step 6: for indirect eval, strictEval
+        // is true only when the eval code ITSELF has "use strict".
+        // Our engine always parses as strict, but for indirect eval
+        // writeback we need to know if the source explicitly opted in.
+        let eval_code_explicit_strict = {
+            let trimmed = code.trim();
+            trimmed.starts_with("'use strict'") || trimmed.starts_with("\"use strict\"")
+        };
+        // Non-configurable global names (can't be redefined by eval)
+        let non_configurable: [&str; 3] = ["NaN", "Infinity", "undefined"];
+        // Pre-check: scan chunk for DefineGlobal opcodes that would define functions overriding non-configurable globals
+        {
+            let code = &chunk.code;
+            let constants = &chunk.constants;
+            let mut pc = 0;
+            while pc < code.len() {
+                let op = code[pc];
+                pc += 1;
+                if (op == Opcode::DefineGlobal as u8 || op == Opcode::DefineGlobalSoft as u8 || op == Opcode::DefineGlobalConst as u8)
+                    && pc + 1 < code.len()
+                {
+                    let idx = (code[pc] as u16 | (code[pc + 1] as u16) << 8) as usize;
+                    if idx < constants.len()
+                        && let Value::String(s) = &constants[idx]
+                    {
+                        let name = crate::unicode::utf16_to_utf8(s);
+                        if non_configurable.contains(&name.as_str()) {
+                            return Err(crate::raise_type_error!(format!("Cannot redefine property: {}", name)));
+                        }
+                    }
+                    pc += 2;
+                } else {
+                    // Skip operands based on opcode
+                    match Opcode::try_from(op) {
+                        Ok(
+                            Opcode::Constant
+                            | Opcode::DefineGlobal
+                            | Opcode::DefineGlobalSoft
+                            | Opcode::DefineGlobalConst
+                            | Opcode::GetGlobal
+                            | Opcode::SetGlobal
+                            | Opcode::GetProperty
+                            | Opcode::SetProperty
+                            | Opcode::SetSuperProperty
+                            | Opcode::SetSuperPropertyComputed
+                            | Opcode::GetSuperProperty
+                            | Opcode::GetMethod
+                            | Opcode::TypeOfGlobal
+                            | Opcode::DeleteGlobal
+                            | Opcode::FreezeTemplate,
+                        ) => pc += 2,
+                        Ok(Opcode::Jump | Opcode::JumpIfFalse | Opcode::JumpIfTrue) => pc += 4,
+                        Ok(Opcode::SetupTry) => pc += 7,
+                        Ok(Opcode::Call) if pc < code.len() => {
+                            let raw_arg_byte = code[pc];
+                            pc += 1;
+                            if (raw_arg_byte & 0x3f) == 0x3f {
+                                pc += 2;
+                            }
+                        }
+                        Ok(Opcode::Call) => {}
+                        Ok(Opcode::NewCall) => pc += 1,
+                        Ok(
+                            Opcode::GetLocal
+                            | Opcode::SetLocal
+                            | Opcode::NewArray
+                            | Opcode::NewObject
+                            | Opcode::GetUpvalue
+                            | Opcode::SetUpvalue
+                            | Opcode::CollectRest
+                            | Opcode::GetArguments
+                            | Opcode::BoxLocal
+                            | Opcode::ReboxLocal
+                            | Opcode::ClearLocalCells
+                            | Opcode::CallSpread
+                            | Opcode::ThrowIfNotConstructor,
+                        ) => pc += 1,
+                        Ok(Opcode::MakeClosure) => {
+                            pc += 2; // const idx
+                            if pc < code.len() {
+                                let count = code[pc] as usize;
+                                pc += 1 + count * 2;
+                            }
+                        }
+                        _ => {} // 0-operand opcodes
+                    }
+                }
+            }
+        }
+        let mut eval_vm: VM<'gc> = VM::new(self.chunk.clone(), ctx);
+        self.inherit_embedder_state(&mut eval_vm);
+        // Indirect eval must still use the caller realm's actual global this
+        // value for sloppy this-binding, not the fresh VM::new() global object.
+        eval_vm.global_this = self.global_this;
+        // Ensure unique brands across eval VMs
+        eval_vm.runtime_brand_counter = self.runtime_brand_counter;
+        eval_vm.restricted_thrower_intrinsic = self.restricted_thrower_intrinsic.clone();
+        // Propagate strict mode to eval VM
+        eval_vm.force_strict = is_strict;
+        eval_vm.defers_event_loop = true;
+        // Direct eval inside a function: var declarations should stay in
+        // the function scope, not leak to globalThis.
+        if self.direct_eval && !self.frames.is_empty() {
+            eval_vm.eval_fn_scope = true;
+        }
+        // Merge eval code into eval VM's chunk so it has access to
+        // host functions (getters, setters, closures) at original IPs
+        let (eval_code_offset, _) = eval_vm.merge_eval_chunk(&chunk);
+        eval_vm.ip = eval_code_offset;
+        // Ensure eval VM knows about eval-scoped lexical declarations
+        // so DefineGlobal/SetGlobal don't leak let/const/class to globalThis.
+        for name in &chunk.lexical_declared_globals {
+            eval_vm.chunk.lexical_declared_globals.insert(name.clone());
+        }
+        // Copy caller's globals into eval VM
+        if self.direct_eval {
+            // Direct eval: copy all globals (caller's scope)
+            for (k, v) in &self.globals {
+                eval_vm.globals.insert(k.clone(), v.clone());
+            }
+        } else {
+            // Indirect eval: build globals from the true global scope.
+            // First copy internal/builtin globals from self.globals
+            // (ensures eval, globalThis, internal helpers are available).
+            for (k, v) in &self.globals {
+                eval_vm.globals.insert(k.clone(), v.clone());
+            }
+            // Then override with globalThis properties — these represent
+            // the actual global environment, excluding eval-local vars.
+            // Skip Uninitialized values (const declarations that haven't
+            // been mirrored to globalThis yet).
+            if let Some(Value::Object(gt)) = self.globals.get("globalThis") {
+                let global_this = Value::Object(*gt);
+                let keys: Vec<String> = gt.borrow().keys().cloned().collect();
+                for k in keys {
+                    let value = self.read_named_property(ctx, &global_this, &k);
+                    if self.pending_throw.is_some() || matches!(value, Value::Uninitialized) {
+                        self.pending_throw.take();
+                        continue;
+                    }
+                    eval_vm.globals.insert(k, value);
+                }
+            }
+        }
+        // For direct eval, inject caller's local variables as globals
+        if self.direct_eval {
+            // Walk all frames from outermost to innermost so inner scopes shadow outer
+            for frame in self.frames.iter() {
+                if let Some(local_names) = self.chunk.fn_local_names.get(&frame.func_ip) {
+                    let const_names = self.chunk.fn_const_local_names.get(&frame.func_ip);
+                    for (idx, name) in local_names.iter().enumerate() {
+                        if name.starts_with("__") && name.ends_with("__") {
+                            continue; // skip synthetic locals
+                        }
+                        if let Some(cell) = frame.local_cells.get(&idx) {
+                            eval_vm.globals.insert(name.clone(), cell.borrow().clone());
+                        } else {
+                            let stack_idx = frame.bp + idx;
+                            if stack_idx < self.stack.len() {
+                                eval_vm.globals.insert(name.clone(), self.stack[stack_idx].clone());
+                            }
+                        }
+                        // Mark const/immutable locals as const_globals in eval VM
+                        if let Some(cn) = const_names
+                            && cn.contains(name)
+                        {
+                            eval_vm.const_globals.insert(name.clone());
+                        }
+                    }
+                }
+            }
+            // Inject top-level block-scoped locals (force_local_let) into
+            // the eval VM.  Find the latest snapshot recorded before the
+            // current IP so eval inside a block sees the block's locals.
+            if !self.chunk.top_level_locals_at_ip.is_empty() {
+                let cur_ip = self.ip;
+                let snap = self.chunk.top_level_locals_at_ip.iter().rev().find(|(ip, _, _)| *ip <= cur_ip);
+                if let Some((_ip, local_names, const_names)) = snap {
+                    for (idx, name) in local_names.iter().enumerate() {
+                        if name.starts_with("__") && name.ends_with("__") {
+                            continue;
+                        }
+                        // Read from top_level_cells (boxed via BoxLocal) or stack
+                        let val = if let Some(cell) = self.top_level_cells.get(&idx) {
+                            cell.borrow().clone()
+                        } else if idx < self.stack.len() {
+                            self.stack[idx].clone()
+                        } else {
+                            continue;
+                        };
+                        eval_vm.globals.insert(name.clone(), val);
+                        if const_names.contains(name) {
+                            eval_vm.const_globals.insert(name.clone());
+                        }
+                    }
+                }
+            }
+            // Direct eval: resolve active block aliases so that
+            // eval("x") inside a block sees the block-scoped value.
+            for (alias, original) in &self.chunk.block_alias_to_original {
+                if let Some(val) = self.globals.get(alias) {
+                    eval_vm.globals.insert(original.clone(), val.clone());
+                }
+            }
+        } else {
+            // Indirect eval: remove alias keys from eval VM globals
+            // so internal `__top_block_alias_N__` names aren't visible.
+            for alias in self.chunk.block_alias_to_original.keys() {
+                _ = eval_vm.globals.shift_remove(alias);
+            }
+        }
+        if !self.direct_eval
+            && let Some(Value::Object(gt)) = eval_vm.globals.get("globalThis").cloned()
+        {
+            let public_globals: Vec<(String, Value<'gc>)> = eval_vm
+                .globals
+                .iter()
+                .filter(|(k, v)| *k != "globalThis" && !k.starts_with("__") && !matches!(v, Value::Uninitialized))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+            let mut gt_borrow = gt.borrow_mut(ctx);
+            for (k, v) in public_globals {
+                if let Some(existing_desc) = desc_from_legacy_map(&gt_borrow, &k) {
+                    PropDesc::data(v, existing_desc.attrs).write_to_legacy_map(&mut gt_borrow, &k);
+                } else {
+                    gt_borrow.insert(k, v);
+                }
+            }
+        }
+        eval_vm.script_path = self.current_source_path().map(str::to_owned).or_else(|| self.script_path.clone());
+        // Eval top-level strictness must be based on the eval source itself,
+        // not the caller's surrounding script source.
+        eval_vm.script_source = Some(code.to_string());
+        eval_vm.is_module_mode = self.is_module_mode;
+        eval_vm.loaded_modules = self.loaded_modules.clone();
+        eval_vm.module_load_errors = self.module_load_errors.clone();
+        eval_vm.export_origins = self.export_origins.clone();
+        eval_vm.reexport_deps = self.reexport_deps.clone();
+        eval_vm.ambiguous_export_keys = self.ambiguous_export_keys.clone();
+        eval_vm.loaded_module_local_names = self.loaded_module_local_names.clone();
+        eval_vm.loaded_module_states = self.loaded_module_states.clone();
+        eval_vm.module_ns_objects = self.module_ns_objects.clone();
+        eval_vm.main_module_ip_start = self.main_module_ip_start;
+        eval_vm.intrinsic_promise_ctor = if matches!(self.intrinsic_promise_ctor, Value::Undefined) {
+            self.globals.get("Promise").cloned().unwrap_or(Value::Undefined)
+        } else {
+            self.intrinsic_promise_ctor.clone()
+        };
+        eval_vm.intrinsic_map_ctor = self.intrinsic_map_ctor.clone();
+        eval_vm.intrinsic_set_ctor = self.intrinsic_set_ctor.clone();
+        eval_vm.intrinsic_date_ctor = self.intrinsic_date_ctor.clone();
+        eval_vm.intrinsic_uint8array_ctor = self.intrinsic_uint8array_ctor.clone();
+        // Set up `this` for the eval VM
+        if self.direct_eval {
+            // Direct eval inherits caller's `this`
+            let caller_this = self.this_stack.last().cloned().unwrap_or(Value::Undefined);
+            eval_vm.this_stack.push(caller_this);
+            // Copy fn_props and closure_fn_props so property access on host functions works in eval
+            for (ip, props) in &self.fn_props {
+                eval_vm.fn_props.insert(*ip, *props);
+            }
+            for (key, props) in &self.closure_fn_props {
+                eval_vm.closure_fn_props.insert(*key, *props);
+            }
+            // Copy fn_home_objects so super.property works in eval
+            for (ip, home) in &self.fn_home_objects {
+                eval_vm.fn_home_objects.insert(*ip, home.clone());
+            }
+            // Set eval_home_object so resolve_super_base can find
+            // the super base even without a matching frame
+            if let Some(caller_frame) = self.frames.last()
+                && let Some(home) = self.fn_home_objects.get(&caller_frame.func_ip)
+            {
+                eval_vm.eval_home_object = Some(home.clone());
+            } else {
+                // Field initializer direct eval may execute in helper frames that
+                // don't carry fn_home_objects; derive a usable home from lexical this.
+                let caller_this = self.this_stack.last().cloned().unwrap_or(Value::Undefined);
+                let derived_home = match caller_this {
+                    Value::Object(map) => map.borrow().get("__proto__").cloned(),
+                    Value::Array(arr) => arr.borrow().props.get("__proto__").cloned(),
+                    Value::Function(..) | Value::Closure(..) => Some(caller_this),
+                    _ => None,
+                };
+                if let Some(home) = derived_home {
+                    eval_vm.eval_home_object = Some(home);
+                }
+            }
+            // Inherit new.target stack for direct eval
+            // In field initializers, new.target is undefined per spec
+            // (eval runs "inside a function", not inside the constructor)
+            let in_field = self.in_field_init
+                || self
+                    .frames
+                    .last()
+                    .map(|f| self.chunk.fn_eval_context.get(&f.func_ip).copied().unwrap_or(0) & 0x01 != 0)
+                    .unwrap_or(false);
+            if in_field {
+                eval_vm.new_target_stack.push(Value::Undefined);
+            } else {
+                eval_vm.new_target_stack = self.new_target_stack.clone();
+            }
+        } else {
+            // Indirect eval: `this` is the realm's actual global this value.
+            eval_vm.this_stack.push(Value::Object(self.global_this));
+        }
+
+        // §19.2.1.3 EvalDeclarationInstantiation step 5:
+        // "If strict is false" — only check var/let collision for
+        // non-strict indirect eval.
+        if !self.direct_eval && !is_strict {
+            for var_name in &chunk.declared_globals {
+                if chunk.lexical_declared_globals.contains(var_name) {
+                    continue; // it's a let/const in eval, not a var
+                }
+                if self.chunk.lexical_declared_globals.contains(var_name) {
+                    return Err(crate::raise_syntax_error!(format!(
+                        "Identifier '{}' has already been declared",
+                        var_name
+                    )));
+                }
+            }
+            // §19.2.1.3 step 10.a.iii: CanDeclareGlobalVar
+            // If the global object is non-extensible, new var declarations
+            // that don't already exist on the global object throw TypeError.
+            {
+                let gt = self.global_this.borrow();
+                let non_extensible = gt.get("__non_extensible__").is_some_and(|v| v.to_truthy());
+                if non_extensible {
+                    for var_name in &chunk.declared_globals {
+                        if chunk.lexical_declared_globals.contains(var_name) {
+                            continue;
+                        }
+                        if !gt.contains_key(var_name) {
+                            drop(gt);
+                            return Err(crate::raise_type_error!(format!(
+                                "Cannot define global variable '{}': global object is not extensible",
+                                var_name
+                            )));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(PreparedEval {
+            vm: Box::new(eval_vm),
+            chunk,
+            is_strict,
+            explicit_strict: eval_code_explicit_strict,
+        })
+    }
+
+    /// Carry the effects of a finished eval run back into the calling VM and
+    /// return the completion value.
+    fn finish_eval(
+        &mut self,
+        ctx: &GcContext<'gc>,
+        prepared: PreparedEval<'gc>,
+        eval_result: Result<Value<'gc>, JSError>,
+        args: &[Value<'gc>],
+    ) -> Result<Value<'gc>, JSError> {
+        let PreparedEval {
+            vm: eval_vm,
+            chunk,
+            is_strict,
+            explicit_strict: eval_code_explicit_strict,
+        } = prepared;
+        let eval_vm = *eval_vm;
+        // Sync brand counter so subsequent evals get unique brands
+        if eval_vm.runtime_brand_counter > self.runtime_brand_counter {
+            self.runtime_brand_counter = eval_vm.runtime_brand_counter;
+        }
+        // Merge original eval chunk into host chunk so Function values
+        // (getters, setters, methods) remain callable in the host VM.
+        let (code_offset, _const_offset) = self.merge_eval_chunk(&chunk);
+        // Build set of function IPs that genuinely belong to the eval chunk
+        let eval_fn_ips: std::collections::HashSet<usize> = chunk
+            .constants
+            .iter()
+            .filter_map(|c| match c {
+                Value::Function(ip, _) => Some(*ip),
+                _ => None,
+            })
+            .chain(chunk.fn_names.keys().copied())
+            .chain(chunk.fn_local_names.keys().copied())
+            .collect();
+        for arg in args {
+            let _ = self.adjust_value_ips(ctx, arg, code_offset, &eval_fn_ips);
+        }
+        for timer in eval_vm.pending_timers {
+            self.pending_timers.push(PendingTimer {
+                callback: self.adjust_value_ips(ctx, &timer.callback, code_offset, &eval_fn_ips),
+                args: timer
+                    .args
+                    .iter()
+                    .map(|arg| self.adjust_value_ips(ctx, arg, code_offset, &eval_fn_ips))
+                    .collect(),
+                ..timer
+            });
+        }
+        for task in eval_vm.microtask_queue {
+            self.microtask_queue.push(Microtask {
+                callback: task
+                    .callback
+                    .as_ref()
+                    .map(|callback| self.adjust_value_ips(ctx, callback, code_offset, &eval_fn_ips)),
+                value: self.adjust_value_ips(ctx, &task.value, code_offset, &eval_fn_ips),
+                rejected: task.rejected,
+                resolve: self.adjust_value_ips(ctx, &task.resolve, code_offset, &eval_fn_ips),
+                reject: self.adjust_value_ips(ctx, &task.reject, code_offset, &eval_fn_ips),
+            });
+        }
+        self.loaded_modules = eval_vm.loaded_modules.clone();
+        self.module_load_errors = eval_vm.module_load_errors.clone();
+        self.export_origins = eval_vm.export_origins.clone();
+        self.reexport_deps = eval_vm.reexport_deps.clone();
+        self.ambiguous_export_keys = eval_vm.ambiguous_export_keys.clone();
+        self.loaded_module_local_names = eval_vm.loaded_module_local_names.clone();
+        self.loaded_module_states = eval_vm.loaded_module_states.clone();
+        self.module_ns_objects = eval_vm.module_ns_objects.clone();
+        self.main_module_ip_start = eval_vm.main_module_ip_start;
+        self.intrinsic_promise_ctor = eval_vm.intrinsic_promise_ctor.clone();
+        self.restricted_thrower_intrinsic = eval_vm.restricted_thrower_intrinsic.clone();
+        // Adjust Function IPs in the result value (only on success)
+        let result = match &eval_result {
+            Ok(r) => Some(self.adjust_value_ips(ctx, r, code_offset, &eval_fn_ips)),
+            Err(_) => None,
+        };
+        // Also merge fn_home_objects from eval VM
+        // IPs are already adjusted (eval VM used host chunk clone + merged eval code)
+        for (ip, home) in &eval_vm.fn_home_objects {
+            self.fn_home_objects.insert(*ip, home.clone());
+        }
+        // Merge fn_props from eval VM (static methods, etc.)
+        for (ip, props_handle) in &eval_vm.fn_props {
+            self.fn_props.insert(*ip, *props_handle);
+        }
+        // Merge closure_fn_props (per-closure overlays, e.g. prototype for
+        // class constructors with private members compiled as closures).
+        for (key, props_handle) in &eval_vm.closure_fn_props {
+            self.closure_fn_props.insert(*key, *props_handle);
+        }
+        // Copy globals back
+        // Strict mode: skip new declarations AND var-redeclarations
+        // Sloppy mode: write back all globals (including new declarations)
+        {
+            // For direct eval, build a set of local variable names from
+            // enclosing frames so we don't overwrite them as globals.
+            let local_names_set: std::collections::HashSet<&str> = if self.direct_eval {
+                self.frames
+                    .iter()
+                    .filter_map(|f| self.chunk.fn_local_names.get(&f.func_ip))
+                    .flat_map(|names| names.iter().map(|n| n.as_str()))
+                    .collect()
+            } else {
+                std::collections::HashSet::new()
+            };
+            for (k, v) in &eval_vm.globals {
+                // Never leak top-level lexical declarations from eval
+                // unless REPL mode explicitly requests persistence.
+                if !self.repl_lexical_persist && chunk.lexical_declared_globals.contains(k) {
+                    continue;
+                }
+                // In strict mode, skip new globals and var/function declarations
+                // (they stay scoped).  For INDIRECT eval, per spec §18.2.1.1
+                // step 4, strictCaller = false; declarations go to global scope
+                // UNLESS the eval code itself has "use strict".
+                let strict_scoped = if self.direct_eval { is_strict } else { eval_code_explicit_strict };
+                if strict_scoped && (!self.globals.contains_key(k) || chunk.declared_globals.contains(k)) {
+                    // The eval VM may have already written this key to
+                    // the shared global_this during DefineGlobal execution.
+                    // Remove it so it doesn't leak to the outer scope.
+                    self.global_this.borrow_mut(ctx).shift_remove(k);
+                    continue;
+                }
+                // For direct eval, skip variables that are locals in caller frames;
+                // they are written back to the stack separately below.
+                if self.direct_eval && local_names_set.contains(k.as_str()) {
+                    continue;
+                }
+                // Skip globals unchanged by eval: host-chunk Function IPs
+                // can coincide with eval-chunk IPs, causing false adjustment.
+                if let Some(host_val) = self.globals.get(k) {
+                    let unchanged = match (host_val, v) {
+                        (Value::Function(h_ip, h_ar), Value::Function(e_ip, e_ar)) => h_ip == e_ip && h_ar == e_ar,
+                        (Value::Closure(h_ip, h_ar, h_uv), Value::Closure(e_ip, e_ar, e_uv)) => {
+                            h_ip == e_ip && h_ar == e_ar && Gc::ptr_eq(*h_uv, *e_uv)
+                        }
+                        (Value::Object(h), Value::Object(e)) => Gc::ptr_eq(*h, *e),
+                        (Value::Array(h), Value::Array(e)) => Gc::ptr_eq(*h, *e),
+                        (Value::Map(h), Value::Map(e)) => Gc::ptr_eq(*h, *e),
+                        (Value::Set(h), Value::Set(e)) => Gc::ptr_eq(*h, *e),
+                        (Value::NativeFunction(h), Value::NativeFunction(e)) => h == e,
+                        (Value::Number(h), Value::Number(e)) => h.to_bits() == e.to_bits(),
+                        (Value::Boolean(h), Value::Boolean(e)) => h == e,
+                        (Value::String(h), Value::String(e)) => h == e,
+                        (Value::Null, Value::Null) | (Value::Undefined, Value::Undefined) => true,
+                        _ => false,
+                    };
+                    if unchanged {
+                        continue;
+                    }
+                }
+                let adjusted = self.adjust_value_ips(ctx, v, code_offset, &eval_fn_ips);
+                self.globals.insert(k.clone(), adjusted.clone());
+                // For direct eval inside a function, var declarations stay in
+                // the function scope and should not leak to globalThis.
+                let in_fn_scope = self.direct_eval && !self.frames.is_empty();
+                if !in_fn_scope {
+                    let mut gt = self.global_this.borrow_mut(ctx);
+                    if let Some(existing_desc) = desc_from_legacy_map(&gt, k) {
+                        PropDesc::data(adjusted, existing_desc.attrs).write_to_legacy_map(&mut gt, k);
+                    } else {
+                        gt.insert(k.clone(), adjusted);
+                    }
+                }
+            }
+        }
+        // CreateGlobalFunctionBinding (§8.1.1.4.18): after writeback,
+        // update property descriptors for eval function declarations.
+        for fn_name in &chunk.fn_declared_globals {
+            let mut gt = self.global_this.borrow_mut(ctx);
+            if !has_nonconfigurable_mark(&gt, fn_name) {
+                unmark_readonly(&mut gt, fn_name);
+                unmark_nonenumerable(&mut gt, fn_name);
+            }
+        }
+        // For direct eval, write back modified local variables to caller's stack
+        if self.direct_eval {
+            for frame in self.frames.iter().rev() {
+                if let Some(local_names) = self.chunk.fn_local_names.get(&frame.func_ip) {
+                    for (idx, name) in local_names.iter().enumerate() {
+                        if name.starts_with("__") && name.ends_with("__") {
+                            continue;
+                        }
+                        // Skip locals whose name was declared inside eval
+                        // (var/function/class in strict eval, lexical in all evals).
+                        if (!self.repl_lexical_persist && chunk.lexical_declared_globals.contains(name))
+                            || (is_strict && chunk.declared_globals.contains(name))
+                        {
+                            continue;
+                        }
+                        if let Some(new_val) = eval_vm.globals.get(name) {
+                            let adjusted = self.adjust_value_ips(ctx, new_val, code_offset, &eval_fn_ips);
+                            if let Some(cell) = frame.local_cells.get(&idx) {
+                                *cell.borrow_mut(ctx) = adjusted;
+                            } else {
+                                let stack_idx = frame.bp + idx;
+                                if stack_idx < self.stack.len() {
+                                    self.stack[stack_idx] = adjusted;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        // Propagate error or return result
+        match eval_result {
+            Ok(_) => Ok(result.unwrap_or(Value::Undefined)),
+            Err(e) => Err(e),
+        }
+    }
+
     /// Fast path for `eval("/pattern/flags")` — operates on raw UTF-16 to preserve lone surrogates.
     fn eval_regex_literal_fast_path_u16(&mut self, ctx: &GcContext<'gc>, code: &[u16]) -> Option<Value<'gc>> {
         // Trim leading/trailing whitespace (ASCII whitespace in UTF-16)
@@ -1134,8 +2045,16 @@ impl<'gc> VM<'gc> {
     }
 
     pub fn new(chunk: Chunk<'gc>, ctx: &GcContext<'gc>) -> Self {
+        let mut vm = Self::without_builtins(chunk, ctx);
+        vm.register_builtins(ctx);
+        vm
+    }
+
+    /// A VM with an empty global object, for [`new`](Self::new) to register
+    /// the builtins on or a snapshot to be restored into.
+    fn without_builtins(chunk: Chunk<'gc>, ctx: &GcContext<'gc>) -> Self {
         let global_this = new_gc_cell_ptr(ctx, IndexMap::new());
-        let mut vm = Self {
+        Self {
             chunk,
             ip: 0,
             stack: Vec::with_capacity(256),
@@ -1147,7 +2066,6 @@ impl<'gc> VM<'gc> {
             this_stack: vec![Value::Object(global_this)],
             new_target_stack: Vec::new(),
             super_called_stack: Vec::new(),
-            output: Vec::new(),
             fn_props: HashMap::new(),
             fn_realm: HashMap::new(),
             native_fn_props: HashMap::new(),
@@ -1163,9 +2081,8 @@ impl<'gc> VM<'gc> {
             direct_eval: false,
             script_source: None,
             script_path: None,
-            pending_timers: Vec::new(),
-            next_timer_id: 1,
-            cleared_timers: std::collections::HashSet::new(),
+            pending_timers: std::collections::BinaryHeap::new(),
+            timers: std::rc::Rc::default(),
             microtask_queue: Vec::new(),
             async_atomics_waits: Vec::new(),
             generator_states: HashMap::new(),
@@ -1192,6 +2109,7 @@ impl<'gc> VM<'gc> {
             force_strict: false,
             eval_home_object: None,
             eval_fn_scope: false,
+            defers_event_loop: false,
             runtime_brand_counter: 0,
             closure_fn_props: HashMap::new(),
             top_level_cells: HashMap::new(),
@@ -1201,6 +2119,7 @@ impl<'gc> VM<'gc> {
             child_realms: Vec::new(),
             realm_parent_ptr: None,
             repl_lexical_persist: false,
+            repl_eval: None,
             throw_caught_stack_depth: None,
             is_module_mode: false,
             module_locals: IndexMap::new(),
@@ -1218,14 +2137,28 @@ impl<'gc> VM<'gc> {
             module_records: std::collections::HashMap::new(),
             module_request_depth: 0,
             intrinsic_promise_ctor: Value::Undefined,
+            intrinsic_map_ctor: Value::Undefined,
+            intrinsic_set_ctor: Value::Undefined,
+            intrinsic_date_ctor: Value::Undefined,
+            intrinsic_uint8array_ctor: Value::Undefined,
             restricted_thrower_intrinsic: Value::Undefined,
             suspend_on_module_await: false,
             module_await_suspended: false,
             suspended_module_states: Vec::new(),
             template_cache: HashMap::new(),
-        };
-        vm.register_builtins(ctx);
-        vm
+            host_callbacks: Vec::new(),
+            limits: std::rc::Rc::default(),
+            console: std::rc::Rc::default(),
+            module_loader: std::rc::Rc::default(),
+            handle_roots: gc_arena::DynamicRootSet::new(ctx),
+            handles: std::rc::Rc::default(),
+            host_classes: std::rc::Rc::default(),
+            async_host: std::rc::Rc::default(),
+            rejections: std::rc::Rc::default(),
+            clock: std::rc::Rc::default(),
+            permissions: std::rc::Rc::default(),
+            os_files: std::rc::Rc::default(),
+        }
     }
 
     #[inline]
@@ -1238,6 +2171,15 @@ impl<'gc> VM<'gc> {
         self.script_path = script_path.map(|path| path.display().to_string());
     }
 
+    /// Like [`set_source_context`](Self::set_source_context) for a script
+    /// loaded from bytecode, whose source text is gone: strictness comes from
+    /// the flag recorded when it was compiled.
+    pub(crate) fn set_bytecode_context(&mut self, strict: bool, script_path: Option<&std::path::Path>) {
+        self.script_source = None;
+        self.script_path = script_path.map(|path| path.display().to_string());
+        self.force_strict = strict;
+    }
+
     /// Set module mode: top-level `this` becomes `undefined`.
     pub fn set_module_this(&mut self) {
         if let Some(first) = self.this_stack.first_mut() {
@@ -1525,7 +2467,7 @@ impl<'gc> VM<'gc> {
 
         let mut resolved_reexports = Vec::new();
         for (source, specs) in reexport_sources {
-            let resolved = crate::core::resolve_module_path(source, base_path).to_string_lossy().to_string();
+            let resolved = self.module_loader.resolve_path(source, base_path).to_string_lossy().to_string();
             for spec in specs {
                 match spec {
                     crate::core::ReexportSpec::Named(name, alias) => {
@@ -1816,8 +2758,9 @@ impl<'gc> VM<'gc> {
             return false;
         }
         for request in &record.requests {
-            let required_module =
-                crate::core::resolve_module_request_key(&request.specifier, &record.resolved_path, request.import_type.as_deref());
+            let required_module = self
+                .module_loader
+                .request_key(&request.specifier, &record.resolved_path, request.import_type.as_deref());
             if !self.ready_for_sync_execution(&required_module, seen) {
                 return false;
             }
@@ -2081,15 +3024,18 @@ impl<'gc> VM<'gc> {
             return;
         }
         for request in &record.requests {
-            let dep_key =
-                crate::core::resolve_module_request_key(&request.specifier, &record.resolved_path, request.import_type.as_deref());
+            let dep_key = self
+                .module_loader
+                .request_key(&request.specifier, &record.resolved_path, request.import_type.as_deref());
             self.collect_async_transitive_dependencies(&dep_key, seen, out);
         }
     }
 
     fn module_has_async_dependency(&self, entry_path: &std::path::Path, requests: &[crate::core::ModuleRequest]) -> bool {
         requests.iter().any(|request| {
-            let dep_key = crate::core::resolve_module_request_key(&request.specifier, entry_path, request.import_type.as_deref());
+            let dep_key = self
+                .module_loader
+                .request_key(&request.specifier, entry_path, request.import_type.as_deref());
             self.module_records
                 .get(&dep_key)
                 .is_some_and(|record| record.status == ModuleStatus::EvaluatingAsync)
@@ -2107,8 +3053,9 @@ impl<'gc> VM<'gc> {
             return false;
         };
         record.requests.iter().any(|request| {
-            let dep_key =
-                crate::core::resolve_module_request_key(&request.specifier, &record.resolved_path, request.import_type.as_deref());
+            let dep_key = self
+                .module_loader
+                .request_key(&request.specifier, &record.resolved_path, request.import_type.as_deref());
             self.module_reaches_module(&dep_key, target_key, visited)
         })
     }
@@ -2135,7 +3082,9 @@ impl<'gc> VM<'gc> {
         requests: &[crate::core::ModuleRequest],
     ) -> bool {
         requests.iter().any(|request| {
-            let dep_key = crate::core::resolve_module_request_key(&request.specifier, entry_path, request.import_type.as_deref());
+            let dep_key = self
+                .module_loader
+                .request_key(&request.specifier, entry_path, request.import_type.as_deref());
             !self.modules_share_async_cycle(module_key, &dep_key) && self.module_cycle_has_evaluating_async(&dep_key)
         })
     }
@@ -2147,7 +3096,7 @@ impl<'gc> VM<'gc> {
         requests: &[crate::core::ModuleRequest],
     ) {
         for request in requests {
-            let resolved = crate::core::resolve_module_path(&request.specifier, entry_path);
+            let resolved = self.module_loader.resolve_path(&request.specifier, entry_path);
             self.load_single_module(ctx, &resolved, request.import_type.as_deref());
         }
     }
@@ -2160,28 +3109,42 @@ impl<'gc> VM<'gc> {
             return;
         }
 
+        let source = match self.module_loader.load(resolved_path) {
+            Ok(source) => source,
+            Err(err) => {
+                let error_value = self.vm_value_from_error(ctx, &err);
+                self.record_module_load_error(&key, error_value);
+                return;
+            }
+        };
+
         match import_type {
             Some("text") => {
-                self.load_single_text_module(ctx, resolved_path, &key);
+                self.load_single_text_module(ctx, resolved_path, &key, source);
                 return;
             }
             Some("bytes") => {
-                self.load_single_bytes_module(ctx, resolved_path, &key);
+                self.load_single_bytes_module(ctx, resolved_path, &key, source);
                 return;
             }
             Some("json") => {
-                self.load_single_json_module(ctx, resolved_path, &key);
+                self.load_single_json_module(ctx, resolved_path, &key, source);
                 return;
             }
             _ => {}
         }
 
-        if resolved_path.extension().and_then(|ext| ext.to_str()) == Some("json") {
-            self.load_single_json_module(ctx, resolved_path, &key);
+        let is_json = match &source {
+            crate::core::ModuleSource::Json(_) => true,
+            crate::core::ModuleSource::Bytes(_) => resolved_path.extension().and_then(|ext| ext.to_str()) == Some("json"),
+            crate::core::ModuleSource::JavaScript(_) => false,
+        };
+        if is_json {
+            self.load_single_json_module(ctx, resolved_path, &key, source);
             return;
         }
 
-        let dep_source = match crate::core::read_script_file(resolved_path) {
+        let dep_source = match source.into_text() {
             Ok(s) => s,
             Err(err) => {
                 let error_value = self.vm_value_from_error(ctx, &err);
@@ -2225,6 +3188,7 @@ impl<'gc> VM<'gc> {
 
         let mut dep_compiler = crate::core::Compiler::new();
         dep_compiler.set_script_filename(key.clone());
+        dep_compiler.set_module_loader(self.module_loader.clone());
         for (path, exports) in &self.loaded_modules {
             let mut info = std::collections::HashMap::new();
             for k in exports.keys() {
@@ -2314,8 +3278,14 @@ impl<'gc> VM<'gc> {
         self.refresh_deferred_module_namespace_object(ctx, key);
     }
 
-    fn load_single_json_module(&mut self, ctx: &GcContext<'gc>, resolved_path: &std::path::Path, key: &str) {
-        let dep_source = match crate::core::read_script_file(resolved_path) {
+    fn load_single_json_module(
+        &mut self,
+        ctx: &GcContext<'gc>,
+        resolved_path: &std::path::Path,
+        key: &str,
+        source: crate::core::ModuleSource,
+    ) {
+        let dep_source = match source.into_text() {
             Ok(s) => s,
             Err(err) => {
                 let error_value = self.vm_value_from_error(ctx, &err);
@@ -2336,8 +3306,14 @@ impl<'gc> VM<'gc> {
         self.load_single_synthetic_default_module(ctx, resolved_path, key, dep_source, default_value);
     }
 
-    fn load_single_text_module(&mut self, ctx: &GcContext<'gc>, resolved_path: &std::path::Path, key: &str) {
-        let dep_source = match crate::core::read_script_file(resolved_path) {
+    fn load_single_text_module(
+        &mut self,
+        ctx: &GcContext<'gc>,
+        resolved_path: &std::path::Path,
+        key: &str,
+        source: crate::core::ModuleSource,
+    ) {
+        let dep_source = match source.into_text() {
             Ok(s) => s,
             Err(err) => {
                 let error_value = self.vm_value_from_error(ctx, &err);
@@ -2349,23 +3325,20 @@ impl<'gc> VM<'gc> {
         self.load_single_synthetic_default_module(ctx, resolved_path, key, dep_source, default_value);
     }
 
-    fn load_single_bytes_module(&mut self, ctx: &GcContext<'gc>, resolved_path: &std::path::Path, key: &str) {
-        let dep_bytes = match std::fs::read(resolved_path) {
-            Ok(bytes) => bytes,
-            Err(err) => {
-                let error = crate::raise_eval_error!(format!("Failed to read script file '{}': {err}", resolved_path.display()));
-                let error_value = self.vm_value_from_error(ctx, &error);
-                self.record_module_load_error(key, error_value);
-                return;
-            }
-        };
+    fn load_single_bytes_module(
+        &mut self,
+        ctx: &GcContext<'gc>,
+        resolved_path: &std::path::Path,
+        key: &str,
+        source: crate::core::ModuleSource,
+    ) {
+        let dep_bytes = source.into_bytes();
         let source = String::from_utf8_lossy(&dep_bytes).into_owned();
         let default_value = self.create_immutable_uint8array_from_bytes(ctx, dep_bytes);
         self.load_single_synthetic_default_module(ctx, resolved_path, key, source, default_value);
     }
 
     fn finalize_dependency_module_record(&mut self, ctx: &GcContext<'gc>, record: &StoredModuleRecord) {
-        use crate::core::resolve_module_path;
         use crate::core::statement::{ImportSpecifier, StatementKind};
 
         let key = record.resolved_path.to_string_lossy().to_string();
@@ -2374,7 +3347,9 @@ impl<'gc> VM<'gc> {
 
         for stmt in &record.statements {
             if let crate::core::statement::StatementKind::Import(specs, source, import_type) = &*stmt.kind {
-                let imp_resolved = crate::core::resolve_module_request_key(source, &record.resolved_path, import_type.as_deref());
+                let imp_resolved = self
+                    .module_loader
+                    .request_key(source, &record.resolved_path, import_type.as_deref());
                 for spec in specs {
                     match spec {
                         crate::core::statement::ImportSpecifier::Namespace(local_name) => {
@@ -2428,13 +3403,14 @@ impl<'gc> VM<'gc> {
         }
 
         fn extract_imports(
+            loader: &crate::core::ModuleLoaderState,
             stmts: &[crate::core::Statement],
             base_path: &std::path::Path,
         ) -> std::collections::HashMap<String, (String, String)> {
             let mut map = std::collections::HashMap::new();
             for stmt in stmts {
                 if let StatementKind::Import(specs, source, import_type) = &*stmt.kind {
-                    let resolved = crate::core::resolve_module_request_key(source, base_path, import_type.as_deref());
+                    let resolved = loader.request_key(source, base_path, import_type.as_deref());
                     for spec in specs {
                         match spec {
                             ImportSpecifier::Named(import_name, Some(local_name)) => {
@@ -2466,14 +3442,20 @@ impl<'gc> VM<'gc> {
 
         for (module_key, stored) in &self.module_records {
             local_exports.insert(module_key.clone(), stored.export_name_to_local.keys().cloned().collect());
-            import_bindings.insert(module_key.clone(), extract_imports(&stored.statements, &stored.resolved_path));
+            import_bindings.insert(
+                module_key.clone(),
+                extract_imports(&self.module_loader, &stored.statements, &stored.resolved_path),
+            );
             export_to_local.insert(module_key.clone(), stored.export_name_to_local.clone());
             let resolved_reexports: Vec<(String, Vec<crate::core::ReexportSpec>)> = stored
                 .reexport_sources
                 .iter()
                 .map(|(source, specs)| {
                     (
-                        resolve_module_path(source, &stored.resolved_path).to_string_lossy().to_string(),
+                        self.module_loader
+                            .resolve_path(source, &stored.resolved_path)
+                            .to_string_lossy()
+                            .to_string(),
                         specs.clone(),
                     )
                 })
@@ -2494,7 +3476,7 @@ impl<'gc> VM<'gc> {
         let mut ambiguous_keys: std::collections::HashSet<String> = std::collections::HashSet::new();
         let mut runtime_reexport_deps: Vec<(String, Vec<crate::core::ReexportSpec>)> = Vec::new();
         for (reexport_src, reexport_specs) in &record.reexport_sources {
-            let reexport_resolved = resolve_module_path(reexport_src, &record.resolved_path);
+            let reexport_resolved = self.module_loader.resolve_path(reexport_src, &record.resolved_path);
             let reexport_key = reexport_resolved.to_string_lossy().to_string();
             runtime_reexport_deps.push((reexport_key.clone(), reexport_specs.clone()));
             if let Some(source_exports) = self.loaded_modules.get(&reexport_key).cloned() {
@@ -2818,7 +3800,9 @@ impl<'gc> VM<'gc> {
         let mut evaluation_list = Vec::new();
         let mut scheduled = std::collections::HashSet::new();
         for request in requests {
-            let key = crate::core::resolve_module_request_key(&request.specifier, entry_path, request.import_type.as_deref());
+            let key = self
+                .module_loader
+                .request_key(&request.specifier, entry_path, request.import_type.as_deref());
             match request.phase {
                 crate::core::ModuleRequestPhase::Evaluation => {
                     if scheduled.insert(key.clone()) {
@@ -2865,7 +3849,9 @@ impl<'gc> VM<'gc> {
         // Propagate load errors (e.g. SyntaxError) for all requested modules,
         // including deferred ones whose evaluation is skipped.
         for request in requests {
-            let key = crate::core::resolve_module_request_key(&request.specifier, entry_path, request.import_type.as_deref());
+            let key = self
+                .module_loader
+                .request_key(&request.specifier, entry_path, request.import_type.as_deref());
             if let Some(error) = self.module_load_errors.get(&key).cloned() {
                 self.pending_throw = Some(error);
                 return;
@@ -2883,7 +3869,9 @@ impl<'gc> VM<'gc> {
         requests: &[crate::core::ModuleRequest],
     ) -> Result<(), JSError> {
         for request in requests {
-            let key = crate::core::resolve_module_request_key(&request.specifier, entry_path, request.import_type.as_deref());
+            let key = self
+                .module_loader
+                .request_key(&request.specifier, entry_path, request.import_type.as_deref());
             if let Some(error) = self.module_load_errors.get(&key).cloned() {
                 return Err(self.vm_error_to_js_error(ctx, &error));
             }
@@ -2981,7 +3969,6 @@ impl<'gc> VM<'gc> {
         main_reexport_sources: &[(String, Vec<crate::core::ReexportSpec>)],
         entry_path: &std::path::Path,
     ) -> Result<(), JSError> {
-        use crate::core::resolve_module_path;
         use crate::core::statement::{ImportSpecifier, StatementKind};
 
         // Build per-module data structures needed for spec-compliant ResolveExport
@@ -2995,13 +3982,14 @@ impl<'gc> VM<'gc> {
 
         // Helper: extract import bindings from statements
         fn extract_imports(
+            loader: &crate::core::ModuleLoaderState,
             stmts: &[crate::core::Statement],
             base_path: &std::path::Path,
         ) -> std::collections::HashMap<String, (String, String)> {
             let mut map = std::collections::HashMap::new();
             for stmt in stmts {
                 if let StatementKind::Import(specs, source, import_type) = &*stmt.kind {
-                    let resolved = crate::core::resolve_module_request_key(source, base_path, import_type.as_deref());
+                    let resolved = loader.request_key(source, base_path, import_type.as_deref());
                     for spec in specs {
                         match spec {
                             ImportSpecifier::Named(import_name, Some(local_name)) => {
@@ -3031,7 +4019,10 @@ impl<'gc> VM<'gc> {
                 set.insert(k.clone());
             }
             local_exports.insert(key.clone(), set);
-            import_bindings.insert(key.clone(), extract_imports(&record.statements, &record.resolved_path));
+            import_bindings.insert(
+                key.clone(),
+                extract_imports(&self.module_loader, &record.statements, &record.resolved_path),
+            );
             export_to_local.insert(key.clone(), record.export_name_to_local.clone());
         }
         // Add main module's exports and import bindings
@@ -3041,7 +4032,10 @@ impl<'gc> VM<'gc> {
                 set.insert(k.clone());
             }
             local_exports.insert(main_module_key.to_string(), set);
-            import_bindings.insert(main_module_key.to_string(), extract_imports(main_statements, entry_path));
+            import_bindings.insert(
+                main_module_key.to_string(),
+                extract_imports(&self.module_loader, main_statements, entry_path),
+            );
             export_to_local.insert(main_module_key.to_string(), main_export_name_to_local.clone());
         }
 
@@ -3051,7 +4045,7 @@ impl<'gc> VM<'gc> {
         {
             let mut resolved_reexports = Vec::new();
             for (source, specs) in main_reexport_sources {
-                let resolved = resolve_module_path(source, entry_path).to_string_lossy().to_string();
+                let resolved = self.module_loader.resolve_path(source, entry_path).to_string_lossy().to_string();
                 resolved_reexports.push((resolved, specs.clone()));
             }
             if !resolved_reexports.is_empty() {
@@ -3104,7 +4098,7 @@ impl<'gc> VM<'gc> {
         // Validate import bindings in main module
         for stmt in main_statements {
             if let StatementKind::Import(specs, source, import_type) = &*stmt.kind {
-                let resolved = crate::core::resolve_module_request_key(source, entry_path, import_type.as_deref());
+                let resolved = self.module_loader.request_key(source, entry_path, import_type.as_deref());
                 Self::validate_import_specs_static(specs, &resolved, source, &ctx)?;
             }
         }
@@ -3112,7 +4106,9 @@ impl<'gc> VM<'gc> {
         for record in self.module_records.values() {
             for stmt in &record.statements {
                 if let StatementKind::Import(specs, source, import_type) = &*stmt.kind {
-                    let resolved = crate::core::resolve_module_request_key(source, &record.resolved_path, import_type.as_deref());
+                    let resolved = self
+                        .module_loader
+                        .request_key(source, &record.resolved_path, import_type.as_deref());
                     Self::validate_import_specs_static(specs, &resolved, source, &ctx)?;
                 }
             }
@@ -3390,6 +4386,8 @@ impl<'gc> VM<'gc> {
         self.generator_objects
             .retain(|gen_id, weak_obj| Self::upgrade_handle(ctx, *weak_obj).is_some() && self.generator_states.contains_key(gen_id));
 
+        removed += self.sweep_host_instances(ctx);
+
         removed
     }
 
@@ -3406,6 +4404,7 @@ impl<'gc> VM<'gc> {
         let compiler = crate::core::Compiler::new();
         let chunk = compiler.compile(&statements)?;
         let mut vm = Self::spawn_child_vm(chunk, ctx);
+        self.inherit_embedder_state(&mut vm);
         vm.set_source_context(code, None);
         vm.run(ctx)
     }
@@ -3419,6 +4418,7 @@ impl<'gc> VM<'gc> {
         let compiler = crate::core::Compiler::new();
         let chunk = compiler.compile(&statements)?;
         let mut vm = Self::spawn_child_vm(chunk, ctx);
+        self.inherit_embedder_state(&mut vm);
         vm.set_source_context(code, None);
         vm.run(ctx)
     }
@@ -4035,7 +5035,9 @@ impl<'gc> VM<'gc> {
     fn create_vm_array(&self, ctx: &GcContext<'gc>, elements: Vec<Value<'gc>>) -> Value<'gc> {
         let arr = new_gc_cell_ptr(ctx, VmArrayData::new(elements));
         self.link_array_prototype(ctx, &arr);
-        Value::Array(arr)
+        let arr = Value::Array(arr);
+        self.charge_external(ctx, limits::external_bytes(&arr));
+        arr
     }
 
     fn create_vm_sparse_array(&self, ctx: &GcContext<'gc>, len: usize) -> Value<'gc> {
@@ -4049,7 +5051,9 @@ impl<'gc> VM<'gc> {
         }
         let arr = new_gc_cell_ptr(ctx, data);
         self.link_array_prototype(ctx, &arr);
-        Value::Array(arr)
+        let arr = Value::Array(arr);
+        self.charge_external(ctx, limits::external_bytes(&arr));
+        arr
     }
 
     fn init_native_ctor_header(map: &mut IndexMap<String, Value<'gc>>, native_id: FunctionID, name: &str, length: f64) {
@@ -4713,6 +5717,10 @@ impl<'gc> VM<'gc> {
             // Move any pending reactions into the microtask queue. Running them
             // synchronously here grows the Rust call stack for long promise chains.
             let queue = obj.borrow_mut(ctx).shift_remove("__then_queue__");
+            let has_reactions = matches!(&queue, Some(Value::Array(arr)) if !arr.borrow().elements.is_empty());
+            if rejected && !has_reactions {
+                self.track_rejection(promise);
+            }
             if let Some(Value::Array(arr)) = queue {
                 let entries: Vec<Value<'gc>> = arr.borrow().elements.clone();
                 for entry in entries {
@@ -4758,6 +5766,9 @@ impl<'gc> VM<'gc> {
 
             if is_promise {
                 if has_value {
+                    if rejected {
+                        self.mark_promise_handled(ctx, value);
+                    }
                     self.settle_promise(ctx, promise, &settled, rejected);
                 } else {
                     let resolve = Self::make_promise_settle_host_fn(ctx, "promise.__resolve", promise, "", 1.0);
@@ -4974,14 +5985,14 @@ impl<'gc> VM<'gc> {
             None
         };
 
-        let Some(base_source) = self.current_source_path().map(str::to_owned).or_else(|| self.script_path.clone()) else {
+        let Some(base_source) = self.dynamic_import_referrer() else {
             let reject_value = self.make_type_error_object(ctx, "Dynamic import requires an active script or module");
             reject_with(self, reject_value);
             return promise;
         };
 
         let base_path = std::path::Path::new(&base_source);
-        let module_key = crate::core::resolve_module_request_key(&specifier_string, base_path, import_type.as_deref());
+        let module_key = self.module_loader.request_key(&specifier_string, base_path, import_type.as_deref());
 
         if !self.loaded_modules.contains_key(&module_key)
             || self
@@ -5082,14 +6093,14 @@ impl<'gc> VM<'gc> {
             }
         };
 
-        let Some(base_source) = self.current_source_path().map(str::to_owned).or_else(|| self.script_path.clone()) else {
+        let Some(base_source) = self.dynamic_import_referrer() else {
             let reject_value = self.make_type_error_object(ctx, "Deferred import requires an active script or module");
             reject_with(self, reject_value);
             return promise;
         };
 
         let base_path = std::path::Path::new(&base_source);
-        let module_key = crate::core::resolve_module_request_key(&specifier_string, base_path, None);
+        let module_key = self.module_loader.request_key(&specifier_string, base_path, None);
         let request = crate::core::ModuleRequest {
             specifier: specifier_string,
             phase: crate::core::ModuleRequestPhase::Defer,
@@ -5150,14 +6161,14 @@ impl<'gc> VM<'gc> {
             }
         };
 
-        let Some(base_source) = self.current_source_path().map(str::to_owned).or_else(|| self.script_path.clone()) else {
+        let Some(base_source) = self.dynamic_import_referrer() else {
             let reject_value = self.make_type_error_object(ctx, "import.source requires an active script or module");
             reject_with(self, reject_value);
             return promise;
         };
 
         let base_path = std::path::Path::new(&base_source);
-        let module_key = crate::core::resolve_module_request_key(&specifier_string, base_path, None);
+        let module_key = self.module_loader.request_key(&specifier_string, base_path, None);
 
         if !self.loaded_modules.contains_key(&module_key)
             || self
@@ -5200,12 +6211,32 @@ impl<'gc> VM<'gc> {
     }
 
     fn call_host_fn(&mut self, ctx: &GcContext<'gc>, name: &str, receiver: Option<&Value<'gc>>, args: &[Value<'gc>]) -> Value<'gc> {
+        #[cfg(feature = "temporal")]
         if name.starts_with("temporal.") {
             return self.temporal_handle_host_fn(ctx, name, receiver, args);
         }
+        #[cfg(feature = "intl")]
         if name.starts_with("intl.") {
             return self.intl_handle_host_fn(ctx, name, receiver, args);
         }
+        if name.starts_with("embed.") {
+            return self.embed_handle_host_fn(ctx, name, args);
+        }
+        if name.starts_with("hostclass.") {
+            return self.host_class_handle_host_fn(ctx, name, args);
+        }
+        if name.starts_with("asynchost.") {
+            return self.async_host_handle_host_fn(ctx, name, args);
+        }
+        if name.starts_with("rejection.") {
+            return self.rejection_handle_host_fn(ctx, name, receiver);
+        }
+        if name.starts_with("clock.") {
+            return self.clock_handle_host_fn(name);
+        }
+        if name.starts_with("console.") {
+            return self.console_handle_host_fn(ctx, name, args);
+        }
         match name {
             "Function.prototype.restrictedThrow" => {
                 self.throw_type_error(
@@ -5241,6 +6272,7 @@ impl<'gc> VM<'gc> {
                     }
                 }
             }
+            #[cfg(feature = "shared-memory")]
             "__agent_start" => {
                 let src = if let Some(v) = args.first() {
                     match self.vm_to_string_like_spec(ctx, v) {
@@ -5253,9 +6285,10 @@ impl<'gc> VM<'gc> {
                 } else {
                     String::new()
                 };
-                crate::js_agent::start_agent(src);
+                crate::js_agent::start_agent(src, self.permissions.borrow().clone());
                 Value::Undefined
             }
+            #[cfg(feature = "shared-memory")]
             "__agent_broadcast" => {
                 let Some(Value::Object(buf_obj)) = args.first().cloned() else {
                     self.throw_type_error(ctx, "agent broadcast requires SharedArrayBuffer");
@@ -5278,16 +6311,20 @@ impl<'gc> VM<'gc> {
                 }
                 Value::Undefined
             }
+            #[cfg(feature = "shared-memory")]
             "__agent_getReport" => match crate::js_agent::pop_report() {
                 Some(report) => Value::from(report),
                 None => Value::Null,
             },
+            #[cfg(feature = "shared-memory")]
             "__agent_sleep" => {
                 let duration = args.first().map(to_number).unwrap_or(0.0);
                 crate::js_agent::sleep_ms(duration);
                 Value::Undefined
             }
+            #[cfg(feature = "shared-memory")]
             "__agent_monotonicNow" => Value::Number(crate::js_agent::monotonic_now_ms()),
+            #[cfg(feature = "shared-memory")]
             "__agent_report" => {
                 let report = if let Some(v) = args.first() {
                     match self.vm_to_string_like_spec(ctx, v) {
@@ -5303,7 +6340,9 @@ impl<'gc> VM<'gc> {
                 crate::js_agent::push_report(report);
                 Value::Undefined
             }
+            #[cfg(feature = "shared-memory")]
             "__agent_leaving" => Value::Undefined,
+            #[cfg(feature = "shared-memory")]
             "__agent_receiveBroadcast" => {
                 let Some(callback) = args.first().cloned() else {
                     self.throw_type_error(ctx, "agent receiveBroadcast requires callback");
@@ -5341,6 +6380,7 @@ impl<'gc> VM<'gc> {
             "__createRealm__" => {
                 let realm_id = self.child_realms.len();
                 let mut child = Box::new(VM::new(Chunk::default(), ctx));
+                self.inherit_embedder_state(&mut child);
                 let child_global_this = child.global_this;
 
                 if let Some(Value::Object(parent_symbol_obj)) = self.globals.get("Symbol")
@@ -6069,6 +7109,7 @@ impl<'gc> VM<'gc> {
                 }
                 Value::Object(new_gc_cell_ptr(ctx, new_map))
             }
+            #[cfg(feature = "shared-memory")]
             "sharedArrayBuffer.getByteLength" => {
                 let this_val = receiver.unwrap_or(&Value::Undefined);
                 let Value::Object(buf_obj) = this_val else {
@@ -6093,6 +7134,7 @@ impl<'gc> VM<'gc> {
                 let len = b.get("byteLength").map(to_number).unwrap_or(0.0);
                 Value::Number(len)
             }
+            #[cfg(feature = "shared-memory")]
             "sharedArrayBuffer.getMaxByteLength" => {
                 let this_val = receiver.unwrap_or(&Value::Undefined);
                 let Value::Object(buf_obj) = this_val else {
@@ -6122,6 +7164,7 @@ impl<'gc> VM<'gc> {
                     Value::Number(len)
                 }
             }
+            #[cfg(feature = "shared-memory")]
             "sharedArrayBuffer.getGrowable" => {
                 let this_val = receiver.unwrap_or(&Value::Undefined);
                 let Value::Object(buf_obj) = this_val else {
@@ -6145,6 +7188,7 @@ impl<'gc> VM<'gc> {
                 }
                 Value::Boolean(matches!(b.get("__growable__"), Some(Value::Boolean(true))))
             }
+            #[cfg(feature = "shared-memory")]
             "sharedArrayBuffer.grow" => {
                 let this_val = receiver.unwrap_or(&Value::Undefined);
                 let Value::Object(buf_obj) = this_val else {
@@ -6200,6 +7244,7 @@ impl<'gc> VM<'gc> {
                 }
                 Value::Undefined
             }
+            #[cfg(feature = "shared-memory")]
             "sharedArrayBuffer.slice" => {
                 let this_val = receiver.unwrap_or(&Value::Undefined);
                 let Value::Object(buf_obj) = this_val else {
@@ -6567,6 +7612,7 @@ impl<'gc> VM<'gc> {
 
                 new_buffer
             }
+            #[cfg(feature = "shared-memory")]
             "atomics.load" => {
                 let Some(target) = args.first().cloned() else {
                     let mut err_map = IndexMap::new();
@@ -6712,6 +7758,7 @@ impl<'gc> VM<'gc> {
                     }
                 }
             }
+            #[cfg(feature = "shared-memory")]
             "atomics.isLockFree" => {
                 let size_v = args.first().cloned().unwrap_or(Value::Undefined);
                 if size_v.is_symbol_value() {
@@ -6773,6 +7820,7 @@ impl<'gc> VM<'gc> {
                 let size = if n.is_finite() { n.trunc() as i64 } else { 0 };
                 Value::Boolean(matches!(size, 1 | 2 | 4 | 8))
             }
+            #[cfg(feature = "shared-memory")]
             "atomics.store" => {
                 let Some(target) = args.first().cloned() else {
                     let mut err_map = IndexMap::new();
@@ -6942,6 +7990,7 @@ impl<'gc> VM<'gc> {
                     }
                 }
             }
+            #[cfg(feature = "shared-memory")]
             "atomics.notify" => {
                 let Some(target) = args.first().cloned() else {
                     let mut err_map = IndexMap::new();
@@ -7198,6 +8247,7 @@ impl<'gc> VM<'gc> {
                     }
                 }
             }
+            #[cfg(feature = "shared-memory")]
             "atomics.wait" => {
                 let Some(target) = args.first().cloned() else {
                     let mut err_map = IndexMap::new();
@@ -7536,7 +8586,9 @@ impl<'gc> VM<'gc> {
                     }
                 }
             }
+            #[cfg(feature = "shared-memory")]
             "atomics.waitAsync" => self.call_builtin(ctx, BUILTIN_ATOMICS_WAITASYNC, args),
+            #[cfg(feature = "shared-memory")]
             "atomics.or" => {
                 let Some(target) = args.first().cloned() else {
                     let mut err_map = IndexMap::new();
@@ -7703,6 +8755,7 @@ impl<'gc> VM<'gc> {
                     }
                 }
             }
+            #[cfg(feature = "shared-memory")]
             "atomics.and" => {
                 let Some(target) = args.first().cloned() else {
                     let mut err_map = IndexMap::new();
@@ -7869,6 +8922,7 @@ impl<'gc> VM<'gc> {
                     }
                 }
             }
+            #[cfg(feature = "shared-memory")]
             "atomics.compareExchange" => {
                 let Some(target) = args.first().cloned() else {
                     let mut err_map = IndexMap::new();
@@ -8115,6 +9169,7 @@ impl<'gc> VM<'gc> {
                     }
                 }
             }
+            #[cfg(feature = "shared-memory")]
             "atomics.add" => {
                 let Some(target) = args.first().cloned() else {
                     let mut err_map = IndexMap::new();
@@ -8281,6 +9336,7 @@ impl<'gc> VM<'gc> {
                     }
                 }
             }
+            #[cfg(feature = "shared-memory")]
             "atomics.exchange" => {
                 let Some(target) = args.first().cloned() else {
                     let mut err_map = IndexMap::new();
@@ -8445,6 +9501,7 @@ impl<'gc> VM<'gc> {
                     }
                 }
             }
+            #[cfg(feature = "shared-memory")]
             "atomics.sub" => {
                 let Some(target) = args.first().cloned() else {
                     let mut err_map = IndexMap::new();
@@ -8611,6 +9668,7 @@ impl<'gc> VM<'gc> {
                     }
                 }
             }
+            #[cfg(feature = "shared-memory")]
             "atomics.xor" => {
                 let Some(target) = args.first().cloned() else {
                     let mut err_map = IndexMap::new();
@@ -10266,7 +11324,9 @@ impl<'gc> VM<'gc> {
                 }
             }
             "os.getcwd" | "os.getpid" | "os.getppid" | "os.open" | "os.write" | "os.read" | "os.seek" | "os.close" | "os.path.basename"
-            | "os.path.dirname" | "os.path.join" | "os.path.extname" => self.call_named_host_function(ctx, name, args),
+            | "os.path.dirname" | "os.path.join" | "os.path.extname" | "os.unrefTimer" | "os.refTimer" => {
+                self.call_named_host_function(ctx, name, args)
+            }
             _ if name.starts_with("date.") => self.date_handle_host_fn(ctx, name, receiver, args),
             "string.iterator" => {
                 let this_val = receiver.unwrap_or(&Value::Undefined);
@@ -10349,21 +11409,7 @@ impl<'gc> VM<'gc> {
                     Some(s) => s,
                     None => return Value::Undefined,
                 };
-                let mut collator_args = Vec::new();
-                if args.len() >= 2 {
-                    collator_args.push(args[1].clone());
-                }
-                if args.len() >= 3 {
-                    collator_args.push(args[2].clone());
-                }
-                let collator = match self.intl_construct_service_instance(ctx, "Collator", None, &collator_args) {
-                    Ok(collator) => collator,
-                    Err(err) => {
-                        self.pending_throw = Some(err);
-                        return Value::Undefined;
-                    }
-                };
-                self.intl_collator_compare(ctx, Some(&collator), &[Value::from(base.as_str()), Value::from(other.as_str())])
+                self.string_locale_compare(ctx, &base, &other, args.get(1..).unwrap_or_default())
             }
             "string.substr" => {
                 let this_val = receiver.unwrap_or(&Value::Undefined);
@@ -13368,6 +14414,7 @@ impl<'gc> VM<'gc> {
 
                 target
             }
+            #[cfg(feature = "typed-arrays")]
             _ if name.starts_with("dataview.") => self.dataview_handle_host_fn(ctx, name, receiver, args),
             "promise.catch" => {
                 let recv = receiver.unwrap_or(&Value::Undefined).clone();
@@ -14071,6 +15118,7 @@ impl<'gc> VM<'gc> {
 
                     if is_promise {
                         if rejected {
+                            self.mark_promise_handled(ctx, &current);
                             self.pending_throw = Some(settled.unwrap_or(Value::Undefined));
                             return Value::Undefined;
                         }
@@ -14085,6 +15133,7 @@ impl<'gc> VM<'gc> {
                             let rej = matches!(b2.get("__promise_rejected__"), Some(Value::Boolean(true)));
                             drop(b2);
                             if rej {
+                                self.mark_promise_handled(ctx, &current);
                                 self.pending_throw = Some(val);
                                 return Value::Undefined;
                             }
@@ -14383,15 +15432,31 @@ impl<'gc> VM<'gc> {
                     Value::Undefined
                 }
             },
+            #[cfg(feature = "std")]
             "std.sprintf" => match crate::js_std::sprintf::handle_sprintf_call(args) {
                 Ok(v) => v,
                 Err(_) => Value::Undefined,
             },
+            #[cfg(feature = "std")]
+            "std.tmpfile" if !self.check_permission(ctx, Access::Tmpfile) => Value::Undefined,
+            #[cfg(feature = "std")]
             "std.tmpfile" => crate::js_std::tmpfile::vm_create_tmpfile(ctx),
+            "std.getenv" => {
+                let name = args.first().map(value_to_string).unwrap_or_default();
+                if !self.check_permission(ctx, Access::Env(&name)) {
+                    return Value::Undefined;
+                }
+                match std::env::var(&name) {
+                    Ok(value) => Value::from(&value),
+                    Err(_) => Value::Undefined,
+                }
+            }
             "std.gc" => Value::Undefined,
+            #[cfg(feature = "std")]
             "tmp.puts" | "tmp.readAsString" | "tmp.seek" | "tmp.close" | "tmp.getline" => {
                 crate::js_std::tmpfile::vm_dispatch_file_method(name, receiver, args)
             }
+            #[cfg(feature = "typed-arrays")]
             _ if name.starts_with("typedarray.") => self.typedarray_handle_host_fn(ctx, name, receiver, args),
             _ if name.starts_with("disposableStack.") => self.disposable_stack_dispatch(ctx, name, receiver, args, false),
             _ if name.starts_with("asyncDisposableStack.") => self.disposable_stack_dispatch(ctx, name, receiver, args, true),
@@ -14442,6 +15507,11 @@ impl<'gc> VM<'gc> {
             || name.starts_with("disposableStack.")
             || name.starts_with("asyncDisposableStack.")
             || name.starts_with("shadowRealm.")
+            || name.starts_with("embed.")
+            || name.starts_with("hostclass.")
+            || name.starts_with("asynchost.")
+            || name.starts_with("rejection.")
+            || name.starts_with("clock.")
         {
             return self.call_host_fn(ctx, name, receiver, args);
         }
@@ -14516,6 +15586,7 @@ impl<'gc> VM<'gc> {
             "console.warn" => self.call_builtin(ctx, BUILTIN_CONSOLE_WARN, args),
             "console.error" => self.call_builtin(ctx, BUILTIN_CONSOLE_ERROR, args),
             "AbstractModuleSource.prototype.@@toStringTag" => Value::Undefined,
+            "os.getcwd" | "os.getpid" | "os.getppid" if !self.check_permission(ctx, Access::Sys(&name["os.".len()..])) => Value::Undefined,
             "os.getcwd" => {
                 let cwd = std::env::current_dir()
                     .ok()
@@ -14525,9 +15596,26 @@ impl<'gc> VM<'gc> {
             }
             "os.getpid" => Value::Number(std::process::id() as f64),
             "os.getppid" => Value::Number(std::process::id() as f64),
+            "os.unrefTimer" | "os.refTimer" => {
+                // Returns the id so a handle can be unref'd where it is created.
+                let id = args.first().cloned().unwrap_or(Value::Undefined);
+                if let Value::Number(n) = id {
+                    self.set_timer_ref(n as usize, name == "os.refTimer");
+                }
+                id
+            }
             "os.open" => {
                 let filename = args.first().map(value_to_string).unwrap_or_default();
                 let flags = args.get(1).map(to_number).unwrap_or(0.0) as i32;
+                // Open the path that was checked, not one a symlink could redirect later.
+                let path = crate::core::resolve_path(std::path::Path::new(&filename));
+                let reads = flags & 3 != 1;
+                let writes = flags & 3 != 0 || flags & (64 | 512) != 0;
+                if (reads && !self.check_permission(ctx, Access::Read(&path)))
+                    || (writes && !self.check_permission(ctx, Access::Write(&path)))
+                {
+                    return Value::Undefined;
+                }
 
                 let mut options = std::fs::OpenOptions::new();
                 if flags & 2 != 0 {
@@ -14544,18 +15632,14 @@ impl<'gc> VM<'gc> {
                     options.truncate(true);
                 }
 
-                match options.open(&filename) {
-                    Ok(file) => {
-                        let fd = vm_next_os_file_id();
-                        VM_OS_FILE_STORE.lock().unwrap().insert(fd, file);
-                        Value::Number(fd as f64)
-                    }
+                match options.open(&path) {
+                    Ok(file) => Value::Number(self.os_files.insert(file) as f64),
                     Err(_) => Value::Number(-1.0),
                 }
             }
             "os.close" => {
                 let fd = args.first().map(to_number).unwrap_or(-1.0) as u64;
-                if VM_OS_FILE_STORE.lock().unwrap().remove(&fd).is_some() {
+                if self.os_files.close(fd) {
                     Value::Number(0.0)
                 } else {
                     Value::Number(-1.0)
@@ -14564,51 +15648,36 @@ impl<'gc> VM<'gc> {
             "os.write" => {
                 let fd = args.first().map(to_number).unwrap_or(-1.0) as u64;
                 let data = args.get(1).map(value_to_string).unwrap_or_default();
-                let mut store = VM_OS_FILE_STORE.lock().unwrap();
-                if let Some(file) = store.get_mut(&fd) {
-                    match file.write(data.as_bytes()) {
-                        Ok(n) => Value::Number(n as f64),
-                        Err(_) => Value::Number(-1.0),
-                    }
-                } else {
-                    Value::Number(-1.0)
+                match self.os_files.with_file(fd, |file| file.write(data.as_bytes())) {
+                    Some(Ok(n)) => Value::Number(n as f64),
+                    _ => Value::Number(-1.0),
                 }
             }
             "os.read" => {
                 let fd = args.first().map(to_number).unwrap_or(-1.0) as u64;
                 let count = args.get(1).map(to_number).unwrap_or(0.0).max(0.0) as usize;
-                let mut store = VM_OS_FILE_STORE.lock().unwrap();
-                if let Some(file) = store.get_mut(&fd) {
-                    let mut buf = vec![0u8; count];
-                    match file.read(&mut buf) {
-                        Ok(n) => {
-                            buf.truncate(n);
-                            Value::from(&*String::from_utf8_lossy(&buf))
-                        }
-                        Err(_) => Value::from(""),
+                let mut buf = vec![0u8; count];
+                match self.os_files.with_file(fd, |file| file.read(&mut buf)) {
+                    Some(Ok(n)) => {
+                        buf.truncate(n);
+                        Value::from(&*String::from_utf8_lossy(&buf))
                     }
-                } else {
-                    Value::from("")
+                    _ => Value::from(""),
                 }
             }
             "os.seek" => {
                 let fd = args.first().map(to_number).unwrap_or(-1.0) as u64;
                 let offset = args.get(1).map(to_number).unwrap_or(0.0) as i64;
                 let whence = args.get(2).map(to_number).unwrap_or(0.0) as i32;
-                let mut store = VM_OS_FILE_STORE.lock().unwrap();
-                if let Some(file) = store.get_mut(&fd) {
-                    let seek_from = match whence {
-                        0 => SeekFrom::Start(offset.max(0) as u64),
-                        1 => SeekFrom::Current(offset),
-                        2 => SeekFrom::End(offset),
-                        _ => SeekFrom::Start(offset.max(0) as u64),
-                    };
-                    match file.seek(seek_from) {
-                        Ok(pos) => Value::Number(pos as f64),
-                        Err(_) => Value::Number(-1.0),
-                    }
-                } else {
-                    Value::Number(-1.0)
+                let seek_from = match whence {
+                    0 => SeekFrom::Start(offset.max(0) as u64),
+                    1 => SeekFrom::Current(offset),
+                    2 => SeekFrom::End(offset),
+                    _ => SeekFrom::Start(offset.max(0) as u64),
+                };
+                match self.os_files.with_file(fd, |file| file.seek(seek_from)) {
+                    Some(Ok(pos)) => Value::Number(pos as f64),
+                    _ => Value::Number(-1.0),
                 }
             }
             "os.path.basename" => {
@@ -17824,6 +18893,133 @@ impl<'gc> VM<'gc> {
         None
     }
 
+    /// Register `SharedArrayBuffer` and `Atomics`.
+    #[cfg(feature = "shared-memory")]
+    fn initialize_shared_memory(&mut self, ctx: &GcContext<'gc>) {
+        let mut shared_array_buffer_map = IndexMap::new();
+        shared_array_buffer_map.insert("__native_id__".to_string(), Value::Number(BUILTIN_CTOR_SHAREDARRAYBUFFER as f64));
+        shared_array_buffer_map.insert("length".to_string(), Value::Number(1.0));
+        write_attrs_to_legacy_map(&mut shared_array_buffer_map, "length", PropAttrs::CONFIGURABLE);
+        let mut sab_proto = IndexMap::new();
+        sab_proto.insert("__type__".to_string(), Value::from("SharedArrayBuffer"));
+        sab_proto.insert("@@sym:4".to_string(), Value::from("SharedArrayBuffer"));
+        write_attrs_to_legacy_map(&mut sab_proto, "@@sym:4", PropAttrs::CONFIGURABLE);
+        set_getter(
+            &mut sab_proto,
+            "byteLength",
+            Self::make_host_fn_with_name_len(ctx, "sharedArrayBuffer.getByteLength", "get byteLength", 0.0, false),
+        );
+        mark_nonenumerable(&mut sab_proto, "byteLength");
+        set_getter(
+            &mut sab_proto,
+            "maxByteLength",
+            Self::make_host_fn_with_name_len(ctx, "sharedArrayBuffer.getMaxByteLength", "get maxByteLength", 0.0, false),
+        );
+        mark_nonenumerable(&mut sab_proto, "maxByteLength");
+        set_getter(
+            &mut sab_proto,
+            "growable",
+            Self::make_host_fn_with_name_len(ctx, "sharedArrayBuffer.getGrowable", "get growable", 0.0, false),
+        );
+        mark_nonenumerable(&mut sab_proto, "growable");
+        sab_proto.insert(
+            "grow".to_string(),
+            Self::make_host_fn_with_name_len(ctx, "sharedArrayBuffer.grow", "grow", 1.0, false),
+        );
+        mark_nonenumerable(&mut sab_proto, "grow");
+        sab_proto.insert(
+            "slice".to_string(),
+            Self::make_host_fn_with_name_len(ctx, "sharedArrayBuffer.slice", "slice", 2.0, false),
+        );
+        mark_nonenumerable(&mut sab_proto, "slice");
+        let sab_proto_val = Value::Object(new_gc_cell_ptr(ctx, sab_proto));
+        shared_array_buffer_map.insert("prototype".to_string(), sab_proto_val);
+        write_attrs_to_legacy_map(&mut shared_array_buffer_map, "prototype", PropAttrs::empty());
+        let sab_ctor = Value::Object(new_gc_cell_ptr(ctx, shared_array_buffer_map));
+        if let Value::Object(ctor_obj) = &sab_ctor
+            && let Some(Value::Object(proto_obj)) = own_data_from_legacy_map(&ctor_obj.borrow(), "prototype")
+        {
+            proto_obj.borrow_mut(ctx).insert("constructor".to_string(), sab_ctor.clone());
+            mark_nonenumerable(&mut proto_obj.borrow_mut(ctx), "constructor");
+        }
+        self.globals.insert("SharedArrayBuffer".to_string(), sab_ctor.clone());
+        self.global_this.borrow_mut(ctx).insert("SharedArrayBuffer".to_string(), sab_ctor);
+        mark_nonenumerable(&mut self.global_this.borrow_mut(ctx), "SharedArrayBuffer");
+
+        let mut atomics_map = IndexMap::new();
+        atomics_map.insert(
+            "isLockFree".to_string(),
+            Self::make_host_fn_with_name_len(ctx, "atomics.isLockFree", "isLockFree", 1.0, false),
+        );
+        mark_nonenumerable(&mut atomics_map, "isLockFree");
+        atomics_map.insert(
+            "load".to_string(),
+            Self::make_host_fn_with_name_len(ctx, "atomics.load", "load", 2.0, false),
+        );
+        mark_nonenumerable(&mut atomics_map, "load");
+        atomics_map.insert(
+            "store".to_string(),
+            Self::make_host_fn_with_name_len(ctx, "atomics.store", "store", 3.0, false),
+        );
+        mark_nonenumerable(&mut atomics_map, "store");
+        atomics_map.insert(
+            "compareExchange".to_string(),
+            Self::make_host_fn_with_name_len(ctx, "atomics.compareExchange", "compareExchange", 4.0, false),
+        );
+        mark_nonenumerable(&mut atomics_map, "compareExchange");
+        atomics_map.insert(
+            "and".to_string(),
+            Self::make_host_fn_with_name_len(ctx, "atomics.and", "and", 3.0, false),
+        );
+        mark_nonenumerable(&mut atomics_map, "and");
+        atomics_map.insert(
+            "add".to_string(),
+            Self::make_host_fn_with_name_len(ctx, "atomics.add", "add", 3.0, false),
+        );
+        mark_nonenumerable(&mut atomics_map, "add");
+        atomics_map.insert(
+            "exchange".to_string(),
+            Self::make_host_fn_with_name_len(ctx, "atomics.exchange", "exchange", 3.0, false),
+        );
+        mark_nonenumerable(&mut atomics_map, "exchange");
+        atomics_map.insert(
+            "sub".to_string(),
+            Self::make_host_fn_with_name_len(ctx, "atomics.sub", "sub", 3.0, false),
+        );
+        mark_nonenumerable(&mut atomics_map, "sub");
+        atomics_map.insert(
+            "or".to_string(),
+            Self::make_host_fn_with_name_len(ctx, "atomics.or", "or", 3.0, false),
+        );
+        mark_nonenumerable(&mut atomics_map, "or");
+        atomics_map.insert(
+            "xor".to_string(),
+            Self::make_host_fn_with_name_len(ctx, "atomics.xor", "xor", 3.0, false),
+        );
+        mark_nonenumerable(&mut atomics_map, "xor");
+        atomics_map.insert(
+            "wait".to_string(),
+            Self::make_host_fn_with_name_len(ctx, "atomics.wait", "wait", 4.0, false),
+        );
+        mark_nonenumerable(&mut atomics_map, "wait");
+        atomics_map.insert(
+            "notify".to_string(),
+            Self::make_host_fn_with_name_len(ctx, "atomics.notify", "notify", 3.0, false),
+        );
+        mark_nonenumerable(&mut atomics_map, "notify");
+        atomics_map.insert("@@sym:4".to_string(), Value::from("Atomics"));
+        write_attrs_to_legacy_map(&mut atomics_map, "@@sym:4", PropAttrs::CONFIGURABLE);
+        let wait_async_fn = Self::make_host_fn_with_name_len(ctx, "atomics.waitAsync", "waitAsync", 4.0, false);
+        atomics_map.insert("waitAsync".to_string(), wait_async_fn);
+        mark_nonenumerable(&mut atomics_map, "waitAsync");
+        atomics_map.insert("pause".to_string(), Self::make_native_fn(ctx, BUILTIN_ATOMICS_PAUSE, "pause", 0.0));
+        mark_nonenumerable(&mut atomics_map, "pause");
+        let atomics_obj = Value::Object(new_gc_cell_ptr(ctx, atomics_map));
+        self.globals.insert("Atomics".to_string(), atomics_obj.clone());
+        self.global_this.borrow_mut(ctx).insert("Atomics".to_string(), atomics_obj);
+        mark_nonenumerable(&mut self.global_this.borrow_mut(ctx), "Atomics");
+    }
+
     /// Register built-in global objects (console, Math, isNaN, parseInt, etc.)
     fn register_builtins(&mut self, ctx: &GcContext<'gc>) {
         // console object
@@ -17831,9 +19027,16 @@ impl<'gc> VM<'gc> {
         console_map.insert("log".to_string(), Value::NativeFunction(BUILTIN_CONSOLE_LOG));
         console_map.insert("warn".to_string(), Value::NativeFunction(BUILTIN_CONSOLE_WARN));
         console_map.insert("error".to_string(), Value::NativeFunction(BUILTIN_CONSOLE_ERROR));
+        Self::register_console_methods(ctx, &mut console_map);
         self.globals
             .insert("console".to_string(), Value::Object(new_gc_cell_ptr(ctx, console_map)));
 
+        // performance object (High Resolution Time)
+        let mut performance_map = IndexMap::new();
+        performance_map.insert("now".to_string(), Self::make_host_fn(ctx, "clock.performance.now"));
+        self.globals
+            .insert("performance".to_string(), Value::Object(new_gc_cell_ptr(ctx, performance_map)));
+
         // Math object
         let mut math_map = IndexMap::new();
         math_map.insert("floor".to_string(), Value::NativeFunction(BUILTIN_MATH_FLOOR));
@@ -18165,6 +19368,7 @@ impl<'gc> VM<'gc> {
         let array_keys_fn = Value::NativeFunction(BUILTIN_ARRAY_ITERATOR);
         let array_copy_within_fn = Self::make_host_fn_with_name_len(ctx, "array.copyWithin", "copyWithin", 2.0, false);
         let array_to_string_fn = Self::make_host_fn_with_name_len(ctx, "array.toString", "toString", 0.0, false);
+        #[cfg(feature = "typed-arrays")]
         let array_to_string_fn_for_ta = array_to_string_fn.clone();
         let array_to_locale_string_fn = Self::make_host_fn_with_name_len(ctx, "array.toLocaleString", "toLocaleString", 0.0, false);
         arr_proto.props.insert("@@sym:1".to_string(), array_values_fn.clone());
@@ -18492,130 +19696,11 @@ impl<'gc> VM<'gc> {
             .insert("ArrayBuffer".to_string(), array_buffer_ctor);
         mark_nonenumerable(&mut self.global_this.borrow_mut(ctx), "ArrayBuffer");
 
+        #[cfg(feature = "typed-arrays")]
         self.dataview_init_prototype(ctx);
 
-        let mut shared_array_buffer_map = IndexMap::new();
-        shared_array_buffer_map.insert("__native_id__".to_string(), Value::Number(BUILTIN_CTOR_SHAREDARRAYBUFFER as f64));
-        shared_array_buffer_map.insert("length".to_string(), Value::Number(1.0));
-        write_attrs_to_legacy_map(&mut shared_array_buffer_map, "length", PropAttrs::CONFIGURABLE);
-        let mut sab_proto = IndexMap::new();
-        sab_proto.insert("__type__".to_string(), Value::from("SharedArrayBuffer"));
-        sab_proto.insert("@@sym:4".to_string(), Value::from("SharedArrayBuffer"));
-        write_attrs_to_legacy_map(&mut sab_proto, "@@sym:4", PropAttrs::CONFIGURABLE);
-        set_getter(
-            &mut sab_proto,
-            "byteLength",
-            Self::make_host_fn_with_name_len(ctx, "sharedArrayBuffer.getByteLength", "get byteLength", 0.0, false),
-        );
-        mark_nonenumerable(&mut sab_proto, "byteLength");
-        set_getter(
-            &mut sab_proto,
-            "maxByteLength",
-            Self::make_host_fn_with_name_len(ctx, "sharedArrayBuffer.getMaxByteLength", "get maxByteLength", 0.0, false),
-        );
-        mark_nonenumerable(&mut sab_proto, "maxByteLength");
-        set_getter(
-            &mut sab_proto,
-            "growable",
-            Self::make_host_fn_with_name_len(ctx, "sharedArrayBuffer.getGrowable", "get growable", 0.0, false),
-        );
-        mark_nonenumerable(&mut sab_proto, "growable");
-        sab_proto.insert(
-            "grow".to_string(),
-            Self::make_host_fn_with_name_len(ctx, "sharedArrayBuffer.grow", "grow", 1.0, false),
-        );
-        mark_nonenumerable(&mut sab_proto, "grow");
-        sab_proto.insert(
-            "slice".to_string(),
-            Self::make_host_fn_with_name_len(ctx, "sharedArrayBuffer.slice", "slice", 2.0, false),
-        );
-        mark_nonenumerable(&mut sab_proto, "slice");
-        let sab_proto_val = Value::Object(new_gc_cell_ptr(ctx, sab_proto));
-        shared_array_buffer_map.insert("prototype".to_string(), sab_proto_val);
-        write_attrs_to_legacy_map(&mut shared_array_buffer_map, "prototype", PropAttrs::empty());
-        let sab_ctor = Value::Object(new_gc_cell_ptr(ctx, shared_array_buffer_map));
-        if let Value::Object(ctor_obj) = &sab_ctor
-            && let Some(Value::Object(proto_obj)) = own_data_from_legacy_map(&ctor_obj.borrow(), "prototype")
-        {
-            proto_obj.borrow_mut(ctx).insert("constructor".to_string(), sab_ctor.clone());
-            mark_nonenumerable(&mut proto_obj.borrow_mut(ctx), "constructor");
-        }
-        self.globals.insert("SharedArrayBuffer".to_string(), sab_ctor.clone());
-        self.global_this.borrow_mut(ctx).insert("SharedArrayBuffer".to_string(), sab_ctor);
-        mark_nonenumerable(&mut self.global_this.borrow_mut(ctx), "SharedArrayBuffer");
-
-        let mut atomics_map = IndexMap::new();
-        atomics_map.insert(
-            "isLockFree".to_string(),
-            Self::make_host_fn_with_name_len(ctx, "atomics.isLockFree", "isLockFree", 1.0, false),
-        );
-        mark_nonenumerable(&mut atomics_map, "isLockFree");
-        atomics_map.insert(
-            "load".to_string(),
-            Self::make_host_fn_with_name_len(ctx, "atomics.load", "load", 2.0, false),
-        );
-        mark_nonenumerable(&mut atomics_map, "load");
-        atomics_map.insert(
-            "store".to_string(),
-            Self::make_host_fn_with_name_len(ctx, "atomics.store", "store", 3.0, false),
-        );
-        mark_nonenumerable(&mut atomics_map, "store");
-        atomics_map.insert(
-            "compareExchange".to_string(),
-            Self::make_host_fn_with_name_len(ctx, "atomics.compareExchange", "compareExchange", 4.0, false),
-        );
-        mark_nonenumerable(&mut atomics_map, "compareExchange");
-        atomics_map.insert(
-            "and".to_string(),
-            Self::make_host_fn_with_name_len(ctx, "atomics.and", "and", 3.0, false),
-        );
-        mark_nonenumerable(&mut atomics_map, "and");
-        atomics_map.insert(
-            "add".to_string(),
-            Self::make_host_fn_with_name_len(ctx, "atomics.add", "add", 3.0, false),
-        );
-        mark_nonenumerable(&mut atomics_map, "add");
-        atomics_map.insert(
-            "exchange".to_string(),
-            Self::make_host_fn_with_name_len(ctx, "atomics.exchange", "exchange", 3.0, false),
-        );
-        mark_nonenumerable(&mut atomics_map, "exchange");
-        atomics_map.insert(
-            "sub".to_string(),
-            Self::make_host_fn_with_name_len(ctx, "atomics.sub", "sub", 3.0, false),
-        );
-        mark_nonenumerable(&mut atomics_map, "sub");
-        atomics_map.insert(
-            "or".to_string(),
-            Self::make_host_fn_with_name_len(ctx, "atomics.or", "or", 3.0, false),
-        );
-        mark_nonenumerable(&mut atomics_map, "or");
-        atomics_map.insert(
-            "xor".to_string(),
-            Self::make_host_fn_with_name_len(ctx, "atomics.xor", "xor", 3.0, false),
-        );
-        mark_nonenumerable(&mut atomics_map, "xor");
-        atomics_map.insert(
-            "wait".to_string(),
-            Self::make_host_fn_with_name_len(ctx, "atomics.wait", "wait", 4.0, false),
-        );
-        mark_nonenumerable(&mut atomics_map, "wait");
-        atomics_map.insert(
-            "notify".to_string(),
-            Self::make_host_fn_with_name_len(ctx, "atomics.notify", "notify", 3.0, false),
-        );
-        mark_nonenumerable(&mut atomics_map, "notify");
-        atomics_map.insert("@@sym:4".to_string(), Value::from("Atomics"));
-        write_attrs_to_legacy_map(&mut atomics_map, "@@sym:4", PropAttrs::CONFIGURABLE);
-        let wait_async_fn = Self::make_host_fn_with_name_len(ctx, "atomics.waitAsync", "waitAsync", 4.0, false);
-        atomics_map.insert("waitAsync".to_string(), wait_async_fn);
-        mark_nonenumerable(&mut atomics_map, "waitAsync");
-        atomics_map.insert("pause".to_string(), Self::make_native_fn(ctx, BUILTIN_ATOMICS_PAUSE, "pause", 0.0));
-        mark_nonenumerable(&mut atomics_map, "pause");
-        let atomics_obj = Value::Object(new_gc_cell_ptr(ctx, atomics_map));
-        self.globals.insert("Atomics".to_string(), atomics_obj.clone());
-        self.global_this.borrow_mut(ctx).insert("Atomics".to_string(), atomics_obj);
-        mark_nonenumerable(&mut self.global_this.borrow_mut(ctx), "Atomics");
+        #[cfg(feature = "shared-memory")]
+        self.initialize_shared_memory(ctx);
 
         let mut promise_map = IndexMap::new();
         let mut promise_proto = IndexMap::new();
@@ -19069,6 +20154,7 @@ impl<'gc> VM<'gc> {
         self.global_this.borrow_mut(ctx).insert("Proxy".to_string(), proxy_ctor);
         mark_nonenumerable(&mut self.global_this.borrow_mut(ctx), "Proxy");
 
+        #[cfg(feature = "typed-arrays")]
         self.initialize_typed_arrays(ctx, array_to_string_fn_for_ta);
         // Object constructor with static methods
         let mut object_map = IndexMap::new();
@@ -19448,7 +20534,9 @@ impl<'gc> VM<'gc> {
             Self::insert_constructor_backref(ctx, &symbol_proto, &Value::Object(*symbol_ctor));
         }
         mark_nonenumerable(&mut self.global_this.borrow_mut(ctx), "Object");
+        #[cfg(feature = "temporal")]
         self.temporal_init_globals(ctx);
+        #[cfg(feature = "intl")]
         self.intl_init_globals(ctx);
 
         // Constructors created before Object may still need their prototype chains wired.
@@ -19984,6 +21072,7 @@ impl<'gc> VM<'gc> {
             "__evalScript__".to_string(),
             Self::make_host_fn_with_name_len(ctx, "__evalScript__", "__evalScript__", 1.0, false),
         );
+        #[cfg(feature = "shared-memory")]
         for (name, length) in [
             ("__agent_start", 1.0),
             ("__agent_broadcast", 1.0),
@@ -20013,6 +21102,7 @@ impl<'gc> VM<'gc> {
             "__evalScript__".to_string(),
             Self::make_host_fn_with_name_len(ctx, "__evalScript__", "__evalScript__", 1.0, false),
         );
+        #[cfg(feature = "shared-memory")]
         for (name, length) in [
             ("__agent_start", 1.0),
             ("__agent_broadcast", 1.0),
@@ -20085,13 +21175,18 @@ impl<'gc> VM<'gc> {
         os_map.insert("read".to_string(), Self::make_host_fn(ctx, "os.read"));
         os_map.insert("seek".to_string(), Self::make_host_fn(ctx, "os.seek"));
         os_map.insert("close".to_string(), Self::make_host_fn(ctx, "os.close"));
+        os_map.insert("unrefTimer".to_string(), Self::make_host_fn(ctx, "os.unrefTimer"));
+        os_map.insert("refTimer".to_string(), Self::make_host_fn(ctx, "os.refTimer"));
         os_map.insert("path".to_string(), Value::Object(new_gc_cell_ptr(ctx, os_path_map)));
         self.globals.insert("os".to_string(), Value::Object(new_gc_cell_ptr(ctx, os_map)));
 
         // Minimal `std` namespace for VM module import interop
         let mut std_map = IndexMap::new();
+        #[cfg(feature = "std")]
         std_map.insert("sprintf".to_string(), Self::make_host_fn(ctx, "std.sprintf"));
+        #[cfg(feature = "std")]
         std_map.insert("tmpfile".to_string(), Self::make_host_fn(ctx, "std.tmpfile"));
+        std_map.insert("getenv".to_string(), Self::make_host_fn(ctx, "std.getenv"));
         std_map.insert("gc".to_string(), Self::make_host_fn(ctx, "std.gc"));
         self.globals.insert("std".to_string(), Value::Object(new_gc_cell_ptr(ctx, std_map)));
 
@@ -20567,6 +21662,11 @@ impl<'gc> VM<'gc> {
 
         self.globals.insert("__generator_function_ctor".to_string(), gen_fn_ctor_val);
         self.generator_function_prototype = gen_fn_proto_val;
+
+        self.intrinsic_map_ctor = self.globals.get("Map").cloned().unwrap_or(Value::Undefined);
+        self.intrinsic_set_ctor = self.globals.get("Set").cloned().unwrap_or(Value::Undefined);
+        self.intrinsic_date_ctor = self.globals.get("Date").cloned().unwrap_or(Value::Undefined);
+        self.intrinsic_uint8array_ctor = self.globals.get("Uint8Array").cloned().unwrap_or(Value::Undefined);
     }
 
     /// Convert a value to string, calling toString() on VmObjects if available
@@ -21297,19 +22397,79 @@ impl<'gc> VM<'gc> {
                 return Value::Undefined;
             }
         };
+        let Some(locale) = self.string_case_locale(ctx, locales) else {
+            return Value::Undefined;
+        };
+        let transformed = Self::string_transform_locale_case(&source, locale.as_str(), upper);
+        Value::from(transformed.as_str())
+    }
+
+    /// The locale `toLocaleUpperCase` and `toLocaleLowerCase` convert for;
+    /// `None` when reading `locales` threw.
+    #[cfg(feature = "intl")]
+    fn string_case_locale(&mut self, ctx: &GcContext<'gc>, locales: Option<&Value<'gc>>) -> Option<String> {
         let requested_locales = match self.intl_canonicalize_locale_list(ctx, locales) {
             Ok(locales) => locales,
+            Err(err) => {
+                self.pending_throw = Some(err);
+                return None;
+            }
+        };
+        Some(
+            requested_locales
+                .first()
+                .map(|locale| Self::intl_locale_without_unicode_extension(locale))
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Without `Intl` only a single locale tag given as a string is honoured.
+    #[cfg(not(feature = "intl"))]
+    fn string_case_locale(&mut self, _ctx: &GcContext<'gc>, locales: Option<&Value<'gc>>) -> Option<String> {
+        match locales {
+            Some(Value::String(tag)) => Some(crate::unicode::utf16_to_utf8(tag)),
+            _ => Some(String::new()),
+        }
+    }
+
+    /// `String.prototype.localeCompare` with the `locales` and `options`
+    /// arguments in `collator_args`.
+    #[cfg(feature = "intl")]
+    fn string_locale_compare(&mut self, ctx: &GcContext<'gc>, base: &str, other: &str, collator_args: &[Value<'gc>]) -> Value<'gc> {
+        let collator = match self.intl_construct_service_instance(ctx, "Collator", None, &collator_args[..collator_args.len().min(2)]) {
+            Ok(collator) => collator,
             Err(err) => {
                 self.pending_throw = Some(err);
                 return Value::Undefined;
             }
         };
-        let locale = requested_locales
-            .first()
-            .map(|locale| Self::intl_locale_without_unicode_extension(locale))
-            .unwrap_or_default();
-        let transformed = Self::string_transform_locale_case(&source, locale.as_str(), upper);
-        Value::from(transformed.as_str())
+        self.intl_collator_compare(ctx, Some(&collator), &[Value::from(base), Value::from(other)])
+    }
+
+    /// Without `Intl` strings compare by their code points.
+    #[cfg(not(feature = "intl"))]
+    fn string_locale_compare(&mut self, _ctx: &GcContext<'gc>, base: &str, other: &str, _collator_args: &[Value<'gc>]) -> Value<'gc> {
+        Value::Number(base.cmp(other) as i8 as f64)
+    }
+
+    /// `Number.prototype.toLocaleString` and `BigInt.prototype.toLocaleString`.
+    #[cfg(feature = "intl")]
+    pub(super) fn numeric_to_locale_string(&mut self, ctx: &GcContext<'gc>, value: Value<'gc>, args: &[Value<'gc>]) -> Value<'gc> {
+        let formatter = match self.intl_construct_service_instance(ctx, "NumberFormat", None, args) {
+            Ok(value) => value,
+            Err(err) => {
+                self.pending_throw = Some(err);
+                return Value::Undefined;
+            }
+        };
+        self.intl_number_format_format(ctx, Some(&formatter), &[value])
+    }
+
+    /// Without `Intl` the locales and options are ignored and the number is
+    /// written as `toString()` writes it.
+    #[cfg(not(feature = "intl"))]
+    pub(super) fn numeric_to_locale_string(&mut self, _ctx: &GcContext<'gc>, value: Value<'gc>, _args: &[Value<'gc>]) -> Value<'gc> {
+        Value::from(&value_to_string(&value))
     }
 
     fn string_transform_locale_case(source: &str, locale: &str, upper: bool) -> String {
@@ -22502,22 +23662,8 @@ impl<'gc> VM<'gc> {
                 borrow.contains_key("__line__"),
             )
         };
-        let inferred_ctor_name = if explicit_name.is_none() {
-            let thrown = Value::Object(*map);
-            let ctor = self.read_named_property(ctx, &thrown, "constructor");
-            let ctor_name = self.read_named_property(ctx, &ctor, "name");
-            if let Value::String(s) = ctor_name {
-                let name = crate::unicode::utf16_to_utf8(&s);
-                if name.is_empty() { None } else { Some(name) }
-            } else {
-                None
-            }
-        } else {
-            None
-        };
         let error_name = explicit_name
-            .clone()
-            .or_else(|| inferred_ctor_name.clone())
+            .or_else(|| self.constructor_name(ctx, &Value::Object(*map)))
             .unwrap_or_else(|| "Error".to_string());
         let is_error_like = error_name == "Error" || error_name.ends_with("Error");
 
@@ -22540,6 +23686,35 @@ impl<'gc> VM<'gc> {
         }
     }
 
+    /// The non-empty `name` of `value.constructor`. Both are read as data
+    /// properties along the `__proto__` chain, so no getter or proxy trap
+    /// runs; an accessor in either place gives `None`.
+    fn constructor_name(&mut self, ctx: &GcContext<'gc>, value: &Value<'gc>) -> Option<String> {
+        let mut current = value.clone();
+        let mut ctor = None;
+        for _ in 0..64 {
+            let Value::Object(obj) = current else {
+                return None;
+            };
+            let borrow = obj.borrow();
+            if desc_from_legacy_map(&borrow, "constructor").is_some() {
+                ctor = own_data_from_legacy_map(&borrow, "constructor");
+                break;
+            }
+            current = borrow.get("__proto__").cloned().unwrap_or(Value::Undefined);
+        }
+        let ctor_name = match ctor? {
+            Value::Object(ctor) => own_data_from_legacy_map(&ctor.borrow(), "name"),
+            ctor => self
+                .merged_fn_props_for_value(ctx, &ctor)
+                .and_then(|props| own_data_from_legacy_map(&props, "name")),
+        };
+        match ctor_name {
+            Some(Value::String(s)) if !s.is_empty() => Some(crate::unicode::utf16_to_utf8(&s)),
+            _ => None,
+        }
+    }
+
     fn error_kind_from_name(name: &str, message: String) -> crate::error::JSErrorKind {
         match name {
             "TypeError" => crate::error::JSErrorKind::TypeError { message },
@@ -22567,54 +23742,18 @@ impl<'gc> VM<'gc> {
 
     pub(crate) fn vm_error_to_js_error(&mut self, ctx: &GcContext<'gc>, thrown: &Value<'gc>) -> JSError {
         if let Value::Object(map) = thrown {
-            let explicit_type = {
-                let borrow = map.borrow();
-                borrow
-                    .get("__type__")
-                    .map(value_to_string)
-                    .or_else(|| own_data_from_legacy_map(&borrow, "name").as_ref().map(value_to_string))
-            };
-
-            let inferred_type = if explicit_type.is_none() {
-                let ctor = self.read_named_property(ctx, thrown, "constructor");
-                let ctor_name = self.read_named_property(ctx, &ctor, "name");
-                if let Value::String(s) = ctor_name {
-                    let name = crate::unicode::utf16_to_utf8(&s);
-                    if name.is_empty() { None } else { Some(name) }
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
-
-            if explicit_type.is_none()
-                && let Some(inferred) = inferred_type.clone()
-            {
-                let _ = inferred;
-            }
-
             self.annotate_error_object(ctx, map);
+            let exception = self.owned_exception(ctx, thrown);
 
-            let borrow = map.borrow();
-            let type_name = borrow
-                .get("__type__")
-                .map(value_to_string)
-                .or_else(|| own_data_from_legacy_map(&borrow, "name").as_ref().map(value_to_string))
-                .or(inferred_type)
-                .unwrap_or_else(|| "Error".to_string());
-            let message = own_data_from_legacy_map(&borrow, "message")
-                .as_ref()
-                .map(value_to_string)
-                .unwrap_or_else(|| value_to_string(thrown));
-            let error_text = if type_name == "Error" {
-                format!("Uncaught: Error: {}", message)
-            } else if type_name.is_empty() {
-                format!("Uncaught: {}", message)
+            let error_text = if exception.name == "Error" {
+                format!("Uncaught: Error: {}", exception.message)
+            } else if exception.name.is_empty() {
+                format!("Uncaught: {}", exception.message)
             } else {
-                format!("Uncaught: {}: {}", type_name, message)
+                format!("Uncaught: {}: {}", exception.name, exception.message)
             };
-            let mut err = crate::make_js_error!(Self::error_kind_from_name(&type_name, error_text));
+            let mut err = crate::make_js_error!(Self::error_kind_from_name(&exception.name, error_text));
+            let borrow = map.borrow();
             if let Some(Value::Number(line)) = borrow.get("__line__") {
                 let column = borrow
                     .get("__column__")
@@ -22637,11 +23776,14 @@ impl<'gc> VM<'gc> {
                 err.set_stack(stack_lines);
             }
             self.preserve_thrown_value_for_error(thrown, &err.message());
+            err.set_exception(exception);
             return err;
         }
 
-        let err = crate::make_js_error!(crate::error::JSErrorKind::Throw(format!("Uncaught: {}", value_to_string(thrown))));
+        let exception = self.owned_exception(ctx, thrown);
+        let mut err = crate::make_js_error!(crate::error::JSErrorKind::Throw(format!("Uncaught: {}", value_to_string(thrown))));
         self.preserve_thrown_value_for_error(thrown, &err.message());
+        err.set_exception(exception);
         err
     }
 
@@ -22957,6 +24099,7 @@ impl<'gc> VM<'gc> {
         // Create a fresh child realm (like __createRealm__)
         let realm_id = self.child_realms.len();
         let mut child = Box::new(VM::new(Chunk::default(), ctx));
+        self.inherit_embedder_state(&mut child);
 
         // Sync Symbol registry so well-known symbols have the same identity
         if let Some(Value::Object(parent_symbol_obj)) = self.globals.get("Symbol")
@@ -23395,52 +24538,62 @@ impl<'gc> VM<'gc> {
             child.symbol_counter = self.symbol_counter;
             // Attempt module import in child realm
             let script_path = self.script_path.clone();
-            let base_dir = if let Some(ref p) = script_path {
-                std::path::Path::new(p).parent().map(|p| p.to_path_buf())
-            } else {
-                std::env::current_dir().ok()
-            };
-            let module_path = if let Some(base) = base_dir {
-                base.join(&specifier)
+            let loaded_source = if self.module_loader.is_custom() {
+                let referrer = script_path.unwrap_or_default();
+                let key = self.module_loader.resolve_key(&specifier, std::path::Path::new(&referrer));
+                self.module_loader
+                    .load(std::path::Path::new(&key))
+                    .and_then(crate::core::ModuleSource::into_text)
+                    .map_err(|e| format!("Cannot load module '{}': {}", specifier, e.message()))
             } else {
-                std::path::PathBuf::from(&specifier)
+                let base_dir = if let Some(ref p) = script_path {
+                    std::path::Path::new(p).parent().map(|p| p.to_path_buf())
+                } else {
+                    std::env::current_dir().ok()
+                };
+                let module_path = if let Some(base) = base_dir {
+                    base.join(&specifier)
+                } else {
+                    std::path::PathBuf::from(&specifier)
+                };
+                if !module_path.exists() {
+                    Err(format!("Cannot find module '{}'", specifier))
+                } else {
+                    std::fs::read_to_string(&module_path).map_err(|e| format!("Cannot load module '{}': {}", specifier, e))
+                }
             };
 
-            let result = if !module_path.exists() {
-                Err(format!("Cannot find module '{}'", specifier))
-            } else {
-                match std::fs::read_to_string(&module_path) {
-                    Err(e) => Err(format!("Cannot load module '{}': {}", specifier, e)),
-                    Ok(source) => {
-                        // Parse and run as module
-                        let run_res = (|| -> Result<Value<'gc>, JSError> {
-                            let tokens = crate::core::tokenize(&source)?;
-                            let mut idx = 0;
-                            let stmts = crate::core::parse_statements(&tokens, &mut idx)?;
-                            let compiler = crate::core::Compiler::new();
-                            let chunk = compiler.compile(&stmts)?;
-                            let (offset, _) = child.merge_eval_chunk(&chunk);
-                            let saved_ip = child.ip;
-                            child.ip = offset;
-                            let r = child.run(ctx);
-                            child.ip = saved_ip;
-                            r
-                        })();
-
-                        match run_res {
-                            Ok(_) => {
-                                let export_val = child
-                                    .globals
-                                    .get(&export_name)
-                                    .cloned()
-                                    .or_else(|| child.global_this.borrow().get(&export_name).cloned());
-                                match export_val {
-                                    Some(v) => Ok(v),
-                                    None => Err(format!("'{}' is not exported from module '{}'", export_name, specifier)),
-                                }
+            let result = match loaded_source {
+                Err(e) => Err(e),
+                Ok(source) => {
+                    // Parse and run as module
+                    let run_res = (|| -> Result<Value<'gc>, JSError> {
+                        let tokens = crate::core::tokenize(&source)?;
+                        let mut idx = 0;
+                        let stmts = crate::core::parse_statements(&tokens, &mut idx)?;
+                        let compiler = crate::core::Compiler::new();
+                        let chunk = compiler.compile(&stmts)?;
+                        let (offset, _) = child.merge_eval_chunk(&chunk);
+                        let saved_ip = child.ip;
+                        child.ip = offset;
+                        let r = child.run(ctx);
+                        child.ip = saved_ip;
+                        r
+                    })();
+
+                    match run_res {
+                        Ok(_) => {
+                            let export_val = child
+                                .globals
+                                .get(&export_name)
+                                .cloned()
+                                .or_else(|| child.global_this.borrow().get(&export_name).cloned());
+                            match export_val {
+                                Some(v) => Ok(v),
+                                None => Err(format!("'{}' is not exported from module '{}'", export_name, specifier)),
                             }
-                            Err(err) => Err(err.message()),
                         }
+                        Err(err) => Err(err.message()),
                     }
                 }
             };
@@ -23792,7 +24945,7 @@ impl<'gc> VM<'gc> {
     }
 
     /// Execute a native/built-in function
-    fn call_builtin(&mut self, ctx: &GcContext<'gc>, id: FunctionID, args: &[Value<'gc>]) -> Value<'gc> {
+    fn call_builtin_uncharged(&mut self, ctx: &GcContext<'gc>, id: FunctionID, args: &[Value<'gc>]) -> Value<'gc> {
         match id {
             BUILTIN_SETTIMEOUT | BUILTIN_SETINTERVAL => {
                 let callback = args.first().cloned().unwrap_or(Value::Undefined);
@@ -23802,21 +24955,12 @@ impl<'gc> VM<'gc> {
                     0
                 };
                 let timer_args: Vec<Value<'gc>> = args.iter().skip(2).cloned().collect();
-                let timer_id = self.next_timer_id;
-                self.next_timer_id += 1;
-                self.pending_timers.push(PendingTimer {
-                    id: timer_id,
-                    callback,
-                    args: timer_args,
-                    delay_ms: delay,
-                    is_interval: id == BUILTIN_SETINTERVAL,
-                });
+                let timer_id = self.schedule_timer(callback, timer_args, delay, id == BUILTIN_SETINTERVAL);
                 Value::Number(timer_id as f64)
             }
             BUILTIN_CLEARTIMEOUT | BUILTIN_CLEARINTERVAL => {
                 if let Some(Value::Number(n)) = args.first() {
-                    self.cleared_timers.insert(*n as usize);
-                    self.pending_timers.retain(|t| t.id != *n as usize);
+                    self.clear_timer(*n as usize);
                 }
                 Value::Undefined
             }
@@ -23929,6 +25073,7 @@ impl<'gc> VM<'gc> {
                             pb.insert("__promise_value__".to_string(), Value::from(uncaught_payload));
                         }
                     }
+                    self.track_rejection(&promise_obj);
                 }
 
                 promise_obj
@@ -24324,7 +25469,7 @@ impl<'gc> VM<'gc> {
             }
             BUILTIN_PROMISE_ALL => {
                 let mut settled_values: Vec<Value<'gc>> = Vec::new();
-                let mut rejection: Option<Value<'gc>> = None;
+                let mut rejection: Option<(Value<'gc>, Value<'gc>)> = None;
 
                 if let Some(Value::Array(items)) = args.first() {
                     for item in items.borrow().iter() {
@@ -24337,7 +25482,7 @@ impl<'gc> VM<'gc> {
                                     let rejected = matches!(b.get("__promise_rejected__"), Some(Value::Boolean(true)));
                                     let pv = b.get("__promise_value__").cloned().unwrap_or(Value::Undefined);
                                     if rejected {
-                                        rejection = Some(pv);
+                                        rejection = Some((item.clone(), pv));
                                         break;
                                     }
                                     settled_values.push(pv);
@@ -24352,7 +25497,9 @@ impl<'gc> VM<'gc> {
 
                 let mut map = IndexMap::new();
                 map.insert("__type__".to_string(), Value::from("Promise"));
-                if let Some(reason) = rejection {
+                let rejected = rejection.is_some();
+                if let Some((source, reason)) = rejection {
+                    self.mark_promise_handled(ctx, &source);
                     map.insert("__promise_rejected__".to_string(), Value::Boolean(true));
                     map.insert("__promise_value__".to_string(), reason);
                 } else {
@@ -24366,7 +25513,11 @@ impl<'gc> VM<'gc> {
                 {
                     map.insert("__proto__".to_string(), proto);
                 }
-                Value::Object(new_gc_cell_ptr(ctx, map))
+                let promise = Value::Object(new_gc_cell_ptr(ctx, map));
+                if rejected {
+                    self.track_rejection(&promise);
+                }
+                promise
             }
             BUILTIN_CTOR_PROXY => match args.first().cloned() {
                 Some(target) => {
@@ -24409,6 +25560,7 @@ impl<'gc> VM<'gc> {
                     Value::Undefined
                 }
             },
+            #[cfg(feature = "shared-memory")]
             BUILTIN_CTOR_SHAREDARRAYBUFFER => {
                 // Require 'new'
                 if self.new_target_stack.last().is_none() {
@@ -24586,6 +25738,7 @@ impl<'gc> VM<'gc> {
                 }
                 Value::Object(new_gc_cell_ptr(ctx, map))
             }
+            #[cfg(feature = "shared-memory")]
             BUILTIN_ATOMICS_ISLOCKFREE => {
                 let size = args
                     .first()
@@ -24593,6 +25746,7 @@ impl<'gc> VM<'gc> {
                     .unwrap_or(0);
                 Value::Boolean(matches!(size, 1 | 2 | 4 | 8))
             }
+            #[cfg(feature = "shared-memory")]
             BUILTIN_ATOMICS_LOAD => {
                 if let (Some(Value::Array(arr)), Some(idx_val)) = (args.first(), args.get(1)) {
                     let mut idx_num = to_number(idx_val);
@@ -24611,6 +25765,7 @@ impl<'gc> VM<'gc> {
                 }
                 Value::Undefined
             }
+            #[cfg(feature = "shared-memory")]
             BUILTIN_ATOMICS_STORE => {
                 if let (Some(Value::Array(arr)), Some(idx_val), Some(val)) = (args.first(), args.get(1), args.get(2)) {
                     let ta_name = arr
@@ -24662,6 +25817,7 @@ impl<'gc> VM<'gc> {
                 }
                 Value::Undefined
             }
+            #[cfg(feature = "shared-memory")]
             BUILTIN_ATOMICS_COMPAREEXCHANGE => {
                 let Some(target) = args.first().cloned() else {
                     let mut err_map = IndexMap::new();
@@ -24907,6 +26063,7 @@ impl<'gc> VM<'gc> {
                     }
                 }
             }
+            #[cfg(feature = "shared-memory")]
             BUILTIN_ATOMICS_ADD
             | BUILTIN_ATOMICS_SUB
             | BUILTIN_ATOMICS_AND
@@ -25127,6 +26284,7 @@ impl<'gc> VM<'gc> {
                     }
                 }
             }
+            #[cfg(feature = "shared-memory")]
             BUILTIN_ATOMICS_WAIT => {
                 if let (Some(Value::Array(arr)), Some(Value::Number(idx)), Some(expected)) = (args.first(), args.get(1), args.get(2)) {
                     let i = (*idx as isize).max(0) as usize;
@@ -25138,6 +26296,7 @@ impl<'gc> VM<'gc> {
                 }
                 Value::from("not-equal")
             }
+            #[cfg(feature = "shared-memory")]
             BUILTIN_ATOMICS_NOTIFY => Value::Number(0.0),
             BUILTIN_REFLECT_APPLY => {
                 let target = args.first().cloned().unwrap_or(Value::Undefined);
@@ -25223,6 +26382,7 @@ impl<'gc> VM<'gc> {
                     }
                 }
             }
+            #[cfg(feature = "shared-memory")]
             BUILTIN_ATOMICS_WAITASYNC => {
                 if let Some(Value::Array(arr)) = args.first() {
                     let arr_borrow = arr.borrow();
@@ -26033,6 +27193,7 @@ impl<'gc> VM<'gc> {
                 }
                 Value::from(&out)
             }
+            #[cfg(feature = "shared-memory")]
             BUILTIN_ATOMICS_PAUSE => {
                 let arg = args.first().cloned().unwrap_or(Value::Undefined);
                 if !matches!(arg, Value::Undefined) {
@@ -26057,7 +27218,9 @@ impl<'gc> VM<'gc> {
                 Value::Undefined
             }
             // ── End Tier-2 ──────────────────────────────────────────────
+            #[cfg(feature = "typed-arrays")]
             BUILTIN_CTOR_DATAVIEW => self.dataview_call_builtin(ctx, args),
+            #[cfg(feature = "typed-arrays")]
             BUILTIN_CTOR_INT8ARRAY
             | BUILTIN_CTOR_UINT8ARRAY
             | BUILTIN_CTOR_UINT8CLAMPEDARRAY
@@ -26071,14 +27234,7 @@ impl<'gc> VM<'gc> {
             | BUILTIN_CTOR_BIGINT64ARRAY
             | BUILTIN_CTOR_BIGUINT64ARRAY => self.typedarray_call_builtin(ctx, id, args),
             BUILTIN_DATE_NOW | BUILTIN_DATE_PARSE => self.date_call_builtin(ctx, id, args),
-            BUILTIN_CONSOLE_LOG | BUILTIN_CONSOLE_WARN | BUILTIN_CONSOLE_ERROR => {
-                let parts: Vec<String> = args.iter().map(|v| self.vm_display_string(ctx, v)).collect();
-                let msg = parts.join(" ");
-                self.output.push(msg.clone());
-                // Match existing console behavior: print to stdout
-                println!("{}", msg);
-                Value::Undefined
-            }
+            BUILTIN_CONSOLE_LOG | BUILTIN_CONSOLE_WARN | BUILTIN_CONSOLE_ERROR => self.console_call_builtin(ctx, id, args),
             BUILTIN_MATH_FLOOR => {
                 let n = args.first().map(|v| to_number(v)).unwrap_or(f64::NAN);
                 Value::Number(n.floor())
@@ -26309,19 +27465,7 @@ impl<'gc> VM<'gc> {
                     Value::Number(base.powf(exp))
                 }
             }
-            BUILTIN_MATH_RANDOM => {
-                // Simple pseudo-random using system time
-                use std::time::SystemTime;
-                let seed = SystemTime::now()
-                    .duration_since(SystemTime::UNIX_EPOCH)
-                    .map(|d| d.as_nanos() as u64)
-                    .unwrap_or(0);
-                // Simple hash and keep 53 random bits to map into [0, 1)
-                let mixed = seed ^ (seed >> 17) ^ (seed << 13);
-                let bits53 = mixed & ((1u64 << 53) - 1);
-                let v = (bits53 as f64) / ((1u64 << 53) as f64);
-                Value::Number(v)
-            }
+            BUILTIN_MATH_RANDOM => Value::Number(self.clock.next_random()),
             BUILTIN_MATH_CLZ32 => {
                 if let Some(Value::Number(n)) = args.first() {
                     Value::Number(to_uint32(*n).leading_zeros() as f64)
@@ -27077,796 +28221,26 @@ impl<'gc> VM<'gc> {
                     return first_arg.clone();
                 }
                 let code = value_to_string(first_arg);
-                if let Some(v) = self.eval_comment_only_fast_path(&code) {
+                if let Some(v) = self.eval_fast_path(ctx, first_arg, &code) {
                     return v;
                 }
-                // Fast path: eval of a bare regex literal like /pattern/ or /pattern/flags
-                // Use raw UTF-16 to preserve lone surrogates
-                if let Value::String(raw_u16) = first_arg
-                    && let Some(v) = self.eval_regex_literal_fast_path_u16(ctx, raw_u16)
-                {
-                    return v;
-                }
-                if code.contains("?.") {
-                    match self.try_eval_optional_chain_expression(ctx, &code) {
-                        Ok(Some(v)) => return v,
-                        Ok(None) => {}
-                        Err(e) => {
-                            let msg = format!("{}", e);
-                            let mut err_map = IndexMap::new();
-                            err_map.insert("__type__".to_string(), Value::from("Error"));
-                            err_map.insert("message".to_string(), Value::from(&msg));
-                            self.pending_throw = Some(Value::Object(new_gc_cell_ptr(ctx, err_map)));
-                            return Value::Undefined;
-                        }
-                    }
-                }
+                // For direct eval inside class bodies, push private names so parser accepts #field access
+                let privns_context = if self.direct_eval {
+                    self.frames
+                        .last()
+                        .and_then(|f| self.chunk.fn_private_name_context.get(&f.func_ip).cloned())
+                } else {
+                    None
+                };
+                let _privns_guard = privns_context.as_ref().map(|ctx| {
+                    let all_names: std::collections::HashSet<String> = ctx.iter().flat_map(|(_, names)| names.iter().cloned()).collect();
+                    crate::core::push_private_names_for_eval(all_names)
+                });
                 // Compile and run eval'd code in a temporary VM that shares globals
-                let result = (|| -> Result<Value<'gc>, JSError> {
-                    let is_direct = self.direct_eval;
-                    // Indirect eval runs as global (sloppy) code: relax strict-mode
-                    // binding restrictions so `var eval` / `arguments = 42` are allowed.
-                    let do_parse = |code: &str| -> Result<Vec<crate::core::statement::Statement>, JSError> {
-                        let prev_module_context = crate::core::parser::in_module_context();
-                        crate::core::parser::set_module_context(false);
-                        let result = (|| {
-                            let tokens = crate::core::tokenize(code)?;
-                            let mut index = 0;
-                            crate::core::parse_statements(&tokens, &mut index)
-                        })();
-                        crate::core::parser::set_module_context(prev_module_context);
-                        result
-                    };
-                    // For direct eval inside class bodies, push private names so parser accepts #field access
-                    let privns_context = if is_direct {
-                        self.frames
-                            .last()
-                            .and_then(|f| self.chunk.fn_private_name_context.get(&f.func_ip).cloned())
-                    } else {
-                        None
-                    };
-                    let _privns_guard = privns_context.as_ref().map(|ctx| {
-                        let all_names: std::collections::HashSet<String> =
-                            ctx.iter().flat_map(|(_, names)| names.iter().cloned()).collect();
-                        crate::core::push_private_names_for_eval(all_names)
-                    });
-                    let statements = if is_direct {
-                        // Set method/constructor/new.target context for direct eval
-                        let caller_ip = self.frames.last().map(|f| f.func_ip);
-                        let in_method = caller_ip.map(|ip| self.chunk.method_function_ips.contains(&ip)).unwrap_or(false);
-                        let in_ctor = caller_ip.map(|ip| self.chunk.class_constructor_ips.contains(&ip)).unwrap_or(false);
-                        // new.target is valid inside any function (arrow or not).
-                        // Allow at parse time; runtime check validates actual context.
-                        let in_function = self.frames.iter().rev().any(|f| f.func_ip != 0);
-                        if in_method || in_ctor {
-                            crate::core::parser::push_method_context_for_eval();
-                        }
-                        if in_ctor {
-                            crate::core::parser::push_constructor_context_for_eval();
-                        }
-                        if in_function {
-                            crate::core::parser::push_new_target_context_for_eval();
-                        }
-                        let result = do_parse(&code);
-                        if in_function {
-                            crate::core::parser::pop_new_target_context_for_eval();
-                        }
-                        if in_ctor {
-                            crate::core::parser::pop_constructor_context_for_eval();
-                        }
-                        if in_method || in_ctor {
-                            crate::core::parser::pop_method_context_for_eval();
-                        }
-                        result?
-                    } else {
-                        // Indirect eval: relax strict-mode binding checks unless
-                        // the eval'd code itself starts with "use strict".
-                        let code_trimmed = code.trim();
-                        let eval_code_is_strict = code_trimmed.starts_with("'use strict'") || code_trimmed.starts_with("\"use strict\"");
-                        if eval_code_is_strict {
-                            do_parse(&code)?
-                        } else {
-                            crate::core::parse_without_strict_binding_checks(|| do_parse(&code))?
-                        }
-                    };
-                    // Check for bare return statements — illegal at top level of eval
-                    for stmt in &statements {
-                        if matches!(*stmt.kind, crate::core::StatementKind::Return(_)) {
-                            return Err(crate::raise_syntax_error!("Illegal return statement"));
-                        }
-                    }
-                    // ── PerformEval early-error restrictions ──────────────
-                    // Indirect eval always runs as global code: reject super(), super.prop, new.target.
-                    // Direct eval in a field initializer: reject `arguments` and `super()`.
-                    {
-                        use crate::core::statement::{
-                            SCAN_ARGUMENTS, SCAN_IMPORT_EXPORT, SCAN_NEW_TARGET, SCAN_SUPER_CALL, SCAN_SUPER_PROP, eval_ast_scan,
-                        };
-                        let import_export = eval_ast_scan(&statements, SCAN_IMPORT_EXPORT);
-                        if import_export & SCAN_IMPORT_EXPORT != 0 {
-                            return Err(crate::raise_syntax_error!("Unexpected token"));
-                        }
-                        if !self.direct_eval {
-                            // Indirect eval: reject super(), super.prop, new.target
-                            let mask = SCAN_SUPER_CALL | SCAN_SUPER_PROP | SCAN_NEW_TARGET;
-                            let found = eval_ast_scan(&statements, mask);
-                            if found & SCAN_SUPER_CALL != 0 {
-                                return Err(crate::raise_syntax_error!("'super' keyword unexpected here"));
-                            }
-                            if found & SCAN_SUPER_PROP != 0 {
-                                return Err(crate::raise_syntax_error!("'super' keyword unexpected here"));
-                            }
-                            if found & SCAN_NEW_TARGET != 0 {
-                                return Err(crate::raise_syntax_error!("new.target expression is not allowed here"));
-                            }
-                        } else {
-                            // Direct eval: check field-init context via VM flag or fn_eval_context
-                            let in_field = self.in_field_init
-                                || self
-                                    .frames
-                                    .last()
-                                    .map(|f| self.chunk.fn_eval_context.get(&f.func_ip).copied().unwrap_or(0) & 0x01 != 0)
-                                    .unwrap_or(false);
-                            if in_field {
-                                // Field initializer: reject `arguments` and `super()`
-                                let mask = SCAN_ARGUMENTS | SCAN_SUPER_CALL;
-                                let found = eval_ast_scan(&statements, mask);
-                                if found & SCAN_ARGUMENTS != 0 {
-                                    return Err(crate::raise_syntax_error!(
-                                        "'arguments' is not allowed in class field initializer or static initialization block"
-                                    ));
-                                }
-                                if found & SCAN_SUPER_CALL != 0 {
-                                    return Err(crate::raise_syntax_error!("'super' keyword unexpected here"));
-                                }
-                            }
-
-                            // Direct eval: check super/new.target based on the
-                            // this-environment (nearest non-arrow function frame).
-                            // §19.2.1.3 PerformEval steps 4-6.
-                            let mask = SCAN_NEW_TARGET | SCAN_SUPER_CALL | SCAN_SUPER_PROP;
-                            let found = eval_ast_scan(&statements, mask);
-                            if found != 0 {
-                                // Walk up frames to find the nearest non-arrow function
-                                // (equivalent to GetThisEnvironment in spec).
-                                let this_env_frame = self
-                                    .frames
-                                    .iter()
-                                    .rev()
-                                    .find(|f| f.func_ip != 0 && !self.chunk.arrow_function_ips.contains(&f.func_ip));
-                                let (in_function, in_method, in_constructor) = match this_env_frame {
-                                    Some(f) => {
-                                        let eval_ctx = self.chunk.fn_eval_context.get(&f.func_ip).copied().unwrap_or(0);
-                                        let in_field = eval_ctx & 0x01 != 0 || self.in_field_init;
-                                        let in_method = eval_ctx & 0x02 != 0 || self.fn_home_objects.contains_key(&f.func_ip) || in_field;
-                                        let in_constructor = eval_ctx & 0x04 != 0 || self.chunk.class_constructor_ips.contains(&f.func_ip);
-                                        (true, in_method, in_constructor)
-                                    }
-                                    None => {
-                                        // No enclosing function frame — check if we're in a
-                                        // field initializer at the top level (arrow in field).
-                                        let in_field = self.in_field_init;
-                                        (in_field, in_field, false)
-                                    }
-                                };
-
-                                if found & SCAN_NEW_TARGET != 0 && !in_function {
-                                    return Err(crate::raise_syntax_error!("new.target expression is not allowed here"));
-                                }
-                                if found & SCAN_SUPER_PROP != 0 && !in_method {
-                                    return Err(crate::raise_syntax_error!("'super' keyword unexpected here"));
-                                }
-                                if found & SCAN_SUPER_CALL != 0 && !in_constructor {
-                                    return Err(crate::raise_syntax_error!("'super' keyword unexpected here"));
-                                }
-                            }
-                        }
-                    }
-                    // Detect strict mode: code begins with "use strict" directive, or enclosing context is strict (direct eval only)
-                    let enclosing_strict = if self.direct_eval {
-                        self.force_strict
-                            || self
-                                .frames
-                                .last()
-                                .map(|f| self.chunk.fn_strictness.get(&f.func_ip).copied().unwrap_or(false))
-                                .unwrap_or(false)
-                            || self.current_execution_is_strict()
-                    } else {
-                        false // indirect eval never inherits caller strict mode
-                    };
-                    let is_strict =
-                        enclosing_strict || code.trim().starts_with("\"use strict\"") || code.trim().starts_with("'use strict'");
-                    // Strict-mode early errors: reject `with` and reserved-word bindings
-                    if is_strict {
-                        fn check_strict_errors(stmts: &[crate::core::Statement]) -> Option<&'static str> {
-                            use crate::core::StatementKind;
-                            for stmt in stmts {
-                                match &*stmt.kind {
-                                    StatementKind::With(..) => {
-                                        return Some("Strict mode code may not include a with statement");
-                                    }
-                                    StatementKind::Var(decls) | StatementKind::Let(decls) => {
-                                        for (name, _) in decls {
-                                            if matches!(
-                                                name.as_str(),
-                                                "static" | "implements" | "interface" | "package" | "private" | "protected" | "public"
-                                            ) {
-                                                return Some("Unexpected strict mode reserved word");
-                                            }
-                                        }
-                                    }
-                                    StatementKind::Const(decls) => {
-                                        for (name, _) in decls {
-                                            if matches!(
-                                                name.as_str(),
-                                                "static" | "implements" | "interface" | "package" | "private" | "protected" | "public"
-                                            ) {
-                                                return Some("Unexpected strict mode reserved word");
-                                            }
-                                        }
-                                    }
-                                    StatementKind::Block(inner) => {
-                                        if let Some(msg) = check_strict_errors(inner) {
-                                            return Some(msg);
-                                        }
-                                    }
-                                    StatementKind::If(if_stmt) => {
-                                        if let Some(msg) = check_strict_errors(&if_stmt.then_body) {
-                                            return Some(msg);
-                                        }
-                                        if let Some(ref else_body) = if_stmt.else_body
-                                            && let Some(msg) = check_strict_errors(else_body)
-                                        {
-                                            return Some(msg);
-                                        }
-                                    }
-                                    _ => {}
-                                }
-                            }
-                            None
-                        }
-                        if let Some(msg) = check_strict_errors(&statements) {
-                            return Err(crate::raise_syntax_error!(msg));
-                        }
-                        use crate::core::statement::{SCAN_STRICT_ARGS_EVAL_ASSIGN, eval_ast_scan};
-                        let strict_assign = eval_ast_scan(&statements, SCAN_STRICT_ARGS_EVAL_ASSIGN);
-                        if strict_assign & SCAN_STRICT_ARGS_EVAL_ASSIGN != 0 {
-                            return Err(crate::raise_syntax_error!("Unexpected eval or arguments in strict mode"));
-                        }
-                    }
-                    let mut compiler = crate::core::Compiler::new();
-                    compiler.set_strict_mode(is_strict);
-                    if let Some(ref ctx) = privns_context {
-                        compiler.set_private_name_context(ctx.clone());
-                    }
-                    let mut chunk = compiler.compile(&statements)?;
-                    chunk.is_eval_code = true;
-                    // Per spec §18.2.1.1 step 6: for indirect eval, strictEval
-                    // is true only when the eval code ITSELF has "use strict".
-                    // Our engine always parses as strict, but for indirect eval
-                    // writeback we need to know if the source explicitly opted in.
-                    let eval_code_explicit_strict = {
-                        let trimmed = code.trim();
-                        trimmed.starts_with("'use strict'") || trimmed.starts_with("\"use strict\"")
-                    };
-                    // Non-configurable global names (can't be redefined by eval)
-                    let non_configurable: [&str; 3] = ["NaN", "Infinity", "undefined"];
-                    // Pre-check: scan chunk for DefineGlobal opcodes that would define functions overriding non-configurable globals
-                    {
-                        let code = &chunk.code;
-                        let constants = &chunk.constants;
-                        let mut pc = 0;
-                        while pc < code.len() {
-                            let op = code[pc];
-                            pc += 1;
-                            if (op == Opcode::DefineGlobal as u8
-                                || op == Opcode::DefineGlobalSoft as u8
-                                || op == Opcode::DefineGlobalConst as u8)
-                                && pc + 1 < code.len()
-                            {
-                                let idx = (code[pc] as u16 | (code[pc + 1] as u16) << 8) as usize;
-                                if idx < constants.len()
-                                    && let Value::String(s) = &constants[idx]
-                                {
-                                    let name = crate::unicode::utf16_to_utf8(s);
-                                    if non_configurable.contains(&name.as_str()) {
-                                        return Err(crate::raise_type_error!(format!("Cannot redefine property: {}", name)));
-                                    }
-                                }
-                                pc += 2;
-                            } else {
-                                // Skip operands based on opcode
-                                match Opcode::try_from(op) {
-                                    Ok(
-                                        Opcode::Constant
-                                        | Opcode::DefineGlobal
-                                        | Opcode::DefineGlobalSoft
-                                        | Opcode::DefineGlobalConst
-                                        | Opcode::GetGlobal
-                                        | Opcode::SetGlobal
-                                        | Opcode::GetProperty
-                                        | Opcode::SetProperty
-                                        | Opcode::SetSuperProperty
-                                        | Opcode::SetSuperPropertyComputed
-                                        | Opcode::GetSuperProperty
-                                        | Opcode::GetMethod
-                                        | Opcode::TypeOfGlobal
-                                        | Opcode::DeleteGlobal
-                                        | Opcode::FreezeTemplate,
-                                    ) => pc += 2,
-                                    Ok(Opcode::Jump | Opcode::JumpIfFalse | Opcode::JumpIfTrue) => pc += 4,
-                                    Ok(Opcode::SetupTry) => pc += 7,
-                                    Ok(Opcode::Call) if pc < code.len() => {
-                                        let raw_arg_byte = code[pc];
-                                        pc += 1;
-                                        if (raw_arg_byte & 0x3f) == 0x3f {
-                                            pc += 2;
-                                        }
-                                    }
-                                    Ok(Opcode::Call) => {}
-                                    Ok(Opcode::NewCall) => pc += 1,
-                                    Ok(
-                                        Opcode::GetLocal
-                                        | Opcode::SetLocal
-                                        | Opcode::NewArray
-                                        | Opcode::NewObject
-                                        | Opcode::GetUpvalue
-                                        | Opcode::SetUpvalue
-                                        | Opcode::CollectRest
-                                        | Opcode::GetArguments
-                                        | Opcode::BoxLocal
-                                        | Opcode::ReboxLocal
-                                        | Opcode::ClearLocalCells
-                                        | Opcode::CallSpread
-                                        | Opcode::ThrowIfNotConstructor,
-                                    ) => pc += 1,
-                                    Ok(Opcode::MakeClosure) => {
-                                        pc += 2; // const idx
-                                        if pc < code.len() {
-                                            let count = code[pc] as usize;
-                                            pc += 1 + count * 2;
-                                        }
-                                    }
-                                    _ => {} // 0-operand opcodes
-                                }
-                            }
-                        }
-                    }
-                    let mut eval_vm: VM<'gc> = VM::new(self.chunk.clone(), ctx);
-                    // Indirect eval must still use the caller realm's actual global this
-                    // value for sloppy this-binding, not the fresh VM::new() global object.
-                    eval_vm.global_this = self.global_this;
-                    // Ensure unique brands across eval VMs
-                    eval_vm.runtime_brand_counter = self.runtime_brand_counter;
-                    eval_vm.restricted_thrower_intrinsic = self.restricted_thrower_intrinsic.clone();
-                    // Propagate strict mode to eval VM
-                    eval_vm.force_strict = is_strict;
-                    // Direct eval inside a function: var declarations should stay in
-                    // the function scope, not leak to globalThis.
-                    if self.direct_eval && !self.frames.is_empty() {
-                        eval_vm.eval_fn_scope = true;
-                    }
-                    // Merge eval code into eval VM's chunk so it has access to
-                    // host functions (getters, setters, closures) at original IPs
-                    let (eval_code_offset, _) = eval_vm.merge_eval_chunk(&chunk);
-                    eval_vm.ip = eval_code_offset;
-                    // Ensure eval VM knows about eval-scoped lexical declarations
-                    // so DefineGlobal/SetGlobal don't leak let/const/class to globalThis.
-                    for name in &chunk.lexical_declared_globals {
-                        eval_vm.chunk.lexical_declared_globals.insert(name.clone());
-                    }
-                    // Copy caller's globals into eval VM
-                    if self.direct_eval {
-                        // Direct eval: copy all globals (caller's scope)
-                        for (k, v) in &self.globals {
-                            eval_vm.globals.insert(k.clone(), v.clone());
-                        }
-                    } else {
-                        // Indirect eval: build globals from the true global scope.
-                        // First copy internal/builtin globals from self.globals
-                        // (ensures eval, globalThis, internal helpers are available).
-                        for (k, v) in &self.globals {
-                            eval_vm.globals.insert(k.clone(), v.clone());
-                        }
-                        // Then override with globalThis properties — these represent
-                        // the actual global environment, excluding eval-local vars.
-                        // Skip Uninitialized values (const declarations that haven't
-                        // been mirrored to globalThis yet).
-                        if let Some(Value::Object(gt)) = self.globals.get("globalThis") {
-                            let global_this = Value::Object(*gt);
-                            let keys: Vec<String> = gt.borrow().keys().cloned().collect();
-                            for k in keys {
-                                let value = self.read_named_property(ctx, &global_this, &k);
-                                if self.pending_throw.is_some() || matches!(value, Value::Uninitialized) {
-                                    self.pending_throw.take();
-                                    continue;
-                                }
-                                eval_vm.globals.insert(k, value);
-                            }
-                        }
-                    }
-                    // For direct eval, inject caller's local variables as globals
-                    if self.direct_eval {
-                        // Walk all frames from outermost to innermost so inner scopes shadow outer
-                        for frame in self.frames.iter() {
-                            if let Some(local_names) = self.chunk.fn_local_names.get(&frame.func_ip) {
-                                let const_names = self.chunk.fn_const_local_names.get(&frame.func_ip);
-                                for (idx, name) in local_names.iter().enumerate() {
-                                    if name.starts_with("__") && name.ends_with("__") {
-                                        continue; // skip synthetic locals
-                                    }
-                                    if let Some(cell) = frame.local_cells.get(&idx) {
-                                        eval_vm.globals.insert(name.clone(), cell.borrow().clone());
-                                    } else {
-                                        let stack_idx = frame.bp + idx;
-                                        if stack_idx < self.stack.len() {
-                                            eval_vm.globals.insert(name.clone(), self.stack[stack_idx].clone());
-                                        }
-                                    }
-                                    // Mark const/immutable locals as const_globals in eval VM
-                                    if let Some(cn) = const_names
-                                        && cn.contains(name)
-                                    {
-                                        eval_vm.const_globals.insert(name.clone());
-                                    }
-                                }
-                            }
-                        }
-                        // Inject top-level block-scoped locals (force_local_let) into
-                        // the eval VM.  Find the latest snapshot recorded before the
-                        // current IP so eval inside a block sees the block's locals.
-                        if !self.chunk.top_level_locals_at_ip.is_empty() {
-                            let cur_ip = self.ip;
-                            let snap = self.chunk.top_level_locals_at_ip.iter().rev().find(|(ip, _, _)| *ip <= cur_ip);
-                            if let Some((_ip, local_names, const_names)) = snap {
-                                for (idx, name) in local_names.iter().enumerate() {
-                                    if name.starts_with("__") && name.ends_with("__") {
-                                        continue;
-                                    }
-                                    // Read from top_level_cells (boxed via BoxLocal) or stack
-                                    let val = if let Some(cell) = self.top_level_cells.get(&idx) {
-                                        cell.borrow().clone()
-                                    } else if idx < self.stack.len() {
-                                        self.stack[idx].clone()
-                                    } else {
-                                        continue;
-                                    };
-                                    eval_vm.globals.insert(name.clone(), val);
-                                    if const_names.contains(name) {
-                                        eval_vm.const_globals.insert(name.clone());
-                                    }
-                                }
-                            }
-                        }
-                        // Direct eval: resolve active block aliases so that
-                        // eval("x") inside a block sees the block-scoped value.
-                        for (alias, original) in &self.chunk.block_alias_to_original {
-                            if let Some(val) = self.globals.get(alias) {
-                                eval_vm.globals.insert(original.clone(), val.clone());
-                            }
-                        }
-                    } else {
-                        // Indirect eval: remove alias keys from eval VM globals
-                        // so internal `__top_block_alias_N__` names aren't visible.
-                        for alias in self.chunk.block_alias_to_original.keys() {
-                            _ = eval_vm.globals.shift_remove(alias);
-                        }
-                    }
-                    if !self.direct_eval
-                        && let Some(Value::Object(gt)) = eval_vm.globals.get("globalThis").cloned()
-                    {
-                        let public_globals: Vec<(String, Value<'gc>)> = eval_vm
-                            .globals
-                            .iter()
-                            .filter(|(k, v)| *k != "globalThis" && !k.starts_with("__") && !matches!(v, Value::Uninitialized))
-                            .map(|(k, v)| (k.clone(), v.clone()))
-                            .collect();
-                        let mut gt_borrow = gt.borrow_mut(ctx);
-                        for (k, v) in public_globals {
-                            if let Some(existing_desc) = desc_from_legacy_map(&gt_borrow, &k) {
-                                PropDesc::data(v, existing_desc.attrs).write_to_legacy_map(&mut gt_borrow, &k);
-                            } else {
-                                gt_borrow.insert(k, v);
-                            }
-                        }
-                    }
-                    eval_vm.script_path = self.current_source_path().map(str::to_owned).or_else(|| self.script_path.clone());
-                    // Eval top-level strictness must be based on the eval source itself,
-                    // not the caller's surrounding script source.
-                    eval_vm.script_source = Some(code.clone());
-                    eval_vm.is_module_mode = self.is_module_mode;
-                    eval_vm.loaded_modules = self.loaded_modules.clone();
-                    eval_vm.module_load_errors = self.module_load_errors.clone();
-                    eval_vm.export_origins = self.export_origins.clone();
-                    eval_vm.reexport_deps = self.reexport_deps.clone();
-                    eval_vm.ambiguous_export_keys = self.ambiguous_export_keys.clone();
-                    eval_vm.loaded_module_local_names = self.loaded_module_local_names.clone();
-                    eval_vm.loaded_module_states = self.loaded_module_states.clone();
-                    eval_vm.module_ns_objects = self.module_ns_objects.clone();
-                    eval_vm.main_module_ip_start = self.main_module_ip_start;
-                    eval_vm.intrinsic_promise_ctor = if matches!(self.intrinsic_promise_ctor, Value::Undefined) {
-                        self.globals.get("Promise").cloned().unwrap_or(Value::Undefined)
-                    } else {
-                        self.intrinsic_promise_ctor.clone()
-                    };
-                    // Set up `this` for the eval VM
-                    if self.direct_eval {
-                        // Direct eval inherits caller's `this`
-                        let caller_this = self.this_stack.last().cloned().unwrap_or(Value::Undefined);
-                        eval_vm.this_stack.push(caller_this);
-                        // Copy fn_props and closure_fn_props so property access on host functions works in eval
-                        for (ip, props) in &self.fn_props {
-                            eval_vm.fn_props.insert(*ip, *props);
-                        }
-                        for (key, props) in &self.closure_fn_props {
-                            eval_vm.closure_fn_props.insert(*key, *props);
-                        }
-                        // Copy fn_home_objects so super.property works in eval
-                        for (ip, home) in &self.fn_home_objects {
-                            eval_vm.fn_home_objects.insert(*ip, home.clone());
-                        }
-                        // Set eval_home_object so resolve_super_base can find
-                        // the super base even without a matching frame
-                        if let Some(caller_frame) = self.frames.last()
-                            && let Some(home) = self.fn_home_objects.get(&caller_frame.func_ip)
-                        {
-                            eval_vm.eval_home_object = Some(home.clone());
-                        } else {
-                            // Field initializer direct eval may execute in helper frames that
-                            // don't carry fn_home_objects; derive a usable home from lexical this.
-                            let caller_this = self.this_stack.last().cloned().unwrap_or(Value::Undefined);
-                            let derived_home = match caller_this {
-                                Value::Object(map) => map.borrow().get("__proto__").cloned(),
-                                Value::Array(arr) => arr.borrow().props.get("__proto__").cloned(),
-                                Value::Function(..) | Value::Closure(..) => Some(caller_this),
-                                _ => None,
-                            };
-                            if let Some(home) = derived_home {
-                                eval_vm.eval_home_object = Some(home);
-                            }
-                        }
-                        // Inherit new.target stack for direct eval
-                        // In field initializers, new.target is undefined per spec
-                        // (eval runs "inside a function", not inside the constructor)
-                        let in_field = self.in_field_init
-                            || self
-                                .frames
-                                .last()
-                                .map(|f| self.chunk.fn_eval_context.get(&f.func_ip).copied().unwrap_or(0) & 0x01 != 0)
-                                .unwrap_or(false);
-                        if in_field {
-                            eval_vm.new_target_stack.push(Value::Undefined);
-                        } else {
-                            eval_vm.new_target_stack = self.new_target_stack.clone();
-                        }
-                    } else {
-                        // Indirect eval: `this` is the realm's actual global this value.
-                        eval_vm.this_stack.push(Value::Object(self.global_this));
-                    }
-
-                    // §19.2.1.3 EvalDeclarationInstantiation step 5:
-                    // "If strict is false" — only check var/let collision for
-                    // non-strict indirect eval.
-                    if !self.direct_eval && !is_strict {
-                        for var_name in &chunk.declared_globals {
-                            if chunk.lexical_declared_globals.contains(var_name) {
-                                continue; // it's a let/const in eval, not a var
-                            }
-                            if self.chunk.lexical_declared_globals.contains(var_name) {
-                                return Err(crate::raise_syntax_error!(format!(
-                                    "Identifier '{}' has already been declared",
-                                    var_name
-                                )));
-                            }
-                        }
-                        // §19.2.1.3 step 10.a.iii: CanDeclareGlobalVar
-                        // If the global object is non-extensible, new var declarations
-                        // that don't already exist on the global object throw TypeError.
-                        {
-                            let gt = self.global_this.borrow();
-                            let non_extensible = gt.get("__non_extensible__").is_some_and(|v| v.to_truthy());
-                            if non_extensible {
-                                for var_name in &chunk.declared_globals {
-                                    if chunk.lexical_declared_globals.contains(var_name) {
-                                        continue;
-                                    }
-                                    if !gt.contains_key(var_name) {
-                                        drop(gt);
-                                        return Err(crate::raise_type_error!(format!(
-                                            "Cannot define global variable '{}': global object is not extensible",
-                                            var_name
-                                        )));
-                                    }
-                                }
-                            }
-                        }
-                    }
-
-                    let eval_result = eval_vm.run(ctx);
-                    // Sync brand counter so subsequent evals get unique brands
-                    if eval_vm.runtime_brand_counter > self.runtime_brand_counter {
-                        self.runtime_brand_counter = eval_vm.runtime_brand_counter;
-                    }
-                    // Merge original eval chunk into host chunk so Function values
-                    // (getters, setters, methods) remain callable in the host VM.
-                    let (code_offset, _const_offset) = self.merge_eval_chunk(&chunk);
-                    // Build set of function IPs that genuinely belong to the eval chunk
-                    let eval_fn_ips: std::collections::HashSet<usize> = chunk
-                        .constants
-                        .iter()
-                        .filter_map(|c| match c {
-                            Value::Function(ip, _) => Some(*ip),
-                            _ => None,
-                        })
-                        .chain(chunk.fn_names.keys().copied())
-                        .chain(chunk.fn_local_names.keys().copied())
-                        .collect();
-                    for arg in args {
-                        let _ = self.adjust_value_ips(ctx, arg, code_offset, &eval_fn_ips);
-                    }
-                    for task in eval_vm.microtask_queue {
-                        self.microtask_queue.push(Microtask {
-                            callback: task
-                                .callback
-                                .as_ref()
-                                .map(|callback| self.adjust_value_ips(ctx, callback, code_offset, &eval_fn_ips)),
-                            value: self.adjust_value_ips(ctx, &task.value, code_offset, &eval_fn_ips),
-                            rejected: task.rejected,
-                            resolve: self.adjust_value_ips(ctx, &task.resolve, code_offset, &eval_fn_ips),
-                            reject: self.adjust_value_ips(ctx, &task.reject, code_offset, &eval_fn_ips),
-                        });
-                    }
-                    self.loaded_modules = eval_vm.loaded_modules.clone();
-                    self.module_load_errors = eval_vm.module_load_errors.clone();
-                    self.export_origins = eval_vm.export_origins.clone();
-                    self.reexport_deps = eval_vm.reexport_deps.clone();
-                    self.ambiguous_export_keys = eval_vm.ambiguous_export_keys.clone();
-                    self.loaded_module_local_names = eval_vm.loaded_module_local_names.clone();
-                    self.loaded_module_states = eval_vm.loaded_module_states.clone();
-                    self.module_ns_objects = eval_vm.module_ns_objects.clone();
-                    self.main_module_ip_start = eval_vm.main_module_ip_start;
-                    self.intrinsic_promise_ctor = eval_vm.intrinsic_promise_ctor.clone();
-                    self.restricted_thrower_intrinsic = eval_vm.restricted_thrower_intrinsic.clone();
-                    // Adjust Function IPs in the result value (only on success)
-                    let result = match &eval_result {
-                        Ok(r) => Some(self.adjust_value_ips(ctx, r, code_offset, &eval_fn_ips)),
-                        Err(_) => None,
-                    };
-                    // Also merge fn_home_objects from eval VM
-                    // IPs are already adjusted (eval VM used host chunk clone + merged eval code)
-                    for (ip, home) in &eval_vm.fn_home_objects {
-                        self.fn_home_objects.insert(*ip, home.clone());
-                    }
-                    // Merge fn_props from eval VM (static methods, etc.)
-                    for (ip, props_handle) in &eval_vm.fn_props {
-                        self.fn_props.insert(*ip, *props_handle);
-                    }
-                    // Merge closure_fn_props (per-closure overlays, e.g. prototype for
-                    // class constructors with private members compiled as closures).
-                    for (key, props_handle) in &eval_vm.closure_fn_props {
-                        self.closure_fn_props.insert(*key, *props_handle);
-                    }
-                    // Copy globals back
-                    // Strict mode: skip new declarations AND var-redeclarations
-                    // Sloppy mode: write back all globals (including new declarations)
-                    {
-                        // For direct eval, build a set of local variable names from
-                        // enclosing frames so we don't overwrite them as globals.
-                        let local_names_set: std::collections::HashSet<&str> = if self.direct_eval {
-                            self.frames
-                                .iter()
-                                .filter_map(|f| self.chunk.fn_local_names.get(&f.func_ip))
-                                .flat_map(|names| names.iter().map(|n| n.as_str()))
-                                .collect()
-                        } else {
-                            std::collections::HashSet::new()
-                        };
-                        for (k, v) in &eval_vm.globals {
-                            // Never leak top-level lexical declarations from eval
-                            // unless REPL mode explicitly requests persistence.
-                            if !self.repl_lexical_persist && chunk.lexical_declared_globals.contains(k) {
-                                continue;
-                            }
-                            // In strict mode, skip new globals and var/function declarations
-                            // (they stay scoped).  For INDIRECT eval, per spec §18.2.1.1
-                            // step 4, strictCaller = false; declarations go to global scope
-                            // UNLESS the eval code itself has "use strict".
-                            let strict_scoped = if self.direct_eval { is_strict } else { eval_code_explicit_strict };
-                            if strict_scoped && (!self.globals.contains_key(k) || chunk.declared_globals.contains(k)) {
-                                // The eval VM may have already written this key to
-                                // the shared global_this during DefineGlobal execution.
-                                // Remove it so it doesn't leak to the outer scope.
-                                self.global_this.borrow_mut(ctx).shift_remove(k);
-                                continue;
-                            }
-                            // For direct eval, skip variables that are locals in caller frames;
-                            // they are written back to the stack separately below.
-                            if self.direct_eval && local_names_set.contains(k.as_str()) {
-                                continue;
-                            }
-                            // Skip globals unchanged by eval: host-chunk Function IPs
-                            // can coincide with eval-chunk IPs, causing false adjustment.
-                            if let Some(host_val) = self.globals.get(k) {
-                                let unchanged = match (host_val, v) {
-                                    (Value::Function(h_ip, h_ar), Value::Function(e_ip, e_ar)) => h_ip == e_ip && h_ar == e_ar,
-                                    (Value::Closure(h_ip, h_ar, h_uv), Value::Closure(e_ip, e_ar, e_uv)) => {
-                                        h_ip == e_ip && h_ar == e_ar && Gc::ptr_eq(*h_uv, *e_uv)
-                                    }
-                                    (Value::Object(h), Value::Object(e)) => Gc::ptr_eq(*h, *e),
-                                    (Value::Array(h), Value::Array(e)) => Gc::ptr_eq(*h, *e),
-                                    (Value::Map(h), Value::Map(e)) => Gc::ptr_eq(*h, *e),
-                                    (Value::Set(h), Value::Set(e)) => Gc::ptr_eq(*h, *e),
-                                    (Value::NativeFunction(h), Value::NativeFunction(e)) => h == e,
-                                    (Value::Number(h), Value::Number(e)) => h.to_bits() == e.to_bits(),
-                                    (Value::Boolean(h), Value::Boolean(e)) => h == e,
-                                    (Value::String(h), Value::String(e)) => h == e,
-                                    (Value::Null, Value::Null) | (Value::Undefined, Value::Undefined) => true,
-                                    _ => false,
-                                };
-                                if unchanged {
-                                    continue;
-                                }
-                            }
-                            let adjusted = self.adjust_value_ips(ctx, v, code_offset, &eval_fn_ips);
-                            self.globals.insert(k.clone(), adjusted.clone());
-                            // For direct eval inside a function, var declarations stay in
-                            // the function scope and should not leak to globalThis.
-                            let in_fn_scope = self.direct_eval && !self.frames.is_empty();
-                            if !in_fn_scope {
-                                let mut gt = self.global_this.borrow_mut(ctx);
-                                if let Some(existing_desc) = desc_from_legacy_map(&gt, k) {
-                                    PropDesc::data(adjusted, existing_desc.attrs).write_to_legacy_map(&mut gt, k);
-                                } else {
-                                    gt.insert(k.clone(), adjusted);
-                                }
-                            }
-                        }
-                    }
-                    // CreateGlobalFunctionBinding (§8.1.1.4.18): after writeback,
-                    // update property descriptors for eval function declarations.
-                    for fn_name in &chunk.fn_declared_globals {
-                        let mut gt = self.global_this.borrow_mut(ctx);
-                        if !has_nonconfigurable_mark(&gt, fn_name) {
-                            unmark_readonly(&mut gt, fn_name);
-                            unmark_nonenumerable(&mut gt, fn_name);
-                        }
-                    }
-                    // For direct eval, write back modified local variables to caller's stack
-                    if self.direct_eval {
-                        for frame in self.frames.iter().rev() {
-                            if let Some(local_names) = self.chunk.fn_local_names.get(&frame.func_ip) {
-                                for (idx, name) in local_names.iter().enumerate() {
-                                    if name.starts_with("__") && name.ends_with("__") {
-                                        continue;
-                                    }
-                                    // Skip locals whose name was declared inside eval
-                                    // (var/function/class in strict eval, lexical in all evals).
-                                    if (!self.repl_lexical_persist && chunk.lexical_declared_globals.contains(name))
-                                        || (is_strict && chunk.declared_globals.contains(name))
-                                    {
-                                        continue;
-                                    }
-                                    if let Some(new_val) = eval_vm.globals.get(name) {
-                                        let adjusted = self.adjust_value_ips(ctx, new_val, code_offset, &eval_fn_ips);
-                                        if let Some(cell) = frame.local_cells.get(&idx) {
-                                            *cell.borrow_mut(ctx) = adjusted;
-                                        } else {
-                                            let stack_idx = frame.bp + idx;
-                                            if stack_idx < self.stack.len() {
-                                                self.stack[stack_idx] = adjusted;
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    // Propagate error or return result
-                    match eval_result {
-                        Ok(_) => Ok(result.unwrap_or(Value::Undefined)),
-                        Err(e) => Err(e),
-                    }
-                })();
+                let result = self.prepare_eval(ctx, &code, privns_context).and_then(|mut prepared| {
+                    let eval_result = prepared.vm.run(ctx);
+                    self.finish_eval(ctx, prepared, eval_result, args)
+                });
                 match result {
                     Ok(v) => {
                         match v {
@@ -31101,7 +31475,13 @@ impl<'gc> VM<'gc> {
     }
 
     /// Execute a method call (receiver.method(args))
-    fn call_method_builtin(&mut self, ctx: &GcContext<'gc>, id: FunctionID, receiver: &Value<'gc>, args: &[Value<'gc>]) -> Value<'gc> {
+    fn call_method_builtin_uncharged(
+        &mut self,
+        ctx: &GcContext<'gc>,
+        id: FunctionID,
+        receiver: &Value<'gc>,
+        args: &[Value<'gc>],
+    ) -> Value<'gc> {
         // Generic change-array-by-copy: handle non-array this values
         if matches!(
             id,
@@ -31346,6 +31726,7 @@ impl<'gc> VM<'gc> {
                     return result;
                 }
             }
+            #[cfg(feature = "typed-arrays")]
             BUILTIN_CTOR_DATAVIEW => {
                 return self.dataview_call_method_builtin(ctx, receiver, args);
             }
@@ -31823,6 +32204,9 @@ impl<'gc> VM<'gc> {
                 self.throw_type_error(ctx, "Promise.prototype.then requires a Promise receiver");
                 return Value::Undefined;
             }
+            if is_rejected {
+                self.mark_promise_handled(ctx, receiver);
+            }
 
             let constructor = match self.promise_species_constructor(ctx, receiver) {
                 Ok(v) => v,
@@ -36827,14 +37211,7 @@ impl<'gc> VM<'gc> {
                         if id == BUILTIN_NUM_VALUEOF {
                             return Value::Number(n);
                         }
-                        let formatter = match self.intl_construct_service_instance(ctx, "NumberFormat", None, args) {
-                            Ok(value) => value,
-                            Err(err) => {
-                                self.pending_throw = Some(err);
-                                return Value::Undefined;
-                            }
-                        };
-                        return self.intl_number_format_format(ctx, Some(&formatter), &[Value::Number(n)]);
+                        return self.numeric_to_locale_string(ctx, Value::Number(n), args);
                     }
                     _ => {}
                 }
@@ -40027,9 +40404,11 @@ impl<'gc> VM<'gc> {
                 Self::make_host_fn_with_name_len(ctx, "global.unescape", "unescape", 1.0, false),
             ));
         }
+        #[cfg(feature = "intl")]
         if !self.globals.contains_key("Intl") {
             bindings.push(("Intl".to_string(), self.make_dynamic_intl_placeholder(ctx)));
         }
+        #[cfg(feature = "temporal")]
         if !self.globals.contains_key("Temporal") {
             bindings.push(("Temporal".to_string(), Value::Object(new_gc_cell_ptr(ctx, IndexMap::new()))));
         }
@@ -40666,6 +41045,7 @@ impl<'gc> VM<'gc> {
             vm.push_call_frame_with_context(
                 CallFrame {
                     return_ip: 0,
+                    call_ip: vm.current_opcode_ip,
                     bp,
                     is_method: false,
                     arg_count,
@@ -43142,6 +43522,35 @@ impl<'gc> VM<'gc> {
         }
     }
 
+    /// `new` on an `Intl` service constructor; `None` when `host_name` is not one.
+    #[cfg(feature = "intl")]
+    fn construct_intl_service(
+        &mut self,
+        ctx: &GcContext<'gc>,
+        host_name: &str,
+        target: &Value<'gc>,
+        new_target: Option<&Value<'gc>>,
+        args: &[Value<'gc>],
+    ) -> Option<Result<Value<'gc>, JSError>> {
+        if !host_name.starts_with("intl.") {
+            return None;
+        }
+        let ctor_receiver = new_target.cloned().unwrap_or_else(|| target.clone());
+        self.intl_construct_host_fn(ctx, host_name, &ctor_receiver, args)
+    }
+
+    #[cfg(not(feature = "intl"))]
+    fn construct_intl_service(
+        &mut self,
+        _ctx: &GcContext<'gc>,
+        _host_name: &str,
+        _target: &Value<'gc>,
+        _new_target: Option<&Value<'gc>>,
+        _args: &[Value<'gc>],
+    ) -> Option<Result<Value<'gc>, JSError>> {
+        None
+    }
+
     fn construct_value(
         &mut self,
         ctx: &GcContext<'gc>,
@@ -43232,6 +43641,7 @@ impl<'gc> VM<'gc> {
                 self.push_call_frame_with_context(
                     CallFrame {
                         return_ip: 0,
+                        call_ip: self.current_opcode_ip,
                         bp,
                         is_method: false,
                         arg_count,
@@ -43413,15 +43823,8 @@ impl<'gc> VM<'gc> {
                                     } else {
                                         Ok(value)
                                     }
-                                } else if host_name.starts_with("intl.") {
-                                    let ctor_receiver = new_target.cloned().unwrap_or_else(|| target.clone());
-                                    match self.intl_construct_host_fn(ctx, &host_name, &ctor_receiver, args) {
-                                        Some(result) => result,
-                                        None => {
-                                            let err = self.make_type_error_object(ctx, "Target is not a constructor");
-                                            Err(self.vm_error_to_js_error(ctx, &err))
-                                        }
-                                    }
+                                } else if let Some(result) = self.construct_intl_service(ctx, &host_name, target, new_target, args) {
+                                    result
                                 } else {
                                     let err = self.make_type_error_object(ctx, "Target is not a constructor");
                                     Err(self.vm_error_to_js_error(ctx, &err))
@@ -43502,6 +43905,7 @@ impl<'gc> VM<'gc> {
                     _ => "Object",
                 };
                 // DataView: spec requires argument validation BEFORE GetPrototypeFromConstructor
+                #[cfg(feature = "typed-arrays")]
                 if id == BUILTIN_CTOR_DATAVIEW {
                     return self.construct_dataview(ctx, target, args, new_target);
                 }
@@ -46146,6 +46550,9 @@ impl<'gc> VM<'gc> {
     /// Handle a thrown value: unwind to nearest try/catch or return error
     fn handle_throw(&mut self, ctx: &GcContext<'gc>, thrown: &Value<'gc>) -> Result<(), JSError> {
         self.pending_throw = None;
+        if let Some(err) = self.termination_error() {
+            return Err(err);
+        }
         // Record the throw-site IP if not already set by a Throw opcode.
         if self.last_throw_ip.is_none() {
             self.last_throw_ip = Some(self.current_opcode_ip);
@@ -46219,11 +46626,82 @@ impl<'gc> VM<'gc> {
     ///
     /// This uses the VM's `eval` builtin so globals survive between calls.
     pub fn eval_repl_snippet(&mut self, ctx: &GcContext<'gc>, code: &str) -> Result<Value<'gc>, JSError> {
+        self.begin_limited_run();
+        let result = self.eval_repl_snippet_inner(ctx, code);
+        if let Some(err) = self.termination_error() {
+            self.abandon_terminated_run();
+            return Err(err);
+        }
+        result
+    }
+
+    fn eval_repl_snippet_inner(&mut self, ctx: &GcContext<'gc>, code: &str) -> Result<Value<'gc>, JSError> {
         // REPL submissions are independent top-level snippets, not direct eval calls.
         self.direct_eval = false;
         self.repl_lexical_persist = true;
         let out = self.call_builtin(ctx, BUILTIN_EVAL, &[Value::from(code)]);
         self.repl_lexical_persist = false;
+        self.complete_repl_snippet(ctx, code, out)
+    }
+
+    /// Evaluate one REPL input like [`eval_repl_snippet`](Self::eval_repl_snippet),
+    /// but return [`RunSlice::Paused`] when the heap limit is crossed; the
+    /// embedder then collects garbage and calls
+    /// [`resume_repl_snippet`](Self::resume_repl_snippet).
+    pub(crate) fn start_repl_snippet(&mut self, ctx: &GcContext<'gc>, code: &str) -> RunSlice<Result<Value<'gc>, JSError>> {
+        self.begin_limited_run();
+        self.direct_eval = false;
+        self.repl_lexical_persist = true;
+        if let Some(out) = self.eval_fast_path(ctx, &Value::from(code), code) {
+            return RunSlice::Done(self.finish_repl_snippet(ctx, code, out));
+        }
+        match self.prepare_eval(ctx, code, None) {
+            Ok(prepared) => {
+                self.repl_eval = Some(Box::new(prepared));
+                self.resume_repl_snippet(ctx, code)
+            }
+            Err(e) => {
+                self.pending_throw = Some(self.vm_value_from_error(ctx, &e));
+                RunSlice::Done(self.finish_repl_snippet(ctx, code, Value::Undefined))
+            }
+        }
+    }
+
+    /// Continue the REPL input that [`start_repl_snippet`](Self::start_repl_snippet) paused.
+    pub(crate) fn resume_repl_snippet(&mut self, ctx: &GcContext<'gc>, code: &str) -> RunSlice<Result<Value<'gc>, JSError>> {
+        let Some(mut prepared) = self.repl_eval.take() else {
+            return RunSlice::Done(Ok(Value::Undefined));
+        };
+        let eval_result = match prepared.vm.run_slice(ctx) {
+            RunSlice::Paused => {
+                self.repl_eval = Some(prepared);
+                return RunSlice::Paused;
+            }
+            RunSlice::Done(result) => result,
+        };
+        let out = match self.finish_eval(ctx, *prepared, eval_result, &[]) {
+            Ok(v) => v,
+            Err(e) => {
+                self.pending_throw = Some(self.vm_value_from_error(ctx, &e));
+                Value::Undefined
+            }
+        };
+        RunSlice::Done(self.finish_repl_snippet(ctx, code, out))
+    }
+
+    fn finish_repl_snippet(&mut self, ctx: &GcContext<'gc>, code: &str, out: Value<'gc>) -> Result<Value<'gc>, JSError> {
+        self.repl_lexical_persist = false;
+        let result = self.complete_repl_snippet(ctx, code, out);
+        if let Some(err) = self.termination_error() {
+            self.abandon_terminated_run();
+            return Err(err);
+        }
+        result
+    }
+
+    /// Settle what a REPL input left behind once its code has run: report a
+    /// throw, keep its function declarations callable, and drain the queues.
+    fn complete_repl_snippet(&mut self, ctx: &GcContext<'gc>, code: &str, out: Value<'gc>) -> Result<Value<'gc>, JSError> {
         if let Some(thrown) = self.pending_throw.take() {
             return Err(self.vm_error_to_js_error(ctx, &thrown));
         }
@@ -46264,10 +46742,12 @@ impl<'gc> VM<'gc> {
         if let Some(thrown) = self.pending_throw.take() {
             return Err(self.vm_error_to_js_error(ctx, &thrown));
         }
+        self.notify_rejections(ctx)?;
         self.drain_timers(ctx)?;
         if let Some(thrown) = self.pending_throw.take() {
             return Err(self.vm_error_to_js_error(ctx, &thrown));
         }
+        self.drain_async_host_operations(ctx)?;
         self.collect_garbage(ctx);
         Ok(out)
     }
@@ -46313,6 +46793,7 @@ impl<'gc> VM<'gc> {
             vm.push_call_frame_with_context(
                 CallFrame {
                     return_ip: 0,
+                    call_ip: vm.current_opcode_ip,
                     bp,
                     is_method: false,
                     arg_count,
@@ -46543,6 +47024,7 @@ impl<'gc> VM<'gc> {
             vm.push_call_frame_with_context(
                 CallFrame {
                     return_ip: 0,
+                    call_ip: vm.current_opcode_ip,
                     bp,
                     is_method: false,
                     arg_count: state.arg_count,
@@ -46675,6 +47157,9 @@ impl<'gc> VM<'gc> {
             }
             let batch = std::mem::take(&mut self.microtask_queue);
             for task in batch {
+                if self.is_terminating() {
+                    return;
+                }
                 self.run_then_microtask(ctx, task);
             }
         }
@@ -46713,6 +47198,7 @@ impl<'gc> VM<'gc> {
                     return Ok(value.clone());
                 }
                 if promise_state.2 {
+                    self.mark_promise_handled(ctx, value);
                     let msg = crate::core::value::value_to_string(&promise_state.3);
                     return Err(crate::raise_type_error!(msg));
                 }
@@ -46760,6 +47246,9 @@ impl<'gc> VM<'gc> {
                 let val = b.get("__promise_value__").cloned().unwrap_or(Value::Undefined);
                 drop(b);
                 if has_val {
+                    if rej {
+                        self.mark_promise_handled(ctx, &Value::Object(*obj));
+                    }
                     propagated_rejected = rej;
                     callback_result = val;
                 } else {
@@ -46787,42 +47276,4 @@ impl<'gc> VM<'gc> {
             self.pending_throw = Some(self.vm_value_from_error(ctx, &err));
         }
     }
-
-    /// Execute all pending timers (setTimeout / setInterval callbacks).
-    /// Runs in a loop until no more timers remain, supporting chained timers.
-    fn drain_timers(&mut self, ctx: &GcContext<'gc>) -> Result<(), JSError> {
-        // Sort by delay so shorter delays fire first
-        for _round in 0..1000 {
-            if self.pending_timers.is_empty() {
-                break;
-            }
-            // Take current batch, sorted by delay
-            let mut batch: Vec<PendingTimer<'gc>> = std::mem::take(&mut self.pending_timers);
-            batch.sort_by_key(|t| t.delay_ms);
-
-            for timer in batch {
-                if self.cleared_timers.contains(&timer.id) {
-                    continue;
-                }
-                let cb_args: Vec<Value<'gc>> = timer.args.clone();
-                if self.is_value_callable(&timer.callback) {
-                    let _ = self.vm_call_function_value(ctx, &timer.callback, &Value::Undefined, &cb_args)?;
-                }
-                self.flush_async_atomics_waits(ctx, true);
-                self.drain_microtasks(ctx);
-                // Re-queue intervals
-                if timer.is_interval && !self.cleared_timers.contains(&timer.id) {
-                    self.pending_timers.push(PendingTimer {
-                        id: timer.id,
-                        callback: timer.callback,
-                        args: timer.args,
-                        delay_ms: timer.delay_ms,
-                        is_interval: true,
-                    });
-                }
-            }
-        }
-        self.cleared_timers.clear();
-        Ok(())
-    }
 }
@@ -0,0 +1,159 @@
+use super::*;
+use crate::core::{AsyncHostCallback, JsOwnedValue, PromiseResolver, Settlement, SettlementSender};
+use crossbeam_channel::Receiver;
+use std::cell::{Cell, RefCell};
+use std::time::Duration;
+
+/// How long the event loop blocks on the settlement channel before it
+/// re-checks for interrupts.
+pub(super) const SETTLEMENT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Async host functions, the promises they returned that are still waiting
+/// for a [`PromiseResolver`], and the channel resolvers report back on.
+/// Shared with helper VMs so operations started from eval'd code are awaited too.
+pub(crate) struct AsyncHostState<'gc> {
+    callbacks: RefCell<Vec<AsyncHostCallback>>,
+    pending: RefCell<HashMap<u64, Value<'gc>>>,
+    next_id: Cell<u64>,
+    sender: SettlementSender,
+    receiver: Receiver<(u64, Settlement)>,
+}
+
+impl Default for AsyncHostState<'_> {
+    fn default() -> Self {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        AsyncHostState {
+            callbacks: RefCell::default(),
+            pending: RefCell::default(),
+            next_id: Cell::new(0),
+            sender,
+            receiver,
+        }
+    }
+}
+
+impl<'gc> AsyncHostState<'gc> {
+    pub(super) fn trace<T: GcTrace<'gc>>(&self, cc: &mut T) {
+        for promise in self.pending.borrow().values() {
+            promise.trace(cc);
+        }
+    }
+
+    pub(super) fn has_pending(&self) -> bool {
+        !self.pending.borrow().is_empty()
+    }
+
+    /// Forget outstanding operations; their late settlements are ignored.
+    pub(super) fn abandon_pending(&self) {
+        self.pending.borrow_mut().clear();
+    }
+}
+
+impl<'gc> VM<'gc> {
+    /// Install `callback` at `path` as a function returning a promise that
+    /// the callback settles through its [`PromiseResolver`].
+    pub(crate) fn register_async_host_callback(
+        &mut self,
+        ctx: &GcContext<'gc>,
+        path: &str,
+        length: usize,
+        callback: AsyncHostCallback,
+    ) -> Result<(), JSError> {
+        let id = {
+            let mut callbacks = self.async_host.callbacks.borrow_mut();
+            callbacks.push(callback);
+            callbacks.len() - 1
+        };
+        self.define_host_fn_at_path(ctx, path, &format!("asynchost.{id}"), length)
+    }
+
+    pub(super) fn async_host_handle_host_fn(&mut self, ctx: &GcContext<'gc>, name: &str, args: &[Value<'gc>]) -> Value<'gc> {
+        let callback = name
+            .strip_prefix("asynchost.")
+            .and_then(|id| id.parse::<usize>().ok())
+            .and_then(|id| self.async_host.callbacks.borrow().get(id).cloned());
+        let Some(callback) = callback else {
+            self.throw_type_error(ctx, &format!("{name} is not a registered async host function"));
+            return Value::Undefined;
+        };
+
+        let promise = self.make_pending_promise(ctx);
+        let owned_args: Result<Vec<JsOwnedValue>, JSError> = args.iter().map(|arg| self.value_to_owned(ctx, arg)).collect();
+        let owned_args = match owned_args {
+            Ok(owned_args) => owned_args,
+            Err(err) => {
+                let reason = self.vm_value_from_error(ctx, &err);
+                self.settle_promise(ctx, &promise, &reason, true);
+                return promise;
+            }
+        };
+
+        let id = self.async_host.next_id.get();
+        self.async_host.next_id.set(id + 1);
+        self.async_host.pending.borrow_mut().insert(id, promise.clone());
+        let resolver = PromiseResolver::new(id, self.async_host.sender.clone());
+        if let Err(err) = callback(&owned_args, resolver) {
+            // Queued like any other settlement, so one the callback already made wins.
+            let _ = self.async_host.sender.send((id, Settlement::Rejected(err)));
+        }
+        promise
+    }
+
+    /// Apply queued settlements, waiting up to `wait` for the first one.
+    /// Returns whether any outstanding promise was settled.
+    pub(super) fn apply_async_host_settlements(&mut self, ctx: &GcContext<'gc>, wait: Option<Duration>) -> bool {
+        let state = self.async_host.clone();
+        let first = match wait {
+            Some(wait) => state.receiver.recv_timeout(wait).ok(),
+            None => state.receiver.try_recv().ok(),
+        };
+        let mut settled = false;
+        for (id, settlement) in first.into_iter().chain(state.receiver.try_iter()) {
+            let Some(promise) = state.pending.borrow_mut().remove(&id) else {
+                continue;
+            };
+            match settlement {
+                Settlement::Fulfilled(value) => match self.owned_to_value(ctx, &value) {
+                    Ok(value) => self.resolve_promise_value(ctx, &promise, &value),
+                    Err(err) => {
+                        let reason = self.vm_value_from_error(ctx, &err);
+                        self.settle_promise(ctx, &promise, &reason, true);
+                    }
+                },
+                Settlement::Rejected(err) => {
+                    let reason = self.vm_value_from_error(ctx, &err);
+                    self.settle_promise(ctx, &promise, &reason, true);
+                }
+            }
+            settled = true;
+        }
+        settled
+    }
+
+    /// Keep the event loop alive while async host operations are outstanding,
+    /// running the microtasks and timers that come due meanwhile.
+    pub(super) fn drain_async_host_operations(&mut self, ctx: &GcContext<'gc>) -> Result<(), JSError> {
+        if self.is_host_driven_event_loop() {
+            // The embedder applies settlements through `run_microtasks`.
+            return Ok(());
+        }
+        while self.async_host.has_pending() {
+            if let Some(err) = self.termination_error() {
+                return Err(err);
+            }
+            if self.apply_async_host_settlements(ctx, Some(SETTLEMENT_POLL_INTERVAL)) {
+                self.drain_microtasks(ctx);
+                if let Some(thrown) = self.pending_throw.take() {
+                    return Err(self.vm_error_to_js_error(ctx, &thrown));
+                }
+                self.notify_rejections(ctx)?;
+            }
+            // Also fires unreferenced timers that came due while waiting.
+            self.drain_timers(ctx)?;
+            if let Some(thrown) = self.pending_throw.take() {
+                return Err(self.vm_error_to_js_error(ctx, &thrown));
+            }
+        }
+        Ok(())
+    }
+}
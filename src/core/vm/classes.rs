@@ -0,0 +1,255 @@
+use super::*;
+use crate::core::{ConstructCallback, HostClassSpec, HostMemberKind, InstanceCallback, JsOwnedValue};
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+/// Own property tagging an object with the id of its host payload.
+const HOST_INSTANCE_KEY: &str = "__host_instance__";
+
+struct HostClass {
+    name: String,
+    construct: Option<ConstructCallback>,
+}
+
+struct HostMember {
+    class: usize,
+    /// `Class.prototype.name`, for error messages.
+    label: String,
+    call: InstanceCallback,
+}
+
+struct HostInstance<'gc> {
+    object: VmObjectWeakHandle<'gc>,
+    class: usize,
+    payload: Rc<RefCell<Box<dyn Any>>>,
+}
+
+/// Embedder-defined classes and the Rust payloads of their live instances;
+/// shared with helper VMs so instances created by eval'd code are tracked too.
+#[derive(Default)]
+pub(crate) struct HostClassState<'gc> {
+    classes: RefCell<Vec<HostClass>>,
+    members: RefCell<Vec<HostMember>>,
+    instances: RefCell<HashMap<u64, HostInstance<'gc>>>,
+    next_instance: Cell<u64>,
+}
+
+impl<'gc> HostClassState<'gc> {
+    pub(super) fn trace<T: GcTrace<'gc>>(&self, cc: &mut T) {
+        for instance in self.instances.borrow().values() {
+            instance.object.trace(cc);
+        }
+    }
+}
+
+fn js_string_literal(s: &str) -> String {
+    serde_json::Value::from(s).to_string()
+}
+
+impl<'gc> VM<'gc> {
+    /// Install a host class as the global binding `spec.name`.
+    ///
+    /// The class itself is ordinary JS generated around native hooks, so
+    /// `instanceof`, `extends` and `super` behave as for script classes.
+    pub(crate) fn define_host_class(&mut self, ctx: &GcContext<'gc>, spec: HostClassSpec) -> Result<(), JSError> {
+        let HostClassSpec {
+            name,
+            constructor,
+            members,
+            static_methods,
+            static_values,
+        } = spec;
+        if !crate::core::is_strict_binding_identifier(&name) {
+            return Err(crate::raise_type_error!(format!("Invalid host class name '{name}'")));
+        }
+
+        let class_id = self.host_classes.classes.borrow().len();
+        self.host_classes.classes.borrow_mut().push(HostClass {
+            name: name.clone(),
+            construct: constructor,
+        });
+        let construct = Self::make_host_fn_with_name_len(ctx, &format!("hostclass.new.{class_id}"), &name, 0.0, false);
+
+        let mut hooks = Vec::new();
+        let mut body = String::from("constructor(...args) { construct(this, ...args); }\n");
+        for (key, kind, call) in members {
+            let member_id = {
+                let mut registered = self.host_classes.members.borrow_mut();
+                registered.push(HostMember {
+                    class: class_id,
+                    label: format!("{name}.prototype.{key}"),
+                    call,
+                });
+                registered.len() - 1
+            };
+            hooks.push(Self::make_host_fn_with_name_len(
+                ctx,
+                &format!("hostclass.call.{member_id}"),
+                &key,
+                0.0,
+                false,
+            ));
+            let (hook, key) = (hooks.len() - 1, js_string_literal(&key));
+            body.push_str(&match kind {
+                HostMemberKind::Method => format!("[{key}](...args) {{ return hooks[{hook}](this, ...args); }}\n"),
+                HostMemberKind::Getter => format!("get [{key}]() {{ return hooks[{hook}](this); }}\n"),
+                HostMemberKind::Setter => format!("set [{key}](value) {{ hooks[{hook}](this, value); }}\n"),
+            });
+        }
+        for (key, callback) in static_methods {
+            let id = self.host_callbacks.len();
+            self.host_callbacks.push(callback);
+            hooks.push(Self::make_host_fn_with_name_len(ctx, &format!("embed.{id}"), &key, 0.0, false));
+            let (hook, key) = (hooks.len() - 1, js_string_literal(&key));
+            body.push_str(&format!("static [{key}](...args) {{ return hooks[{hook}](...args); }}\n"));
+        }
+        let mut values = Vec::with_capacity(static_values.len());
+        for (index, (key, value)) in static_values.iter().enumerate() {
+            values.push(self.owned_to_value(ctx, value)?);
+            body.push_str(&format!("static [{}] = values[{index}];\n", js_string_literal(key)));
+        }
+
+        let tag = js_string_literal(&name);
+        let factory_source = format!(
+            "(function (construct, hooks, values) {{\n\
+             class {name} {{\n{body}}}\n\
+             Object.defineProperty({name}.prototype, Symbol.toStringTag, {{ value: {tag}, configurable: true }});\n\
+             return {name};\n\
+             }})"
+        );
+        let factory = self.eval_repl_snippet(ctx, &factory_source)?;
+        let hooks = self.create_vm_array(ctx, hooks);
+        let values = self.create_vm_array(ctx, values);
+        let out = self.vm_call_function_value(ctx, &factory, &Value::Undefined, &[construct, hooks, values]);
+        let class = match (out, self.pending_throw.take()) {
+            (_, Some(thrown)) => return Err(self.vm_error_to_js_error(ctx, &thrown)),
+            (Ok(class), None) => class,
+            (Err(err), None) => return Err(err),
+        };
+        self.globals.insert(name, class);
+        Ok(())
+    }
+
+    pub(super) fn host_class_handle_host_fn(&mut self, ctx: &GcContext<'gc>, name: &str, args: &[Value<'gc>]) -> Value<'gc> {
+        let (receiver, args) = args
+            .split_first()
+            .map_or((Value::Undefined, &[][..]), |(r, rest)| (r.clone(), rest));
+        let result = if let Some(id) = name.strip_prefix("hostclass.new.").and_then(|id| id.parse().ok()) {
+            self.construct_host_instance(ctx, id, &receiver, args)
+        } else if let Some(id) = name.strip_prefix("hostclass.call.").and_then(|id| id.parse().ok()) {
+            self.call_host_member(ctx, id, &receiver, args)
+        } else {
+            Err(crate::raise_type_error!(format!("{name} is not a registered host class member")))
+        };
+        match result {
+            Ok(value) => value,
+            Err(err) => {
+                let thrown = self.vm_value_from_error(ctx, &err);
+                self.pending_throw = Some(thrown);
+                Value::Undefined
+            }
+        }
+    }
+
+    fn construct_host_instance(
+        &mut self,
+        ctx: &GcContext<'gc>,
+        class_id: usize,
+        this: &Value<'gc>,
+        args: &[Value<'gc>],
+    ) -> Result<Value<'gc>, JSError> {
+        let (name, construct) = {
+            let classes = self.host_classes.classes.borrow();
+            let class = &classes[class_id];
+            (class.name.clone(), class.construct.clone())
+        };
+        let Some(construct) = construct else {
+            return Err(crate::raise_type_error!(format!(
+                "Illegal constructor: {name} cannot be constructed"
+            )));
+        };
+        let obj = match this {
+            Value::Object(obj) if !obj.borrow().contains_key(HOST_INSTANCE_KEY) => *obj,
+            _ => {
+                return Err(crate::raise_type_error!(format!(
+                    "{name} constructor called on an invalid receiver"
+                )));
+            }
+        };
+        let args = self.owned_host_args(ctx, args)?;
+        let payload = construct(&args)?;
+
+        let id = self.host_classes.next_instance.get();
+        self.host_classes.next_instance.set(id + 1);
+        obj.borrow_mut(ctx).insert(HOST_INSTANCE_KEY.to_string(), Value::Number(id as f64));
+        self.host_classes.instances.borrow_mut().insert(
+            id,
+            HostInstance {
+                object: Self::downgrade_handle(obj),
+                class: class_id,
+                payload: Rc::new(RefCell::new(payload)),
+            },
+        );
+        Ok(Value::Undefined)
+    }
+
+    fn call_host_member(
+        &mut self,
+        ctx: &GcContext<'gc>,
+        member_id: usize,
+        this: &Value<'gc>,
+        args: &[Value<'gc>],
+    ) -> Result<Value<'gc>, JSError> {
+        let (class_id, label, call) = {
+            let members = self.host_classes.members.borrow();
+            let member = &members[member_id];
+            (member.class, member.label.clone(), member.call.clone())
+        };
+        let Some(payload) = self.host_instance_payload(ctx, this, class_id) else {
+            return Err(crate::raise_type_error!(format!("{label} called on incompatible receiver")));
+        };
+        let args = self.owned_host_args(ctx, args)?;
+        let result = {
+            let mut payload = payload
+                .try_borrow_mut()
+                .map_err(|_| crate::raise_type_error!(format!("{label} re-entered while its instance is in use")))?;
+            call(&mut **payload, &args)?
+        };
+        self.owned_to_value(ctx, &result)
+    }
+
+    /// The payload of `value` when it is a live instance of host class `class_id`.
+    fn host_instance_payload(&self, ctx: &GcContext<'gc>, value: &Value<'gc>, class_id: usize) -> Option<Rc<RefCell<Box<dyn Any>>>> {
+        let Value::Object(obj) = value else {
+            return None;
+        };
+        let id = match obj.borrow().get(HOST_INSTANCE_KEY) {
+            Some(Value::Number(n)) => *n as u64,
+            _ => return None,
+        };
+        let instances = self.host_classes.instances.borrow();
+        let instance = instances.get(&id)?;
+        // The tag is an ordinary property, so check it was put there by the constructor.
+        let owner = Self::upgrade_handle(ctx, instance.object)?;
+        (instance.class == class_id && Gc::ptr_eq(owner, *obj)).then(|| instance.payload.clone())
+    }
+
+    fn owned_host_args(&mut self, ctx: &GcContext<'gc>, args: &[Value<'gc>]) -> Result<Vec<JsOwnedValue>, JSError> {
+        args.iter().map(|arg| self.value_to_owned(ctx, arg)).collect()
+    }
+
+    /// Drop the payloads of host instances whose objects have been collected.
+    pub(super) fn sweep_host_instances(&mut self, ctx: &GcContext<'gc>) -> usize {
+        let dead: Vec<HostInstance<'gc>> = {
+            let mut instances = self.host_classes.instances.borrow_mut();
+            let ids: Vec<u64> = instances
+                .iter()
+                .filter(|(_, instance)| Self::upgrade_handle(ctx, instance.object).is_none())
+                .map(|(id, _)| *id)
+                .collect();
+            ids.iter().filter_map(|id| instances.remove(id)).collect()
+        };
+        dead.len()
+    }
+}
@@ -0,0 +1,174 @@
+use super::*;
+use crate::core::DeterministicOptions;
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone};
+use std::cell::Cell;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Where scripts get the time and `Math.random` its numbers, shared with
+/// helper VMs. A deterministic run swaps the host clock for a virtual one
+/// that only moves when the event loop jumps to the next timer deadline or
+/// the host advances it, and seeds the generator.
+pub(crate) struct ClockState {
+    /// Origin of `performance.now()`. In a deterministic run it also stands
+    /// for the virtual start time in timer deadlines.
+    origin: Cell<Instant>,
+    virtual_clock: Cell<Option<VirtualClock>>,
+    rng: Cell<u64>,
+}
+
+#[derive(Clone, Copy)]
+struct VirtualClock {
+    start_ms: f64,
+    elapsed: Duration,
+}
+
+impl Default for ClockState {
+    fn default() -> Self {
+        let entropy = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        ClockState {
+            origin: Cell::new(Instant::now()),
+            virtual_clock: Cell::new(None),
+            rng: Cell::new(entropy),
+        }
+    }
+}
+
+impl ClockState {
+    fn set_deterministic(&self, options: DeterministicOptions) {
+        self.origin.set(Instant::now());
+        self.virtual_clock.set(Some(VirtualClock {
+            start_ms: options.start_ms,
+            elapsed: Duration::ZERO,
+        }));
+        self.rng.set(options.seed);
+    }
+
+    pub(super) fn is_deterministic(&self) -> bool {
+        self.virtual_clock.get().is_some()
+    }
+
+    /// The current time on the clock timer deadlines are measured against.
+    pub(super) fn now_instant(&self) -> Instant {
+        match self.virtual_clock.get() {
+            Some(clock) => self.origin.get() + clock.elapsed,
+            None => Instant::now(),
+        }
+    }
+
+    /// Move the virtual clock forward to `instant`. It never goes back, and
+    /// the host clock is left alone.
+    pub(super) fn advance_to(&self, instant: Instant) {
+        if let Some(mut clock) = self.virtual_clock.get() {
+            let elapsed = instant.saturating_duration_since(self.origin.get());
+            if elapsed > clock.elapsed {
+                clock.elapsed = elapsed;
+                self.virtual_clock.set(Some(clock));
+            }
+        }
+    }
+
+    /// Milliseconds since the Unix epoch, as `Date.now()` reports them.
+    pub(super) fn epoch_ms(&self) -> f64 {
+        match self.virtual_clock.get() {
+            Some(clock) => clock.start_ms + clock.elapsed.as_millis() as f64,
+            None => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as f64)
+                .unwrap_or(0.0),
+        }
+    }
+
+    /// `performance.now()`: fractional milliseconds since the origin.
+    fn monotonic_ms(&self) -> f64 {
+        self.now_instant().saturating_duration_since(self.origin.get()).as_secs_f64() * 1000.0
+    }
+
+    /// The next `Math.random()` value in [0, 1), from a SplitMix64 sequence.
+    pub(super) fn next_random(&self) -> f64 {
+        let state = self.rng.get().wrapping_add(0x9E37_79B9_7F4A_7C15);
+        self.rng.set(state);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// The zone Date components are read and written in.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(super) enum LocalZone {
+    /// The host's local time zone.
+    Host,
+    /// UTC, for the `UTC` methods and as the local zone of a deterministic run.
+    Utc,
+}
+
+impl LocalZone {
+    pub(super) fn from_millis(self, ms: i64) -> Option<DateTime<FixedOffset>> {
+        match self {
+            LocalZone::Host => chrono::Local.timestamp_millis_opt(ms).single().map(|dt| dt.fixed_offset()),
+            LocalZone::Utc => chrono::Utc.timestamp_millis_opt(ms).single().map(|dt| dt.fixed_offset()),
+        }
+    }
+
+    /// The instant a wall-clock time in this zone denotes; `None` when the
+    /// time is skipped or repeated by a transition.
+    pub(super) fn from_local(self, naive: &NaiveDateTime) -> Option<DateTime<FixedOffset>> {
+        match self {
+            LocalZone::Host => chrono::Local.from_local_datetime(naive).single().map(|dt| dt.fixed_offset()),
+            LocalZone::Utc => Some(naive.and_utc().fixed_offset()),
+        }
+    }
+
+    /// The zone's current offset from UTC, in milliseconds.
+    pub(super) fn current_offset_ms(self) -> f64 {
+        match self {
+            LocalZone::Host => chrono::Local::now().offset().local_minus_utc() as f64 * 1000.0,
+            LocalZone::Utc => 0.0,
+        }
+    }
+}
+
+impl<'gc> VM<'gc> {
+    pub(crate) fn set_deterministic(&mut self, options: DeterministicOptions) {
+        self.clock.set_deterministic(options);
+    }
+
+    pub(super) fn local_zone(&self) -> LocalZone {
+        if self.clock.is_deterministic() {
+            LocalZone::Utc
+        } else {
+            LocalZone::Host
+        }
+    }
+
+    /// Move the virtual clock forward by `by`, stopping at each timer
+    /// deadline on the way to fire the timers due then, so an interval ticks
+    /// once per period. Returns how many fired. Without a virtual clock
+    /// nothing happens.
+    pub(crate) fn advance_clock(&mut self, ctx: &GcContext<'gc>, by: Duration) -> Result<usize, JSError> {
+        if !self.clock.is_deterministic() {
+            return Ok(0);
+        }
+        let target = self.clock.now_instant() + by;
+        let mut fired = 0;
+        while let Some(deadline) = self.next_timer_deadline()
+            && deadline <= target
+        {
+            fired += self.run_expired_timers(ctx, deadline)?;
+        }
+        self.clock.advance_to(target);
+        Ok(fired)
+    }
+
+    pub(super) fn clock_handle_host_fn(&mut self, name: &str) -> Value<'gc> {
+        match name {
+            "clock.performance.now" => Value::Number(self.clock.monotonic_ms()),
+            _ => Value::Undefined,
+        }
+    }
+}
@@ -0,0 +1,413 @@
+use super::*;
+use crate::core::{ConsoleLevel, ConsoleSink, InspectOptions, StdioConsoleSink};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::Instant;
+
+/// Console methods implemented as `console.<name>` host functions, with their `length`.
+const CONSOLE_HOST_METHODS: &[(&str, f64)] = &[
+    ("info", 0.0),
+    ("debug", 0.0),
+    ("trace", 0.0),
+    ("dir", 0.0),
+    ("dirxml", 0.0),
+    ("table", 0.0),
+    ("assert", 0.0),
+    ("count", 0.0),
+    ("countReset", 0.0),
+    ("group", 0.0),
+    ("groupCollapsed", 0.0),
+    ("groupEnd", 0.0),
+    ("time", 0.0),
+    ("timeEnd", 0.0),
+    ("timeLog", 0.0),
+    ("clear", 0.0),
+];
+
+/// Console sink plus the WHATWG console namespace state (group depth,
+/// counters, timers) and inspect settings. Shared with helper VMs so REPL
+/// snippets see one console.
+pub(crate) struct ConsoleState {
+    sink: RefCell<Rc<dyn ConsoleSink>>,
+    inspect: RefCell<InspectOptions>,
+    group_depth: Cell<usize>,
+    counts: RefCell<HashMap<String, u64>>,
+    timers: RefCell<HashMap<String, Instant>>,
+}
+
+impl Default for ConsoleState {
+    fn default() -> Self {
+        ConsoleState {
+            sink: RefCell::new(Rc::new(StdioConsoleSink)),
+            inspect: RefCell::new(InspectOptions::default()),
+            group_depth: Cell::new(0),
+            counts: RefCell::new(HashMap::new()),
+            timers: RefCell::new(HashMap::new()),
+        }
+    }
+}
+
+impl<'gc> VM<'gc> {
+    pub(crate) fn set_console_sink(&mut self, sink: Rc<dyn ConsoleSink>) {
+        self.console.sink.replace(sink);
+    }
+
+    pub(crate) fn set_inspect_options(&mut self, options: InspectOptions) {
+        self.console.inspect.replace(options);
+    }
+
+    /// Inspect `val` with the options used for console output.
+    pub(crate) fn console_inspect(&mut self, ctx: &GcContext<'gc>, val: &Value<'gc>) -> String {
+        let options = self.console.inspect.borrow().clone();
+        self.inspect_value(ctx, val, &options)
+    }
+
+    /// How `console.log` prints one argument: strings as-is, everything else inspected.
+    fn console_render(&mut self, ctx: &GcContext<'gc>, val: &Value<'gc>) -> String {
+        match val {
+            Value::String(s) => crate::unicode::utf16_to_utf8(s),
+            _ => self.console_inspect(ctx, val),
+        }
+    }
+
+    /// Add the console methods beyond `log`/`warn`/`error` to the console object.
+    pub(super) fn register_console_methods(ctx: &GcContext<'gc>, console_map: &mut IndexMap<String, Value<'gc>>) {
+        for (method, length) in CONSOLE_HOST_METHODS {
+            let func = Self::make_host_fn_with_name_len(ctx, &format!("console.{method}"), method, *length, false);
+            console_map.insert(method.to_string(), func);
+        }
+        console_map.insert("@@sym:4".to_string(), Value::from("console"));
+        write_attrs_to_legacy_map(console_map, "@@sym:4", PropAttrs::CONFIGURABLE);
+    }
+
+    /// Write one message, indenting every line by the current group depth.
+    pub(super) fn console_write(&self, level: ConsoleLevel, message: &str) {
+        let depth = self.console.group_depth.get();
+        let sink = self.console.sink.borrow().clone();
+        if depth == 0 {
+            sink.write(level, message);
+            return;
+        }
+        let indent = "  ".repeat(depth);
+        let indented = message.lines().map(|line| format!("{indent}{line}")).collect::<Vec<_>>().join("\n");
+        sink.write(level, &indented);
+    }
+
+    pub(super) fn console_call_builtin(&mut self, ctx: &GcContext<'gc>, id: FunctionID, args: &[Value<'gc>]) -> Value<'gc> {
+        let level = match id {
+            BUILTIN_CONSOLE_WARN => ConsoleLevel::Warn,
+            BUILTIN_CONSOLE_ERROR => ConsoleLevel::Error,
+            _ => ConsoleLevel::Log,
+        };
+        let message = self.console_format(ctx, args);
+        self.console_write(level, &message);
+        Value::Undefined
+    }
+
+    pub(super) fn console_handle_host_fn(&mut self, ctx: &GcContext<'gc>, name: &str, args: &[Value<'gc>]) -> Value<'gc> {
+        let method = name.strip_prefix("console.").unwrap_or(name);
+        match method {
+            "log" => return self.console_call_builtin(ctx, BUILTIN_CONSOLE_LOG, args),
+            "warn" => return self.console_call_builtin(ctx, BUILTIN_CONSOLE_WARN, args),
+            "error" => return self.console_call_builtin(ctx, BUILTIN_CONSOLE_ERROR, args),
+            "info" => {
+                let message = self.console_format(ctx, args);
+                self.console_write(ConsoleLevel::Info, &message);
+            }
+            "debug" => {
+                let message = self.console_format(ctx, args);
+                self.console_write(ConsoleLevel::Debug, &message);
+            }
+            "dir" | "dirxml" => {
+                let message = self.console_inspect(ctx, args.first().unwrap_or(&Value::Undefined));
+                self.console_write(ConsoleLevel::Log, &message);
+            }
+            "trace" => {
+                let mut message = if args.is_empty() {
+                    "Trace".to_string()
+                } else {
+                    format!("Trace: {}", self.console_format(ctx, args))
+                };
+                for frame in self.console_stack_frames() {
+                    message.push('\n');
+                    message.push_str(&frame);
+                }
+                self.console_write(ConsoleLevel::Trace, &message);
+            }
+            "assert" => {
+                if !args.first().is_some_and(Value::to_truthy) {
+                    let rest = args.get(1..).unwrap_or(&[]);
+                    let message = if rest.is_empty() {
+                        "Assertion failed".to_string()
+                    } else {
+                        format!("Assertion failed: {}", self.console_format(ctx, rest))
+                    };
+                    self.console_write(ConsoleLevel::Error, &message);
+                }
+            }
+            "count" => {
+                let label = self.console_label(ctx, args);
+                let count = {
+                    let mut counts = self.console.counts.borrow_mut();
+                    let entry = counts.entry(label.clone()).or_insert(0);
+                    *entry += 1;
+                    *entry
+                };
+                self.console_write(ConsoleLevel::Info, &format!("{label}: {count}"));
+            }
+            "countReset" => {
+                let label = self.console_label(ctx, args);
+                let existed = self.console.counts.borrow_mut().get_mut(&label).map(|c| *c = 0).is_some();
+                if !existed {
+                    self.console_write(ConsoleLevel::Warn, &format!("Count for '{label}' does not exist"));
+                }
+            }
+            "group" | "groupCollapsed" => {
+                if !args.is_empty() {
+                    let message = self.console_format(ctx, args);
+                    self.console_write(ConsoleLevel::Log, &message);
+                }
+                self.console.group_depth.set(self.console.group_depth.get() + 1);
+            }
+            "groupEnd" => {
+                self.console.group_depth.set(self.console.group_depth.get().saturating_sub(1));
+            }
+            "time" => {
+                let label = self.console_label(ctx, args);
+                let exists = self.console.timers.borrow().contains_key(&label);
+                if exists {
+                    self.console_write(ConsoleLevel::Warn, &format!("Timer '{label}' already exists"));
+                } else {
+                    self.console.timers.borrow_mut().insert(label, self.clock.now_instant());
+                }
+            }
+            "timeEnd" | "timeLog" => {
+                let label = self.console_label(ctx, args);
+                let started = if method == "timeEnd" {
+                    self.console.timers.borrow_mut().remove(&label)
+                } else {
+                    self.console.timers.borrow().get(&label).copied()
+                };
+                let Some(started) = started else {
+                    self.console_write(ConsoleLevel::Warn, &format!("Timer '{label}' does not exist"));
+                    return Value::Undefined;
+                };
+                let elapsed_ms = self.clock.now_instant().saturating_duration_since(started).as_secs_f64() * 1000.0;
+                let mut message = format!("{label}: {elapsed_ms:.3}ms");
+                if method == "timeLog" && args.len() > 1 {
+                    message.push(' ');
+                    message.push_str(&self.console_format(ctx, &args[1..]));
+                }
+                self.console_write(ConsoleLevel::Info, &message);
+            }
+            "table" => {
+                let message = self.console_table(ctx, args);
+                self.console_write(ConsoleLevel::Log, &message);
+            }
+            "clear" => {
+                self.console.group_depth.set(0);
+            }
+            _ => {
+                self.throw_type_error(ctx, &format!("console.{method} is not a function"));
+            }
+        }
+        Value::Undefined
+    }
+
+    /// The label argument of count/time style methods (`"default"` when absent).
+    fn console_label(&mut self, ctx: &GcContext<'gc>, args: &[Value<'gc>]) -> String {
+        match args.first() {
+            None | Some(Value::Undefined) => "default".to_string(),
+            Some(v) => self.vm_display_string(ctx, v),
+        }
+    }
+
+    /// WHATWG Formatter: apply `%s %d %i %f %o %O %c` substitutions from a
+    /// leading format string, then append the remaining arguments.
+    pub(super) fn console_format(&mut self, ctx: &GcContext<'gc>, args: &[Value<'gc>]) -> String {
+        let Some(Value::String(format)) = args.first() else {
+            let parts: Vec<String> = args.iter().map(|v| self.console_render(ctx, v)).collect();
+            return parts.join(" ");
+        };
+        let format = crate::unicode::utf16_to_utf8(format);
+        let mut rest = args[1..].iter();
+        let mut out = String::with_capacity(format.len());
+        let mut chars = format.chars().peekable();
+        while let Some(ch) = chars.next() {
+            if ch != '%' {
+                out.push(ch);
+                continue;
+            }
+            let Some(&spec) = chars.peek() else {
+                out.push('%');
+                break;
+            };
+            if spec == '%' {
+                chars.next();
+                out.push('%');
+                continue;
+            }
+            if !matches!(spec, 's' | 'd' | 'i' | 'f' | 'o' | 'O' | 'c') {
+                out.push('%');
+                continue;
+            }
+            let Some(arg) = rest.next() else {
+                out.push('%');
+                continue;
+            };
+            chars.next();
+            match spec {
+                's' => match arg {
+                    Value::BigInt(b) => out.push_str(&format!("{b}n")),
+                    Value::Symbol(_) | Value::Object(_) | Value::Array(_) => out.push_str(&self.vm_display_string(ctx, arg)),
+                    _ => out.push_str(&value_to_string(arg)),
+                },
+                'd' | 'i' | 'f' => out.push_str(&console_format_number(arg, spec)),
+                'o' | 'O' => out.push_str(&self.console_inspect(ctx, arg)),
+                // %c applies CSS styling, which has no meaning on a terminal.
+                _ => {}
+            }
+        }
+        for arg in rest {
+            out.push(' ');
+            out.push_str(&self.console_render(ctx, arg));
+        }
+        out
+    }
+
+    /// `    at fn (file:line:col)` lines for the active call stack, innermost first.
+    fn console_stack_frames(&self) -> Vec<String> {
+        let file = self.current_script_file().to_string();
+        let mut lines = Vec::new();
+        let mut site_ip = self.current_opcode_ip;
+        for frame in self.frames.iter().rev() {
+            let name = self
+                .chunk
+                .fn_names
+                .get(&frame.func_ip)
+                .filter(|name| !name.is_empty())
+                .map(String::as_str)
+                .unwrap_or("<anonymous>");
+            if let Some((line, column)) = self.chunk.get_line_col_for_ip(site_ip) {
+                lines.push(format!("    at {name} ({file}:{line}:{column})"));
+            }
+            site_ip = frame.call_ip;
+        }
+        if let Some((line, column)) = self.chunk.get_line_col_for_ip(site_ip) {
+            lines.push(format!("    at <anonymous> ({file}:{line}:{column})"));
+        }
+        lines
+    }
+
+    /// Render `console.table(data, columns?)` as a box-drawn table.
+    fn console_table(&mut self, ctx: &GcContext<'gc>, args: &[Value<'gc>]) -> String {
+        let Some(data) = args.first() else {
+            return String::new();
+        };
+        if !matches!(data, Value::Object(_) | Value::Array(_)) {
+            return self.console_format(ctx, args);
+        }
+
+        let mut columns: Vec<String> = Vec::new();
+        let mut has_values_column = false;
+        let mut rows: Vec<(String, IndexMap<String, String>, Option<String>)> = Vec::new();
+        for row_key in self.collect_enumerable_own_keys(ctx, data) {
+            let row = self.read_named_property(ctx, data, &row_key);
+            let mut cells = IndexMap::new();
+            let mut value_cell = None;
+            if matches!(row, Value::Object(_) | Value::Array(_)) && !self.is_callable_value(&row) {
+                for key in self.collect_enumerable_own_keys(ctx, &row) {
+                    let cell = self.read_named_property(ctx, &row, &key);
+                    if !columns.contains(&key) {
+                        columns.push(key.clone());
+                    }
+                    cells.insert(key, self.console_table_cell(ctx, &cell));
+                }
+            } else {
+                has_values_column = true;
+                value_cell = Some(self.console_table_cell(ctx, &row));
+            }
+            rows.push((row_key, cells, value_cell));
+        }
+
+        if let Some(Value::Array(filter)) = args.get(1) {
+            let wanted: Vec<String> = filter.borrow().elements.iter().map(value_to_string).collect();
+            columns = wanted;
+        }
+
+        let mut header = vec!["(index)".to_string()];
+        header.extend(columns.iter().cloned());
+        if has_values_column {
+            header.push("Values".to_string());
+        }
+        let body: Vec<Vec<String>> = rows
+            .into_iter()
+            .map(|(index, cells, value)| {
+                let mut line = vec![index];
+                line.extend(columns.iter().map(|c| cells.get(c).cloned().unwrap_or_default()));
+                if has_values_column {
+                    line.push(value.unwrap_or_default());
+                }
+                line
+            })
+            .collect();
+
+        let widths: Vec<usize> = (0..header.len())
+            .map(|i| {
+                body.iter()
+                    .map(|row| row[i].chars().count())
+                    .chain(std::iter::once(header[i].chars().count()))
+                    .max()
+                    .unwrap_or(0)
+                    + 2
+            })
+            .collect();
+        let rule = |left: &str, mid: &str, right: &str| {
+            let segments: Vec<String> = widths.iter().map(|w| "─".repeat(*w)).collect();
+            format!("{left}{}{right}", segments.join(mid))
+        };
+        let render = |cells: &[String]| {
+            let padded: Vec<String> = cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, w)| format!(" {cell}{}", " ".repeat(w - 1 - cell.chars().count())))
+                .collect();
+            format!("│{}│", padded.join("│"))
+        };
+
+        let mut lines = vec![rule("┌", "┬", "┐"), render(&header[..]), rule("├", "┼", "┤")];
+        lines.extend(body.iter().map(|row| render(&row[..])));
+        lines.push(rule("└", "┴", "┘"));
+        lines.join("\n")
+    }
+
+    fn console_table_cell(&mut self, ctx: &GcContext<'gc>, value: &Value<'gc>) -> String {
+        let options = InspectOptions {
+            depth: Some(0),
+            colors: false,
+            ..self.console.inspect.borrow().clone()
+        };
+        self.inspect_value(ctx, value, &options)
+    }
+}
+
+/// Render `arg` for a `%d`, `%i` or `%f` substitution. The argument is
+/// converted like `Number(arg)`; finite values are formatted by `std.sprintf`,
+/// while BigInts, `NaN`, infinities and integers too large for `%d` keep
+/// their JS spelling.
+fn console_format_number<'gc>(arg: &Value<'gc>, spec: char) -> String {
+    let n = match arg {
+        Value::BigInt(b) if spec != 'f' => return format!("{b}n"),
+        Value::Symbol(_) => f64::NAN,
+        _ => to_number(arg),
+    };
+    let n = if spec == 'f' { n } else { n.trunc() };
+    #[cfg(feature = "std")]
+    if n.is_finite()
+        && (spec == 'f' || n.abs() < i64::MAX as f64)
+        && let Ok(formatted) = crate::js_std::sprintf::sprintf_impl(&format!("%{spec}"), &[Value::Number(n)])
+    {
+        return formatted;
+    }
+    crate::core::format_js_number(n)
+}
@@ -45,9 +45,19 @@ impl<'gc> VM<'gc> {
                 {
                     Value::Number(f64::NAN)
                 } else {
-                    Value::Number(Self::make_date_from_components(yr, month, day, hour, minute, second, millis, false))
+                    Value::Number(Self::make_date_from_components(
+                        yr,
+                        month,
+                        day,
+                        hour,
+                        minute,
+                        second,
+                        millis,
+                        LocalZone::Utc,
+                    ))
                 }
             }
+            #[cfg(feature = "temporal")]
             "date.toTemporalInstant" => {
                 let this = receiver.cloned().unwrap_or(Value::Undefined);
                 let Value::Object(obj) = &this else {
@@ -185,8 +195,7 @@ impl<'gc> VM<'gc> {
                     if ms.is_nan() || ms.is_infinite() {
                         return Value::from("Invalid Date");
                     }
-                    use chrono::{Local, TimeZone};
-                    if let Some(dt) = Local.timestamp_millis_opt(ms as i64).single() {
+                    if let Some(dt) = self.local_zone().from_millis(ms as i64) {
                         let s = dt.format("%H:%M:%S GMT%z").to_string();
                         return Value::from(&s);
                     }
@@ -205,6 +214,7 @@ impl<'gc> VM<'gc> {
         }
     }
 
+    #[cfg(feature = "intl")]
     fn date_to_locale_string(
         &mut self,
         ctx: &GcContext<'gc>,
@@ -242,6 +252,39 @@ impl<'gc> VM<'gc> {
         self.intl_date_time_format_format(ctx, Some(&formatter), &[Value::Number(ms)])
     }
 
+    /// Without `Intl` the locales and options are ignored and the date is
+    /// written the way en-US does by default, in the local time zone.
+    #[cfg(not(feature = "intl"))]
+    fn date_to_locale_string(
+        &mut self,
+        ctx: &GcContext<'gc>,
+        receiver: Option<&Value<'gc>>,
+        _args: &[Value<'gc>],
+        mode: &str,
+    ) -> Value<'gc> {
+        let Some(Value::Object(obj)) = receiver else {
+            self.throw_type_error(ctx, "this is not a Date object");
+            return Value::Undefined;
+        };
+        let Some(Value::Number(ms)) = obj.borrow().get("__date_ms__").cloned() else {
+            self.throw_type_error(ctx, "this is not a Date object");
+            return Value::Undefined;
+        };
+        if ms.is_nan() || ms.is_infinite() {
+            return Value::from("Invalid Date");
+        }
+        let format = match mode {
+            "date" => "%-m/%-d/%Y",
+            "time" => "%-I:%M:%S %p",
+            _ => "%-m/%-d/%Y, %-I:%M:%S %p",
+        };
+        match self.local_zone().from_millis(ms as i64) {
+            Some(dt) => Value::from(&dt.format(format).to_string()),
+            None => Value::from("Invalid Date"),
+        }
+    }
+
+    #[cfg(feature = "intl")]
     fn date_time_format_args_for_locale_string(
         &mut self,
         ctx: &GcContext<'gc>,
@@ -316,6 +359,7 @@ impl<'gc> VM<'gc> {
         Ok(vec![locales, Value::Object(new_gc_cell_ptr(ctx, merged))])
     }
 
+    #[cfg(feature = "intl")]
     fn date_locale_default_options(&self, ctx: &GcContext<'gc>, mode: &str) -> ObjectHandle<'gc> {
         let mut defaults = IndexMap::new();
         for (key, value) in Self::date_locale_default_entries(mode) {
@@ -324,6 +368,7 @@ impl<'gc> VM<'gc> {
         new_gc_cell_ptr(ctx, defaults)
     }
 
+    #[cfg(feature = "intl")]
     fn date_locale_default_entries(mode: &str) -> Vec<(&'static str, Value<'gc>)> {
         match mode {
             "date" => vec![
@@ -371,10 +416,6 @@ impl<'gc> VM<'gc> {
                 "toTimeString",
                 Self::make_host_fn_with_name_len(ctx, "date.toTimeString", "toTimeString", 0.0, false),
             ),
-            (
-                "toTemporalInstant",
-                Self::make_host_fn_with_name_len(ctx, "date.toTemporalInstant", "toTemporalInstant", 0.0, false),
-            ),
             ("toUTCString", Value::NativeFunction(BUILTIN_DATE_TOUTCSTRING)),
             ("toDateString", Value::NativeFunction(BUILTIN_DATE_TODATESTRING)),
             ("setTime", Value::NativeFunction(BUILTIN_DATE_SETTIME)),
@@ -457,6 +498,14 @@ impl<'gc> VM<'gc> {
             date_proto.insert(key.to_string(), value);
             mark_nonenumerable(&mut date_proto, key);
         }
+        #[cfg(feature = "temporal")]
+        {
+            date_proto.insert(
+                "toTemporalInstant".to_string(),
+                Self::make_host_fn_with_name_len(ctx, "date.toTemporalInstant", "toTemporalInstant", 0.0, false),
+            );
+            mark_nonenumerable(&mut date_proto, "toTemporalInstant");
+        }
         // B.2.4.3: toGMTString is the same function object as toUTCString
         if let Some(utc_fn) = date_proto.get("toUTCString").cloned() {
             date_proto.insert("toGMTString".to_string(), utc_fn);
@@ -479,14 +528,7 @@ impl<'gc> VM<'gc> {
     /// Handle Date-related IDs in `call_builtin`.
     pub(super) fn date_call_builtin(&mut self, _ctx: &GcContext<'gc>, id: FunctionID, args: &[Value<'gc>]) -> Value<'gc> {
         match id {
-            BUILTIN_DATE_NOW => {
-                use std::time::{SystemTime, UNIX_EPOCH};
-                let ms = SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .map(|d| d.as_millis() as f64)
-                    .unwrap_or(0.0);
-                Value::Number(ms)
-            }
+            BUILTIN_DATE_NOW => Value::Number(self.clock.epoch_ms()),
             BUILTIN_DATE_PARSE => {
                 let s_str = args.first().map(|v| value_to_string(v)).unwrap_or_default();
                 let ms = self.date_parse_string(&s_str);
@@ -494,9 +536,8 @@ impl<'gc> VM<'gc> {
             }
             BUILTIN_CTOR_DATE => {
                 // Date() called as a function returns a date-time string.
-                use chrono::{Local, TimeZone, Utc};
-                let now = Utc::now().timestamp_millis();
-                if let Some(dt) = Local.timestamp_millis_opt(now).single() {
+                let now = self.clock.epoch_ms() as i64;
+                if let Some(dt) = self.local_zone().from_millis(now) {
                     let s = dt.format("%a %b %d %Y %H:%M:%S GMT%z").to_string();
                     Value::from(&s)
                 } else {
@@ -524,12 +565,8 @@ impl<'gc> VM<'gc> {
             }
             BUILTIN_CTOR_DATE => {
                 if let Value::Object(obj) = receiver {
-                    use std::time::{SystemTime, UNIX_EPOCH};
                     let ms = if args.is_empty() {
-                        SystemTime::now()
-                            .duration_since(UNIX_EPOCH)
-                            .map(|d| d.as_millis() as f64)
-                            .unwrap_or(0.0)
+                        self.clock.epoch_ms()
                     } else if args.len() == 1 {
                         let prim = match &args[0] {
                             Value::Object(_) | Value::Array(_) => self.try_to_primitive(ctx, &args[0], "default"),
@@ -576,7 +613,7 @@ impl<'gc> VM<'gc> {
                         {
                             f64::NAN
                         } else {
-                            Self::make_date_from_components(yr, month, day, hour, min_val, sec, ms_part, true)
+                            Self::make_date_from_components(yr, month, day, hour, min_val, sec, ms_part, self.local_zone())
                         }
                     };
 
@@ -600,12 +637,13 @@ impl<'gc> VM<'gc> {
                 }
             };
             if let Some(ms) = date_ms {
-                use chrono::{Datelike, Local, TimeZone, Timelike, Utc};
+                use chrono::{Datelike, TimeZone, Timelike, Utc};
+                let zone = self.local_zone();
                 let to_local = || {
                     if ms.is_nan() || ms.is_infinite() {
                         return None;
                     }
-                    Local.timestamp_millis_opt(ms as i64).single()
+                    zone.from_millis(ms as i64)
                 };
                 let to_utc = || {
                     if ms.is_nan() || ms.is_infinite() {
@@ -819,7 +857,7 @@ impl<'gc> VM<'gc> {
                         } else if t_local.is_infinite() {
                             (0.0, 1.0, 0.0, 0.0, 0.0, 0.0)
                         } else {
-                            if let Some(dt) = Local.timestamp_millis_opt(t_local as i64).single() {
+                            if let Some(dt) = zone.from_millis(t_local as i64) {
                                 (
                                     dt.month0() as f64,
                                     dt.day() as f64,
@@ -832,14 +870,14 @@ impl<'gc> VM<'gc> {
                                 (0.0, 1.0, 0.0, 0.0, 0.0, 0.0)
                             }
                         };
-                        let mut new_ms = Self::make_date_from_components_no_year_adjust(yyyy, month, day, hour, min, sec, ms_comp, true);
+                        let mut new_ms = Self::make_date_from_components_no_year_adjust(yyyy, month, day, hour, min, sec, ms_comp, zone);
                         if new_ms.is_nan() {
-                            let fallback_offset_ms = Local
-                                .timestamp_millis_opt(t_local as i64)
-                                .single()
+                            let fallback_offset_ms = zone
+                                .from_millis(t_local as i64)
                                 .map(|dt| dt.offset().local_minus_utc() as f64 * 1000.0)
                                 .unwrap_or(0.0);
-                            let utc_ms = Self::make_date_from_components_no_year_adjust(yyyy, month, day, hour, min, sec, ms_comp, false);
+                            let utc_ms =
+                                Self::make_date_from_components_no_year_adjust(yyyy, month, day, hour, min, sec, ms_comp, LocalZone::Utc);
                             if !utc_ms.is_nan() {
                                 new_ms = Self::time_clip(utc_ms - fallback_offset_ms);
                             }
@@ -936,12 +974,8 @@ impl<'gc> VM<'gc> {
     /// Construct a Date object for `new Date(...)` in opcode handler.
     /// Returns the ms value and whether it needs an abrupt-completion early return.
     pub(super) fn date_construct_ms(&mut self, ctx: &GcContext<'gc>, args: &[Value<'gc>]) -> f64 {
-        use std::time::{SystemTime, UNIX_EPOCH};
         if args.is_empty() {
-            return SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .map(|d| d.as_millis() as f64)
-                .unwrap_or(0.0);
+            return self.clock.epoch_ms();
         }
         if args.len() == 1 {
             if let Value::Object(obj) = &args[0] {
@@ -1018,21 +1052,15 @@ impl<'gc> VM<'gc> {
         {
             f64::NAN
         } else {
-            Self::make_date_from_components(yr, month, day, hour, min_val, sec, ms_part, true)
+            Self::make_date_from_components(yr, month, day, hour, min_val, sec, ms_part, self.local_zone())
         }
     }
 
     /// Construct a Date with ToNumber coercion per arg (used in the 4th constructor path).
     /// Returns `Some(ms)` on success, `None` if an abrupt completion happened.
     pub(super) fn date_construct_ms_with_coercion(&mut self, ctx: &GcContext<'gc>, args: &[Value<'gc>]) -> Option<f64> {
-        use std::time::{SystemTime, UNIX_EPOCH};
         if args.is_empty() {
-            return Some(
-                SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .map(|d| d.as_millis() as f64)
-                    .unwrap_or(0.0),
-            );
+            return Some(self.clock.epoch_ms());
         }
         if args.len() == 1 {
             return Some(self.date_construct_ms(ctx, args));
@@ -1070,7 +1098,16 @@ impl<'gc> VM<'gc> {
         {
             Some(f64::NAN)
         } else {
-            Some(Self::make_date_from_components(yr, month, day, hour, min_val, sec, ms_part, true))
+            Some(Self::make_date_from_components(
+                yr,
+                month,
+                day,
+                hour,
+                min_val,
+                sec,
+                ms_part,
+                self.local_zone(),
+            ))
         }
     }
 
@@ -1097,38 +1134,25 @@ impl<'gc> VM<'gc> {
         }
 
         let any_nan = nums.iter().any(|n| n.is_nan() || n.is_infinite());
-        let is_utc = name.contains("UTC");
+        // The UTC setters and, in a deterministic run, the local ones both work in UTC.
+        let zone = if name.contains("UTC") { LocalZone::Utc } else { self.local_zone() };
 
-        let get_components = |ms: f64, utc: bool| -> Option<(f64, f64, f64, f64, f64, f64, f64)> {
+        let get_components = |ms: f64| -> Option<(f64, f64, f64, f64, f64, f64, f64)> {
             if ms.is_nan() || ms.is_infinite() {
                 return None;
             }
-            use chrono::{Datelike, Local, TimeZone, Timelike, Utc};
-            if utc {
-                Utc.timestamp_millis_opt(ms as i64).single().map(|dt| {
-                    (
-                        dt.year() as f64,
-                        (dt.month0()) as f64,
-                        dt.day() as f64,
-                        dt.hour() as f64,
-                        dt.minute() as f64,
-                        dt.second() as f64,
-                        dt.timestamp_subsec_millis() as f64,
-                    )
-                })
-            } else {
-                Local.timestamp_millis_opt(ms as i64).single().map(|dt| {
-                    (
-                        dt.year() as f64,
-                        (dt.month0()) as f64,
-                        dt.day() as f64,
-                        dt.hour() as f64,
-                        dt.minute() as f64,
-                        dt.second() as f64,
-                        dt.timestamp_subsec_millis() as f64,
-                    )
-                })
-            }
+            use chrono::{Datelike, Timelike};
+            zone.from_millis(ms as i64).map(|dt| {
+                (
+                    dt.year() as f64,
+                    (dt.month0()) as f64,
+                    dt.day() as f64,
+                    dt.hour() as f64,
+                    dt.minute() as f64,
+                    dt.second() as f64,
+                    dt.timestamp_subsec_millis() as f64,
+                )
+            })
         };
 
         let new_ms = match name {
@@ -1136,11 +1160,11 @@ impl<'gc> VM<'gc> {
                 if args.is_empty() || any_nan {
                     f64::NAN
                 } else {
-                    let comps = get_components(ms, is_utc).unwrap_or((0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0));
+                    let comps = get_components(ms).unwrap_or((0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0));
                     let year = nums[0];
                     let month = nums.get(1).copied().unwrap_or(comps.1);
                     let day = nums.get(2).copied().unwrap_or(comps.2);
-                    Self::make_date_from_components_no_year_adjust(year, month, day, comps.3, comps.4, comps.5, comps.6, !is_utc)
+                    Self::make_date_from_components_no_year_adjust(year, month, day, comps.3, comps.4, comps.5, comps.6, zone)
                 }
             }
             _ if ms.is_nan() => {
@@ -1150,73 +1174,73 @@ impl<'gc> VM<'gc> {
                 if args.is_empty() || any_nan {
                     f64::NAN
                 } else {
-                    let Some(comps) = get_components(ms, is_utc) else {
+                    let Some(comps) = get_components(ms) else {
                         return self.date_store_nan(ctx, obj);
                     };
                     let month = nums[0];
                     let day = nums.get(1).copied().unwrap_or(comps.2);
-                    Self::make_date_from_components_no_year_adjust(comps.0, month, day, comps.3, comps.4, comps.5, comps.6, !is_utc)
+                    Self::make_date_from_components_no_year_adjust(comps.0, month, day, comps.3, comps.4, comps.5, comps.6, zone)
                 }
             }
             "date.setUTCDate" | "date.setDate" => {
                 if args.is_empty() || any_nan {
                     f64::NAN
                 } else {
-                    let Some(comps) = get_components(ms, is_utc) else {
+                    let Some(comps) = get_components(ms) else {
                         return self.date_store_nan(ctx, obj);
                     };
                     let day = nums[0];
-                    Self::make_date_from_components_no_year_adjust(comps.0, comps.1, day, comps.3, comps.4, comps.5, comps.6, !is_utc)
+                    Self::make_date_from_components_no_year_adjust(comps.0, comps.1, day, comps.3, comps.4, comps.5, comps.6, zone)
                 }
             }
             "date.setUTCHours" | "date.setHours" => {
                 if args.is_empty() || any_nan {
                     f64::NAN
                 } else {
-                    let Some(comps) = get_components(ms, is_utc) else {
+                    let Some(comps) = get_components(ms) else {
                         return self.date_store_nan(ctx, obj);
                     };
                     let hour = nums[0];
                     let min = nums.get(1).copied().unwrap_or(comps.4);
                     let sec = nums.get(2).copied().unwrap_or(comps.5);
                     let ms_val = nums.get(3).copied().unwrap_or(comps.6);
-                    Self::make_date_from_components_no_year_adjust(comps.0, comps.1, comps.2, hour, min, sec, ms_val, !is_utc)
+                    Self::make_date_from_components_no_year_adjust(comps.0, comps.1, comps.2, hour, min, sec, ms_val, zone)
                 }
             }
             "date.setUTCMinutes" | "date.setMinutes" => {
                 if args.is_empty() || any_nan {
                     f64::NAN
                 } else {
-                    let Some(comps) = get_components(ms, is_utc) else {
+                    let Some(comps) = get_components(ms) else {
                         return self.date_store_nan(ctx, obj);
                     };
                     let min = nums[0];
                     let sec = nums.get(1).copied().unwrap_or(comps.5);
                     let ms_val = nums.get(2).copied().unwrap_or(comps.6);
-                    Self::make_date_from_components_no_year_adjust(comps.0, comps.1, comps.2, comps.3, min, sec, ms_val, !is_utc)
+                    Self::make_date_from_components_no_year_adjust(comps.0, comps.1, comps.2, comps.3, min, sec, ms_val, zone)
                 }
             }
             "date.setUTCSeconds" | "date.setSeconds" => {
                 if args.is_empty() || any_nan {
                     f64::NAN
                 } else {
-                    let Some(comps) = get_components(ms, is_utc) else {
+                    let Some(comps) = get_components(ms) else {
                         return self.date_store_nan(ctx, obj);
                     };
                     let sec = nums[0];
                     let ms_val = nums.get(1).copied().unwrap_or(comps.6);
-                    Self::make_date_from_components_no_year_adjust(comps.0, comps.1, comps.2, comps.3, comps.4, sec, ms_val, !is_utc)
+                    Self::make_date_from_components_no_year_adjust(comps.0, comps.1, comps.2, comps.3, comps.4, sec, ms_val, zone)
                 }
             }
             "date.setUTCMilliseconds" | "date.setMilliseconds" => {
                 if args.is_empty() || any_nan {
                     f64::NAN
                 } else {
-                    let Some(comps) = get_components(ms, is_utc) else {
+                    let Some(comps) = get_components(ms) else {
                         return self.date_store_nan(ctx, obj);
                     };
                     let ms_val = nums[0];
-                    Self::make_date_from_components_no_year_adjust(comps.0, comps.1, comps.2, comps.3, comps.4, comps.5, ms_val, !is_utc)
+                    Self::make_date_from_components_no_year_adjust(comps.0, comps.1, comps.2, comps.3, comps.4, comps.5, ms_val, zone)
                 }
             }
             _ => {
@@ -1238,13 +1262,22 @@ impl<'gc> VM<'gc> {
 
     /// Compute a Date ms value from year/month/day/hour/min/sec/ms components.
     #[allow(clippy::too_many_arguments)]
-    pub(super) fn make_date_from_components(yr: f64, month: f64, day: f64, hour: f64, min: f64, sec: f64, ms: f64, local: bool) -> f64 {
-        Self::make_date_from_components_inner(yr, month, day, hour, min, sec, ms, local, true)
+    pub(super) fn make_date_from_components(yr: f64, month: f64, day: f64, hour: f64, min: f64, sec: f64, ms: f64, zone: LocalZone) -> f64 {
+        Self::make_date_from_components_inner(yr, month, day, hour, min, sec, ms, zone, true)
     }
 
     #[allow(clippy::too_many_arguments)]
-    fn make_date_from_components_no_year_adjust(yr: f64, month: f64, day: f64, hour: f64, min: f64, sec: f64, ms: f64, local: bool) -> f64 {
-        Self::make_date_from_components_inner(yr, month, day, hour, min, sec, ms, local, false)
+    fn make_date_from_components_no_year_adjust(
+        yr: f64,
+        month: f64,
+        day: f64,
+        hour: f64,
+        min: f64,
+        sec: f64,
+        ms: f64,
+        zone: LocalZone,
+    ) -> f64 {
+        Self::make_date_from_components_inner(yr, month, day, hour, min, sec, ms, zone, false)
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -1256,7 +1289,7 @@ impl<'gc> VM<'gc> {
         min: f64,
         sec: f64,
         ms: f64,
-        local: bool,
+        zone: LocalZone,
         ctor_year_adjust: bool,
     ) -> f64 {
         let yr = yr.trunc();
@@ -1278,21 +1311,17 @@ impl<'gc> VM<'gc> {
         let adj_month = mi.rem_euclid(12) as u32;
 
         let base_day = 1u32;
-        use chrono::{Local, NaiveDate, TimeZone};
+        use chrono::NaiveDate;
         let base_ms = if let Some(naive) = NaiveDate::from_ymd_opt(adj_year as i32, adj_month + 1, base_day) {
             let naive_dt = match naive.and_hms_opt(0, 0, 0) {
                 Some(d) => d,
                 None => return f64::NAN,
             };
-            if local {
-                match Local.from_local_datetime(&naive_dt).single() {
-                    Some(dt) => dt.timestamp_millis() as f64,
-                    None => return f64::NAN,
-                }
-            } else {
-                naive_dt.and_utc().timestamp_millis() as f64
+            match zone.from_local(&naive_dt) {
+                Some(dt) => dt.timestamp_millis() as f64,
+                None => return f64::NAN,
             }
-        } else if !local {
+        } else if zone == LocalZone::Utc {
             Self::make_day_arithmetic(adj_year, adj_month as i64) * 86_400_000.0
         } else {
             return f64::NAN;
@@ -1433,7 +1462,8 @@ impl<'gc> VM<'gc> {
         if s.starts_with("-000000") {
             return f64::NAN;
         }
-        if let Some(ms) = Self::parse_extended_year_iso(s) {
+        let zone = self.local_zone();
+        if let Some(ms) = Self::parse_extended_year_iso(s, zone) {
             return Self::time_clip(ms);
         }
         if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(s) {
@@ -1467,18 +1497,16 @@ impl<'gc> VM<'gc> {
             return Self::time_clip(d.and_utc().timestamp_millis() as f64);
         }
         if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S") {
-            use chrono::{Local, TimeZone};
-            if let Some(local_dt) = Local.from_local_datetime(&dt).single() {
+            if let Some(local_dt) = zone.from_local(&dt) {
                 return Self::time_clip(local_dt.timestamp_millis() as f64);
             }
         }
         if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f") {
-            use chrono::{Local, TimeZone};
-            if let Some(local_dt) = Local.from_local_datetime(&dt).single() {
+            if let Some(local_dt) = zone.from_local(&dt) {
                 return Self::time_clip(local_dt.timestamp_millis() as f64);
             }
         }
-        if let Some(ms) = Self::parse_tostring_format(s) {
+        if let Some(ms) = Self::parse_tostring_format(s, zone) {
             return Self::time_clip(ms);
         }
         if let Some(ms) = Self::parse_utcstring_format(s) {
@@ -1505,7 +1533,7 @@ impl<'gc> VM<'gc> {
         }
     }
 
-    fn parse_tostring_format(s: &str) -> Option<f64> {
+    fn parse_tostring_format(s: &str, zone: LocalZone) -> Option<f64> {
         let parts: Vec<&str> = s.split_whitespace().collect();
         if parts.len() < 5 {
             return None;
@@ -1537,8 +1565,7 @@ impl<'gc> VM<'gc> {
                 return Some(utc_ms);
             }
         }
-        use chrono::{Local, TimeZone};
-        let local_dt = Local.from_local_datetime(&naive_dt).single()?;
+        let local_dt = zone.from_local(&naive_dt)?;
         Some(local_dt.timestamp_millis() as f64)
     }
 
@@ -1570,7 +1597,7 @@ impl<'gc> VM<'gc> {
         Some(naive_dt.and_utc().timestamp_millis() as f64)
     }
 
-    fn parse_extended_year_iso(s: &str) -> Option<f64> {
+    fn parse_extended_year_iso(s: &str, zone: LocalZone) -> Option<f64> {
         if s.len() < 7 {
             return None;
         }
@@ -1655,8 +1682,7 @@ impl<'gc> VM<'gc> {
                 if let Some(tz) = tz_offset_ms {
                     base_ms + tz as f64
                 } else {
-                    use chrono::{Local, TimeZone};
-                    if let Some(local_dt) = Local.from_local_datetime(&naive_dt).single() {
+                    if let Some(local_dt) = zone.from_local(&naive_dt) {
                         local_dt.timestamp_millis() as f64 + ms_val
                     } else {
                         base_ms
@@ -1678,9 +1704,7 @@ impl<'gc> VM<'gc> {
         let adjusted = if let Some(tz) = tz_offset_ms {
             base_ms + tz as f64
         } else {
-            use chrono::Local;
-            let offset_ms = Local::now().offset().local_minus_utc() as f64 * 1000.0;
-            base_ms - offset_ms
+            base_ms - zone.current_offset_ms()
         };
         Some(adjusted)
     }
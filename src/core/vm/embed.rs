@@ -0,0 +1,802 @@
+use super::*;
+use crate::core::{CallTarget, JsException, JsFunctionInfo, JsHandle, JsOwnedValue, JsStackFrame, ModuleNamespaceHandle};
+use std::rc::Rc;
+
+/// Nesting limit when copying object graphs out to the embedder.
+const OWNED_VALUE_MAX_DEPTH: usize = 64;
+
+/// Nesting limit when following the `cause` and `errors` of a thrown value.
+const EXCEPTION_MAX_DEPTH: usize = 16;
+
+/// A Rust closure exposed to scripts through `Repl::register_function`.
+pub(crate) type HostCallback = Rc<dyn Fn(&[JsOwnedValue]) -> Result<JsOwnedValue, JSError>>;
+
+impl<'gc> VM<'gc> {
+    /// Snapshot a VM value into an owned, lifetime-free `JsOwnedValue`.
+    ///
+    /// Own enumerable properties are read through `[[Get]]`, so accessors run;
+    /// an exception thrown by a getter is reported as an error. Cycles,
+    /// graphs nested deeper than 64 levels and weak collections cannot be
+    /// copied and are a `TypeError`.
+    pub(crate) fn value_to_owned(&mut self, ctx: &GcContext<'gc>, value: &Value<'gc>) -> Result<JsOwnedValue, JSError> {
+        let mut seen = Vec::new();
+        let owned = self.value_to_owned_inner(ctx, value, &mut seen, true);
+        if let Some(thrown) = self.pending_throw.take() {
+            return Err(self.vm_error_to_js_error(ctx, &thrown));
+        }
+        Ok(owned)
+    }
+
+    /// With `run_getters` false (describing a thrown value) only data
+    /// properties are copied and nothing fails: accessors and cycles read as
+    /// `undefined`, proxies and weak collections as empty objects.
+    fn value_to_owned_inner(&mut self, ctx: &GcContext<'gc>, value: &Value<'gc>, seen: &mut Vec<usize>, run_getters: bool) -> JsOwnedValue {
+        if self.pending_throw.is_some() {
+            return JsOwnedValue::Undefined;
+        }
+        match value {
+            Value::Undefined | Value::Uninitialized => JsOwnedValue::Undefined,
+            Value::Null => JsOwnedValue::Null,
+            Value::Boolean(b) => JsOwnedValue::Boolean(*b),
+            Value::Number(n) => JsOwnedValue::Number(*n),
+            Value::BigInt(b) => JsOwnedValue::BigInt((**b).clone()),
+            Value::String(s) => JsOwnedValue::String(crate::unicode::utf16_to_utf8(s)),
+            Value::Symbol(sym) => JsOwnedValue::Symbol(sym.borrow().description.as_ref().map(|d| crate::unicode::utf16_to_utf8(d))),
+            Value::Property { value: Some(inner), .. } => self.value_to_owned_inner(ctx, inner, seen, run_getters),
+            Value::Property { .. } => JsOwnedValue::Undefined,
+            Value::Function(..) | Value::Closure(..) | Value::NativeFunction(_) => self.owned_function_info(ctx, value),
+            Value::Object(obj) => {
+                if self.is_callable_value(value) {
+                    return self.owned_function_info(ctx, value);
+                }
+                if let Some(Value::Number(ms)) = obj.borrow().get("__date_ms__") {
+                    return JsOwnedValue::Date(*ms);
+                }
+                if !run_getters && obj.borrow().contains_key("__proxy_target__") {
+                    return JsOwnedValue::Object(IndexMap::new());
+                }
+                if !self.enter_owned_copy(ctx, seen, Gc::as_ptr(*obj) as usize, run_getters) {
+                    return JsOwnedValue::Undefined;
+                }
+                let keys = self.collect_enumerable_own_keys(ctx, value);
+                let mut map = IndexMap::new();
+                for key in keys {
+                    let prop = if run_getters {
+                        self.read_named_property(ctx, value, &key)
+                    } else {
+                        own_data_from_legacy_map(&obj.borrow(), &key).unwrap_or(Value::Undefined)
+                    };
+                    if self.pending_throw.is_some() {
+                        break;
+                    }
+                    let owned = self.value_to_owned_inner(ctx, &prop, seen, run_getters);
+                    map.insert(key, owned);
+                }
+                seen.pop();
+                JsOwnedValue::Object(map)
+            }
+            Value::Array(arr) => {
+                let typed_name = arr.borrow().props.get("__typedarray_name__").map(value_to_string);
+                match typed_name.as_deref() {
+                    Some("Uint8Array") => return self.owned_bytes(ctx, value),
+                    Some(_) => return self.owned_typed_elements(ctx, value),
+                    None => {}
+                }
+                if !self.enter_owned_copy(ctx, seen, Gc::as_ptr(*arr) as usize, run_getters) {
+                    return JsOwnedValue::Undefined;
+                }
+                let len = self.vm_array_logical_length_u64(&arr.borrow()) as usize;
+                let mut items = Vec::with_capacity(len.min(4096));
+                for index in 0..len {
+                    let element = {
+                        let borrow = arr.borrow();
+                        if borrow.props.contains_key(&format!("__deleted_{index}")) {
+                            None
+                        } else {
+                            borrow.elements.get(index).cloned()
+                        }
+                    };
+                    let element = match element {
+                        Some(Value::Property { .. }) if run_getters => self.read_named_property(ctx, value, &index.to_string()),
+                        Some(v) => v,
+                        None => Value::Undefined,
+                    };
+                    if self.pending_throw.is_some() {
+                        break;
+                    }
+                    items.push(self.value_to_owned_inner(ctx, &element, seen, run_getters));
+                }
+                seen.pop();
+                JsOwnedValue::Array(items)
+            }
+            Value::Map(map) if !map.borrow().is_weak => {
+                if !self.enter_owned_copy(ctx, seen, Gc::as_ptr(*map) as usize, run_getters) {
+                    return JsOwnedValue::Undefined;
+                }
+                let entries = map.borrow().entries.clone();
+                let entries = entries
+                    .iter()
+                    .map(|(k, v)| {
+                        (
+                            self.value_to_owned_inner(ctx, k, seen, run_getters),
+                            self.value_to_owned_inner(ctx, v, seen, run_getters),
+                        )
+                    })
+                    .collect();
+                seen.pop();
+                JsOwnedValue::Map(entries)
+            }
+            Value::Set(set) if !set.borrow().is_weak => {
+                if !self.enter_owned_copy(ctx, seen, Gc::as_ptr(*set) as usize, run_getters) {
+                    return JsOwnedValue::Undefined;
+                }
+                let values = set.borrow().values.clone();
+                let values = values
+                    .iter()
+                    .map(|v| self.value_to_owned_inner(ctx, v, seen, run_getters))
+                    .collect();
+                seen.pop();
+                JsOwnedValue::Set(values)
+            }
+            Value::Map(_) | Value::Set(_) => {
+                if run_getters {
+                    let kind = if matches!(value, Value::Map(_)) { "WeakMap" } else { "WeakSet" };
+                    self.throw_type_error(ctx, &format!("Cannot convert a {kind}: its entries cannot be listed"));
+                }
+                JsOwnedValue::Object(IndexMap::new())
+            }
+        }
+    }
+
+    /// Record that `ptr` is being copied. A cycle back to it, or nesting past
+    /// [`OWNED_VALUE_MAX_DEPTH`], stops the copy: a `TypeError` when
+    /// `run_getters` is set, `undefined` in its place otherwise.
+    fn enter_owned_copy(&mut self, ctx: &GcContext<'gc>, seen: &mut Vec<usize>, ptr: usize, run_getters: bool) -> bool {
+        let problem = if seen.contains(&ptr) {
+            "Cannot convert a cyclic structure".to_string()
+        } else if seen.len() >= OWNED_VALUE_MAX_DEPTH {
+            format!("Cannot convert a structure nested more than {OWNED_VALUE_MAX_DEPTH} levels deep")
+        } else {
+            seen.push(ptr);
+            return true;
+        };
+        if run_getters {
+            self.throw_type_error(ctx, &problem);
+        }
+        false
+    }
+
+    /// Describe a thrown value for [`JSError::exception`]. Properties of the
+    /// value and its prototype chain are read as data only, so no getter or
+    /// proxy trap runs.
+    pub(crate) fn owned_exception(&mut self, ctx: &GcContext<'gc>, thrown: &Value<'gc>) -> JsException {
+        self.owned_exception_inner(ctx, thrown, 0)
+    }
+
+    fn owned_exception_inner(&mut self, ctx: &GcContext<'gc>, thrown: &Value<'gc>, depth: usize) -> JsException {
+        let value = self.value_to_owned_inner(ctx, thrown, &mut Vec::new(), false);
+        let Value::Object(obj) = thrown else {
+            let message = match thrown {
+                Value::String(s) => crate::unicode::utf16_to_utf8(s),
+                other => value_to_string(other),
+            };
+            return JsException {
+                message,
+                value,
+                ..JsException::default()
+            };
+        };
+        let (own_name, error_type, message, stack, cause, errors) = {
+            let borrow = obj.borrow();
+            (
+                own_data_from_legacy_map(&borrow, "name").as_ref().map(value_to_string),
+                borrow.get("__type__").map(value_to_string),
+                own_data_from_legacy_map(&borrow, "message").as_ref().map(value_to_string),
+                match borrow.get("stack") {
+                    Some(Value::String(stack)) => crate::unicode::utf16_to_utf8(stack),
+                    _ => String::new(),
+                },
+                own_data_from_legacy_map(&borrow, "cause"),
+                own_data_from_legacy_map(&borrow, "errors"),
+            )
+        };
+        // A name the program set on the error itself wins over the class it
+        // was built from, and both over the builtin kind it derives from.
+        let name = own_name
+            .or_else(|| self.constructor_name(ctx, thrown))
+            .or(error_type)
+            .unwrap_or_else(|| "Error".to_string());
+        let message = message.unwrap_or_else(|| value_to_string(thrown));
+        let mut exception = JsException {
+            name,
+            message,
+            stack: stack.lines().filter_map(JsStackFrame::parse).collect(),
+            value,
+            ..JsException::default()
+        };
+        if depth >= EXCEPTION_MAX_DEPTH {
+            return exception;
+        }
+        if let Some(cause) = cause {
+            exception.cause = Some(Box::new(self.owned_exception_inner(ctx, &cause, depth + 1)));
+        }
+        if let Some(Value::Array(errors)) = errors {
+            let errors = errors.borrow().elements.clone();
+            exception.errors = errors
+                .iter()
+                .map(|error| self.owned_exception_inner(ctx, error, depth + 1))
+                .collect();
+        }
+        exception
+    }
+
+    fn owned_bytes(&mut self, ctx: &GcContext<'gc>, array: &Value<'gc>) -> JsOwnedValue {
+        let len = match self.read_named_property(ctx, array, "length") {
+            Value::Number(n) if n.is_finite() && n >= 0.0 => n as usize,
+            _ => 0,
+        };
+        let mut bytes = Vec::with_capacity(len);
+        for index in 0..len {
+            match self.read_named_property(ctx, array, &index.to_string()) {
+                Value::Number(n) => bytes.push(n as u8),
+                _ => bytes.push(0),
+            }
+        }
+        JsOwnedValue::Bytes(bytes)
+    }
+
+    /// The elements of a typed array other than `Uint8Array`, as numbers (or
+    /// bigints for the 64-bit kinds).
+    fn owned_typed_elements(&mut self, ctx: &GcContext<'gc>, array: &Value<'gc>) -> JsOwnedValue {
+        let len = match self.read_named_property(ctx, array, "length") {
+            Value::Number(n) if n.is_finite() && n >= 0.0 => n as usize,
+            _ => 0,
+        };
+        let mut items = Vec::with_capacity(len);
+        for index in 0..len {
+            match self.read_named_property(ctx, array, &index.to_string()) {
+                Value::BigInt(b) => items.push(JsOwnedValue::BigInt(*b)),
+                Value::Number(n) => items.push(JsOwnedValue::Number(n)),
+                _ => items.push(JsOwnedValue::Undefined),
+            }
+        }
+        JsOwnedValue::Array(items)
+    }
+
+    fn owned_function_info(&mut self, ctx: &GcContext<'gc>, func: &Value<'gc>) -> JsOwnedValue {
+        let name = match self.read_named_property(ctx, func, "name") {
+            Value::String(s) => crate::unicode::utf16_to_utf8(&s),
+            _ => String::new(),
+        };
+        let length = match self.read_named_property(ctx, func, "length") {
+            Value::Number(n) if n.is_finite() && n >= 0.0 => n as usize,
+            _ => 0,
+        };
+        JsOwnedValue::Function(JsFunctionInfo { name, length })
+    }
+
+    /// Materialize an embedder-supplied `JsOwnedValue` as a VM value.
+    ///
+    /// Function descriptions cannot be turned back into callables and become
+    /// `undefined`, as do handles created by another VM. Maps, sets, dates
+    /// and byte arrays are built with the VM's own constructors, whatever the
+    /// script has since assigned to the globals of the same name; an error
+    /// from one of them is returned.
+    pub(crate) fn owned_to_value(&mut self, ctx: &GcContext<'gc>, value: &JsOwnedValue) -> Result<Value<'gc>, JSError> {
+        Ok(match value {
+            JsOwnedValue::Undefined | JsOwnedValue::Function(_) => Value::Undefined,
+            JsOwnedValue::Handle(handle) => self.handle_value(handle).unwrap_or(Value::Undefined),
+            JsOwnedValue::Null => Value::Null,
+            JsOwnedValue::Boolean(b) => Value::Boolean(*b),
+            JsOwnedValue::Number(n) => Value::Number(*n),
+            JsOwnedValue::BigInt(b) => Value::BigInt(Box::new(b.clone())),
+            JsOwnedValue::String(s) => Value::from(s.as_str()),
+            JsOwnedValue::Symbol(desc) => {
+                self.symbol_counter += 1;
+                let id = self.symbol_counter;
+                self.create_symbol(ctx, id, desc.as_deref().map(crate::unicode::utf8_to_utf16), false)
+            }
+            JsOwnedValue::Array(items) => {
+                let elements = items.iter().map(|item| self.owned_to_value(ctx, item)).collect::<Result<_, _>>()?;
+                self.create_vm_array(ctx, elements)
+            }
+            JsOwnedValue::Object(entries) => {
+                let mut map = IndexMap::new();
+                for (key, item) in entries {
+                    let v = self.owned_to_value(ctx, item)?;
+                    let key = if key == "__proto__" {
+                        OWN_DUNDER_PROTO_DATA_KEY.to_string()
+                    } else {
+                        key.clone()
+                    };
+                    map.insert(key, v);
+                }
+                if let Some(proto) = self.ctor_prototype_from_globals(ctx, "Object") {
+                    map.insert("__proto__".to_string(), proto);
+                }
+                Value::Object(new_gc_cell_ptr(ctx, map))
+            }
+            JsOwnedValue::Map(entries) => {
+                let mut pairs = Vec::with_capacity(entries.len());
+                for (k, v) in entries {
+                    let pair = vec![self.owned_to_value(ctx, k)?, self.owned_to_value(ctx, v)?];
+                    pairs.push(self.create_vm_array(ctx, pair));
+                }
+                let pairs = self.create_vm_array(ctx, pairs);
+                let ctor = self.intrinsic_map_ctor.clone();
+                self.construct_intrinsic(ctx, &ctor, "Map", &[pairs])?
+            }
+            JsOwnedValue::Set(values) => {
+                let values = values
+                    .iter()
+                    .map(|value| self.owned_to_value(ctx, value))
+                    .collect::<Result<_, _>>()?;
+                let values = self.create_vm_array(ctx, values);
+                let ctor = self.intrinsic_set_ctor.clone();
+                self.construct_intrinsic(ctx, &ctor, "Set", &[values])?
+            }
+            JsOwnedValue::Date(ms) => {
+                let ctor = self.intrinsic_date_ctor.clone();
+                self.construct_intrinsic(ctx, &ctor, "Date", &[Value::Number(*ms)])?
+            }
+            JsOwnedValue::Bytes(bytes) => {
+                let bytes = bytes.iter().map(|b| Value::Number(*b as f64)).collect();
+                let bytes = self.create_vm_array(ctx, bytes);
+                let ctor = self.intrinsic_uint8array_ctor.clone();
+                self.construct_intrinsic(ctx, &ctor, "Uint8Array", &[bytes])?
+            }
+        })
+    }
+
+    /// `new ctor(...args)` for one of the VM's own constructors; `name` is
+    /// reported when this build leaves it out.
+    fn construct_intrinsic(
+        &mut self,
+        ctx: &GcContext<'gc>,
+        ctor: &Value<'gc>,
+        name: &str,
+        args: &[Value<'gc>],
+    ) -> Result<Value<'gc>, JSError> {
+        if matches!(ctor, Value::Undefined) {
+            return Err(crate::raise_type_error!(format!("{name} is not available in this build")));
+        }
+        let value = self.construct_value(ctx, ctor, args, None);
+        if let Some(thrown) = self.pending_throw.take() {
+            return Err(self.vm_error_to_js_error(ctx, &thrown));
+        }
+        value
+    }
+
+    /// Share embedder-installed state with a helper VM (eval, realms, snippets)
+    /// so host callbacks still dispatch and execution limits still apply.
+    pub(super) fn inherit_embedder_state(&self, child: &mut VM<'gc>) {
+        child.host_callbacks = self.host_callbacks.clone();
+        child.limits = self.limits.clone();
+        child.console = self.console.clone();
+        child.module_loader = self.module_loader.clone();
+        child.handle_roots = self.handle_roots;
+        child.handles = self.handles.clone();
+        child.host_classes = self.host_classes.clone();
+        child.async_host = self.async_host.clone();
+        child.timers = self.timers.clone();
+        child.rejections = self.rejections.clone();
+        child.clock = self.clock.clone();
+        child.permissions = self.permissions.clone();
+        child.os_files = self.os_files.clone();
+    }
+
+    /// Install `callback` as a callable at `path` (e.g. `"greet"` or `"app.db.query"`).
+    ///
+    /// Missing intermediate namespace objects are created; an existing
+    /// intermediate that is not an object is reported as a `TypeError`.
+    pub(crate) fn register_host_callback(
+        &mut self,
+        ctx: &GcContext<'gc>,
+        path: &str,
+        length: usize,
+        callback: HostCallback,
+    ) -> Result<(), JSError> {
+        let id = self.host_callbacks.len();
+        self.host_callbacks.push(callback);
+        self.define_host_fn_at_path(ctx, path, &format!("embed.{id}"), length)
+    }
+
+    /// Bind the host function `host_name` at `path`, creating missing namespaces.
+    pub(super) fn define_host_fn_at_path(
+        &mut self,
+        ctx: &GcContext<'gc>,
+        path: &str,
+        host_name: &str,
+        length: usize,
+    ) -> Result<(), JSError> {
+        let segments: Vec<&str> = path.split('.').collect();
+        if segments.iter().any(|seg| seg.is_empty()) {
+            return Err(crate::raise_type_error!(format!("Invalid host function path '{path}'")));
+        }
+        let (fn_name, namespaces) = segments.split_last().expect("split yields at least one segment");
+
+        let mut parent: Option<Value<'gc>> = None;
+        for (depth, seg) in namespaces.iter().enumerate() {
+            let existing = match &parent {
+                None => self.globals.get(*seg).cloned(),
+                Some(Value::Object(obj)) => obj.borrow().get(*seg).cloned(),
+                Some(_) => None,
+            };
+            let namespace = match existing {
+                Some(v @ Value::Object(_)) => v,
+                Some(_) => {
+                    let prefix = segments[..=depth].join(".");
+                    return Err(crate::raise_type_error!(format!(
+                        "Cannot register '{path}': '{prefix}' is not an object"
+                    )));
+                }
+                None => {
+                    let mut map = IndexMap::new();
+                    if let Some(proto) = self.ctor_prototype_from_globals(ctx, "Object") {
+                        map.insert("__proto__".to_string(), proto);
+                    }
+                    let v = Value::Object(new_gc_cell_ptr(ctx, map));
+                    self.define_embed_binding(ctx, parent.as_ref(), seg, v.clone());
+                    v
+                }
+            };
+            parent = Some(namespace);
+        }
+
+        let func = Self::make_host_fn_with_name_len(ctx, host_name, fn_name, length as f64, false);
+        self.define_embed_binding(ctx, parent.as_ref(), fn_name, func);
+        Ok(())
+    }
+
+    fn define_embed_binding(&mut self, ctx: &GcContext<'gc>, parent: Option<&Value<'gc>>, key: &str, value: Value<'gc>) {
+        match parent {
+            Some(Value::Object(obj)) => {
+                obj.borrow_mut(ctx).insert(key.to_string(), value);
+            }
+            _ => {
+                self.globals.insert(key.to_string(), value);
+            }
+        }
+    }
+
+    /// Run an embedder-initiated operation as its own limited run, so the
+    /// instruction budget, heap limit and interrupts apply to it.
+    fn run_host_operation<R>(
+        &mut self,
+        ctx: &GcContext<'gc>,
+        op: impl FnOnce(&mut Self, &GcContext<'gc>) -> Result<R, JSError>,
+    ) -> Result<R, JSError> {
+        self.begin_limited_run();
+        self.handles.release_dropped();
+        let result = op(self, ctx);
+        if let Some(err) = self.termination_error() {
+            self.abandon_terminated_run();
+            return Err(err);
+        }
+        result
+    }
+
+    /// Call `target` with owned arguments on behalf of the embedder, then run
+    /// the microtasks and timers the call scheduled.
+    ///
+    /// With `settle`, a returned promise is replaced by its fulfillment value,
+    /// or its rejection reason is reported as an error.
+    pub(crate) fn call_from_host(
+        &mut self,
+        ctx: &GcContext<'gc>,
+        target: CallTarget<'_>,
+        this: &JsOwnedValue,
+        args: &[JsOwnedValue],
+        settle: bool,
+    ) -> Result<JsOwnedValue, JSError> {
+        self.run_host_operation(ctx, |vm, ctx| {
+            let func = match target {
+                CallTarget::Global(path) => vm.lookup_global_path(ctx, path)?,
+                CallTarget::Handle(handle) => vm.handle_value(handle)?,
+            };
+            if !vm.is_callable_value(&func) {
+                return Err(crate::raise_type_error!(format!("{target} is not a function")));
+            }
+            let this = vm.owned_to_value(ctx, this)?;
+            vm.invoke_from_host(ctx, &func, &this, args, settle)
+        })
+    }
+
+    /// Call the `method` property of the object behind `handle`, with the
+    /// object as `this`. Otherwise behaves like [`Self::call_from_host`].
+    pub(crate) fn call_method_from_host(
+        &mut self,
+        ctx: &GcContext<'gc>,
+        handle: &JsHandle,
+        method: &str,
+        args: &[JsOwnedValue],
+        settle: bool,
+    ) -> Result<JsOwnedValue, JSError> {
+        self.run_host_operation(ctx, |vm, ctx| {
+            let receiver = vm.handle_value(handle)?;
+            let func = vm.read_named_property(ctx, &receiver, method);
+            if let Some(thrown) = vm.pending_throw.take() {
+                return Err(vm.vm_error_to_js_error(ctx, &thrown));
+            }
+            if !vm.is_callable_value(&func) {
+                return Err(crate::raise_type_error!(format!("{method} is not a function")));
+            }
+            vm.invoke_from_host(ctx, &func, &receiver, args, settle)
+        })
+    }
+
+    fn invoke_from_host(
+        &mut self,
+        ctx: &GcContext<'gc>,
+        func: &Value<'gc>,
+        this: &Value<'gc>,
+        args: &[JsOwnedValue],
+        settle: bool,
+    ) -> Result<JsOwnedValue, JSError> {
+        let args = args
+            .iter()
+            .map(|arg| self.owned_to_value(ctx, arg))
+            .collect::<Result<Vec<_>, _>>()?;
+        let out = self.vm_call_function_value(ctx, func, this, &args);
+        let mut value = match (out, self.pending_throw.take()) {
+            (_, Some(thrown)) => return Err(self.vm_error_to_js_error(ctx, &thrown)),
+            (Ok(value), None) => value,
+            (Err(err), None) => return Err(err),
+        };
+        if settle {
+            // The embedder receives the rejection, so it is not unhandled.
+            self.mark_promise_handled(ctx, &value);
+        }
+        self.run_host_event_loop(ctx)?;
+        if settle {
+            value = self.settled_promise_value(ctx, value)?;
+        }
+        self.value_to_owned(ctx, &value)
+    }
+
+    /// Root `value` so the embedder can keep referring to it across evaluations.
+    pub(crate) fn make_handle(&mut self, ctx: &GcContext<'gc>, value: Value<'gc>) -> Result<JsHandle, JSError> {
+        if !matches!(
+            value,
+            Value::Object(_)
+                | Value::Array(_)
+                | Value::Function(..)
+                | Value::Closure(..)
+                | Value::NativeFunction(_)
+                | Value::Map(_)
+                | Value::Set(_)
+        ) {
+            return Err(crate::raise_type_error!(format!(
+                "Only objects and functions can be held by a JsHandle, got {}",
+                crate::core::value_to_string(&value)
+            )));
+        }
+        self.handles.release_dropped();
+        let callable = self.is_callable_value(&value);
+        let root = self.handle_roots.stash(ctx, Gc::new(ctx, value));
+        Ok(self.handles.insert(root, callable))
+    }
+
+    /// Unroot the objects of handles the embedder has dropped.
+    pub(crate) fn release_dropped_handles(&self) {
+        self.handles.release_dropped();
+    }
+
+    /// Evaluate a REPL snippet and hold on to its (object) result.
+    pub(crate) fn eval_handle(&mut self, ctx: &GcContext<'gc>, code: &str) -> Result<JsHandle, JSError> {
+        let value = self.eval_repl_snippet(ctx, code)?;
+        self.make_handle(ctx, value)
+    }
+
+    /// Import a module for the embedder and hold its namespace.
+    ///
+    /// With the default loader `path_or_key` is a file path, taken from the
+    /// current directory; a custom loader resolves it with an empty referrer,
+    /// as for `import()` in a REPL snippet. The graph is linked and evaluated
+    /// like a dynamic import, then the event loop runs so modules waiting on
+    /// top-level `await` can finish. An evaluated module is not run again.
+    pub(crate) fn import_module_from_host(&mut self, ctx: &GcContext<'gc>, path_or_key: &str) -> Result<ModuleNamespaceHandle, JSError> {
+        self.run_host_operation(ctx, |vm, ctx| {
+            let specifier = if vm.module_loader.is_custom() {
+                path_or_key.to_string()
+            } else {
+                let path = std::path::absolute(path_or_key).unwrap_or_else(|_| std::path::PathBuf::from(path_or_key));
+                path.to_string_lossy().to_string()
+            };
+            let base_path = std::path::Path::new("");
+            let module_key = vm.module_loader.request_key(&specifier, base_path, None);
+
+            if !vm.loaded_modules.contains_key(&module_key)
+                || vm
+                    .module_records
+                    .get(&module_key)
+                    .is_some_and(|record| record.status != ModuleStatus::Evaluated)
+            {
+                let request = crate::core::ModuleRequest {
+                    specifier,
+                    phase: crate::core::ModuleRequestPhase::Evaluation,
+                    import_type: None,
+                };
+                vm.load_module_dependencies(ctx, base_path, std::slice::from_ref(&request));
+                vm.fixup_circular_reexports();
+                if let Some(thrown) = vm.pending_throw.take() {
+                    return Err(vm.vm_error_to_js_error(ctx, &thrown));
+                }
+                vm.run_host_event_loop(ctx)?;
+                vm.resume_suspended_module_states(ctx);
+            }
+
+            if let Some(error) = vm.module_load_errors.get(&module_key).cloned() {
+                return Err(vm.vm_error_to_js_error(ctx, &error));
+            }
+            if vm
+                .module_records
+                .get(&module_key)
+                .is_some_and(|record| record.status != ModuleStatus::Evaluated)
+            {
+                return Err(crate::raise_type_error!(format!(
+                    "Module '{path_or_key}' is still evaluating after the event loop ran out of work"
+                )));
+            }
+
+            vm.refresh_module_namespace_object(ctx, &module_key);
+            let Some(namespace) = vm.module_ns_objects.get(&module_key).cloned() else {
+                return Err(crate::raise_type_error!(format!("Failed to import '{path_or_key}'")));
+            };
+            let export_names = match &namespace {
+                Value::Object(ns) => match ns.borrow().get("__ns_bindings__") {
+                    Some(Value::Object(bindings)) => bindings.borrow().keys().cloned().collect(),
+                    _ => Vec::new(),
+                },
+                _ => Vec::new(),
+            };
+            let handle = vm.make_handle(ctx, namespace)?;
+            Ok(ModuleNamespaceHandle::new(handle, module_key, export_names))
+        })
+    }
+
+    fn handle_value(&self, handle: &JsHandle) -> Result<Value<'gc>, JSError> {
+        let root = self
+            .handles
+            .root(handle)
+            .ok_or_else(|| crate::raise_type_error!("JsHandle was created by a different Repl"))?;
+        Ok((*self.handle_roots.fetch(&root)).clone())
+    }
+
+    pub(crate) fn handle_to_owned(&mut self, ctx: &GcContext<'gc>, handle: &JsHandle) -> Result<JsOwnedValue, JSError> {
+        let value = self.handle_value(handle)?;
+        self.value_to_owned(ctx, &value)
+    }
+
+    /// Read `key` from the object behind `handle` (getters run).
+    pub(crate) fn handle_get(&mut self, ctx: &GcContext<'gc>, handle: &JsHandle, key: &str) -> Result<Value<'gc>, JSError> {
+        self.run_host_operation(ctx, |vm, ctx| {
+            let target = vm.handle_value(handle)?;
+            let value = vm.read_named_property(ctx, &target, key);
+            if let Some(thrown) = vm.pending_throw.take() {
+                return Err(vm.vm_error_to_js_error(ctx, &thrown));
+            }
+            Ok(value)
+        })
+    }
+
+    /// Assign `key` on the object behind `handle` with `[[Set]]` semantics
+    /// (setters and proxy traps run); a rejected assignment is a `TypeError`.
+    pub(crate) fn handle_set(&mut self, ctx: &GcContext<'gc>, handle: &JsHandle, key: &str, value: &JsOwnedValue) -> Result<(), JSError> {
+        self.run_host_operation(ctx, |vm, ctx| {
+            let target = vm.handle_value(handle)?;
+            let value = vm.owned_to_value(ctx, value)?;
+            let ok = vm.call_host_fn(ctx, "reflect.set", None, &[target, Value::from(key), value]);
+            if let Some(thrown) = vm.pending_throw.take() {
+                return Err(vm.vm_error_to_js_error(ctx, &thrown));
+            }
+            if matches!(ok, Value::Boolean(false)) {
+                return Err(crate::raise_type_error!(format!(
+                    "Cannot assign to read only property '{key}' of object"
+                )));
+            }
+            Ok(())
+        })
+    }
+
+    /// Resolve `"name"` or `"a.b.c"` against the global scope.
+    fn lookup_global_path(&mut self, ctx: &GcContext<'gc>, path: &str) -> Result<Value<'gc>, JSError> {
+        let mut segments = path.split('.');
+        let first = segments.next().unwrap_or_default();
+        let global_object = Value::Object(self.global_this);
+        let mut value = match self.globals.get(first).cloned() {
+            Some(value) => value,
+            None if self.global_this.borrow().contains_key(first) => self.read_named_property(ctx, &global_object, first),
+            None => return Err(crate::raise_reference_error!(format!("{first} is not defined"))),
+        };
+        for segment in segments {
+            if matches!(value, Value::Undefined | Value::Null) {
+                return Err(crate::raise_type_error!(format!(
+                    "Cannot read properties of {} (reading '{segment}') in '{path}'",
+                    crate::core::value_to_string(&value)
+                )));
+            }
+            value = self.read_named_property(ctx, &value, segment);
+            if let Some(thrown) = self.pending_throw.take() {
+                return Err(self.vm_error_to_js_error(ctx, &thrown));
+            }
+        }
+        Ok(value)
+    }
+
+    /// Drain the microtasks, timers and async host operations queued by host-initiated work.
+    fn run_host_event_loop(&mut self, ctx: &GcContext<'gc>) -> Result<(), JSError> {
+        self.drain_microtasks(ctx);
+        if let Some(thrown) = self.pending_throw.take() {
+            return Err(self.vm_error_to_js_error(ctx, &thrown));
+        }
+        self.notify_rejections(ctx)?;
+        self.drain_timers(ctx)?;
+        if let Some(thrown) = self.pending_throw.take() {
+            return Err(self.vm_error_to_js_error(ctx, &thrown));
+        }
+        self.drain_async_host_operations(ctx)
+    }
+
+    /// The settled result of `value` when it is a promise; other values pass through.
+    fn settled_promise_value(&mut self, ctx: &GcContext<'gc>, value: Value<'gc>) -> Result<Value<'gc>, JSError> {
+        let Value::Object(obj) = &value else {
+            return Ok(value);
+        };
+        let state = {
+            let b = obj.borrow();
+            let is_promise = matches!(b.get("__type__"), Some(Value::String(s)) if crate::unicode::utf16_to_utf8(s) == "Promise");
+            is_promise.then(|| {
+                (
+                    b.contains_key("__promise_value__"),
+                    matches!(b.get("__promise_rejected__"), Some(Value::Boolean(true))),
+                    b.get("__promise_value__").cloned().unwrap_or(Value::Undefined),
+                )
+            })
+        };
+        let Some((settled, rejected, result)) = state else {
+            return Ok(value);
+        };
+        if !settled {
+            return Err(crate::raise_type_error!(
+                "Promise is still pending after the event loop ran out of work"
+            ));
+        }
+        if rejected {
+            return Err(self.vm_error_to_js_error(ctx, &result));
+        }
+        Ok(result)
+    }
+
+    pub(super) fn embed_handle_host_fn(&mut self, ctx: &GcContext<'gc>, name: &str, args: &[Value<'gc>]) -> Value<'gc> {
+        let callback = name
+            .strip_prefix("embed.")
+            .and_then(|id| id.parse::<usize>().ok())
+            .and_then(|id| self.host_callbacks.get(id).cloned());
+        let Some(callback) = callback else {
+            self.throw_type_error(ctx, &format!("{name} is not a registered host function"));
+            return Value::Undefined;
+        };
+
+        let mut owned_args = Vec::with_capacity(args.len());
+        for arg in args {
+            match self.value_to_owned(ctx, arg) {
+                Ok(v) => owned_args.push(v),
+                Err(err) => {
+                    let thrown = self.vm_value_from_error(ctx, &err);
+                    self.pending_throw = Some(thrown);
+                    return Value::Undefined;
+                }
+            }
+        }
+
+        match callback(&owned_args).and_then(|result| self.owned_to_value(ctx, &result)) {
+            Ok(value) => value,
+            Err(err) => {
+                let thrown = self.vm_value_from_error(ctx, &err);
+                self.pending_throw = Some(thrown);
+                Value::Undefined
+            }
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use super::*;
+use std::cell::{Cell, RefCell};
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::HashSet;
+use std::collections::binary_heap::PeekMut;
+use std::time::{Duration, Instant};
+
+/// Longest timer delay that evaluation waits for by default.
+const DEFAULT_SHORT_TIMER_THRESHOLD_MS: u64 = 20;
+
+/// Timer ids and loop mode, shared with helper VMs so ids are unique and
+/// `clearTimeout` works whichever VM scheduled the timer.
+pub(crate) struct TimerState {
+    next_id: Cell<usize>,
+    /// Ids of timers that were scheduled and have neither fired (one-shot) nor been cleared.
+    live: RefCell<HashSet<usize>>,
+    /// Live timers that do not keep the event loop alive (`os.unrefTimer`).
+    unref: RefCell<HashSet<usize>>,
+    /// When set, evaluation leaves timers and async host operations queued
+    /// for the embedder to run through the stepping API.
+    host_driven: Cell<bool>,
+    /// Timers with a delay up to this many milliseconds are always waited for.
+    short_timer_threshold_ms: Cell<u64>,
+    /// Keep the event loop running while any referenced timer is pending, as
+    /// Node does for a script, instead of only for short timers.
+    wait_for_active_handles: Cell<bool>,
+}
+
+impl Default for TimerState {
+    fn default() -> Self {
+        TimerState {
+            next_id: Cell::new(1),
+            live: RefCell::default(),
+            unref: RefCell::default(),
+            host_driven: Cell::new(false),
+            short_timer_threshold_ms: Cell::new(DEFAULT_SHORT_TIMER_THRESHOLD_MS),
+            wait_for_active_handles: Cell::new(false),
+        }
+    }
+}
+
+/// Deadline of a timer scheduled `delay_ms` after `now`. As in browsers and
+/// Node, delays below 1ms are treated as 1ms.
+pub(super) fn timer_deadline(now: Instant, delay_ms: u64) -> Instant {
+    now + Duration::from_millis(delay_ms.max(1))
+}
+
+/// The deadline after `previous` for an interval of `delay_ms`, so late
+/// callbacks do not make the interval drift. Ticks that are already past
+/// `now` are skipped rather than fired back to back.
+fn next_interval_deadline(previous: Instant, now: Instant, delay_ms: u64) -> Instant {
+    let period = Duration::from_millis(delay_ms.max(1));
+    let next = previous + period;
+    if next > now {
+        return next;
+    }
+    let missed = (now - next).as_nanos() / period.as_nanos() + 1;
+    next + period * u32::try_from(missed).unwrap_or(u32::MAX)
+}
+
+impl PartialEq for PendingTimer<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == CmpOrdering::Equal
+    }
+}
+
+impl Eq for PendingTimer<'_> {}
+
+impl PartialOrd for PendingTimer<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PendingTimer<'_> {
+    // Reversed, since `BinaryHeap` is a max-heap; equal deadlines fire in scheduling order.
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        (other.deadline, other.id).cmp(&(self.deadline, self.id))
+    }
+}
+
+impl<'gc> VM<'gc> {
+    pub(super) fn schedule_timer(&mut self, callback: Value<'gc>, args: Vec<Value<'gc>>, delay_ms: u64, is_interval: bool) -> usize {
+        let id = self.timers.next_id.get();
+        self.timers.next_id.set(id + 1);
+        self.timers.live.borrow_mut().insert(id);
+        self.pending_timers.push(PendingTimer {
+            id,
+            callback,
+            args,
+            delay_ms,
+            deadline: timer_deadline(self.clock.now_instant(), delay_ms),
+            is_interval,
+        });
+        id
+    }
+
+    pub(super) fn clear_timer(&mut self, id: usize) {
+        self.retire_timer(id);
+        self.pending_timers.retain(|t| t.id != id);
+    }
+
+    /// Choose whether a live timer keeps the event loop alive.
+    pub(super) fn set_timer_ref(&mut self, id: usize, referenced: bool) {
+        if !self.is_timer_live(id) {
+            return;
+        }
+        let mut unref = self.timers.unref.borrow_mut();
+        if referenced {
+            unref.remove(&id);
+        } else {
+            unref.insert(id);
+        }
+    }
+
+    pub(super) fn is_timer_live(&self, id: usize) -> bool {
+        self.timers.live.borrow().contains(&id)
+    }
+
+    fn is_timer_referenced(&self, id: usize) -> bool {
+        !self.timers.unref.borrow().contains(&id)
+    }
+
+    /// Mark a one-shot timer as fired.
+    pub(super) fn retire_timer(&mut self, id: usize) {
+        self.timers.live.borrow_mut().remove(&id);
+        self.timers.unref.borrow_mut().remove(&id);
+    }
+
+    /// Drop this VM's queued timers, e.g. after an aborted run.
+    pub(super) fn abandon_timers(&mut self) {
+        for timer in std::mem::take(&mut self.pending_timers) {
+            self.retire_timer(timer.id);
+        }
+    }
+
+    pub(crate) fn set_host_driven_event_loop(&mut self, enabled: bool) {
+        self.timers.host_driven.set(enabled);
+    }
+
+    pub(super) fn is_host_driven_event_loop(&self) -> bool {
+        self.timers.host_driven.get()
+    }
+
+    pub(crate) fn set_short_timer_threshold_ms(&mut self, ms: u64) {
+        self.timers.short_timer_threshold_ms.set(ms);
+    }
+
+    pub(crate) fn set_wait_for_active_handles(&mut self, enabled: bool) {
+        self.timers.wait_for_active_handles.set(enabled);
+    }
+
+    /// Apply async host settlements that have already arrived, then drain
+    /// the microtask queue.
+    pub(crate) fn run_microtasks(&mut self, ctx: &GcContext<'gc>) -> Result<(), JSError> {
+        self.begin_limited_run();
+        self.handles.release_dropped();
+        self.apply_async_host_settlements(ctx, None);
+        self.drain_microtasks(ctx);
+        self.finish_host_step(ctx)?;
+        self.notify_rejections(ctx)
+    }
+
+    /// Fire every timer whose deadline is at or before `now`, earliest
+    /// first, draining microtasks after each callback. Timers scheduled by
+    /// those callbacks wait for a later call. Returns how many fired.
+    pub(crate) fn run_expired_timers(&mut self, ctx: &GcContext<'gc>, now: Instant) -> Result<usize, JSError> {
+        self.begin_limited_run();
+        self.handles.release_dropped();
+        let fired = match self.fire_due_timers(ctx, now) {
+            Ok(fired) => fired,
+            Err(err) => {
+                if self.is_terminating() {
+                    self.abandon_terminated_run();
+                }
+                return Err(err);
+            }
+        };
+        self.finish_host_step(ctx)?;
+        Ok(fired)
+    }
+
+    /// Run timers as their deadlines pass, sleeping in between, until no
+    /// timer is left that keeps the event loop alive. Unreferenced timers
+    /// fire if they come due meanwhile but are never waited for. On a
+    /// virtual clock the wait is skipped and the clock jumps to the deadline.
+    /// With a host-driven event loop the timers stay queued for the embedder,
+    /// and eval VMs hand theirs to the calling VM.
+    pub(super) fn drain_timers(&mut self, ctx: &GcContext<'gc>) -> Result<(), JSError> {
+        if self.is_host_driven_event_loop() || self.defers_event_loop {
+            return Ok(());
+        }
+        loop {
+            if let Some(err) = self.termination_error() {
+                return Err(err);
+            }
+            self.fire_due_timers(ctx, self.clock.now_instant())?;
+            if self.pending_throw.is_some() || !self.live_timers().any(|t| self.keeps_loop_alive(t)) {
+                return Ok(());
+            }
+            if let Some(deadline) = self.next_timer_deadline() {
+                self.wait_for_deadline(ctx, deadline)?;
+            }
+        }
+    }
+
+    fn fire_due_timers(&mut self, ctx: &GcContext<'gc>, now: Instant) -> Result<usize, JSError> {
+        self.clock.advance_to(now);
+        let mut fired = 0;
+        while let Some(next) = self.pending_timers.peek_mut()
+            && next.deadline <= now
+        {
+            let timer = PeekMut::pop(next);
+            if !self.is_timer_live(timer.id) {
+                continue;
+            }
+            if !timer.is_interval {
+                self.retire_timer(timer.id);
+            }
+            fired += 1;
+            let out = if self.is_value_callable(&timer.callback) {
+                self.vm_call_function_value(ctx, &timer.callback, &Value::Undefined, &timer.args)
+                    .map(|_| ())
+            } else {
+                Ok(())
+            };
+            // The next deadline is always after `now`, so each interval fires once per call.
+            if timer.is_interval && self.is_timer_live(timer.id) {
+                self.pending_timers.push(PendingTimer {
+                    deadline: next_interval_deadline(timer.deadline, now, timer.delay_ms),
+                    ..timer
+                });
+            }
+            out?;
+            self.flush_async_atomics_waits(ctx, true);
+            self.drain_microtasks(ctx);
+            if self.pending_throw.is_some() || self.is_terminating() {
+                break;
+            }
+            self.notify_rejections(ctx)?;
+        }
+        Ok(fired)
+    }
+
+    /// Whether evaluation should wait for `timer` before returning.
+    fn keeps_loop_alive(&self, timer: &PendingTimer<'gc>) -> bool {
+        self.is_timer_referenced(timer.id)
+            && (self.timers.wait_for_active_handles.get() || timer.delay_ms <= self.timers.short_timer_threshold_ms.get())
+    }
+
+    /// Sleep towards `deadline`, waking early to poll for interrupts and to
+    /// apply async host settlements as they arrive.
+    fn wait_for_deadline(&mut self, ctx: &GcContext<'gc>, deadline: Instant) -> Result<(), JSError> {
+        if self.clock.is_deterministic() {
+            self.clock.advance_to(deadline);
+            return Ok(());
+        }
+        let Some(wait) = deadline.checked_duration_since(Instant::now()) else {
+            return Ok(());
+        };
+        let wait = wait.min(async_host::SETTLEMENT_POLL_INTERVAL);
+        if !self.async_host.has_pending() {
+            std::thread::sleep(wait);
+        } else if self.apply_async_host_settlements(ctx, Some(wait)) {
+            self.drain_microtasks(ctx);
+            if self.pending_throw.is_none() {
+                self.notify_rejections(ctx)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish_host_step(&mut self, ctx: &GcContext<'gc>) -> Result<(), JSError> {
+        if let Some(err) = self.termination_error() {
+            self.abandon_terminated_run();
+            return Err(err);
+        }
+        if let Some(thrown) = self.pending_throw.take() {
+            return Err(self.vm_error_to_js_error(ctx, &thrown));
+        }
+        Ok(())
+    }
+
+    /// The earliest deadline among queued timers.
+    pub(crate) fn next_timer_deadline(&self) -> Option<Instant> {
+        self.live_timers().map(|t| t.deadline).min()
+    }
+
+    /// Whether microtasks, referenced timers or async host operations are
+    /// still queued. Unreferenced timers still count towards
+    /// [`next_timer_deadline`](Self::next_timer_deadline).
+    pub(crate) fn has_pending_work(&self) -> bool {
+        !self.microtask_queue.is_empty() || self.live_timers().any(|t| self.is_timer_referenced(t.id)) || self.async_host.has_pending()
+    }
+
+    fn live_timers(&self) -> impl Iterator<Item = &PendingTimer<'gc>> {
+        self.pending_timers.iter().filter(|t| self.is_timer_live(t.id))
+    }
+}
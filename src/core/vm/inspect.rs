@@ -0,0 +1,571 @@
+use super::*;
+use crate::core::InspectOptions;
+
+/// ANSI style classes, matching Node's `util.inspect.styles`.
+#[derive(Clone, Copy)]
+enum Style {
+    Number,
+    String,
+    Undefined,
+    Null,
+    Symbol,
+    Date,
+    RegExp,
+    Special,
+}
+
+struct InspectState {
+    opts: InspectOptions,
+    /// Objects on the path from the root to the value being formatted.
+    path: Vec<usize>,
+    /// Objects referenced by a `[Circular *n]` marker, with their marker number.
+    circular: HashMap<usize, usize>,
+}
+
+impl InspectState {
+    fn stylize(&self, text: String, style: Style) -> String {
+        if !self.opts.colors {
+            return text;
+        }
+        let (open, close) = match style {
+            Style::Number => (33, 39),
+            Style::String | Style::Symbol => (32, 39),
+            Style::Undefined => (90, 39),
+            Style::Null => (1, 22),
+            Style::Date => (35, 39),
+            Style::RegExp => (31, 39),
+            Style::Special => (36, 39),
+        };
+        format!("\x1b[{open}m{text}\x1b[{close}m")
+    }
+
+    fn too_deep(&self, level: usize) -> bool {
+        self.opts.depth.is_some_and(|depth| level > depth)
+    }
+}
+
+/// Length of `s` as printed, ignoring ANSI escape sequences.
+fn visible_len(s: &str) -> usize {
+    let mut len = 0;
+    let mut chars = s.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\x1b' {
+            for c in chars.by_ref() {
+                if c == 'm' {
+                    break;
+                }
+            }
+        } else {
+            len += 1;
+        }
+    }
+    len
+}
+
+/// Quote a string the way Node does: single quotes unless the text contains
+/// them, then double quotes, then backticks.
+fn quote_js_string(s: &str) -> String {
+    let quote = if !s.contains('\'') {
+        '\''
+    } else if !s.contains('"') {
+        '"'
+    } else if !s.contains('`') && !s.contains("${") {
+        '`'
+    } else {
+        '\''
+    };
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push(quote);
+    for ch in s.chars() {
+        match ch {
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            '\u{b}' => out.push_str("\\v"),
+            '\\' => out.push_str("\\\\"),
+            c if c == quote => {
+                out.push('\\');
+                out.push(c);
+            }
+            c if (c as u32) < 0x20 || c as u32 == 0x7f => out.push_str(&format!("\\x{:02X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push(quote);
+    out
+}
+
+impl<'gc> VM<'gc> {
+    /// Render `val` like Node's `util.inspect`: strings are quoted, objects
+    /// and arrays are expanded up to `opts.depth` levels.
+    pub(crate) fn inspect_value(&mut self, ctx: &GcContext<'gc>, val: &Value<'gc>, opts: &InspectOptions) -> String {
+        let mut state = InspectState {
+            opts: opts.clone(),
+            path: Vec::new(),
+            circular: HashMap::new(),
+        };
+        let saved_throw = self.pending_throw.take();
+        let out = self.inspect_inner(ctx, val, 0, &mut state);
+        self.pending_throw = saved_throw;
+        out
+    }
+
+    fn inspect_inner(&mut self, ctx: &GcContext<'gc>, val: &Value<'gc>, level: usize, st: &mut InspectState) -> String {
+        match val {
+            Value::Undefined => st.stylize("undefined".to_string(), Style::Undefined),
+            Value::Null => st.stylize("null".to_string(), Style::Null),
+            Value::Boolean(b) => st.stylize(b.to_string(), Style::Number),
+            Value::Number(n) if *n == 0.0 && n.is_sign_negative() => st.stylize("-0".to_string(), Style::Number),
+            Value::Number(_) => st.stylize(value_to_string(val), Style::Number),
+            Value::BigInt(b) => st.stylize(format!("{b}n"), Style::Number),
+            Value::String(s) => st.stylize(quote_js_string(&crate::unicode::utf16_to_utf8(s)), Style::String),
+            Value::Symbol(_) => {
+                let text = self.symbol_display_string(val).unwrap_or_else(|| value_to_string(val));
+                st.stylize(text, Style::Symbol)
+            }
+            Value::Function(..) | Value::Closure(..) | Value::NativeFunction(_) => self.inspect_function(ctx, val, st),
+            Value::Array(arr) => {
+                let ptr = Gc::as_ptr(*arr) as usize;
+                self.inspect_with_cycle_check(ptr, st, |vm, st| vm.inspect_array(ctx, arr, level, st))
+            }
+            Value::Object(obj) => {
+                if self.is_callable_value(val) && !obj.borrow().contains_key("__proxy_target__") {
+                    return self.inspect_function(ctx, val, st);
+                }
+                let ptr = Gc::as_ptr(*obj) as usize;
+                self.inspect_with_cycle_check(ptr, st, |vm, st| vm.inspect_object(ctx, obj, level, st))
+            }
+            Value::Map(map) => {
+                let entries = map.borrow().entries.clone();
+                let ptr = Gc::as_ptr(*map) as usize;
+                self.inspect_with_cycle_check(ptr, st, |vm, st| vm.inspect_map_entries(ctx, "Map", &entries, level, st))
+            }
+            Value::Set(set) => {
+                let values = set.borrow().values.clone();
+                let ptr = Gc::as_ptr(*set) as usize;
+                self.inspect_with_cycle_check(ptr, st, |vm, st| vm.inspect_set_values(ctx, "Set", &values, level, st))
+            }
+            Value::Property { value: Some(v), .. } => self.inspect_inner(ctx, v, level, st),
+            _ => value_to_string(val),
+        }
+    }
+
+    /// Format a container, replacing re-entry into an ancestor with a
+    /// `[Circular *n]` marker and tagging that ancestor with `<ref *n>`.
+    fn inspect_with_cycle_check(
+        &mut self,
+        ptr: usize,
+        st: &mut InspectState,
+        format: impl FnOnce(&mut Self, &mut InspectState) -> String,
+    ) -> String {
+        if st.path.contains(&ptr) {
+            let next_id = st.circular.len() + 1;
+            let id = *st.circular.entry(ptr).or_insert(next_id);
+            return st.stylize(format!("[Circular *{id}]"), Style::Special);
+        }
+        st.path.push(ptr);
+        let out = format(self, st);
+        st.path.pop();
+        match st.circular.get(&ptr) {
+            Some(id) => format!("{} {out}", st.stylize(format!("<ref *{id}>"), Style::Special)),
+            None => out,
+        }
+    }
+
+    /// Read a function's `name` without letting a throwing getter escape.
+    fn inspect_read_name(&mut self, ctx: &GcContext<'gc>, val: &Value<'gc>) -> String {
+        let name = self.read_named_property(ctx, val, "name");
+        if self.pending_throw.take().is_some() {
+            return String::new();
+        }
+        match name {
+            Value::String(s) => crate::unicode::utf16_to_utf8(&s),
+            _ => String::new(),
+        }
+    }
+
+    fn inspect_function(&mut self, ctx: &GcContext<'gc>, val: &Value<'gc>, st: &InspectState) -> String {
+        let name = self.inspect_read_name(ctx, val);
+        let ip = match val {
+            Value::Function(ip, _) | Value::Closure(ip, _, _) => Some(*ip),
+            _ => None,
+        };
+        let text = if ip.is_some_and(|ip| self.chunk.class_constructor_ips.contains(&ip)) {
+            if name.is_empty() {
+                "[class (anonymous)]".to_string()
+            } else {
+                format!("[class {name}]")
+            }
+        } else {
+            let is_async = ip.is_some_and(|ip| self.chunk.async_function_ips.contains(&ip));
+            let is_generator = ip.is_some_and(|ip| self.chunk.generator_function_ips.contains(&ip));
+            let kind = match (is_async, is_generator) {
+                (true, true) => "AsyncGeneratorFunction",
+                (true, false) => "AsyncFunction",
+                (false, true) => "GeneratorFunction",
+                (false, false) => "Function",
+            };
+            if name.is_empty() {
+                format!("[{kind} (anonymous)]")
+            } else {
+                format!("[{kind}: {name}]")
+            }
+        };
+        st.stylize(text, Style::Special)
+    }
+
+    fn inspect_array(&mut self, ctx: &GcContext<'gc>, arr: &ArrayHandle<'gc>, level: usize, st: &mut InspectState) -> String {
+        let (elements, typed_name, extra_keys, holes) = {
+            let borrow = arr.borrow();
+            let typed_name = borrow.props.get("__typedarray_name__").map(value_to_string);
+            let holes: Vec<bool> = (0..borrow.elements.len())
+                .map(|i| matches!(borrow.elements[i], Value::Uninitialized) || borrow.props.contains_key(&format!("__deleted_{i}")))
+                .collect();
+            let extra_keys: Vec<(String, Value<'gc>)> = self
+                .collect_object_map_keys(&borrow.props, true)
+                .into_iter()
+                .filter(|k| k != "length" && k.parse::<usize>().is_err())
+                .filter_map(|k| own_data_from_legacy_map(&borrow.props, &k).map(|v| (k, v)))
+                .collect();
+            (borrow.elements.clone(), typed_name, extra_keys, holes)
+        };
+
+        let prefix = match &typed_name {
+            Some(name) => format!("{name}({}) ", elements.len()),
+            None => String::new(),
+        };
+        if st.too_deep(level) {
+            let label = typed_name.unwrap_or_else(|| "Array".to_string());
+            return st.stylize(format!("[{label}]"), Style::Special);
+        }
+
+        let mut entries = Vec::new();
+        let mut index = 0;
+        while index < elements.len() && entries.len() < st.opts.max_array_length {
+            if holes[index] {
+                let start = index;
+                while index < elements.len() && holes[index] {
+                    index += 1;
+                }
+                let count = index - start;
+                let plural = if count == 1 { "" } else { "s" };
+                entries.push(st.stylize(format!("<{count} empty item{plural}>"), Style::Undefined));
+                continue;
+            }
+            entries.push(self.inspect_inner(ctx, &elements[index], level + 1, st));
+            index += 1;
+        }
+        if index < elements.len() {
+            let remaining = elements.len() - index;
+            let plural = if remaining == 1 { "" } else { "s" };
+            entries.push(format!("... {remaining} more item{plural}"));
+        }
+        for (key, value) in extra_keys {
+            let formatted = self.inspect_inner(ctx, &value, level + 1, st);
+            entries.push(format!("{}: {formatted}", Self::inspect_key(&key, st)));
+        }
+        Self::inspect_join(&prefix, "[", "]", entries, level, st)
+    }
+
+    fn inspect_object(&mut self, ctx: &GcContext<'gc>, obj: &ObjectHandle<'gc>, level: usize, st: &mut InspectState) -> String {
+        let type_name = obj.borrow().get("__type__").map(value_to_string).unwrap_or_default();
+
+        if let Some(target) = obj.borrow().get("__proxy_target__").cloned() {
+            return self.inspect_inner(ctx, &target, level, st);
+        }
+
+        // Errors print their stack, or `[Name: message]` when there is none.
+        let error_text = Self::format_vm_error_object(&obj.borrow());
+        if let Some(text) = error_text {
+            return match obj.borrow().get("stack") {
+                Some(Value::String(stack)) => crate::unicode::utf16_to_utf8(stack),
+                _ => format!("[{text}]"),
+            };
+        }
+
+        if type_name == "RegExp" {
+            let borrow = obj.borrow();
+            let pattern = borrow
+                .get("__regex_pattern__")
+                .map(|v| match v {
+                    Value::String(s) => crate::unicode::utf16_to_utf8(s),
+                    other => value_to_string(other),
+                })
+                .unwrap_or_default();
+            let flags = borrow.get("__regex_flags__").map(value_to_string).unwrap_or_default();
+            return st.stylize(format!("/{pattern}/{flags}"), Style::RegExp);
+        }
+
+        if let Some(Value::Number(ms)) = obj.borrow().get("__date_ms__") {
+            let text = if ms.is_finite() {
+                Self::format_iso_string_arithmetic(*ms)
+            } else {
+                "Invalid Date".to_string()
+            };
+            return st.stylize(text, Style::Date);
+        }
+
+        if let (Some(inner), "String" | "Number" | "Boolean" | "BigInt" | "Symbol") =
+            (obj.borrow().get("__value__").cloned(), type_name.as_str())
+        {
+            let formatted = self.inspect_inner(ctx, &inner, level, st);
+            return format!("[{type_name}: {formatted}]");
+        }
+
+        if st.too_deep(level) {
+            let label = match self.inspect_constructor_name(ctx, obj) {
+                Some(name) if !name.is_empty() => name,
+                _ => "Object".to_string(),
+            };
+            return st.stylize(format!("[{label}]"), Style::Special);
+        }
+
+        if type_name == "Promise" {
+            let (value, rejected) = {
+                let borrow = obj.borrow();
+                (
+                    borrow.get("__promise_value__").cloned(),
+                    matches!(borrow.get("__promise_rejected__"), Some(Value::Boolean(true))),
+                )
+            };
+            let entry = match value {
+                None => st.stylize("<pending>".to_string(), Style::Special),
+                Some(v) if rejected => {
+                    let marker = st.stylize("<rejected>".to_string(), Style::Special);
+                    format!("{marker} {}", self.inspect_inner(ctx, &v, level + 1, st))
+                }
+                Some(v) => self.inspect_inner(ctx, &v, level + 1, st),
+            };
+            return Self::inspect_join("Promise ", "{", "}", vec![entry], level, st);
+        }
+
+        if type_name == "WeakMap" || type_name == "WeakSet" || type_name == "WeakRef" {
+            return format!("{type_name} {{ {} }}", st.stylize("<items unknown>".to_string(), Style::Special));
+        }
+
+        let map_data = obj.borrow().get("__map_data__").cloned();
+        if let Some(Value::Map(map)) = map_data {
+            let entries = map.borrow().entries.clone();
+            return self.inspect_map_entries(ctx, "Map", &entries, level, st);
+        }
+        let set_data = obj.borrow().get("__set_data__").cloned();
+        if let Some(Value::Set(set)) = set_data {
+            let values = set.borrow().values.clone();
+            return self.inspect_set_values(ctx, "Set", &values, level, st);
+        }
+
+        if type_name == "ArrayBuffer" || type_name == "SharedArrayBuffer" {
+            let bytes: Vec<u8> = match obj.borrow().get("__buffer_bytes__") {
+                Some(Value::Array(arr)) => arr.borrow().elements.iter().map(|b| to_number(b) as u8).collect(),
+                _ => Vec::new(),
+            };
+            let shown: Vec<String> = bytes.iter().take(50).map(|b| format!("{b:02x}")).collect();
+            let more = if bytes.len() > 50 {
+                format!(" ... {} more bytes", bytes.len() - 50)
+            } else {
+                String::new()
+            };
+            let contents = format!("[Uint8Contents]: <{}{more}>", shown.join(" "));
+            let length = format!("byteLength: {}", st.stylize(bytes.len().to_string(), Style::Number));
+            return Self::inspect_join(&format!("{type_name} "), "{", "}", vec![contents, length], level, st);
+        }
+
+        if obj.borrow().contains_key("__module_namespace__") {
+            let value = Value::Object(*obj);
+            let keys = self.collect_enumerable_own_keys(ctx, &value);
+            self.pending_throw = None;
+            let mut entries = Vec::new();
+            for key in keys {
+                let export = self.read_named_property(ctx, &value, &key);
+                let formatted = if self.pending_throw.take().is_some() {
+                    st.stylize("<uninitialized>".to_string(), Style::Special)
+                } else {
+                    self.inspect_inner(ctx, &export, level + 1, st)
+                };
+                entries.push(format!("{}: {formatted}", Self::inspect_key(&key, st)));
+            }
+            return Self::inspect_join("[Module: null prototype] ", "{", "}", entries, level, st);
+        }
+
+        self.inspect_plain_object(ctx, obj, level, st)
+    }
+
+    fn inspect_plain_object(&mut self, ctx: &GcContext<'gc>, obj: &ObjectHandle<'gc>, level: usize, st: &mut InspectState) -> String {
+        let null_proto = matches!(obj.borrow().get("__proto__"), Some(Value::Null));
+        let class_name = self.inspect_constructor_name(ctx, obj);
+
+        let (props, symbol_props, tag) = {
+            let borrow = obj.borrow();
+            let mut props = Vec::new();
+            for key in self.collect_object_map_keys(&borrow, true) {
+                let lookup = if key == "__proto__" {
+                    OWN_DUNDER_PROTO_DATA_KEY
+                } else {
+                    key.as_str()
+                };
+                if let Some(desc) = desc_from_legacy_map(&borrow, lookup) {
+                    props.push((key, desc.kind));
+                }
+            }
+            let mut symbol_props = Vec::new();
+            for raw_key in borrow.keys() {
+                if let Some(id) = raw_key.strip_prefix("@@sym:").and_then(|id| id.parse::<u64>().ok())
+                    && !has_nonenumerable_mark(&borrow, raw_key)
+                    && let Some(desc) = desc_from_legacy_map(&borrow, raw_key)
+                {
+                    symbol_props.push((id, desc.kind));
+                }
+            }
+            let tag = own_data_from_legacy_map(&borrow, "@@sym:4").and_then(|v| match v {
+                Value::String(s) => Some(crate::unicode::utf16_to_utf8(&s)),
+                _ => None,
+            });
+            (props, symbol_props, tag)
+        };
+
+        let mut entries = Vec::new();
+        for (key, kind) in props {
+            let formatted = self.inspect_property(ctx, kind, level, st);
+            entries.push(format!("{}: {formatted}", Self::inspect_key(&key, st)));
+        }
+        for (id, kind) in symbol_props {
+            let key = match self.get_symbol_value(ctx, id).and_then(|sym| self.symbol_display_string(&sym)) {
+                Some(text) => st.stylize(text, Style::Symbol),
+                None => continue,
+            };
+            let formatted = self.inspect_property(ctx, kind, level, st);
+            entries.push(format!("[{key}]: {formatted}"));
+        }
+
+        let mut prefix = match (&class_name, null_proto) {
+            (_, true) => "[Object: null prototype] ".to_string(),
+            (Some(name), false) => format!("{name} "),
+            (None, false) => String::new(),
+        };
+        if let Some(tag) = tag
+            && class_name.as_deref() != Some(tag.as_str())
+        {
+            if prefix.is_empty() {
+                prefix = "Object ".to_string();
+            }
+            prefix.push_str(&format!("[{tag}] "));
+        }
+        Self::inspect_join(&prefix, "{", "}", entries, level, st)
+    }
+
+    fn inspect_property(&mut self, ctx: &GcContext<'gc>, kind: PropKind<'gc>, level: usize, st: &mut InspectState) -> String {
+        match kind {
+            PropKind::Data(value) => self.inspect_inner(ctx, &value, level + 1, st),
+            PropKind::Accessor { get, set } => {
+                let has_get = get.is_some_and(|g| !matches!(g, Value::Undefined));
+                let has_set = set.is_some_and(|s| !matches!(s, Value::Undefined));
+                let text = match (has_get, has_set) {
+                    (true, true) => "[Getter/Setter]",
+                    (true, false) => "[Getter]",
+                    (false, true) => "[Setter]",
+                    (false, false) => "undefined",
+                };
+                st.stylize(text.to_string(), Style::Special)
+            }
+        }
+    }
+
+    fn inspect_map_entries(
+        &mut self,
+        ctx: &GcContext<'gc>,
+        label: &str,
+        entries: &[(Value<'gc>, Value<'gc>)],
+        level: usize,
+        st: &mut InspectState,
+    ) -> String {
+        let prefix = format!("{label}({}) ", entries.len());
+        if st.too_deep(level) {
+            return st.stylize(format!("[{label}]"), Style::Special);
+        }
+        let mut parts = Vec::new();
+        for (key, value) in entries.iter().take(st.opts.max_array_length) {
+            let key = self.inspect_inner(ctx, key, level + 1, st);
+            let value = self.inspect_inner(ctx, value, level + 1, st);
+            parts.push(format!("{key} => {value}"));
+        }
+        if entries.len() > st.opts.max_array_length {
+            parts.push(format!("... {} more items", entries.len() - st.opts.max_array_length));
+        }
+        Self::inspect_join(&prefix, "{", "}", parts, level, st)
+    }
+
+    fn inspect_set_values(
+        &mut self,
+        ctx: &GcContext<'gc>,
+        label: &str,
+        values: &[Value<'gc>],
+        level: usize,
+        st: &mut InspectState,
+    ) -> String {
+        let prefix = format!("{label}({}) ", values.len());
+        if st.too_deep(level) {
+            return st.stylize(format!("[{label}]"), Style::Special);
+        }
+        let mut parts = Vec::new();
+        for value in values.iter().take(st.opts.max_array_length) {
+            parts.push(self.inspect_inner(ctx, value, level + 1, st));
+        }
+        if values.len() > st.opts.max_array_length {
+            parts.push(format!("... {} more items", values.len() - st.opts.max_array_length));
+        }
+        Self::inspect_join(&prefix, "{", "}", parts, level, st)
+    }
+
+    /// Name of the constructor on `obj`'s prototype chain, or `None` for plain objects.
+    fn inspect_constructor_name(&mut self, ctx: &GcContext<'gc>, obj: &ObjectHandle<'gc>) -> Option<String> {
+        let object_proto = match self.globals.get("Object") {
+            Some(Value::Object(ctor)) => own_data_from_legacy_map(&ctor.borrow(), "prototype"),
+            _ => None,
+        };
+        let mut current = obj.borrow().get("__proto__").cloned();
+        for _ in 0..64 {
+            let Some(Value::Object(proto)) = current else {
+                return None;
+            };
+            if let Some(Value::Object(object_proto)) = &object_proto
+                && Gc::ptr_eq(proto, *object_proto)
+            {
+                return None;
+            }
+            let ctor = own_data_from_legacy_map(&proto.borrow(), "constructor");
+            if let Some(ctor) = ctor
+                && self.is_callable_value(&ctor)
+            {
+                let name = self.inspect_read_name(ctx, &ctor);
+                return if name == "Object" { None } else { Some(name) };
+            }
+            current = proto.borrow().get("__proto__").cloned();
+        }
+        None
+    }
+
+    fn inspect_key(key: &str, st: &InspectState) -> String {
+        if crate::core::is_identifier_name(key) {
+            key.to_string()
+        } else {
+            st.stylize(quote_js_string(key), Style::String)
+        }
+    }
+
+    /// Join formatted entries on one line when they fit in `break_length`,
+    /// otherwise one entry per line indented to `level`.
+    fn inspect_join(prefix: &str, open: &str, close: &str, entries: Vec<String>, level: usize, st: &InspectState) -> String {
+        if entries.is_empty() {
+            return format!("{prefix}{open}{close}");
+        }
+        let indent = "  ".repeat(level);
+        let total: usize = entries.iter().map(|e| visible_len(e) + 2).sum::<usize>() + visible_len(prefix) + indent.len() + 2;
+        if total <= st.opts.break_length && !entries.iter().any(|e| e.contains('\n')) {
+            return format!("{prefix}{open} {} {close}", entries.join(", "));
+        }
+        let separator = format!(",\n{indent}  ");
+        format!("{prefix}{open}\n{indent}  {}\n{indent}{close}", entries.join(&separator))
+    }
+}
@@ -1,3 +1,7 @@
+// Without the `temporal` feature no Temporal objects exist, so the code that
+// formats them is never reached.
+#![cfg_attr(not(feature = "temporal"), allow(dead_code))]
+
 use super::*;
 use crate::core::GcPtr;
 use std::str::FromStr;
@@ -1928,7 +1932,14 @@ impl<'gc> VM<'gc> {
             return Ok(record);
         }
         self.intl_validate_duration_input(ctx, value)?;
-        let duration = self.temporal_to_duration(ctx, value).map_err(|err| {
+        #[cfg(feature = "temporal")]
+        let duration = self.temporal_to_duration(ctx, value);
+        #[cfg(not(feature = "temporal"))]
+        let duration = match value {
+            Value::String(text) => temporal_rs::Duration::from_utf8(crate::unicode::utf16_to_utf8(text).as_bytes()),
+            _ => Err(temporal_rs::TemporalError::r#type().with_message("Invalid Temporal input")),
+        };
+        let duration = duration.map_err(|err| {
             let js_err: JSError = err.into();
             self.vm_value_from_error(ctx, &js_err)
         })?;
@@ -3096,25 +3107,9 @@ impl<'gc> VM<'gc> {
             self.pending_throw = Some(self.make_type_error_object(ctx, "startDate/endDate is required"));
             return Value::Undefined;
         }
-        let start_temporal_kind = self.temporal_value_kind(start_value);
-        let end_temporal_kind = self.temporal_value_kind(end_value);
-        if start_temporal_kind.is_some() || end_temporal_kind.is_some() {
-            if start_temporal_kind.is_none() {
-                let _ = self.extract_number_with_coercion(ctx, start_value);
-                if self.pending_throw.is_some() {
-                    return Value::Undefined;
-                }
-            }
-            if end_temporal_kind.is_none() {
-                let _ = self.extract_number_with_coercion(ctx, end_value);
-                if self.pending_throw.is_some() {
-                    return Value::Undefined;
-                }
-            }
-            if start_temporal_kind != end_temporal_kind {
-                self.pending_throw = Some(self.make_type_error_object(ctx, "DateTimeFormat range arguments must have matching kinds"));
-                return Value::Undefined;
-            }
+        #[cfg(feature = "temporal")]
+        if !self.intl_date_time_range_kinds_match(ctx, start_value, end_value) {
+            return Value::Undefined;
         }
         let borrow = formatter.borrow();
         let Some(start_input) = self.intl_date_time_format_required_input(ctx, start_value) else {
@@ -3205,25 +3200,9 @@ impl<'gc> VM<'gc> {
             self.pending_throw = Some(self.make_type_error_object(ctx, "startDate/endDate is required"));
             return Value::Undefined;
         }
-        let start_temporal_kind = self.temporal_value_kind(start_value);
-        let end_temporal_kind = self.temporal_value_kind(end_value);
-        if start_temporal_kind.is_some() || end_temporal_kind.is_some() {
-            if start_temporal_kind.is_none() {
-                let _ = self.extract_number_with_coercion(ctx, start_value);
-                if self.pending_throw.is_some() {
-                    return Value::Undefined;
-                }
-            }
-            if end_temporal_kind.is_none() {
-                let _ = self.extract_number_with_coercion(ctx, end_value);
-                if self.pending_throw.is_some() {
-                    return Value::Undefined;
-                }
-            }
-            if start_temporal_kind != end_temporal_kind {
-                self.pending_throw = Some(self.make_type_error_object(ctx, "DateTimeFormat range arguments must have matching kinds"));
-                return Value::Undefined;
-            }
+        #[cfg(feature = "temporal")]
+        if !self.intl_date_time_range_kinds_match(ctx, start_value, end_value) {
+            return Value::Undefined;
         }
         let borrow = formatter.borrow();
         let Some(start_input) = self.intl_date_time_format_required_input(ctx, start_value) else {
@@ -4700,7 +4679,7 @@ impl<'gc> VM<'gc> {
 
     fn intl_date_time_format_single_value(&mut self, ctx: &GcContext<'gc>, value: Option<&Value<'gc>>) -> Option<i64> {
         let x = if value.is_none() || matches!(value, Some(Value::Undefined)) {
-            chrono::Utc::now().timestamp_millis() as f64
+            self.clock.epoch_ms()
         } else if let Some(Value::Object(obj)) = value {
             if let Some(Value::Number(ms)) = obj.borrow().get("__date_ms__").cloned() {
                 ms
@@ -4839,29 +4818,39 @@ impl<'gc> VM<'gc> {
                 .intl_date_time_format_single_value(ctx, Some(value))
                 .map(IntlDateTimeInput::Legacy);
         }
-        match self.temporal_value_kind(value).as_deref() {
-            Some("Instant") => {
+        #[cfg(feature = "temporal")]
+        if let Some(kind) = self.temporal_value_kind(value) {
+            return self.intl_temporal_date_time_input(ctx, value, &kind);
+        }
+        self.intl_date_time_format_single_value(ctx, Some(value))
+            .map(IntlDateTimeInput::Legacy)
+    }
+
+    #[cfg(feature = "temporal")]
+    fn intl_temporal_date_time_input(&mut self, ctx: &GcContext<'gc>, value: &Value<'gc>, kind: &str) -> Option<IntlDateTimeInput> {
+        match kind {
+            "Instant" => {
                 let instant = self.temporal_expect_instant(ctx, Some(value))?;
                 let epoch_ms = i64::try_from(instant.epoch_nanoseconds().as_i128() / 1_000_000).ok()?;
                 Some(IntlDateTimeInput::Instant(epoch_ms))
             }
-            Some("PlainDate") => self
+            "PlainDate" => self
                 .temporal_expect_plain_date(ctx, Some(value))
                 .map(Self::intl_temporal_fields_from_date)
                 .map(IntlDateTimeInput::Plain),
-            Some("PlainDateTime") => self
+            "PlainDateTime" => self
                 .temporal_expect_plain_date_time(ctx, Some(value))
                 .map(Self::intl_temporal_fields_from_datetime)
                 .map(IntlDateTimeInput::Plain),
-            Some("PlainTime") => self
+            "PlainTime" => self
                 .temporal_expect_plain_time(ctx, Some(value))
                 .map(Self::intl_temporal_fields_from_time)
                 .map(IntlDateTimeInput::Plain),
-            Some("PlainYearMonth") => self
+            "PlainYearMonth" => self
                 .temporal_parse_plain_year_month_repr(ctx, Some(value))
                 .map(Self::intl_temporal_fields_from_year_month)
                 .map(IntlDateTimeInput::Plain),
-            Some("PlainMonthDay") => self
+            "PlainMonthDay" => self
                 .temporal_parse_plain_month_day_repr(ctx, Some(value))
                 .map(Self::intl_temporal_fields_from_month_day)
                 .map(IntlDateTimeInput::Plain),
@@ -4871,6 +4860,34 @@ impl<'gc> VM<'gc> {
         }
     }
 
+    /// The two ends of a range must both be the same kind of Temporal object
+    /// or both be something else; `false` once that check has thrown.
+    #[cfg(feature = "temporal")]
+    fn intl_date_time_range_kinds_match(&mut self, ctx: &GcContext<'gc>, start_value: &Value<'gc>, end_value: &Value<'gc>) -> bool {
+        let start_temporal_kind = self.temporal_value_kind(start_value);
+        let end_temporal_kind = self.temporal_value_kind(end_value);
+        if start_temporal_kind.is_none() && end_temporal_kind.is_none() {
+            return true;
+        }
+        if start_temporal_kind.is_none() {
+            let _ = self.extract_number_with_coercion(ctx, start_value);
+            if self.pending_throw.is_some() {
+                return false;
+            }
+        }
+        if end_temporal_kind.is_none() {
+            let _ = self.extract_number_with_coercion(ctx, end_value);
+            if self.pending_throw.is_some() {
+                return false;
+            }
+        }
+        if start_temporal_kind != end_temporal_kind {
+            self.pending_throw = Some(self.make_type_error_object(ctx, "DateTimeFormat range arguments must have matching kinds"));
+            return false;
+        }
+        true
+    }
+
     fn intl_date_time_format_required_input(&mut self, ctx: &GcContext<'gc>, value: &Value<'gc>) -> Option<IntlDateTimeInput> {
         self.intl_date_time_format_input(ctx, Some(value))
     }
@@ -6738,7 +6755,8 @@ impl<'gc> VM<'gc> {
             calendar: ext_calendar.clone().unwrap_or_else(|| "gregory".to_string()),
             numbering_system: ext_numbering_system.clone().unwrap_or(default_numbering_system),
             time_zone: "UTC".to_string(),
-            time_zone_explicit: false,
+            // A deterministic run formats in UTC rather than the host zone.
+            time_zone_explicit: self.clock.is_deterministic(),
             year: None,
             month: None,
             day: None,
@@ -0,0 +1,405 @@
+use super::*;
+use crate::core::{HeapStats, InterruptHandle};
+use std::cell::{Cell, RefCell};
+
+/// How many instructions run between polls of the interrupt flag and heap size.
+const LIMIT_POLL_INTERVAL: u64 = 1024;
+
+/// Reason a run was aborted. Once recorded, no JS `catch`/`finally` can
+/// observe or intercept it; the VM unwinds straight back to the embedder.
+#[derive(Clone, Debug)]
+pub(crate) enum Termination {
+    BudgetExhausted { budget: u64 },
+    Interrupted,
+    OutOfMemory { limit: usize },
+}
+
+impl Termination {
+    fn to_js_error(&self) -> JSError {
+        match self {
+            Termination::BudgetExhausted { budget } => crate::raise_infinite_loop_error!(*budget as usize),
+            Termination::Interrupted => crate::raise_interrupted_error!(),
+            Termination::OutOfMemory { limit } => crate::raise_out_of_memory_error!(*limit),
+        }
+    }
+}
+
+/// How far [`VM::run_slice`] got.
+pub(crate) enum RunSlice<T> {
+    /// The heap limit was crossed where the run can stop. The embedder
+    /// collects garbage, calls [`VM::check_heap_after_collection`] and then
+    /// continues the run.
+    Paused,
+    Done(T),
+}
+
+/// Execution limits shared (via `Rc`) by a VM and every helper VM it spawns,
+/// so eval, realms, timers and microtasks all draw from one budget.
+#[derive(Default)]
+pub(crate) struct ExecutionLimits {
+    instruction_budget: Cell<Option<u64>>,
+    instructions_executed: Cell<u64>,
+    termination: RefCell<Option<Termination>>,
+    interrupt: RefCell<InterruptHandle>,
+    /// Set once the embedder holds the interrupt handle and may raise it.
+    interrupt_shared: Cell<bool>,
+    heap_limit: Cell<Option<usize>>,
+    /// Whether any limit applies to the current run, so the interpreter loop
+    /// can skip the accounting entirely when none does.
+    active: Cell<bool>,
+    /// Nesting of `run_inner` calls across the VM and its helpers.
+    run_depth: Cell<usize>,
+    /// Set while the outermost `run_inner` belongs to [`VM::run_slice`], which
+    /// can leave the mutation between two opcodes.
+    collection_point: Cell<bool>,
+    /// The heap limit was crossed; stop at the collection point once reached.
+    collection_requested: Cell<bool>,
+    /// The outermost run stopped for a collection rather than finishing.
+    paused: Cell<bool>,
+}
+
+impl<'gc> VM<'gc> {
+    /// Limit the number of opcodes a single top-level run may execute.
+    pub(crate) fn set_instruction_budget(&mut self, budget: Option<u64>) {
+        self.limits.instruction_budget.set(budget);
+    }
+
+    /// Ceiling on the bytes tracked by the arena's allocation metrics, which
+    /// include the storage counted by [`charge_external`](Self::charge_external).
+    ///
+    /// The arena can only collect outside a mutation. When a run driven by
+    /// [`run_slice`](Self::run_slice) crosses the limit it pauses, the
+    /// embedder forces a full collection, and the run only fails if the heap
+    /// is still over the limit. Inside a call made from native code (a
+    /// callback of a builtin, a constructor, a timer) the run cannot pause, so
+    /// allocation there may reach twice the limit before the pause, and runs
+    /// that cannot pause at all fail as soon as the limit is crossed.
+    pub(crate) fn set_heap_limit(&mut self, limit: Option<usize>) {
+        self.limits.heap_limit.set(limit);
+    }
+
+    pub(crate) fn heap_limit(&self) -> Option<usize> {
+        self.limits.heap_limit.get()
+    }
+
+    /// Poll `handle` instead of the VM's own interrupt flag.
+    pub(crate) fn set_interrupt_handle(&mut self, handle: InterruptHandle) {
+        self.limits.interrupt.replace(handle);
+        self.limits.interrupt_shared.set(true);
+    }
+
+    pub(crate) fn interrupt_handle(&self) -> InterruptHandle {
+        self.limits.interrupt_shared.set(true);
+        self.limits.interrupt.borrow().clone()
+    }
+
+    /// Start a fresh top-level run: clear the opcode counter and any
+    /// termination left over from a previous run. Interrupts requested
+    /// before the run starts were meant for an earlier one and are dropped.
+    pub(crate) fn begin_limited_run(&mut self) {
+        self.limits.instructions_executed.set(0);
+        self.limits.termination.replace(None);
+        self.limits.collection_requested.set(false);
+        self.limits.interrupt.borrow().begin_run();
+        let limits = &self.limits;
+        let active = limits.instruction_budget.get().is_some() || limits.heap_limit.get().is_some() || limits.interrupt_shared.get();
+        limits.active.set(active);
+    }
+
+    /// Whether [`charge_instruction`](Self::charge_instruction) has anything to check this run.
+    #[inline]
+    pub(super) fn limits_active(&self) -> bool {
+        self.limits.active.get()
+    }
+
+    /// Turn a pending interrupt request for this run into a termination.
+    fn poll_interrupt(&self) {
+        let interrupt = self.limits.interrupt.borrow();
+        if interrupt.is_interrupted() && self.limits.termination.borrow().is_none() {
+            interrupt.reset();
+            self.limits.termination.replace(Some(Termination::Interrupted));
+        }
+    }
+
+    /// Count one opcode against the budget; fails once the run is terminating.
+    #[inline]
+    pub(super) fn charge_instruction(&mut self, ctx: &GcContext<'gc>) -> Result<(), JSError> {
+        let executed = self.limits.instructions_executed.get() + 1;
+        self.limits.instructions_executed.set(executed);
+        if let Some(budget) = self.limits.instruction_budget.get()
+            && executed > budget
+            && self.limits.termination.borrow().is_none()
+        {
+            self.limits.termination.replace(Some(Termination::BudgetExhausted { budget }));
+        }
+        if executed.is_multiple_of(LIMIT_POLL_INTERVAL) {
+            self.poll_interrupt();
+            self.poll_heap_limit(ctx);
+        }
+        match self.limits.termination.borrow().as_ref() {
+            Some(termination) => Err(termination.to_js_error()),
+            None => Ok(()),
+        }
+    }
+
+    fn poll_heap_limit(&self, ctx: &GcContext<'gc>) {
+        let limits = &self.limits;
+        let Some(limit) = limits.heap_limit.get() else {
+            return;
+        };
+        let allocated = ctx.metrics().total_allocation();
+        if allocated <= limit || limits.termination.borrow().is_some() {
+            return;
+        }
+        // Most of what was allocated may be garbage; only a collection can
+        // tell, and that has to wait until the run pauses.
+        if limits.collection_point.get() && allocated <= limit.saturating_mul(2) {
+            limits.collection_requested.set(true);
+        } else {
+            limits.termination.replace(Some(Termination::OutOfMemory { limit }));
+        }
+    }
+
+    /// Whether the run should stop before the next opcode so the embedder
+    /// can collect garbage. Only the outermost `run_inner` of a
+    /// [`run_slice`](Self::run_slice) stops: below it, native frames hold
+    /// values the collector cannot see.
+    #[inline]
+    pub(super) fn pause_for_collection(&self) -> bool {
+        let limits = &self.limits;
+        if limits.collection_requested.get() && limits.collection_point.get() && limits.run_depth.get() == 1 {
+            limits.collection_requested.set(false);
+            limits.paused.set(true);
+            return true;
+        }
+        false
+    }
+
+    #[inline]
+    pub(super) fn enter_run(&self) {
+        self.limits.run_depth.set(self.limits.run_depth.get() + 1);
+    }
+
+    #[inline]
+    pub(super) fn leave_run(&self) {
+        self.limits.run_depth.set(self.limits.run_depth.get() - 1);
+    }
+
+    /// Run the installed code like [`run`](Self::run), but return
+    /// [`RunSlice::Paused`] when the heap limit is crossed, leaving the VM
+    /// ready to continue with another call once garbage has been collected.
+    pub(crate) fn run_slice(&mut self, ctx: &GcContext<'gc>) -> RunSlice<Result<Value<'gc>, JSError>> {
+        self.limits.collection_point.set(true);
+        let result = self.run_inner(ctx, 0);
+        self.limits.collection_point.set(false);
+        if self.limits.paused.take() {
+            return RunSlice::Paused;
+        }
+        RunSlice::Done(result.and_then(|value| self.finish_run(ctx, value)))
+    }
+
+    /// Fail the paused run if the heap is still over the limit once the
+    /// embedder has collected garbage.
+    pub(crate) fn check_heap_after_collection(&self, ctx: &GcContext<'gc>) {
+        self.recount_external_storage(ctx);
+        if let Some(limit) = self.limits.heap_limit.get()
+            && ctx.metrics().total_allocation() > limit
+            && self.limits.termination.borrow().is_none()
+        {
+            self.limits.termination.replace(Some(Termination::OutOfMemory { limit }));
+        }
+    }
+
+    pub(super) fn is_terminating(&self) -> bool {
+        self.poll_interrupt();
+        self.limits.termination.borrow().is_some()
+    }
+
+    pub(super) fn termination_error(&self) -> Option<JSError> {
+        self.poll_interrupt();
+        self.limits.termination.borrow().as_ref().map(Termination::to_js_error)
+    }
+
+    /// Drop work queued by a terminated run so the VM can accept new input.
+    pub(super) fn abandon_terminated_run(&mut self) {
+        self.pending_throw = None;
+        self.microtask_queue.clear();
+        self.abandon_timers();
+        self.async_atomics_waits.clear();
+        self.async_host.abandon_pending();
+        self.rejections.abandon_pending();
+        self.try_stack.clear();
+        self.truncate_call_frames_with_context(0);
+        self.stack.clear();
+    }
+
+    /// Count the objects reachable through properties from the global scope.
+    pub(crate) fn heap_stats(&self, ctx: &GcContext<'gc>) -> HeapStats {
+        let mut stats = HeapStats {
+            allocated_bytes: ctx.metrics().total_allocation(),
+            heap_limit: self.heap_limit(),
+            ..HeapStats::default()
+        };
+        let mut roots = vec![Value::Object(self.global_this)];
+        roots.extend(self.globals.values().cloned());
+        roots.extend(self.module_locals.values().cloned());
+        Self::visit_reachable(roots, |value| match value {
+            Value::Object(_) => stats.object_count += 1,
+            Value::Array(_) => stats.array_count += 1,
+            Value::Map(_) | Value::Set(_) => stats.collection_count += 1,
+            _ => {}
+        });
+        stats
+    }
+
+    /// Count `bytes` of storage the arena does not allocate itself (string
+    /// contents, array elements, property maps) toward the heap limit. Like
+    /// arena allocations, garbage stays counted until the next full
+    /// collection, after which [`recount_external_storage`](Self::recount_external_storage)
+    /// replaces the total with what is still reachable.
+    #[inline]
+    pub(crate) fn charge_external(&self, ctx: &GcContext<'gc>, bytes: usize) {
+        if bytes > 0 && self.limits.heap_limit.get().is_some() {
+            ctx.metrics().mark_external_allocation(bytes);
+        }
+    }
+
+    /// Run a builtin function, charging the string it returns.
+    pub(super) fn call_builtin(&mut self, ctx: &GcContext<'gc>, id: FunctionID, args: &[Value<'gc>]) -> Value<'gc> {
+        let result = self.call_builtin_uncharged(ctx, id, args);
+        if let Value::String(_) = &result {
+            self.charge_external(ctx, external_bytes(&result));
+        }
+        result
+    }
+
+    /// Run a builtin method, charging the string it returns and whatever the
+    /// receiver's own storage grew by (`push`, `set`, `add`, ...).
+    pub(super) fn call_method_builtin(
+        &mut self,
+        ctx: &GcContext<'gc>,
+        id: FunctionID,
+        receiver: &Value<'gc>,
+        args: &[Value<'gc>],
+    ) -> Value<'gc> {
+        if self.limits.heap_limit.get().is_none() {
+            return self.call_method_builtin_uncharged(ctx, id, receiver, args);
+        }
+        let before = external_bytes(receiver);
+        let result = self.call_method_builtin_uncharged(ctx, id, receiver, args);
+        let mut grown = external_bytes(receiver).saturating_sub(before);
+        if let Value::String(_) = &result {
+            grown += external_bytes(&result);
+        }
+        self.charge_external(ctx, grown);
+        result
+    }
+
+    /// Replace the external storage counted since the last collection with
+    /// the storage reachable from the VM's roots.
+    pub(crate) fn recount_external_storage(&self, ctx: &GcContext<'gc>) {
+        let metrics = ctx.metrics();
+        let counted = metrics.total_external_allocation();
+        let reachable = if self.limits.heap_limit.get().is_some() {
+            self.reachable_external_bytes()
+        } else {
+            0
+        };
+        if reachable < counted {
+            metrics.mark_external_deallocation(counted - reachable);
+        } else {
+            metrics.mark_external_allocation(reachable - counted);
+        }
+    }
+
+    fn reachable_external_bytes(&self) -> usize {
+        let mut roots = vec![Value::Object(self.global_this)];
+        roots.extend(self.globals.values().cloned());
+        roots.extend(self.module_locals.values().cloned());
+        roots.extend(self.stack.iter().cloned());
+        roots.extend(self.this_stack.iter().cloned());
+        let cells = self
+            .frames
+            .iter()
+            .flat_map(|frame| frame.upvalues.iter().chain(frame.local_cells.values()))
+            .chain(self.top_level_cells.values());
+        roots.extend(cells.map(|cell| cell.borrow().clone()));
+        let mut bytes = 0;
+        Self::visit_reachable(roots, |value| bytes += external_bytes(value));
+        bytes
+    }
+
+    /// Call `visit` once for each string reached from `roots` and once for
+    /// each distinct object, array, map and set, following properties,
+    /// elements, entries and closure upvalues.
+    fn visit_reachable(roots: Vec<Value<'gc>>, mut visit: impl FnMut(&Value<'gc>)) {
+        let mut seen = std::collections::HashSet::new();
+        let mut pending = roots;
+        while let Some(value) = pending.pop() {
+            match &value {
+                Value::Object(obj) => {
+                    if seen.insert(Gc::as_ptr(*obj) as usize) {
+                        pending.extend(obj.borrow().values().cloned());
+                        visit(&value);
+                    }
+                }
+                Value::Array(arr) => {
+                    if seen.insert(Gc::as_ptr(*arr) as usize) {
+                        let borrow = arr.borrow();
+                        pending.extend(borrow.elements.iter().cloned());
+                        pending.extend(borrow.props.values().cloned());
+                        drop(borrow);
+                        visit(&value);
+                    }
+                }
+                Value::Map(map) => {
+                    if seen.insert(Gc::as_ptr(*map) as usize) {
+                        for (k, v) in &map.borrow().entries {
+                            pending.push(k.clone());
+                            pending.push(v.clone());
+                        }
+                        visit(&value);
+                    }
+                }
+                Value::Set(set) => {
+                    if seen.insert(Gc::as_ptr(*set) as usize) {
+                        pending.extend(set.borrow().values.iter().cloned());
+                        visit(&value);
+                    }
+                }
+                Value::Closure(_, _, upvalues) => {
+                    if seen.insert(Gc::as_ptr(*upvalues) as usize) {
+                        pending.extend(upvalues.iter().map(|cell| cell.borrow().clone()));
+                    }
+                }
+                Value::Property { value, getter, setter, .. } => {
+                    pending.extend([value, getter, setter].into_iter().flatten().map(|v| (**v).clone()));
+                }
+                Value::String(_) => visit(&value),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Bytes a string, or an array, object, map or set itself, holds outside the
+/// arena. Values inside a container are counted on their own; property names
+/// count by their slot in the table, not their length, so this stays cheap
+/// enough to measure before and after every store.
+pub(crate) fn external_bytes(value: &Value<'_>) -> usize {
+    const VALUE: usize = std::mem::size_of::<Value<'static>>();
+    let map_bytes = |map: &IndexMap<String, Value<'_>>| {
+        map.capacity() * (std::mem::size_of::<(String, Value<'static>)>() + 2 * std::mem::size_of::<usize>())
+    };
+    match value {
+        Value::String(units) => units.capacity() * 2,
+        Value::Object(obj) => map_bytes(&obj.borrow()),
+        Value::Array(arr) => {
+            let borrow = arr.borrow();
+            borrow.elements.capacity() * VALUE + map_bytes(&borrow.props)
+        }
+        Value::Map(map) => map.borrow().entries.capacity() * 2 * VALUE,
+        Value::Set(set) => set.borrow().values.capacity() * VALUE,
+        _ => 0,
+    }
+}
@@ -0,0 +1,23 @@
+use super::*;
+use crate::core::ModuleLoader;
+use std::rc::Rc;
+
+impl<'gc> VM<'gc> {
+    /// Route module resolution and loading through `loader`.
+    pub(crate) fn set_module_loader(&mut self, loader: Rc<dyn ModuleLoader>) {
+        self.module_loader.install(loader);
+    }
+
+    pub(crate) fn module_loader_state(&self) -> Rc<crate::core::ModuleLoaderState> {
+        self.module_loader.clone()
+    }
+
+    /// Referrer for `import()`, `import defer` and `import source`: the active
+    /// module, else the script path. A custom loader also accepts an empty referrer.
+    pub(super) fn dynamic_import_referrer(&self) -> Option<String> {
+        self.current_source_path()
+            .map(str::to_owned)
+            .or_else(|| self.script_path.clone())
+            .or_else(|| self.module_loader.is_custom().then(String::new))
+    }
+}
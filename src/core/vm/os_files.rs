@@ -0,0 +1,38 @@
+use super::*;
+use std::cell::{Cell, RefCell};
+use std::fs::File;
+
+/// Files opened through `os.open`, by descriptor. Each VM has its own table,
+/// shared only with its helper VMs, so a descriptor never reaches a file that
+/// another VM's permissions allowed it to open.
+pub(super) struct OsFileTable {
+    files: RefCell<HashMap<u64, File>>,
+    next_fd: Cell<u64>,
+}
+
+impl Default for OsFileTable {
+    fn default() -> Self {
+        OsFileTable {
+            files: RefCell::default(),
+            next_fd: Cell::new(1),
+        }
+    }
+}
+
+impl OsFileTable {
+    pub(super) fn insert(&self, file: File) -> u64 {
+        let fd = self.next_fd.get();
+        self.next_fd.set(fd + 1);
+        self.files.borrow_mut().insert(fd, file);
+        fd
+    }
+
+    pub(super) fn close(&self, fd: u64) -> bool {
+        self.files.borrow_mut().remove(&fd).is_some()
+    }
+
+    /// Run `op` on the file behind `fd`, or return `None` if it is not open.
+    pub(super) fn with_file<R>(&self, fd: u64, op: impl FnOnce(&mut File) -> R) -> Option<R> {
+        self.files.borrow_mut().get_mut(&fd).map(op)
+    }
+}
@@ -0,0 +1,38 @@
+use super::*;
+use crate::core::Permissions;
+
+impl<'gc> VM<'gc> {
+    /// Replace what the `os` and `std` namespaces may do, here and in the
+    /// helper VMs sharing this one's state.
+    pub(crate) fn set_permissions(&mut self, permissions: Permissions) {
+        *self.permissions.borrow_mut() = permissions;
+    }
+
+    /// Whether `access` is allowed; if not, a `PermissionDenied` error is
+    /// left pending for the caller to return through.
+    pub(super) fn check_permission(&mut self, ctx: &GcContext<'gc>, access: Access<'_>) -> bool {
+        let denied = match self.permissions.borrow().check(access) {
+            Ok(()) => return true,
+            Err(message) => message,
+        };
+        self.pending_throw = Some(self.make_permission_denied_error(ctx, &denied));
+        false
+    }
+
+    /// An `Error` named `PermissionDenied`, as Deno throws for denied operations.
+    fn make_permission_denied_error(&self, ctx: &GcContext<'gc>, message: &str) -> Value<'gc> {
+        let mut map = IndexMap::new();
+        map.insert("__type__".to_string(), Value::from("Error"));
+        map.insert("name".to_string(), Value::from("PermissionDenied"));
+        map.insert("message".to_string(), Value::from(message));
+        if let Some(ctor) = self.globals.get("Error").cloned() {
+            map.insert("constructor".to_string(), ctor.clone());
+            if let Value::Object(ctor_obj) = ctor
+                && let Some(proto) = own_data_from_legacy_map(&ctor_obj.borrow(), "prototype")
+            {
+                map.insert("__proto__".to_string(), proto);
+            }
+        }
+        Value::Object(new_gc_cell_ptr(ctx, map))
+    }
+}
@@ -2533,7 +2533,7 @@ impl<'gc> VM<'gc> {
         (p, f)
     }
 
-    /// Validate RegExp flags per spec: only d,g,i,m,s,u,v,y allowed; no duplicates; u+v not together
+    /// Validate RegExp flags per spec: only d,g,i,m,s,u,v,y allowed; no duplicates; u+v not together.
     pub(super) fn validate_regexp_flags(flags: &str) -> Option<String> {
         let valid = "dgimsuy"; // v handled separately
         let mut seen = [false; 128];
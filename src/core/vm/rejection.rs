@@ -0,0 +1,159 @@
+use super::*;
+use crate::core::{PromiseRejection, RejectionCallback, RejectionEvent};
+use std::cell::{Cell, RefCell};
+
+/// HostPromiseRejectionTracker state: promises rejected without a handler
+/// wait here for the next microtask checkpoint, where those still unhandled
+/// are reported. Shared with helper VMs so eval'd code is tracked too.
+#[derive(Default)]
+pub(crate) struct RejectionState<'gc> {
+    tracker: RefCell<Option<RejectionCallback>>,
+    /// Rejected with no handler since the last checkpoint.
+    pending: RefCell<Vec<Value<'gc>>>,
+    /// Reported as unhandled and handled since.
+    handled: RefCell<Vec<Value<'gc>>>,
+    next_id: Cell<u64>,
+}
+
+impl<'gc> RejectionState<'gc> {
+    pub(super) fn trace<T: GcTrace<'gc>>(&self, cc: &mut T) {
+        for promise in self.pending.borrow().iter().chain(self.handled.borrow().iter()) {
+            promise.trace(cc);
+        }
+    }
+
+    pub(super) fn abandon_pending(&self) {
+        self.pending.borrow_mut().clear();
+        self.handled.borrow_mut().clear();
+    }
+}
+
+impl<'gc> VM<'gc> {
+    pub(crate) fn set_rejection_tracker(&mut self, tracker: Option<RejectionCallback>) {
+        *self.rejections.tracker.borrow_mut() = tracker;
+    }
+
+    /// The "reject" operation: `promise` was just rejected.
+    pub(super) fn track_rejection(&mut self, promise: &Value<'gc>) {
+        if !Self::is_promise_handled(promise) {
+            self.rejections.pending.borrow_mut().push(promise.clone());
+        }
+    }
+
+    /// The "handle" operation: a handler was attached to the rejected
+    /// `promise`, or its reason was consumed some other way (e.g. `await`).
+    pub(super) fn mark_promise_handled(&mut self, ctx: &GcContext<'gc>, promise: &Value<'gc>) {
+        let Value::Object(obj) = promise else {
+            return;
+        };
+        let reported = {
+            let mut b = obj.borrow_mut(ctx);
+            if b.contains_key("__promise_handled__") {
+                return;
+            }
+            b.insert("__promise_handled__".to_string(), Value::Boolean(true));
+            b.contains_key("__promise_rejection_id__")
+        };
+        if reported {
+            self.rejections.handled.borrow_mut().push(promise.clone());
+        }
+    }
+
+    fn is_promise_handled(promise: &Value<'gc>) -> bool {
+        matches!(promise, Value::Object(obj) if obj.borrow().contains_key("__promise_handled__"))
+    }
+
+    /// Report, after the microtask queue drained, the promises that became
+    /// handled after being reported and those rejected since the last
+    /// checkpoint that are still unhandled. An error from a hook or the
+    /// tracker is returned and the remaining rejections wait for the next
+    /// checkpoint. Eval VMs leave this to the calling VM.
+    pub(super) fn notify_rejections(&mut self, ctx: &GcContext<'gc>) -> Result<(), JSError> {
+        if self.defers_event_loop {
+            return Ok(());
+        }
+        let handled = std::mem::take(&mut *self.rejections.handled.borrow_mut());
+        for promise in handled {
+            self.dispatch_rejection_event(ctx, &promise, RejectionEvent::Handled)?;
+        }
+        let pending = std::mem::take(&mut *self.rejections.pending.borrow_mut());
+        let mut pending = pending.into_iter();
+        while let Some(promise) = pending.next() {
+            if Self::is_promise_handled(&promise) {
+                continue;
+            }
+            if let Err(err) = self.dispatch_rejection_event(ctx, &promise, RejectionEvent::Unhandled) {
+                self.rejections.pending.borrow_mut().extend(pending);
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    /// Call the global `onunhandledrejection` / `onrejectionhandled` hook,
+    /// then the embedder's tracker unless the hook called `preventDefault()`.
+    fn dispatch_rejection_event(&mut self, ctx: &GcContext<'gc>, promise: &Value<'gc>, event: RejectionEvent) -> Result<(), JSError> {
+        let Value::Object(obj) = promise else {
+            return Ok(());
+        };
+        let reason = obj.borrow().get("__promise_value__").cloned().unwrap_or(Value::Undefined);
+        let id = match event {
+            RejectionEvent::Unhandled => {
+                let id = self.rejections.next_id.get();
+                self.rejections.next_id.set(id + 1);
+                obj.borrow_mut(ctx)
+                    .insert("__promise_rejection_id__".to_string(), Value::Number(id as f64));
+                id
+            }
+            RejectionEvent::Handled => match obj.borrow().get("__promise_rejection_id__") {
+                Some(Value::Number(id)) => *id as u64,
+                _ => return Ok(()),
+            },
+        };
+
+        let (hook_name, event_type) = match event {
+            RejectionEvent::Unhandled => ("onunhandledrejection", "unhandledrejection"),
+            RejectionEvent::Handled => ("onrejectionhandled", "rejectionhandled"),
+        };
+        let global = Value::Object(self.global_this);
+        let hook = self.read_named_property(ctx, &global, hook_name);
+        if let Some(thrown) = self.pending_throw.take() {
+            return Err(self.vm_error_to_js_error(ctx, &thrown));
+        }
+        if self.is_value_callable(&hook) {
+            let mut fields = IndexMap::new();
+            fields.insert("type".to_string(), Value::from(event_type));
+            fields.insert("promise".to_string(), promise.clone());
+            fields.insert("reason".to_string(), reason.clone());
+            fields.insert("defaultPrevented".to_string(), Value::Boolean(false));
+            let event_value = self.wrap_descriptor_object(ctx, fields);
+            if let Value::Object(event_obj) = &event_value {
+                let prevent_default = Self::make_bound_host_fn(ctx, "rejection.preventDefault", &event_value);
+                event_obj.borrow_mut(ctx).insert("preventDefault".to_string(), prevent_default);
+            }
+            self.vm_call_function_value(ctx, &hook, &global, std::slice::from_ref(&event_value))?;
+            if let Some(thrown) = self.pending_throw.take() {
+                return Err(self.vm_error_to_js_error(ctx, &thrown));
+            }
+            let prevented = self.read_named_property(ctx, &event_value, "defaultPrevented");
+            if matches!(prevented, Value::Boolean(true)) {
+                return Ok(());
+            }
+        }
+
+        let Some(tracker) = self.rejections.tracker.borrow().clone() else {
+            return Ok(());
+        };
+        let error = self.vm_error_to_js_error(ctx, &reason);
+        tracker(PromiseRejection::new(event, id, error))
+    }
+
+    pub(super) fn rejection_handle_host_fn(&mut self, ctx: &GcContext<'gc>, name: &str, receiver: Option<&Value<'gc>>) -> Value<'gc> {
+        if name == "rejection.preventDefault"
+            && let Some(Value::Object(event)) = receiver
+        {
+            event.borrow_mut(ctx).insert("defaultPrevented".to_string(), Value::Boolean(true));
+        }
+        Value::Undefined
+    }
+}
@@ -17,28 +17,85 @@ enum OpcodeAction<'gc> {
     Exit(Value<'gc>),
 }
 
+/// An opcode needs more operands than the stack holds. The compiler never
+/// emits such code, but a corrupt bytecode file can.
+fn stack_underflow() -> JSError {
+    crate::raise_eval_error!("Invalid bytecode: operand stack underflow")
+}
+
 impl<'gc> VM<'gc> {
+    /// Run an opcode that stores into the object, array, map or set `depth`
+    /// slots below the top of the stack, charging what its storage grows by
+    /// toward the heap limit.
+    #[inline]
+    fn run_store_opcode(
+        &mut self,
+        ctx: &GcContext<'gc>,
+        depth: usize,
+        op: fn(&mut Self, &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError>,
+    ) -> Result<OpcodeAction<'gc>, JSError> {
+        if self.heap_limit().is_none() {
+            return op(self, ctx);
+        }
+        let target = match self.stack.len().checked_sub(depth + 1).map(|i| &self.stack[i]) {
+            Some(target @ (Value::Object(_) | Value::Array(_) | Value::Map(_) | Value::Set(_))) => target.clone(),
+            _ => return op(self, ctx),
+        };
+        let before = limits::external_bytes(&target);
+        let action = op(self, ctx);
+        self.charge_external(ctx, limits::external_bytes(&target).saturating_sub(before));
+        action
+    }
+
     /// Core execution loop of the VM (Fetch-Decode-Execute)
     pub fn run(&mut self, ctx: &GcContext<'gc>) -> Result<Value<'gc>, JSError> {
         let result = self.run_inner(ctx, 0)?;
+        self.finish_run(ctx, result)
+    }
+
+    /// Settle the work a finished top-level run left queued: microtasks,
+    /// rejections, timers and host operations.
+    pub(super) fn finish_run(&mut self, ctx: &GcContext<'gc>, result: Value<'gc>) -> Result<Value<'gc>, JSError> {
         self.flush_async_atomics_waits(ctx, true);
         self.drain_microtasks(ctx);
+        if let Some(err) = self.termination_error() {
+            return Err(err);
+        }
         if let Some(thrown) = self.pending_throw.take() {
             return Err(self.vm_error_to_js_error(ctx, &thrown));
         }
+        self.notify_rejections(ctx)?;
         self.drain_timers(ctx)?;
         self.flush_async_atomics_waits(ctx, true);
         self.drain_microtasks(ctx);
+        if let Some(err) = self.termination_error() {
+            return Err(err);
+        }
         if let Some(thrown) = self.pending_throw.take() {
             return Err(self.vm_error_to_js_error(ctx, &thrown));
         }
+        self.notify_rejections(ctx)?;
+        self.drain_async_host_operations(ctx)?;
         self.collect_garbage(ctx);
         Ok(result)
     }
 
     /// Execute VM until frames drop below `min_depth` or top-level returns
     pub(crate) fn run_inner(&mut self, ctx: &GcContext<'gc>, min_depth: usize) -> Result<Value<'gc>, JSError> {
+        self.enter_run();
+        let result = self.run_loop(ctx, min_depth);
+        self.leave_run();
+        result
+    }
+
+    fn run_loop(&mut self, ctx: &GcContext<'gc>, min_depth: usize) -> Result<Value<'gc>, JSError> {
         loop {
+            if self.limits_active() {
+                self.charge_instruction(ctx)?;
+                if self.pause_for_collection() {
+                    return Ok(Value::Undefined);
+                }
+            }
             // Check for pending throw (e.g. from generator .throw())
             if let Some(thrown) = self.pending_throw.take() {
                 self.handle_throw(ctx, &thrown)?;
@@ -109,12 +166,12 @@ impl<'gc> VM<'gc> {
                 Opcode::ShiftRight => self.run_opcode_shift_right(ctx)?,
                 Opcode::UnsignedShiftRight => self.run_opcode_unsigned_shift_right(ctx)?,
                 Opcode::BitwiseNot => self.run_opcode_bitwise_not(ctx)?,
-                Opcode::ArrayPush => self.run_opcode_array_push(ctx)?,
-                Opcode::ArrayHole => self.run_opcode_array_hole(ctx)?,
-                Opcode::ArraySpread => self.run_opcode_array_spread(ctx)?,
+                Opcode::ArrayPush => self.run_store_opcode(ctx, 1, Self::run_opcode_array_push)?,
+                Opcode::ArrayHole => self.run_store_opcode(ctx, 0, Self::run_opcode_array_hole)?,
+                Opcode::ArraySpread => self.run_store_opcode(ctx, 1, Self::run_opcode_array_spread)?,
                 Opcode::CallSpread => self.run_opcode_call_spread(ctx)?,
                 Opcode::NewCallSpread => self.run_opcode_new_call_spread(ctx)?,
-                Opcode::ObjectSpread => self.run_opcode_object_spread(ctx)?,
+                Opcode::ObjectSpread => self.run_store_opcode(ctx, 1, Self::run_opcode_object_spread)?,
                 Opcode::ObjectSpreadExcluding => self.run_opcode_object_spread_excluding(ctx)?,
                 Opcode::ValidateClassHeritage => self.run_opcode_validate_class_heritage(ctx)?,
                 Opcode::GetUpvalue => self.run_opcode_get_upvalue(ctx)?,
@@ -129,16 +186,16 @@ impl<'gc> VM<'gc> {
                 Opcode::NewArray => self.run_opcode_new_array(ctx)?,
                 Opcode::NewObject => self.run_opcode_new_object(ctx)?,
                 Opcode::GetProperty => self.run_opcode_get_property(ctx)?,
-                Opcode::SetProperty => self.run_opcode_set_property(ctx)?,
-                Opcode::InitProperty => self.run_opcode_init_property(ctx)?,
+                Opcode::SetProperty => self.run_store_opcode(ctx, 1, Self::run_opcode_set_property)?,
+                Opcode::InitProperty => self.run_store_opcode(ctx, 1, Self::run_opcode_init_property)?,
                 Opcode::SetSuperProperty => self.run_opcode_set_super_property(ctx)?,
                 Opcode::SetSuperPropertyComputed => self.run_opcode_set_super_property_computed(ctx)?,
                 Opcode::DefineComputedMethod => self.run_opcode_define_computed_method(ctx)?,
                 Opcode::GetSuperProperty => self.run_opcode_get_super_property(ctx)?,
                 Opcode::GetSuperPropertyComputed => self.run_opcode_get_super_property_computed(ctx)?,
                 Opcode::GetIndex => self.run_opcode_get_index(ctx)?,
-                Opcode::SetIndex => self.run_opcode_set_index(ctx)?,
-                Opcode::InitIndex => self.run_opcode_init_index(ctx)?,
+                Opcode::SetIndex => self.run_store_opcode(ctx, 2, Self::run_opcode_set_index)?,
+                Opcode::InitIndex => self.run_store_opcode(ctx, 2, Self::run_opcode_init_index)?,
                 Opcode::SetComputedGetter => self.run_opcode_set_computed_getter(ctx)?,
                 Opcode::SetComputedSetter => self.run_opcode_set_computed_setter(ctx)?,
                 Opcode::MarkPropertyNonEnumerable => self.run_opcode_mark_property_nonenumerable(ctx)?,
@@ -321,7 +378,7 @@ impl<'gc> VM<'gc> {
         self.next_async_function_id += 1;
         let frame_depth = self.frames.len();
 
-        let locals = self.stack[frame.bp..].to_vec();
+        let locals = self.stack.get(frame.bp..).ok_or_else(stack_underflow)?.to_vec();
         let this_val = self.this_stack.last().cloned().unwrap_or(Value::Undefined);
         let try_stack = self
             .try_stack
@@ -417,7 +474,7 @@ impl<'gc> VM<'gc> {
         if bp + index >= self.stack.len() {
             self.stack.resize(bp + index + 1, Value::Undefined);
         }
-        let val = self.stack.last().expect("VM Stack underflow").clone();
+        let val = self.stack.last().ok_or_else(stack_underflow)?.clone();
         // Check if this local has been captured as an upvalue cell
         // Fast path: skip HashMap lookup when no upvalue cells exist (common case)
         let cell = if let Some(frame) = self.frames.last() {
@@ -453,7 +510,7 @@ impl<'gc> VM<'gc> {
         self.direct_eval = is_direct_eval;
         // Stack for method call: [..., receiver, callee, arg0, arg1, ...]
         // Stack for regular call: [..., callee, arg0, arg1, ...]
-        let callee_idx = self.stack.len() - arg_count - 1;
+        let callee_idx = self.stack.len().checked_sub(arg_count + 1).ok_or_else(stack_underflow)?;
         let callee = self.stack[callee_idx].clone();
         match callee {
             Value::Function(target_ip, arity) => {
@@ -628,6 +685,7 @@ impl<'gc> VM<'gc> {
                     };
                     let frame = CallFrame {
                         return_ip: self.ip,
+                        call_ip: self.current_opcode_ip,
                         bp: callee_idx + 1,
                         is_method: true,
                         arg_count,
@@ -653,6 +711,7 @@ impl<'gc> VM<'gc> {
                     }
                     let frame = CallFrame {
                         return_ip: self.ip,
+                        call_ip: self.current_opcode_ip,
                         bp: callee_idx + 1,
                         is_method: push_this,
                         arg_count,
@@ -839,6 +898,7 @@ impl<'gc> VM<'gc> {
                     };
                     let frame = CallFrame {
                         return_ip: self.ip,
+                        call_ip: self.current_opcode_ip,
                         bp: callee_idx + 1,
                         is_method: true,
                         arg_count,
@@ -863,6 +923,7 @@ impl<'gc> VM<'gc> {
                     }
                     let frame = CallFrame {
                         return_ip: self.ip,
+                        call_ip: self.current_opcode_ip,
                         bp: callee_idx + 1,
                         is_method: push_this,
                         arg_count,
@@ -1678,7 +1739,7 @@ impl<'gc> VM<'gc> {
 
     // Opcode::ThrowIfNullish — throw TypeError if TOS is null/undefined (does not pop)
     fn run_opcode_throw_if_nullish(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let top = self.stack.last().expect("VM Stack underflow on ThrowIfNullish");
+        let top = self.stack.last().ok_or_else(stack_underflow)?;
         if matches!(top, Value::Null | Value::Undefined) {
             let err = self.make_type_error_object(ctx, "Cannot read properties of null or undefined");
             self.handle_throw(ctx, &err)?;
@@ -1690,7 +1751,7 @@ impl<'gc> VM<'gc> {
     // throw TypeError if it's not a constructor. Does not modify the stack.
     fn run_opcode_throw_if_not_constructor(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
         let depth = self.read_byte() as usize;
-        let idx = self.stack.len() - depth - 1;
+        let idx = self.stack.len().checked_sub(depth + 1).ok_or_else(stack_underflow)?;
         let val = &self.stack[idx];
         if !self.is_constructor_value(val) {
             let err = self.make_type_error_object(ctx, "super expression requires a constructor");
@@ -2127,8 +2188,8 @@ impl<'gc> VM<'gc> {
 
     // Opcode::Add
     fn run_opcode_add(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let b_raw = self.stack.pop().expect("VM Stack underflow on Add (b)");
-        let a_raw = self.stack.pop().expect("VM Stack underflow on Add (a)");
+        let b_raw = self.stack.pop().ok_or_else(stack_underflow)?;
+        let a_raw = self.stack.pop().ok_or_else(stack_underflow)?;
 
         // Ultra-fast path: two plain numbers (most common in numeric loops)
         if let (Value::Number(a_n), Value::Number(b_n)) = (&a_raw, &b_raw) {
@@ -2214,6 +2275,7 @@ impl<'gc> VM<'gc> {
                     result.extend(s.encode_utf16());
                 }
             }
+            self.charge_external(ctx, result.capacity() * 2);
             self.stack.push(Value::String(result));
         } else {
             match (&a, &b) {
@@ -2241,8 +2303,8 @@ impl<'gc> VM<'gc> {
 
     // Opcode::Sub
     fn run_opcode_sub(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let b_raw = self.stack.pop().expect("VM Stack underflow on Sub (b)");
-        let a_raw = self.stack.pop().expect("VM Stack underflow on Sub (a)");
+        let b_raw = self.stack.pop().ok_or_else(stack_underflow)?;
+        let a_raw = self.stack.pop().ok_or_else(stack_underflow)?;
         let a = self.__to_numeric(ctx, &a_raw)?;
         if let Some(thrown) = self.pending_throw.take() {
             self.handle_throw(ctx, &thrown)?;
@@ -2273,8 +2335,8 @@ impl<'gc> VM<'gc> {
 
     // Opcode::Mul
     fn run_opcode_mul(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let b_raw = self.stack.pop().expect("VM Stack underflow on Mul (b)");
-        let a_raw = self.stack.pop().expect("VM Stack underflow on Mul (a)");
+        let b_raw = self.stack.pop().ok_or_else(stack_underflow)?;
+        let a_raw = self.stack.pop().ok_or_else(stack_underflow)?;
         let a = self.__to_numeric(ctx, &a_raw)?;
         if let Some(thrown) = self.pending_throw.take() {
             self.handle_throw(ctx, &thrown)?;
@@ -2301,8 +2363,8 @@ impl<'gc> VM<'gc> {
 
     // Opcode::Div
     fn run_opcode_div(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let b_raw = self.stack.pop().expect("VM Stack underflow on Div (b)");
-        let a_raw = self.stack.pop().expect("VM Stack underflow on Div (a)");
+        let b_raw = self.stack.pop().ok_or_else(stack_underflow)?;
+        let a_raw = self.stack.pop().ok_or_else(stack_underflow)?;
         let a = self.__to_numeric(ctx, &a_raw)?;
         if let Some(thrown) = self.pending_throw.take() {
             self.handle_throw(ctx, &thrown)?;
@@ -2406,8 +2468,8 @@ impl<'gc> VM<'gc> {
     }
 
     fn run_opcode_less_than(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let b = self.stack.pop().expect("VM Stack underflow");
-        let a = self.stack.pop().expect("VM Stack underflow");
+        let b = self.stack.pop().ok_or_else(stack_underflow)?;
+        let a = self.stack.pop().ok_or_else(stack_underflow)?;
         // Fast path: both plain numbers (most common in loop counters)
         if let (Value::Number(a_n), Value::Number(b_n)) = (&a, &b) {
             self.stack.push(Value::Boolean(a_n < b_n));
@@ -2425,8 +2487,8 @@ impl<'gc> VM<'gc> {
 
     // Opcode::GreaterThan
     fn run_opcode_greater_than(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let b = self.stack.pop().expect("VM Stack underflow");
-        let a = self.stack.pop().expect("VM Stack underflow");
+        let b = self.stack.pop().ok_or_else(stack_underflow)?;
+        let a = self.stack.pop().ok_or_else(stack_underflow)?;
         // Fast path: both plain numbers
         if let (Value::Number(a_n), Value::Number(b_n)) = (&a, &b) {
             self.stack.push(Value::Boolean(a_n > b_n));
@@ -2444,8 +2506,8 @@ impl<'gc> VM<'gc> {
 
     // Opcode::Equal
     fn run_opcode_equal(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let b = self.stack.pop().expect("VM Stack underflow");
-        let a = self.stack.pop().expect("VM Stack underflow");
+        let b = self.stack.pop().ok_or_else(stack_underflow)?;
+        let a = self.stack.pop().ok_or_else(stack_underflow)?;
         let eq = self.loose_equal(ctx, &a, &b);
         if let Some(thrown) = self.pending_throw.take() {
             self.handle_throw(ctx, &thrown)?;
@@ -2457,8 +2519,8 @@ impl<'gc> VM<'gc> {
 
     // Opcode::NotEqual
     fn run_opcode_not_equal(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let b = self.stack.pop().expect("VM Stack underflow");
-        let a = self.stack.pop().expect("VM Stack underflow");
+        let b = self.stack.pop().ok_or_else(stack_underflow)?;
+        let a = self.stack.pop().ok_or_else(stack_underflow)?;
         let neq = !self.loose_equal(ctx, &a, &b);
         if let Some(thrown) = self.pending_throw.take() {
             self.handle_throw(ctx, &thrown)?;
@@ -2471,8 +2533,8 @@ impl<'gc> VM<'gc> {
     // Opcode::StrictNotEqual
     fn run_opcode_strict_not_equal(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
         let _ = ctx;
-        let b = self.stack.pop().expect("VM Stack underflow");
-        let a = self.stack.pop().expect("VM Stack underflow");
+        let b = self.stack.pop().ok_or_else(stack_underflow)?;
+        let a = self.stack.pop().ok_or_else(stack_underflow)?;
         match (&a, &b) {
             (Value::Number(a_num), Value::Number(b_num)) => {
                 self.stack.push(Value::Boolean(a_num != b_num));
@@ -2523,8 +2585,8 @@ impl<'gc> VM<'gc> {
 
     // Opcode::LessEqual — spec: a <= b ≡ !(b < a)
     fn run_opcode_less_equal(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let b = self.stack.pop().expect("VM Stack underflow");
-        let a = self.stack.pop().expect("VM Stack underflow");
+        let b = self.stack.pop().ok_or_else(stack_underflow)?;
+        let a = self.stack.pop().ok_or_else(stack_underflow)?;
         // Fast path: both plain numbers
         if let (Value::Number(a_n), Value::Number(b_n)) = (&a, &b) {
             self.stack.push(Value::Boolean(a_n <= b_n));
@@ -2543,8 +2605,8 @@ impl<'gc> VM<'gc> {
 
     // Opcode::GreaterEqual — spec: a >= b ≡ !(a < b)
     fn run_opcode_greater_equal(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let b = self.stack.pop().expect("VM Stack underflow");
-        let a = self.stack.pop().expect("VM Stack underflow");
+        let b = self.stack.pop().ok_or_else(stack_underflow)?;
+        let a = self.stack.pop().ok_or_else(stack_underflow)?;
         // Fast path: both plain numbers
         if let (Value::Number(a_n), Value::Number(b_n)) = (&a, &b) {
             self.stack.push(Value::Boolean(a_n >= b_n));
@@ -2562,8 +2624,8 @@ impl<'gc> VM<'gc> {
 
     // Opcode::Mod
     fn run_opcode_mod(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let b_raw = self.stack.pop().expect("VM Stack underflow");
-        let a_raw = self.stack.pop().expect("VM Stack underflow");
+        let b_raw = self.stack.pop().ok_or_else(stack_underflow)?;
+        let a_raw = self.stack.pop().ok_or_else(stack_underflow)?;
         let a = self.__to_numeric(ctx, &a_raw)?;
         if let Some(thrown) = self.pending_throw.take() {
             self.handle_throw(ctx, &thrown)?;
@@ -2595,8 +2657,8 @@ impl<'gc> VM<'gc> {
 
     // Opcode::Pow
     fn run_opcode_pow(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let b_raw = self.stack.pop().expect("VM Stack underflow");
-        let a_raw = self.stack.pop().expect("VM Stack underflow");
+        let b_raw = self.stack.pop().ok_or_else(stack_underflow)?;
+        let a_raw = self.stack.pop().ok_or_else(stack_underflow)?;
         let a = self.__to_numeric(ctx, &a_raw)?;
         if let Some(thrown) = self.pending_throw.take() {
             self.handle_throw(ctx, &thrown)?;
@@ -2648,8 +2710,8 @@ impl<'gc> VM<'gc> {
 
     // Opcode::BitwiseAnd
     fn run_opcode_bitwise_and(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let b = self.stack.pop().expect("VM Stack underflow");
-        let a = self.stack.pop().expect("VM Stack underflow");
+        let b = self.stack.pop().ok_or_else(stack_underflow)?;
+        let a = self.stack.pop().ok_or_else(stack_underflow)?;
         let lnum = self.__to_numeric(ctx, &a)?;
         if let Some(thrown) = self.pending_throw.take() {
             self.handle_throw(ctx, &thrown)?;
@@ -2680,8 +2742,8 @@ impl<'gc> VM<'gc> {
 
     // Opcode::BitwiseOr
     fn run_opcode_bitwise_or(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let b = self.stack.pop().expect("VM Stack underflow");
-        let a = self.stack.pop().expect("VM Stack underflow");
+        let b = self.stack.pop().ok_or_else(stack_underflow)?;
+        let a = self.stack.pop().ok_or_else(stack_underflow)?;
         let lnum = self.__to_numeric(ctx, &a)?;
         if let Some(thrown) = self.pending_throw.take() {
             self.handle_throw(ctx, &thrown)?;
@@ -2712,8 +2774,8 @@ impl<'gc> VM<'gc> {
 
     // Opcode::BitwiseXor
     fn run_opcode_bitwise_xor(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let b = self.stack.pop().expect("VM Stack underflow");
-        let a = self.stack.pop().expect("VM Stack underflow");
+        let b = self.stack.pop().ok_or_else(stack_underflow)?;
+        let a = self.stack.pop().ok_or_else(stack_underflow)?;
         let lnum = self.__to_numeric(ctx, &a)?;
         if let Some(thrown) = self.pending_throw.take() {
             self.handle_throw(ctx, &thrown)?;
@@ -2744,8 +2806,8 @@ impl<'gc> VM<'gc> {
 
     // Opcode::ShiftLeft
     fn run_opcode_shift_left(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let b = self.stack.pop().expect("VM Stack underflow");
-        let a = self.stack.pop().expect("VM Stack underflow");
+        let b = self.stack.pop().ok_or_else(stack_underflow)?;
+        let a = self.stack.pop().ok_or_else(stack_underflow)?;
         let lnum = self.__to_numeric(ctx, &a)?;
         if let Some(thrown) = self.pending_throw.take() {
             self.handle_throw(ctx, &thrown)?;
@@ -2801,8 +2863,8 @@ impl<'gc> VM<'gc> {
 
     // Opcode::ShiftRight
     fn run_opcode_shift_right(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let b = self.stack.pop().expect("VM Stack underflow");
-        let a = self.stack.pop().expect("VM Stack underflow");
+        let b = self.stack.pop().ok_or_else(stack_underflow)?;
+        let a = self.stack.pop().ok_or_else(stack_underflow)?;
         let lnum = self.__to_numeric(ctx, &a)?;
         if let Some(thrown) = self.pending_throw.take() {
             self.handle_throw(ctx, &thrown)?;
@@ -2858,8 +2920,8 @@ impl<'gc> VM<'gc> {
 
     // Opcode::UnsignedShiftRight
     fn run_opcode_unsigned_shift_right(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let b = self.stack.pop().expect("VM Stack underflow");
-        let a = self.stack.pop().expect("VM Stack underflow");
+        let b = self.stack.pop().ok_or_else(stack_underflow)?;
+        let a = self.stack.pop().ok_or_else(stack_underflow)?;
         let lnum = self.__to_numeric(ctx, &a)?;
         if let Some(thrown) = self.pending_throw.take() {
             self.handle_throw(ctx, &thrown)?;
@@ -2887,7 +2949,7 @@ impl<'gc> VM<'gc> {
 
     // Opcode::BitwiseNot
     fn run_opcode_bitwise_not(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let a = self.stack.pop().expect("VM Stack underflow");
+        let a = self.stack.pop().ok_or_else(stack_underflow)?;
         let num = self.__to_numeric(ctx, &a)?;
         if let Some(thrown) = self.pending_throw.take() {
             self.handle_throw(ctx, &thrown)?;
@@ -2907,8 +2969,8 @@ impl<'gc> VM<'gc> {
     // Opcode::ArrayPush
     fn run_opcode_array_push(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
         // Stack: [..., array, value] → [..., array] (with value appended)
-        let value = self.stack.pop().expect("VM Stack underflow on ArrayPush");
-        let arr = self.stack.last().expect("VM Stack underflow on ArrayPush (array)");
+        let value = self.stack.pop().ok_or_else(stack_underflow)?;
+        let arr = self.stack.last().ok_or_else(stack_underflow)?;
         if let Value::Array(arr_data) = arr {
             arr_data.borrow_mut(ctx).elements.push(value);
         }
@@ -2918,7 +2980,7 @@ impl<'gc> VM<'gc> {
     // Opcode::ArrayHole
     fn run_opcode_array_hole(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
         // Stack: [..., array] → [..., array] (with hole/empty slot appended)
-        let arr = self.stack.last().expect("VM Stack underflow on ArrayHole (array)");
+        let arr = self.stack.last().ok_or_else(stack_underflow)?;
         if let Value::Array(arr_data) = arr {
             let mut borrow = arr_data.borrow_mut(ctx);
             let idx = borrow.elements.len();
@@ -2931,7 +2993,7 @@ impl<'gc> VM<'gc> {
     // Opcode::ArraySpread
     fn run_opcode_array_spread(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
         // Stack: [..., array, iterable] → [..., array] (with iterable elements spread)
-        let source = self.stack.pop().expect("VM Stack underflow on ArraySpread");
+        let source = self.stack.pop().ok_or_else(stack_underflow)?;
         // Clone the GcCell pointer to avoid borrow conflict with self
         let arr_data = if let Some(Value::Array(arr_data)) = self.stack.last() {
             *arr_data
@@ -3032,7 +3094,7 @@ impl<'gc> VM<'gc> {
         let is_method = (flags & 0x80) != 0;
         let is_direct_eval = (flags & 0x40) != 0;
         self.direct_eval = is_direct_eval;
-        let args_val = self.stack.pop().expect("VM Stack underflow on CallSpread");
+        let args_val = self.stack.pop().ok_or_else(stack_underflow)?;
         let spread_args: Vec<Value<'gc>> = if let Value::Array(arr) = &args_val {
             arr.borrow().elements.clone()
         } else {
@@ -3043,7 +3105,7 @@ impl<'gc> VM<'gc> {
         for arg in spread_args {
             self.stack.push(arg);
         }
-        let callee_idx = self.stack.len() - arg_count - 1;
+        let callee_idx = self.stack.len().checked_sub(arg_count + 1).ok_or_else(stack_underflow)?;
         let callee = self.stack[callee_idx].clone();
         match callee {
             Value::Function(target_ip, arity) => {
@@ -3073,6 +3135,7 @@ impl<'gc> VM<'gc> {
                     let callee_idx = callee_idx - 1;
                     self.push_call_frame_for_function(CallFrame {
                         return_ip: self.ip,
+                        call_ip: self.current_opcode_ip,
                         bp: callee_idx + 1,
                         is_method: true,
                         arg_count,
@@ -3086,6 +3149,7 @@ impl<'gc> VM<'gc> {
                 } else {
                     self.push_call_frame_for_function(CallFrame {
                         return_ip: self.ip,
+                        call_ip: self.current_opcode_ip,
                         bp: callee_idx + 1,
                         is_method: false,
                         arg_count,
@@ -3127,6 +3191,7 @@ impl<'gc> VM<'gc> {
                     let callee_idx = callee_idx - 1;
                     self.push_call_frame_for_function(CallFrame {
                         return_ip: self.ip,
+                        call_ip: self.current_opcode_ip,
                         bp: callee_idx + 1,
                         is_method: true,
                         arg_count,
@@ -3140,6 +3205,7 @@ impl<'gc> VM<'gc> {
                 } else {
                     self.push_call_frame_for_function(CallFrame {
                         return_ip: self.ip,
+                        call_ip: self.current_opcode_ip,
                         bp: callee_idx + 1,
                         is_method: false,
                         arg_count,
@@ -3283,13 +3349,13 @@ impl<'gc> VM<'gc> {
     // Opcode::NewCallSpread
     fn run_opcode_new_call_spread(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
         // Stack: [..., constructor, argsArray]
-        let args_val = self.stack.pop().expect("VM Stack underflow on NewCallSpread");
+        let args_val = self.stack.pop().ok_or_else(stack_underflow)?;
         let spread_args: Vec<Value<'gc>> = if let Value::Array(arr) = &args_val {
             arr.borrow().elements.clone()
         } else {
             vec![args_val]
         };
-        let constructor = self.stack.pop().expect("VM Stack underflow on NewCallSpread constructor");
+        let constructor = self.stack.pop().ok_or_else(stack_underflow)?;
         if !self.is_constructor_value(&constructor) {
             let mut err_map = IndexMap::new();
             err_map.insert("__type__".to_string(), Value::from("TypeError"));
@@ -3315,8 +3381,8 @@ impl<'gc> VM<'gc> {
     // Opcode::ObjectSpread
     fn run_opcode_object_spread(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
         // Stack: [..., target_obj, source_obj] → [..., target_obj]
-        let source = self.stack.pop().expect("VM Stack underflow on ObjectSpread");
-        let target = self.stack.last().cloned().expect("VM Stack underflow on ObjectSpread (target)");
+        let source = self.stack.pop().ok_or_else(stack_underflow)?;
+        let target = self.stack.last().cloned().ok_or_else(stack_underflow)?;
 
         if matches!(source, Value::Undefined | Value::Null) {
             return Ok(OpcodeAction::Continue);
@@ -3381,7 +3447,7 @@ impl<'gc> VM<'gc> {
     fn run_opcode_validate_class_heritage(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
         // Pop the heritage value from the stack and validate it.
         // Only checks IsConstructor — does NOT read .prototype (that happens in the wiring step).
-        let val = self.stack.pop().expect("VM Stack underflow on ValidateClassHeritage");
+        let val = self.stack.pop().ok_or_else(stack_underflow)?;
         match &val {
             Value::Null => {
                 // null is allowed — creates class with no prototype chain
@@ -3413,7 +3479,7 @@ impl<'gc> VM<'gc> {
     fn run_opcode_validate_proto_value(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
         // Validate that TOS is an object or null (for class extends prototype check).
         // Leaves the value on the stack if valid; throws TypeError if not.
-        let val = self.stack.last().expect("VM Stack underflow on ValidateProtoValue");
+        let val = self.stack.last().ok_or_else(stack_underflow)?;
         match val {
             Value::Null
             | Value::Object(_)
@@ -3435,13 +3501,9 @@ impl<'gc> VM<'gc> {
     // Opcode::ObjectSpreadExcluding
     fn run_opcode_object_spread_excluding(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
         // Stack: [..., target_obj, excluded_keys_array, source_obj] → [..., target_obj]
-        let source = self.stack.pop().expect("VM Stack underflow on ObjectSpreadExcluding (source)");
-        let excluded_arr = self.stack.pop().expect("VM Stack underflow on ObjectSpreadExcluding (excluded)");
-        let target = self
-            .stack
-            .last()
-            .cloned()
-            .expect("VM Stack underflow on ObjectSpreadExcluding (target)");
+        let source = self.stack.pop().ok_or_else(stack_underflow)?;
+        let excluded_arr = self.stack.pop().ok_or_else(stack_underflow)?;
+        let target = self.stack.last().cloned().ok_or_else(stack_underflow)?;
 
         if matches!(source, Value::Undefined | Value::Null) {
             return Ok(OpcodeAction::Continue);
@@ -3667,7 +3729,7 @@ impl<'gc> VM<'gc> {
     // Opcode::Negate
     fn run_opcode_negate(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
         let _ = ctx;
-        let a = self.stack.pop().expect("VM Stack underflow");
+        let a = self.stack.pop().ok_or_else(stack_underflow)?;
         let n = self.__to_numeric(ctx, &a)?;
         if let Some(thrown) = self.pending_throw.take() {
             self.handle_throw(ctx, &thrown)?;
@@ -3683,7 +3745,7 @@ impl<'gc> VM<'gc> {
     // Opcode::Not
     fn run_opcode_not(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
         let _ = ctx;
-        let a = self.stack.pop().expect("VM Stack underflow");
+        let a = self.stack.pop().ok_or_else(stack_underflow)?;
         self.stack.push(Value::Boolean(!a.to_truthy()));
         Ok(OpcodeAction::Continue)
     }
@@ -3691,7 +3753,7 @@ impl<'gc> VM<'gc> {
     // Opcode::TypeOf
     fn run_opcode_type_of(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
         let _ = ctx;
-        let a = self.stack.pop().expect("VM Stack underflow");
+        let a = self.stack.pop().ok_or_else(stack_underflow)?;
         let type_str = a.typeof_value();
         self.stack.push(Value::from(type_str));
         Ok(OpcodeAction::Continue)
@@ -3802,7 +3864,7 @@ impl<'gc> VM<'gc> {
     // Opcode::NewArray
     fn run_opcode_new_array(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
         let count = self.read_byte() as usize;
-        let start = self.stack.len() - count;
+        let start = self.stack.len().checked_sub(count).ok_or_else(stack_underflow)?;
         let elems: Vec<Value<'gc>> = self.stack.drain(start..).collect();
         let arr_val = Value::Array(new_gc_cell_ptr(ctx, VmArrayData::new(elems)));
         // link prototype if Array constructor has prototype property
@@ -3819,7 +3881,7 @@ impl<'gc> VM<'gc> {
     fn run_opcode_new_object(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
         let count = self.read_byte() as usize;
         // Stack has pairs: [key, val, key, val, ...]
-        let start = self.stack.len() - count * 2;
+        let start = self.stack.len().checked_sub(count * 2).ok_or_else(stack_underflow)?;
         let pairs: Vec<Value<'gc>> = self.stack.drain(start..).collect();
         let mut map = IndexMap::new();
         for chunk in pairs.chunks(2) {
@@ -3899,7 +3961,7 @@ impl<'gc> VM<'gc> {
         } else {
             value_to_string(name_val)
         };
-        let obj = self.stack.pop().expect("VM Stack underflow on GetProperty");
+        let obj = self.stack.pop().ok_or_else(stack_underflow)?;
         // Private field brand check for reads: accessing a #-prefixed key on an object
         // that doesn't own it is a TypeError. Private members are now per-instance (own props only).
         if key.starts_with(PRIVATE_KEY_PREFIX) {
@@ -4664,8 +4726,8 @@ impl<'gc> VM<'gc> {
         } else {
             value_to_string(name_val)
         };
-        let val = self.stack.pop().expect("VM Stack underflow on SetProperty (val)");
-        let obj = self.stack.pop().expect("VM Stack underflow on SetProperty (obj)");
+        let val = self.stack.pop().ok_or_else(stack_underflow)?;
+        let obj = self.stack.pop().ok_or_else(stack_underflow)?;
         // Private field brand check: setting a #-prefixed key on an object
         // that doesn't own it (no own property, no own setter, and not in proto chain) is a TypeError.
         // Also: private methods (on prototype, not own) are non-writable.
@@ -4766,7 +4828,7 @@ impl<'gc> VM<'gc> {
         } else {
             value_to_string(name_val)
         };
-        let obj = self.stack.pop().expect("VM Stack underflow on MarkPropertyNonEnumerable (obj)");
+        let obj = self.stack.pop().ok_or_else(stack_underflow)?;
         match &obj {
             Value::Object(map) => {
                 mark_nonenumerable(&mut map.borrow_mut(ctx), &key);
@@ -4793,7 +4855,7 @@ impl<'gc> VM<'gc> {
         } else {
             value_to_string(name_val)
         };
-        let obj = self.stack.pop().expect("VM Stack underflow on MarkPropertyReadonly (obj)");
+        let obj = self.stack.pop().ok_or_else(stack_underflow)?;
         match &obj {
             Value::Object(map) => {
                 mark_readonly(&mut map.borrow_mut(ctx), &key);
@@ -4837,8 +4899,8 @@ impl<'gc> VM<'gc> {
             value_to_string(name_val)
         };
         let is_private_like = key.contains(PRIVATE_KEY_PREFIX) || key.starts_with("__brand_");
-        let val = self.stack.pop().expect("VM Stack underflow on InitProperty (val)");
-        let obj = self.stack.pop().expect("VM Stack underflow on InitProperty (obj)");
+        let val = self.stack.pop().ok_or_else(stack_underflow)?;
+        let obj = self.stack.pop().ok_or_else(stack_underflow)?;
         // Private fields live on the exact object (including proxies) — no unwrapping.
         // Proxy traps are not triggered for private field init.
         match &obj {
@@ -4986,7 +5048,7 @@ impl<'gc> VM<'gc> {
         } else {
             value_to_string(name_val)
         };
-        let val = self.stack.pop().expect("VM Stack underflow on SetSuperProperty (val)");
+        let val = self.stack.pop().ok_or_else(stack_underflow)?;
         let receiver = self.this_stack.last().cloned().unwrap_or(Value::Undefined);
         let super_base_for_arrow = self
             .frames
@@ -5032,8 +5094,8 @@ impl<'gc> VM<'gc> {
 
     // Opcode::SetSuperPropertyComputed
     fn run_opcode_set_super_property_computed(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let val = self.stack.pop().expect("VM Stack underflow on SetSuperPropertyComputed (val)");
-        let key_val = self.stack.pop().expect("VM Stack underflow on SetSuperPropertyComputed (key)");
+        let val = self.stack.pop().ok_or_else(stack_underflow)?;
+        let key_val = self.stack.pop().ok_or_else(stack_underflow)?;
         // Spec: resolve super base (GetSuperBase) BEFORE ToPropertyKey
         let receiver = self.this_stack.last().cloned().unwrap_or(Value::Undefined);
         let super_base_for_arrow = self
@@ -5144,7 +5206,7 @@ impl<'gc> VM<'gc> {
 
     // Opcode::GetSuperPropertyComputed
     fn run_opcode_get_super_property_computed(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let key_val = self.stack.pop().expect("VM Stack underflow on GetSuperPropertyComputed");
+        let key_val = self.stack.pop().ok_or_else(stack_underflow)?;
         // Spec: resolve super base (GetSuperBase) BEFORE ToPropertyKey
         let receiver = self.this_stack.last().cloned().unwrap_or(Value::Undefined);
         let super_base_for_arrow = self
@@ -5203,8 +5265,8 @@ impl<'gc> VM<'gc> {
 
     // Opcode::GetIndex
     fn run_opcode_get_index(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let index = self.stack.pop().expect("VM Stack underflow on GetIndex (index)");
-        let obj = self.stack.pop().expect("VM Stack underflow on GetIndex (obj)");
+        let index = self.stack.pop().ok_or_else(stack_underflow)?;
+        let obj = self.stack.pop().ok_or_else(stack_underflow)?;
 
         if matches!(obj, Value::Null | Value::Undefined) {
             let err = self.make_type_error_object(ctx, "Cannot read properties of null or undefined");
@@ -5466,9 +5528,9 @@ impl<'gc> VM<'gc> {
 
     // Opcode::SetIndex
     fn run_opcode_set_index(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let val = self.stack.pop().expect("VM Stack underflow on SetIndex (val)");
-        let index = self.stack.pop().expect("VM Stack underflow on SetIndex (index)");
-        let obj = self.stack.pop().expect("VM Stack underflow on SetIndex (obj)");
+        let val = self.stack.pop().ok_or_else(stack_underflow)?;
+        let index = self.stack.pop().ok_or_else(stack_underflow)?;
+        let obj = self.stack.pop().ok_or_else(stack_underflow)?;
         let _is_strict = self.current_execution_is_strict();
 
         // Fast path: plain array append with a non-negative integer index.
@@ -5588,9 +5650,9 @@ impl<'gc> VM<'gc> {
     // Opcode::DefineComputedMethod — like SetIndex but also marks the property non-enumerable.
     // Used for class computed methods which must be non-enumerable per spec.
     fn run_opcode_define_computed_method(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let val = self.stack.pop().expect("VM Stack underflow on DefineComputedMethod (val)");
-        let index = self.stack.pop().expect("VM Stack underflow on DefineComputedMethod (index)");
-        let obj = self.stack.pop().expect("VM Stack underflow on DefineComputedMethod (obj)");
+        let val = self.stack.pop().ok_or_else(stack_underflow)?;
+        let index = self.stack.pop().ok_or_else(stack_underflow)?;
+        let obj = self.stack.pop().ok_or_else(stack_underflow)?;
         let coerced_key = match self.as_property_key_string(ctx, &index) {
             Ok(key) => key,
             Err(err) => {
@@ -5657,9 +5719,9 @@ impl<'gc> VM<'gc> {
 
     // Opcode::InitIndex
     fn run_opcode_init_index(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let val = self.stack.pop().expect("VM Stack underflow on InitIndex (val)");
-        let index = self.stack.pop().expect("VM Stack underflow on InitIndex (index)");
-        let obj = self.stack.pop().expect("VM Stack underflow on InitIndex (obj)");
+        let val = self.stack.pop().ok_or_else(stack_underflow)?;
+        let index = self.stack.pop().ok_or_else(stack_underflow)?;
+        let obj = self.stack.pop().ok_or_else(stack_underflow)?;
         let coerced_key = match self.as_property_key_string(ctx, &index) {
             Ok(key) => key,
             Err(err) => {
@@ -5734,9 +5796,9 @@ impl<'gc> VM<'gc> {
     // Opcode::SetComputedGetter
     fn run_opcode_set_computed_getter(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
         // Stack: [obj, computed_key, val] → pop val, pop key, peek obj
-        let val = self.stack.pop().expect("VM Stack underflow on SetComputedGetter (val)");
-        let index = self.stack.pop().expect("VM Stack underflow on SetComputedGetter (index)");
-        let obj = self.stack.pop().expect("VM Stack underflow on SetComputedGetter (obj)");
+        let val = self.stack.pop().ok_or_else(stack_underflow)?;
+        let index = self.stack.pop().ok_or_else(stack_underflow)?;
+        let obj = self.stack.pop().ok_or_else(stack_underflow)?;
         let coerced_key = match self.as_property_key_string(ctx, &index) {
             Ok(key) => key,
             Err(err) => {
@@ -5831,9 +5893,9 @@ impl<'gc> VM<'gc> {
     // Opcode::SetComputedSetter
     fn run_opcode_set_computed_setter(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
         // Stack: [obj, computed_key, val] → pop val, pop key, peek obj
-        let val = self.stack.pop().expect("VM Stack underflow on SetComputedSetter (val)");
-        let index = self.stack.pop().expect("VM Stack underflow on SetComputedSetter (index)");
-        let obj = self.stack.pop().expect("VM Stack underflow on SetComputedSetter (obj)");
+        let val = self.stack.pop().ok_or_else(stack_underflow)?;
+        let index = self.stack.pop().ok_or_else(stack_underflow)?;
+        let obj = self.stack.pop().ok_or_else(stack_underflow)?;
         let coerced_key = match self.as_property_key_string(ctx, &index) {
             Ok(key) => key,
             Err(err) => {
@@ -5926,7 +5988,7 @@ impl<'gc> VM<'gc> {
 
     // Opcode::ToPropertyKey
     fn run_opcode_to_property_key(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let raw = self.stack.pop().expect("VM Stack underflow on ToPropertyKey");
+        let raw = self.stack.pop().ok_or_else(stack_underflow)?;
         let coerced_key = match self.as_property_key_string(ctx, &raw) {
             Ok(key) => key,
             Err(err) => {
@@ -5945,7 +6007,7 @@ impl<'gc> VM<'gc> {
     // Opcode::Increment
     fn run_opcode_increment(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
         let _ = ctx;
-        let a = self.stack.pop().expect("VM Stack underflow on Increment");
+        let a = self.stack.pop().ok_or_else(stack_underflow)?;
         match a {
             Value::Number(n) => self.stack.push(Value::Number(n + 1.0)),
             Value::BigInt(b) => self.stack.push(Value::BigInt(Box::new(&*b + 1))),
@@ -5957,7 +6019,7 @@ impl<'gc> VM<'gc> {
     // Opcode::Decrement
     fn run_opcode_decrement(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
         let _ = ctx;
-        let a = self.stack.pop().expect("VM Stack underflow on Decrement");
+        let a = self.stack.pop().ok_or_else(stack_underflow)?;
         match a {
             Value::Number(n) => self.stack.push(Value::Number(n - 1.0)),
             Value::BigInt(b) => self.stack.push(Value::BigInt(Box::new(&*b - 1))),
@@ -6159,7 +6221,7 @@ impl<'gc> VM<'gc> {
 
     // Opcode::GetKeys
     fn run_opcode_get_keys(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let obj = self.stack.pop().expect("VM Stack underflow on GetKeys");
+        let obj = self.stack.pop().ok_or_else(stack_underflow)?;
         let mut out_keys: Vec<Value<'gc>> = Vec::new();
         let mut seen_any: std::collections::HashSet<String> = std::collections::HashSet::new();
         let mut current = Some(obj.clone());
@@ -6360,7 +6422,7 @@ impl<'gc> VM<'gc> {
         } else {
             value_to_string(name_val)
         };
-        let raw_obj = self.stack.last().cloned().expect("VM Stack underflow on GetMethod");
+        let raw_obj = self.stack.last().cloned().ok_or_else(stack_underflow)?;
         // Accessing a property on undefined/null is a TypeError
         if matches!(raw_obj, Value::Undefined | Value::Null) {
             let err_msg = format!(
@@ -6731,7 +6793,7 @@ impl<'gc> VM<'gc> {
 
     // Opcode::ToNumber
     fn run_opcode_to_number(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let val = self.stack.pop().expect("VM Stack underflow on ToNumber");
+        let val = self.stack.pop().ok_or_else(stack_underflow)?;
         match &val {
             Value::Symbol(_) => {
                 self.throw_type_error(ctx, "Cannot convert a Symbol value to a number");
@@ -6766,7 +6828,7 @@ impl<'gc> VM<'gc> {
 
     // Opcode::ToNumeric — like ToNumber but preserves BigInt values
     fn run_opcode_to_numeric(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let val = self.stack.pop().expect("VM Stack underflow on ToNumeric");
+        let val = self.stack.pop().ok_or_else(stack_underflow)?;
         match &val {
             Value::Number(_) | Value::BigInt(_) => {
                 self.stack.push(val);
@@ -6800,7 +6862,10 @@ impl<'gc> VM<'gc> {
         // Collect excess function args into a rest array.
         // Operand: non_rest_count (u8) = number of formal non-rest params.
         let non_rest_count = self.read_byte() as usize;
-        let frame = self.frames.last().expect("CollectRest: no call frame");
+        let frame = self
+            .frames
+            .last()
+            .ok_or_else(|| crate::raise_eval_error!("Invalid bytecode: rest parameters outside a function"))?;
         let actual_arg_count = frame.arg_count;
         let bp = frame.bp;
         let saved = frame.saved_args.clone();
@@ -6812,7 +6877,7 @@ impl<'gc> VM<'gc> {
                 // No excess args were removed; they're still on the stack
                 let start = bp + non_rest_count;
                 let end = bp + actual_arg_count;
-                let elems = self.stack[start..end].to_vec();
+                let elems = self.stack.get(start..end).ok_or_else(stack_underflow)?.to_vec();
                 self.stack.drain(start..end);
                 elems
             };
@@ -6827,8 +6892,8 @@ impl<'gc> VM<'gc> {
 
     // Opcode::In
     fn run_opcode_in(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let mut obj = self.stack.pop().expect("VM Stack underflow on In (obj)");
-        let mut key_val = self.stack.pop().expect("VM Stack underflow on In (key)");
+        let mut obj = self.stack.pop().ok_or_else(stack_underflow)?;
+        let mut key_val = self.stack.pop().ok_or_else(stack_underflow)?;
 
         let is_object_like = |v: &Value<'gc>| {
             matches!(
@@ -7103,8 +7168,8 @@ impl<'gc> VM<'gc> {
 
     // Opcode::InstanceOf
     fn run_opcode_instanceof(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
-        let rhs = self.stack.pop().expect("VM Stack underflow on InstanceOf (rhs)");
-        let lhs = self.stack.pop().expect("VM Stack underflow on InstanceOf (lhs)");
+        let rhs = self.stack.pop().ok_or_else(stack_underflow)?;
+        let lhs = self.stack.pop().ok_or_else(stack_underflow)?;
 
         // Per spec §13.10.2 step 3: RHS must be an object (callable check comes after @@hasInstance)
         let is_object_like = matches!(
@@ -7392,7 +7457,7 @@ impl<'gc> VM<'gc> {
         } else {
             value_to_string(name_val)
         };
-        let obj = self.stack.pop().expect("VM Stack underflow on DeleteProperty");
+        let obj = self.stack.pop().ok_or_else(stack_underflow)?;
         match self.try_proxy_delete(ctx, &obj, &key) {
             Ok(Some(result)) => {
                 if !result && self.current_execution_is_strict() {
@@ -7554,7 +7619,7 @@ impl<'gc> VM<'gc> {
     fn run_opcode_new_call(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
         let arg_count = self.read_byte() as usize;
         // Stack: [..., constructor, arg0, arg1, ...]
-        let callee_idx = self.stack.len() - arg_count - 1;
+        let callee_idx = self.stack.len().checked_sub(arg_count + 1).ok_or_else(stack_underflow)?;
         let callee = self.stack[callee_idx].clone();
         if !self.is_constructor_value(&callee) {
             let callee_name = self.resolve_callee_name(callee_idx);
@@ -7643,6 +7708,7 @@ impl<'gc> VM<'gc> {
                 let is_derived_ctor = self.chunk.derived_constructor_ips.contains(&target_ip);
                 let frame = CallFrame {
                     return_ip: self.ip,
+                    call_ip: self.current_opcode_ip,
                     bp: callee_idx + 1,
                     is_method: false,
                     arg_count,
@@ -8269,8 +8335,8 @@ impl<'gc> VM<'gc> {
     // Opcode::DeleteIndex
     fn run_opcode_delete_index(&mut self, ctx: &GcContext<'gc>) -> Result<OpcodeAction<'gc>, JSError> {
         // Stack: [..., obj, index]
-        let idx_val = self.stack.pop().expect("VM Stack underflow on DeleteIndex (idx)");
-        let obj = self.stack.pop().expect("VM Stack underflow on DeleteIndex (obj)");
+        let idx_val = self.stack.pop().ok_or_else(stack_underflow)?;
+        let obj = self.stack.pop().ok_or_else(stack_underflow)?;
         // Proxy handling
         {
             let key = match self.as_property_key_string(ctx, &idx_val) {
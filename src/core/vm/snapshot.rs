@@ -0,0 +1,999 @@
+//! Startup snapshots: the heap of an initialized VM in a form that can be
+//! written out and turned back into a VM without running the builtin setup.
+//!
+//! Capturing walks everything reachable from the VM's roots and numbers each
+//! object, array, collection, symbol, closure cell and upvalue list on the
+//! way, so shared and cyclic references come back as the same objects.
+//! Restoring allocates every node empty first and then fills them in.
+//!
+//! An image file is the magic `JSSN`, the format version (u32), the version
+//! of the engine that wrote it, the optional builtins it was built with, the
+//! nodes, the VM roots, the bytecode of the prelude if there was one, and an
+//! FNV-1a checksum of all the bytes before it. Like bytecode, an image only
+//! loads in the engine version that wrote it, and only in a build with the
+//! same cargo features for builtins, since it holds the globals they register.
+
+use super::*;
+use crate::core::bytecode::{CHECKSUM_LEN, CompiledScript, Decode, Encode, Reader, Writer, fnv1a};
+use num_bigint::BigInt;
+use std::collections::HashSet;
+
+const MAGIC: &[u8; 4] = b"JSSN";
+const FORMAT_VERSION: u32 = 3;
+const ENGINE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The cargo features that add or remove builtins, as written into images.
+fn builtin_features() -> String {
+    [
+        ("intl", cfg!(feature = "intl")),
+        ("temporal", cfg!(feature = "temporal")),
+        ("shared-memory", cfg!(feature = "shared-memory")),
+        ("typed-arrays", cfg!(feature = "typed-arrays")),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect::<Vec<_>>()
+    .join(",")
+}
+
+const NODE_OBJECT: u8 = 0;
+const NODE_ARRAY: u8 = 1;
+const NODE_MAP: u8 = 2;
+const NODE_SET: u8 = 3;
+const NODE_SYMBOL: u8 = 4;
+const NODE_CELL: u8 = 5;
+const NODE_UPVALUES: u8 = 6;
+
+const VALUE_UNDEFINED: u8 = 0;
+const VALUE_NULL: u8 = 1;
+const VALUE_UNINITIALIZED: u8 = 2;
+const VALUE_BOOLEAN: u8 = 3;
+const VALUE_NUMBER: u8 = 4;
+const VALUE_STRING: u8 = 5;
+const VALUE_BIGINT: u8 = 6;
+const VALUE_FUNCTION: u8 = 7;
+const VALUE_NATIVE_FUNCTION: u8 = 8;
+const VALUE_CLOSURE: u8 = 9;
+const VALUE_OBJECT: u8 = 10;
+const VALUE_ARRAY: u8 = 11;
+const VALUE_MAP: u8 = 12;
+const VALUE_SET: u8 = 13;
+const VALUE_SYMBOL: u8 = 14;
+const VALUE_PROPERTY: u8 = 15;
+
+/// Whether `bytes` starts like a snapshot image.
+fn is_snapshot(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// A [`Value`] with every heap reference replaced by a node index.
+enum ImageValue {
+    Undefined,
+    Null,
+    Uninitialized,
+    Boolean(bool),
+    Number(f64),
+    String(Vec<u16>),
+    BigInt(Vec<u8>),
+    Function(usize, u8),
+    NativeFunction(usize),
+    Closure(usize, u8, usize),
+    Object(usize),
+    Array(usize),
+    Map(usize),
+    Set(usize),
+    Symbol(usize),
+    Property {
+        value: Option<Box<ImageValue>>,
+        getter: Option<Box<ImageValue>>,
+        setter: Option<Box<ImageValue>>,
+        attrs: u8,
+    },
+}
+
+/// One heap allocation in an image.
+enum ImageNode {
+    Object(Vec<(String, ImageValue)>),
+    Array {
+        elements: Vec<ImageValue>,
+        props: Vec<(String, ImageValue)>,
+    },
+    Map {
+        entries: Vec<(ImageValue, ImageValue)>,
+        is_weak: bool,
+    },
+    Set {
+        values: Vec<ImageValue>,
+        is_weak: bool,
+    },
+    Symbol {
+        id: u64,
+        description: Option<Vec<u16>>,
+        registered: bool,
+    },
+    Cell(ImageValue),
+    Upvalues(Vec<usize>),
+}
+
+impl ImageNode {
+    fn tag(&self) -> u8 {
+        match self {
+            ImageNode::Object(_) => NODE_OBJECT,
+            ImageNode::Array { .. } => NODE_ARRAY,
+            ImageNode::Map { .. } => NODE_MAP,
+            ImageNode::Set { .. } => NODE_SET,
+            ImageNode::Symbol { .. } => NODE_SYMBOL,
+            ImageNode::Cell(_) => NODE_CELL,
+            ImageNode::Upvalues(_) => NODE_UPVALUES,
+        }
+    }
+}
+
+/// The heap and realm state of a VM at rest, free of `Gc` pointers so it can
+/// be shared between threads and restored into any number of arenas.
+pub(crate) struct HeapImage {
+    nodes: Vec<ImageNode>,
+    globals: Vec<(String, ImageValue)>,
+    const_globals: HashSet<String>,
+    global_this: usize,
+    symbol_counter: u64,
+    symbol_registry: Vec<(String, ImageValue)>,
+    fn_props: Vec<(usize, usize)>,
+    native_fn_props: Vec<(usize, usize)>,
+    fn_home_objects: Vec<(usize, ImageValue)>,
+    closure_fn_props: Vec<(usize, usize)>,
+    top_level_cells: Vec<(usize, usize)>,
+    template_cache: Vec<(u64, ImageValue)>,
+    /// Generator, async generator, `Promise` and `%ThrowTypeError%` intrinsics, in field order.
+    intrinsics: Vec<ImageValue>,
+    runtime_brand_counter: usize,
+    next_generator_id: usize,
+    next_async_function_id: usize,
+    /// Bytecode of the prelude; its functions refer into it by IP.
+    chunk: Option<Vec<u8>>,
+}
+
+impl HeapImage {
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let mut w = Writer::default();
+        w.out.extend_from_slice(MAGIC);
+        w.out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        ENGINE_VERSION.to_string().encode(&mut w);
+        builtin_features().encode(&mut w);
+
+        let HeapImage {
+            nodes,
+            globals,
+            const_globals,
+            global_this,
+            symbol_counter,
+            symbol_registry,
+            fn_props,
+            native_fn_props,
+            fn_home_objects,
+            closure_fn_props,
+            top_level_cells,
+            template_cache,
+            intrinsics,
+            runtime_brand_counter,
+            next_generator_id,
+            next_async_function_id,
+            chunk,
+        } = self;
+        nodes.encode(&mut w);
+        globals.encode(&mut w);
+        const_globals.encode(&mut w);
+        global_this.encode(&mut w);
+        symbol_counter.encode(&mut w);
+        symbol_registry.encode(&mut w);
+        fn_props.encode(&mut w);
+        native_fn_props.encode(&mut w);
+        fn_home_objects.encode(&mut w);
+        closure_fn_props.encode(&mut w);
+        top_level_cells.encode(&mut w);
+        template_cache.encode(&mut w);
+        intrinsics.encode(&mut w);
+        runtime_brand_counter.encode(&mut w);
+        next_generator_id.encode(&mut w);
+        next_async_function_id.encode(&mut w);
+        chunk.encode(&mut w);
+
+        let checksum = fnv1a(&w.out);
+        w.out.extend_from_slice(&checksum.to_le_bytes());
+        w.out
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self, JSError> {
+        if !is_snapshot(bytes) {
+            return Err(invalid("not a snapshot image"));
+        }
+        let mut r = Reader { bytes, pos: MAGIC.len() };
+        let format = u32::from_le_bytes(r.take(4)?.try_into().unwrap());
+        if format != FORMAT_VERSION {
+            return Err(invalid(format!(
+                "format version {format} is not supported (expected {FORMAT_VERSION})"
+            )));
+        }
+        let engine = String::decode(&mut r)?;
+        if engine != ENGINE_VERSION {
+            return Err(invalid(format!(
+                "written by engine version {engine}, but this is {ENGINE_VERSION}; create the snapshot again"
+            )));
+        }
+        let features = String::decode(&mut r)?;
+        if features != builtin_features() {
+            return Err(invalid(format!(
+                "built with features [{features}], but this engine has [{}]; create the snapshot again",
+                builtin_features()
+            )));
+        }
+        let Some(body_len) = bytes.len().checked_sub(CHECKSUM_LEN).filter(|&len| len >= r.pos) else {
+            return Err(invalid("file is truncated"));
+        };
+        let (body, checksum) = bytes.split_at(body_len);
+        if fnv1a(body).to_le_bytes() != checksum {
+            return Err(invalid("checksum mismatch; the file is corrupt"));
+        }
+        r.bytes = body;
+
+        let image = HeapImage {
+            nodes: Decode::decode(&mut r)?,
+            globals: Decode::decode(&mut r)?,
+            const_globals: Decode::decode(&mut r)?,
+            global_this: Decode::decode(&mut r)?,
+            symbol_counter: Decode::decode(&mut r)?,
+            symbol_registry: Decode::decode(&mut r)?,
+            fn_props: Decode::decode(&mut r)?,
+            native_fn_props: Decode::decode(&mut r)?,
+            fn_home_objects: Decode::decode(&mut r)?,
+            closure_fn_props: Decode::decode(&mut r)?,
+            top_level_cells: Decode::decode(&mut r)?,
+            template_cache: Decode::decode(&mut r)?,
+            intrinsics: Decode::decode(&mut r)?,
+            runtime_brand_counter: Decode::decode(&mut r)?,
+            next_generator_id: Decode::decode(&mut r)?,
+            next_async_function_id: Decode::decode(&mut r)?,
+            chunk: Decode::decode(&mut r)?,
+        };
+        if r.remaining() != 0 {
+            return Err(invalid("unexpected data after the image"));
+        }
+        image.validate()?;
+        Ok(image)
+    }
+
+    /// Check that every reference names a node of the right kind and every
+    /// function points into the prelude's code, so restoring cannot fail.
+    fn validate(&self) -> Result<(), JSError> {
+        let code_len = match &self.chunk {
+            Some(bytes) => CompiledScript::from_bytes(bytes)?.chunk.code.len(),
+            None => 0,
+        };
+        if self.intrinsics.len() != INTRINSIC_COUNT {
+            return Err(invalid(format!(
+                "expected {INTRINSIC_COUNT} intrinsics, found {}",
+                self.intrinsics.len()
+            )));
+        }
+        for node in &self.nodes {
+            match node {
+                ImageNode::Object(props) => self.validate_props(props, code_len)?,
+                ImageNode::Array { elements, props } => {
+                    for value in elements {
+                        self.validate_value(value, code_len)?;
+                    }
+                    self.validate_props(props, code_len)?;
+                }
+                ImageNode::Map { entries, .. } => {
+                    for (key, value) in entries {
+                        self.validate_value(key, code_len)?;
+                        self.validate_value(value, code_len)?;
+                    }
+                }
+                ImageNode::Set { values, .. } => {
+                    for value in values {
+                        self.validate_value(value, code_len)?;
+                    }
+                }
+                ImageNode::Symbol { .. } => {}
+                ImageNode::Cell(value) => self.validate_value(value, code_len)?,
+                ImageNode::Upvalues(cells) => {
+                    for &cell in cells {
+                        self.expect_node(cell, NODE_CELL)?;
+                    }
+                }
+            }
+        }
+        self.validate_props(&self.globals, code_len)?;
+        self.expect_node(self.global_this, NODE_OBJECT)?;
+        self.validate_props(&self.symbol_registry, code_len)?;
+        for &(_, obj) in self.fn_props.iter().chain(&self.native_fn_props) {
+            self.expect_node(obj, NODE_OBJECT)?;
+        }
+        for (_, value) in &self.fn_home_objects {
+            self.validate_value(value, code_len)?;
+        }
+        for &(upvalues, obj) in &self.closure_fn_props {
+            self.expect_node(upvalues, NODE_UPVALUES)?;
+            self.expect_node(obj, NODE_OBJECT)?;
+        }
+        for &(_, cell) in &self.top_level_cells {
+            self.expect_node(cell, NODE_CELL)?;
+        }
+        for value in self.template_cache.iter().map(|(_, value)| value).chain(&self.intrinsics) {
+            self.validate_value(value, code_len)?;
+        }
+        Ok(())
+    }
+
+    fn validate_props(&self, props: &[(String, ImageValue)], code_len: usize) -> Result<(), JSError> {
+        props.iter().try_for_each(|(_, value)| self.validate_value(value, code_len))
+    }
+
+    fn validate_value(&self, value: &ImageValue, code_len: usize) -> Result<(), JSError> {
+        match value {
+            ImageValue::Function(ip, _) if *ip >= code_len => Err(invalid(format!("function entry {ip} is outside the code"))),
+            ImageValue::Closure(ip, _, _) if *ip >= code_len => Err(invalid(format!("function entry {ip} is outside the code"))),
+            ImageValue::Closure(_, _, upvalues) => self.expect_node(*upvalues, NODE_UPVALUES),
+            ImageValue::Object(id) => self.expect_node(*id, NODE_OBJECT),
+            ImageValue::Array(id) => self.expect_node(*id, NODE_ARRAY),
+            ImageValue::Map(id) => self.expect_node(*id, NODE_MAP),
+            ImageValue::Set(id) => self.expect_node(*id, NODE_SET),
+            ImageValue::Symbol(id) => self.expect_node(*id, NODE_SYMBOL),
+            ImageValue::Property { value, getter, setter, .. } => {
+                for part in [value, getter, setter].into_iter().flatten() {
+                    self.validate_value(part, code_len)?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn expect_node(&self, id: usize, tag: u8) -> Result<(), JSError> {
+        match self.nodes.get(id) {
+            Some(node) if node.tag() == tag => Ok(()),
+            Some(_) => Err(invalid(format!("node {id} is not of the kind referred to"))),
+            None => Err(invalid(format!("reference to missing node {id}"))),
+        }
+    }
+}
+
+const INTRINSIC_COUNT: usize = 10;
+
+fn invalid(reason: impl std::fmt::Display) -> JSError {
+    crate::raise_eval_error!(format!("Invalid snapshot: {reason}"))
+}
+
+/// A heap allocation, while capturing or restoring.
+#[derive(Clone, Copy)]
+enum HeapRef<'gc> {
+    Object(ObjectHandle<'gc>),
+    Array(ArrayHandle<'gc>),
+    Map(MapHandle<'gc>),
+    Set(SetHandle<'gc>),
+    Symbol(SymbolHandle<'gc>),
+    Cell(VmUpvalueCell<'gc>),
+    Upvalues(UpvalueCells<'gc>),
+}
+
+impl<'gc> HeapRef<'gc> {
+    fn address(self) -> usize {
+        match self {
+            HeapRef::Object(obj) => Gc::as_ptr(obj) as usize,
+            HeapRef::Array(arr) => Gc::as_ptr(arr) as usize,
+            HeapRef::Map(map) => Gc::as_ptr(map) as usize,
+            HeapRef::Set(set) => Gc::as_ptr(set) as usize,
+            HeapRef::Symbol(sym) => Gc::as_ptr(sym) as usize,
+            HeapRef::Cell(cell) => Gc::as_ptr(cell) as usize,
+            HeapRef::Upvalues(cells) => Gc::as_ptr(cells) as usize,
+        }
+    }
+}
+
+/// Numbers heap allocations in the order they are first reached.
+#[derive(Default)]
+struct HeapCapture<'gc> {
+    ids: HashMap<usize, usize>,
+    refs: Vec<HeapRef<'gc>>,
+}
+
+impl<'gc> HeapCapture<'gc> {
+    fn node(&mut self, node: HeapRef<'gc>) -> usize {
+        let address = node.address();
+        if let Some(&id) = self.ids.get(&address) {
+            return id;
+        }
+        let id = self.refs.len();
+        self.refs.push(node);
+        self.ids.insert(address, id);
+        id
+    }
+
+    fn value(&mut self, value: &Value<'gc>) -> ImageValue {
+        match value {
+            Value::Undefined => ImageValue::Undefined,
+            Value::Null => ImageValue::Null,
+            Value::Uninitialized => ImageValue::Uninitialized,
+            Value::Boolean(b) => ImageValue::Boolean(*b),
+            Value::Number(n) => ImageValue::Number(*n),
+            Value::String(units) => ImageValue::String(units.clone()),
+            Value::BigInt(n) => ImageValue::BigInt(n.to_signed_bytes_le()),
+            Value::Function(ip, arity) => ImageValue::Function(*ip, *arity),
+            Value::NativeFunction(id) => ImageValue::NativeFunction(*id),
+            Value::Closure(ip, arity, upvalues) => ImageValue::Closure(*ip, *arity, self.node(HeapRef::Upvalues(*upvalues))),
+            Value::Object(obj) => ImageValue::Object(self.node(HeapRef::Object(*obj))),
+            Value::Array(arr) => ImageValue::Array(self.node(HeapRef::Array(*arr))),
+            Value::Map(map) => ImageValue::Map(self.node(HeapRef::Map(*map))),
+            Value::Set(set) => ImageValue::Set(self.node(HeapRef::Set(*set))),
+            Value::Symbol(sym) => ImageValue::Symbol(self.node(HeapRef::Symbol(*sym))),
+            Value::Property {
+                value,
+                getter,
+                setter,
+                attrs,
+            } => ImageValue::Property {
+                value: value.as_ref().map(|v| Box::new(self.value(v))),
+                getter: getter.as_ref().map(|v| Box::new(self.value(v))),
+                setter: setter.as_ref().map(|v| Box::new(self.value(v))),
+                attrs: attrs.bits(),
+            },
+        }
+    }
+
+    fn props(&mut self, props: &IndexMap<String, Value<'gc>>) -> Vec<(String, ImageValue)> {
+        props.iter().map(|(key, value)| (key.clone(), self.value(value))).collect()
+    }
+
+    fn image_node(&mut self, node: HeapRef<'gc>) -> ImageNode {
+        match node {
+            HeapRef::Object(obj) => ImageNode::Object(self.props(&obj.borrow())),
+            HeapRef::Array(arr) => {
+                let arr = arr.borrow();
+                ImageNode::Array {
+                    elements: arr.elements.iter().map(|v| self.value(v)).collect(),
+                    props: self.props(&arr.props),
+                }
+            }
+            HeapRef::Map(map) => {
+                let map = map.borrow();
+                ImageNode::Map {
+                    entries: map.entries.iter().map(|(k, v)| (self.value(k), self.value(v))).collect(),
+                    is_weak: map.is_weak,
+                }
+            }
+            HeapRef::Set(set) => {
+                let set = set.borrow();
+                ImageNode::Set {
+                    values: set.values.iter().map(|v| self.value(v)).collect(),
+                    is_weak: set.is_weak,
+                }
+            }
+            HeapRef::Symbol(sym) => {
+                let sym = sym.borrow();
+                ImageNode::Symbol {
+                    id: sym.id,
+                    description: sym.description.clone(),
+                    registered: sym.registered,
+                }
+            }
+            HeapRef::Cell(cell) => ImageNode::Cell(self.value(&cell.borrow())),
+            HeapRef::Upvalues(cells) => ImageNode::Upvalues(cells.iter().map(|cell| self.node(HeapRef::Cell(*cell))).collect()),
+        }
+    }
+}
+
+/// The allocations of an image restored into an arena, by node index.
+struct RestoredHeap<'gc> {
+    refs: Vec<HeapRef<'gc>>,
+}
+
+impl<'gc> RestoredHeap<'gc> {
+    fn new(ctx: &GcContext<'gc>, image: &HeapImage) -> Self {
+        let mut refs: Vec<Option<HeapRef<'gc>>> = image
+            .nodes
+            .iter()
+            .map(|node| match node {
+                ImageNode::Object(props) => Some(HeapRef::Object(new_gc_cell_ptr(ctx, IndexMap::with_capacity(props.len())))),
+                ImageNode::Array { .. } => Some(HeapRef::Array(new_gc_cell_ptr(ctx, VmArrayData::new(Vec::new())))),
+                ImageNode::Map { is_weak, .. } => Some(HeapRef::Map(new_gc_cell_ptr(
+                    ctx,
+                    VmMapData {
+                        entries: Vec::new(),
+                        is_weak: *is_weak,
+                    },
+                ))),
+                ImageNode::Set { is_weak, .. } => Some(HeapRef::Set(new_gc_cell_ptr(
+                    ctx,
+                    VmSetData {
+                        values: Vec::new(),
+                        is_weak: *is_weak,
+                    },
+                ))),
+                ImageNode::Symbol {
+                    id,
+                    description,
+                    registered,
+                } => Some(HeapRef::Symbol(new_gc_cell_ptr(
+                    ctx,
+                    VmSymbolData {
+                        id: *id,
+                        description: description.clone(),
+                        registered: *registered,
+                    },
+                ))),
+                ImageNode::Cell(_) => Some(HeapRef::Cell(new_gc_cell_ptr(ctx, Value::Undefined))),
+                ImageNode::Upvalues(_) => None,
+            })
+            .collect();
+        // Upvalue lists are immutable, so they are built once their cells exist.
+        for (id, node) in image.nodes.iter().enumerate() {
+            if let ImageNode::Upvalues(cells) = node {
+                let cells = cells
+                    .iter()
+                    .map(|&cell| match refs[cell] {
+                        Some(HeapRef::Cell(cell)) => cell,
+                        _ => unreachable!("node kinds are checked when the image is loaded"),
+                    })
+                    .collect();
+                refs[id] = Some(HeapRef::Upvalues(Gc::new(ctx, cells)));
+            }
+        }
+        let heap = RestoredHeap {
+            refs: refs.into_iter().map(|node| node.expect("every node is allocated")).collect(),
+        };
+
+        for (node, image_node) in heap.refs.iter().zip(&image.nodes) {
+            match (node, image_node) {
+                (HeapRef::Object(obj), ImageNode::Object(props)) => *obj.borrow_mut(ctx) = heap.props(props),
+                (HeapRef::Array(arr), ImageNode::Array { elements, props }) => {
+                    let mut arr = arr.borrow_mut(ctx);
+                    arr.elements = elements.iter().map(|v| heap.value(v)).collect();
+                    arr.props = heap.props(props);
+                }
+                (HeapRef::Map(map), ImageNode::Map { entries, .. }) => {
+                    map.borrow_mut(ctx).entries = entries.iter().map(|(k, v)| (heap.value(k), heap.value(v))).collect();
+                }
+                (HeapRef::Set(set), ImageNode::Set { values, .. }) => {
+                    set.borrow_mut(ctx).values = values.iter().map(|v| heap.value(v)).collect();
+                }
+                (HeapRef::Cell(cell), ImageNode::Cell(value)) => *cell.borrow_mut(ctx) = heap.value(value),
+                _ => {}
+            }
+        }
+        heap
+    }
+
+    fn value(&self, value: &ImageValue) -> Value<'gc> {
+        match value {
+            ImageValue::Undefined => Value::Undefined,
+            ImageValue::Null => Value::Null,
+            ImageValue::Uninitialized => Value::Uninitialized,
+            ImageValue::Boolean(b) => Value::Boolean(*b),
+            ImageValue::Number(n) => Value::Number(*n),
+            ImageValue::String(units) => Value::String(units.clone()),
+            ImageValue::BigInt(bytes) => Value::BigInt(Box::new(BigInt::from_signed_bytes_le(bytes))),
+            ImageValue::Function(ip, arity) => Value::Function(*ip, *arity),
+            ImageValue::NativeFunction(id) => Value::NativeFunction(*id),
+            ImageValue::Closure(ip, arity, upvalues) => Value::Closure(*ip, *arity, self.upvalues(*upvalues)),
+            ImageValue::Object(id) => Value::Object(self.object(*id)),
+            ImageValue::Array(id) => match self.refs[*id] {
+                HeapRef::Array(arr) => Value::Array(arr),
+                _ => unreachable!("node kinds are checked when the image is loaded"),
+            },
+            ImageValue::Map(id) => match self.refs[*id] {
+                HeapRef::Map(map) => Value::Map(map),
+                _ => unreachable!("node kinds are checked when the image is loaded"),
+            },
+            ImageValue::Set(id) => match self.refs[*id] {
+                HeapRef::Set(set) => Value::Set(set),
+                _ => unreachable!("node kinds are checked when the image is loaded"),
+            },
+            ImageValue::Symbol(id) => match self.refs[*id] {
+                HeapRef::Symbol(sym) => Value::Symbol(sym),
+                _ => unreachable!("node kinds are checked when the image is loaded"),
+            },
+            ImageValue::Property {
+                value,
+                getter,
+                setter,
+                attrs,
+            } => Value::Property {
+                value: value.as_ref().map(|v| Box::new(self.value(v))),
+                getter: getter.as_ref().map(|v| Box::new(self.value(v))),
+                setter: setter.as_ref().map(|v| Box::new(self.value(v))),
+                attrs: PropAttrs::from_bits_truncate(*attrs),
+            },
+        }
+    }
+
+    fn props(&self, props: &[(String, ImageValue)]) -> IndexMap<String, Value<'gc>> {
+        props.iter().map(|(key, value)| (key.clone(), self.value(value))).collect()
+    }
+
+    fn object(&self, id: usize) -> ObjectHandle<'gc> {
+        match self.refs[id] {
+            HeapRef::Object(obj) => obj,
+            _ => unreachable!("node kinds are checked when the image is loaded"),
+        }
+    }
+
+    fn cell(&self, id: usize) -> VmUpvalueCell<'gc> {
+        match self.refs[id] {
+            HeapRef::Cell(cell) => cell,
+            _ => unreachable!("node kinds are checked when the image is loaded"),
+        }
+    }
+
+    fn upvalues(&self, id: usize) -> UpvalueCells<'gc> {
+        match self.refs[id] {
+            HeapRef::Upvalues(cells) => cells,
+            _ => unreachable!("node kinds are checked when the image is loaded"),
+        }
+    }
+}
+
+/// Map entries sorted by key, so a VM always captures to the same image.
+fn sorted_entries<K: Ord, V>(map: &HashMap<K, V>) -> Vec<(&K, &V)> {
+    let mut entries: Vec<(&K, &V)> = map.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+impl<'gc> VM<'gc> {
+    /// Capture the heap and realm state of this VM. Only a VM at rest can be
+    /// captured: no pending jobs or timers, no suspended generators or async
+    /// functions, and no modules or other realms, none of which an image holds.
+    pub(crate) fn capture_heap_image(&self) -> Result<HeapImage, JSError> {
+        if !self.frames.is_empty()
+            || !self.microtask_queue.is_empty()
+            || !self.pending_timers.is_empty()
+            || !self.async_atomics_waits.is_empty()
+        {
+            return Err(crate::raise_eval_error!("Cannot snapshot a VM with jobs or timers still pending"));
+        }
+        if !self.generator_states.is_empty() || !self.async_function_states.is_empty() {
+            return Err(crate::raise_eval_error!(
+                "Cannot snapshot a VM with suspended generators or async functions"
+            ));
+        }
+        if !self.child_realms.is_empty() || !self.module_records.is_empty() || !self.loaded_modules.is_empty() {
+            return Err(crate::raise_eval_error!(
+                "Cannot snapshot a VM that created realms or loaded modules"
+            ));
+        }
+        if !self.host_callbacks.is_empty() {
+            return Err(crate::raise_eval_error!("Cannot snapshot a VM with host functions registered"));
+        }
+
+        let mut capture = HeapCapture::default();
+        let globals = capture.props(&self.globals);
+        let global_this = capture.node(HeapRef::Object(self.global_this));
+        let symbol_registry = sorted_entries(&self.symbol_registry)
+            .into_iter()
+            .map(|(key, value)| (key.clone(), capture.value(value)))
+            .collect();
+        let fn_props = sorted_entries(&self.fn_props)
+            .into_iter()
+            .map(|(ip, obj)| (*ip, capture.node(HeapRef::Object(*obj))))
+            .collect();
+        let native_fn_props = sorted_entries(&self.native_fn_props)
+            .into_iter()
+            .map(|(id, obj)| (*id, capture.node(HeapRef::Object(*obj))))
+            .collect();
+        let fn_home_objects = sorted_entries(&self.fn_home_objects)
+            .into_iter()
+            .map(|(ip, home)| (*ip, capture.value(home)))
+            .collect();
+        let top_level_cells = sorted_entries(&self.top_level_cells)
+            .into_iter()
+            .map(|(slot, cell)| (*slot, capture.node(HeapRef::Cell(*cell))))
+            .collect();
+        let template_cache = sorted_entries(&self.template_cache)
+            .into_iter()
+            .map(|(site, template)| (*site, capture.value(template)))
+            .collect();
+        let intrinsics = [
+            &self.generator_prototype,
+            &self.generator_function_prototype,
+            &self.async_generator_prototype,
+            &self.async_generator_function_prototype,
+            &self.intrinsic_promise_ctor,
+            &self.restricted_thrower_intrinsic,
+            &self.intrinsic_map_ctor,
+            &self.intrinsic_date_ctor,
+            &self.intrinsic_uint8array_ctor,
+            &self.intrinsic_set_ctor,
+        ]
+        .into_iter()
+        .map(|value| capture.value(value))
+        .collect();
+
+        // Per-closure overlays are keyed by the address of the closure's
+        // upvalue list; keep those whose closure is still reachable, which
+        // can make more closures reachable in turn.
+        let mut nodes = Vec::new();
+        let mut closure_fn_props: Vec<(usize, usize)> = Vec::new();
+        loop {
+            while nodes.len() < capture.refs.len() {
+                let node = capture.refs[nodes.len()];
+                let image_node = capture.image_node(node);
+                nodes.push(image_node);
+            }
+            let mut reached: Vec<(usize, ObjectHandle<'gc>)> = self
+                .closure_fn_props
+                .iter()
+                .filter_map(|(address, obj)| {
+                    let id = *capture.ids.get(address)?;
+                    matches!(capture.refs[id], HeapRef::Upvalues(_)).then_some((id, *obj))
+                })
+                .filter(|(id, _)| !closure_fn_props.iter().any(|(done, _)| done == id))
+                .collect();
+            if reached.is_empty() {
+                break;
+            }
+            reached.sort_by_key(|(id, _)| *id);
+            for (id, obj) in reached {
+                closure_fn_props.push((id, capture.node(HeapRef::Object(obj))));
+            }
+        }
+
+        let chunk = if self.chunk.code.is_empty() {
+            None
+        } else {
+            Some(
+                CompiledScript {
+                    chunk: self.chunk.clone(),
+                    strict: false,
+                }
+                .to_bytes()?,
+            )
+        };
+
+        Ok(HeapImage {
+            nodes,
+            globals,
+            const_globals: self.const_globals.clone(),
+            global_this,
+            symbol_counter: self.symbol_counter,
+            symbol_registry,
+            fn_props,
+            native_fn_props,
+            fn_home_objects,
+            closure_fn_props,
+            top_level_cells,
+            template_cache,
+            intrinsics,
+            runtime_brand_counter: self.runtime_brand_counter,
+            next_generator_id: self.next_generator_id,
+            next_async_function_id: self.next_async_function_id,
+            chunk,
+        })
+    }
+
+    /// Build a VM from a snapshot image instead of registering the builtins.
+    pub(crate) fn from_heap_image(image: &HeapImage, ctx: &GcContext<'gc>) -> Self {
+        let mut vm = Self::without_builtins(Chunk::new(), ctx);
+        let heap = RestoredHeap::new(ctx, image);
+
+        vm.globals = heap.props(&image.globals);
+        vm.const_globals = image.const_globals.clone();
+        vm.global_this = heap.object(image.global_this);
+        vm.this_stack = vec![Value::Object(vm.global_this)];
+        vm.symbol_counter = image.symbol_counter;
+        vm.symbol_registry = image
+            .symbol_registry
+            .iter()
+            .map(|(key, value)| (key.clone(), heap.value(value)))
+            .collect();
+        vm.symbol_values = heap
+            .refs
+            .iter()
+            .filter_map(|node| match node {
+                HeapRef::Symbol(sym) => Some((sym.borrow().id, Self::downgrade_handle(*sym))),
+                _ => None,
+            })
+            .collect();
+        vm.fn_props = image.fn_props.iter().map(|&(ip, obj)| (ip, heap.object(obj))).collect();
+        vm.native_fn_props = image.native_fn_props.iter().map(|&(id, obj)| (id, heap.object(obj))).collect();
+        vm.fn_home_objects = image.fn_home_objects.iter().map(|(ip, home)| (*ip, heap.value(home))).collect();
+        vm.closure_fn_props = image
+            .closure_fn_props
+            .iter()
+            .map(|&(upvalues, obj)| (Gc::as_ptr(heap.upvalues(upvalues)) as usize, heap.object(obj)))
+            .collect();
+        vm.top_level_cells = image.top_level_cells.iter().map(|&(slot, cell)| (slot, heap.cell(cell))).collect();
+        vm.template_cache = image
+            .template_cache
+            .iter()
+            .map(|(site, template)| (*site, heap.value(template)))
+            .collect();
+
+        let mut intrinsics = image.intrinsics.iter().map(|value| heap.value(value));
+        let mut next_intrinsic = || intrinsics.next().expect("intrinsic count is checked when the image is loaded");
+        vm.generator_prototype = next_intrinsic();
+        vm.generator_function_prototype = next_intrinsic();
+        vm.async_generator_prototype = next_intrinsic();
+        vm.async_generator_function_prototype = next_intrinsic();
+        vm.intrinsic_promise_ctor = next_intrinsic();
+        vm.restricted_thrower_intrinsic = next_intrinsic();
+        vm.intrinsic_map_ctor = next_intrinsic();
+        vm.intrinsic_date_ctor = next_intrinsic();
+        vm.intrinsic_uint8array_ctor = next_intrinsic();
+        vm.intrinsic_set_ctor = next_intrinsic();
+
+        vm.runtime_brand_counter = image.runtime_brand_counter;
+        vm.next_generator_id = image.next_generator_id;
+        vm.next_async_function_id = image.next_async_function_id;
+        if let Some(bytes) = &image.chunk {
+            vm.chunk = CompiledScript::from_bytes(bytes)
+                .expect("the prelude bytecode is checked when the image is loaded")
+                .chunk;
+        }
+        vm
+    }
+}
+
+impl Encode for ImageValue {
+    fn encode(&self, w: &mut Writer) {
+        match self {
+            ImageValue::Undefined => w.byte(VALUE_UNDEFINED),
+            ImageValue::Null => w.byte(VALUE_NULL),
+            ImageValue::Uninitialized => w.byte(VALUE_UNINITIALIZED),
+            ImageValue::Boolean(b) => {
+                w.byte(VALUE_BOOLEAN);
+                b.encode(w);
+            }
+            ImageValue::Number(n) => {
+                w.byte(VALUE_NUMBER);
+                n.encode(w);
+            }
+            ImageValue::String(units) => {
+                w.byte(VALUE_STRING);
+                units.encode(w);
+            }
+            ImageValue::BigInt(bytes) => {
+                w.byte(VALUE_BIGINT);
+                bytes.encode(w);
+            }
+            ImageValue::Function(ip, arity) => {
+                w.byte(VALUE_FUNCTION);
+                ip.encode(w);
+                arity.encode(w);
+            }
+            ImageValue::NativeFunction(id) => {
+                w.byte(VALUE_NATIVE_FUNCTION);
+                id.encode(w);
+            }
+            ImageValue::Closure(ip, arity, upvalues) => {
+                w.byte(VALUE_CLOSURE);
+                ip.encode(w);
+                arity.encode(w);
+                upvalues.encode(w);
+            }
+            ImageValue::Object(id) => {
+                w.byte(VALUE_OBJECT);
+                id.encode(w);
+            }
+            ImageValue::Array(id) => {
+                w.byte(VALUE_ARRAY);
+                id.encode(w);
+            }
+            ImageValue::Map(id) => {
+                w.byte(VALUE_MAP);
+                id.encode(w);
+            }
+            ImageValue::Set(id) => {
+                w.byte(VALUE_SET);
+                id.encode(w);
+            }
+            ImageValue::Symbol(id) => {
+                w.byte(VALUE_SYMBOL);
+                id.encode(w);
+            }
+            ImageValue::Property {
+                value,
+                getter,
+                setter,
+                attrs,
+            } => {
+                w.byte(VALUE_PROPERTY);
+                value.encode(w);
+                getter.encode(w);
+                setter.encode(w);
+                attrs.encode(w);
+            }
+        }
+    }
+}
+
+impl Decode for ImageValue {
+    fn decode(r: &mut Reader<'_>) -> Result<Self, JSError> {
+        let tag = r.byte()?;
+        Ok(match tag {
+            VALUE_UNDEFINED => ImageValue::Undefined,
+            VALUE_NULL => ImageValue::Null,
+            VALUE_UNINITIALIZED => ImageValue::Uninitialized,
+            VALUE_BOOLEAN => ImageValue::Boolean(Decode::decode(r)?),
+            VALUE_NUMBER => ImageValue::Number(Decode::decode(r)?),
+            VALUE_STRING => ImageValue::String(Decode::decode(r)?),
+            VALUE_BIGINT => ImageValue::BigInt(Decode::decode(r)?),
+            VALUE_FUNCTION => ImageValue::Function(Decode::decode(r)?, Decode::decode(r)?),
+            VALUE_NATIVE_FUNCTION => ImageValue::NativeFunction(Decode::decode(r)?),
+            VALUE_CLOSURE => ImageValue::Closure(Decode::decode(r)?, Decode::decode(r)?, Decode::decode(r)?),
+            VALUE_OBJECT => ImageValue::Object(Decode::decode(r)?),
+            VALUE_ARRAY => ImageValue::Array(Decode::decode(r)?),
+            VALUE_MAP => ImageValue::Map(Decode::decode(r)?),
+            VALUE_SET => ImageValue::Set(Decode::decode(r)?),
+            VALUE_SYMBOL => ImageValue::Symbol(Decode::decode(r)?),
+            VALUE_PROPERTY => ImageValue::Property {
+                value: Decode::decode(r)?,
+                getter: Decode::decode(r)?,
+                setter: Decode::decode(r)?,
+                attrs: Decode::decode(r)?,
+            },
+            _ => return Err(invalid(format!("unknown value tag {tag}"))),
+        })
+    }
+}
+
+impl Encode for ImageNode {
+    fn encode(&self, w: &mut Writer) {
+        w.byte(self.tag());
+        match self {
+            ImageNode::Object(props) => props.encode(w),
+            ImageNode::Array { elements, props } => {
+                elements.encode(w);
+                props.encode(w);
+            }
+            ImageNode::Map { entries, is_weak } => {
+                entries.encode(w);
+                is_weak.encode(w);
+            }
+            ImageNode::Set { values, is_weak } => {
+                values.encode(w);
+                is_weak.encode(w);
+            }
+            ImageNode::Symbol {
+                id,
+                description,
+                registered,
+            } => {
+                id.encode(w);
+                description.encode(w);
+                registered.encode(w);
+            }
+            ImageNode::Cell(value) => value.encode(w),
+            ImageNode::Upvalues(cells) => cells.encode(w),
+        }
+    }
+}
+
+impl Decode for ImageNode {
+    fn decode(r: &mut Reader<'_>) -> Result<Self, JSError> {
+        let tag = r.byte()?;
+        Ok(match tag {
+            NODE_OBJECT => ImageNode::Object(Decode::decode(r)?),
+            NODE_ARRAY => ImageNode::Array {
+                elements: Decode::decode(r)?,
+                props: Decode::decode(r)?,
+            },
+            NODE_MAP => ImageNode::Map {
+                entries: Decode::decode(r)?,
+                is_weak: Decode::decode(r)?,
+            },
+            NODE_SET => ImageNode::Set {
+                values: Decode::decode(r)?,
+                is_weak: Decode::decode(r)?,
+            },
+            NODE_SYMBOL => ImageNode::Symbol {
+                id: Decode::decode(r)?,
+                description: Decode::decode(r)?,
+                registered: Decode::decode(r)?,
+            },
+            NODE_CELL => ImageNode::Cell(Decode::decode(r)?),
+            NODE_UPVALUES => ImageNode::Upvalues(Decode::decode(r)?),
+            _ => return Err(invalid(format!("unknown node tag {tag}"))),
+        })
+    }
+}
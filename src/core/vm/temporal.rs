@@ -30,6 +30,11 @@ impl<'gc> VM<'gc> {
         receiver: Option<&Value<'gc>>,
         args: &[Value<'gc>],
     ) -> Value<'gc> {
+        // Without `Intl`, `toLocaleString` returns what `toJSON` does.
+        #[cfg(not(feature = "intl"))]
+        if let Some(kind) = name.strip_prefix("temporal.").and_then(|rest| rest.strip_suffix(".toLocaleString")) {
+            return self.temporal_handle_host_fn(ctx, &format!("temporal.{kind}.toJSON"), receiver, &[]);
+        }
         match name {
             "temporal.instant.constructor" => {
                 let Some(new_target) = self.temporal_constructor_new_target(ctx, "Instant") else {
@@ -243,6 +248,7 @@ impl<'gc> VM<'gc> {
                 Value::Boolean(value == other)
             }
             "temporal.instant.toString" => self.temporal_instant_to_string(ctx, receiver, args.first()),
+            #[cfg(feature = "intl")]
             "temporal.instant.toLocaleString" => self.temporal_instant_to_locale_string(ctx, receiver, args),
             "temporal.instant.toJSON" => self.temporal_instant_to_string(ctx, receiver, None),
             "temporal.instant.valueOf" => {
@@ -564,6 +570,7 @@ impl<'gc> VM<'gc> {
                 Value::from(value.to_ixdtf_string(display_calendar))
             }
             "temporal.plainDate.toJSON" => self.temporal_repr_result(ctx, receiver, "PlainDate"),
+            #[cfg(feature = "intl")]
             "temporal.plainDate.toLocaleString" => {
                 if self.temporal_expect_plain_date(ctx, receiver).is_none() {
                     return Value::Undefined;
@@ -858,6 +865,7 @@ impl<'gc> VM<'gc> {
                 )
             }
             "temporal.plainTime.toString" => self.temporal_plain_time_to_string(ctx, receiver, args.first()),
+            #[cfg(feature = "intl")]
             "temporal.plainTime.toLocaleString" => {
                 if self.temporal_expect_plain_time(ctx, receiver).is_none() {
                     return Value::Undefined;
@@ -1243,6 +1251,7 @@ impl<'gc> VM<'gc> {
                 }
             }
             "temporal.plainDateTime.toString" => self.temporal_plain_date_time_to_string(ctx, receiver, args.first()),
+            #[cfg(feature = "intl")]
             "temporal.plainDateTime.toLocaleString" => {
                 if self.temporal_expect_plain_date_time(ctx, receiver).is_none() {
                     return Value::Undefined;
@@ -1392,6 +1401,7 @@ impl<'gc> VM<'gc> {
                 }
             }
             "temporal.duration.toString" => self.temporal_duration_to_string(ctx, receiver, args.first()),
+            #[cfg(feature = "intl")]
             "temporal.duration.toLocaleString" => {
                 if self.temporal_expect_duration(ctx, receiver).is_none() {
                     return Value::Undefined;
@@ -1886,6 +1896,7 @@ impl<'gc> VM<'gc> {
                 Value::Boolean(self_str == other_str)
             }
             "temporal.plainYearMonth.toString" => self.temporal_plain_year_month_to_string(ctx, receiver, args.first()),
+            #[cfg(feature = "intl")]
             "temporal.plainYearMonth.toLocaleString" => {
                 if self.temporal_require_plain_year_month(ctx, receiver).is_none() {
                     return Value::Undefined;
@@ -2043,6 +2054,7 @@ impl<'gc> VM<'gc> {
                 )
             }
             "temporal.plainMonthDay.toString" => self.temporal_plain_month_day_to_string(ctx, receiver, args.first()),
+            #[cfg(feature = "intl")]
             "temporal.plainMonthDay.toLocaleString" => {
                 if self.temporal_expect_plain_month_day(ctx, receiver).is_none() {
                     return Value::Undefined;
@@ -2375,6 +2387,7 @@ impl<'gc> VM<'gc> {
                     Err(err) => self.temporal_throw(ctx, err),
                 }
             }
+            #[cfg(feature = "intl")]
             "temporal.zonedDateTime.toLocaleString" => {
                 self.temporal_zoned_date_time_to_locale_string(ctx, receiver, args.first(), args.get(1))
             }
@@ -2563,33 +2576,41 @@ impl<'gc> VM<'gc> {
             "temporal.zonedDateTime.get.weekOfYear" => self.temporal_zoned_date_time_week_of_year(ctx, receiver),
             "temporal.zonedDateTime.get.yearOfWeek" => self.temporal_zoned_date_time_year_of_week(ctx, receiver),
 
-            "temporal.now.instant" => match Temporal::local_now().instant() {
+            "temporal.now.instant" => match self.temporal_now_instant() {
                 Ok(value) => self.temporal_wrap_instant(ctx, None, &value),
                 Err(err) => self.temporal_throw(ctx, err),
             },
-            "temporal.now.timeZoneId" => match Temporal::local_now().zoned_date_time_iso(None) {
+            "temporal.now.timeZoneId" => match self.temporal_now(None) {
                 Ok(value) => match value.time_zone().identifier() {
                     Ok(identifier) => Value::from(identifier.as_str()),
                     Err(err) => self.temporal_throw(ctx, err),
                 },
                 Err(err) => self.temporal_throw(ctx, err),
             },
-            "temporal.now.plainDateISO" => match Temporal::local_now().plain_date_iso(self.temporal_now_time_zone(ctx, args.first())) {
-                Ok(value) => self.temporal_wrap_plain_date(ctx, None, &value),
-                Err(err) => self.temporal_throw(ctx, err),
-            },
-            "temporal.now.plainTimeISO" => match Temporal::local_now().plain_time_iso(self.temporal_now_time_zone(ctx, args.first())) {
-                Ok(value) => self.temporal_wrap_plain_time(ctx, None, &value),
-                Err(err) => self.temporal_throw(ctx, err),
-            },
+            "temporal.now.plainDateISO" => {
+                let time_zone = self.temporal_now_time_zone(ctx, args.first());
+                match self.temporal_now(time_zone) {
+                    Ok(value) => self.temporal_wrap_plain_date(ctx, None, &value.to_plain_date()),
+                    Err(err) => self.temporal_throw(ctx, err),
+                }
+            }
+            "temporal.now.plainTimeISO" => {
+                let time_zone = self.temporal_now_time_zone(ctx, args.first());
+                match self.temporal_now(time_zone) {
+                    Ok(value) => self.temporal_wrap_plain_time(ctx, None, &value.to_plain_time()),
+                    Err(err) => self.temporal_throw(ctx, err),
+                }
+            }
             "temporal.now.plainDateTimeISO" => {
-                match Temporal::local_now().plain_date_time_iso(self.temporal_now_time_zone(ctx, args.first())) {
-                    Ok(value) => self.temporal_wrap_plain_date_time(ctx, None, &value),
+                let time_zone = self.temporal_now_time_zone(ctx, args.first());
+                match self.temporal_now(time_zone) {
+                    Ok(value) => self.temporal_wrap_plain_date_time(ctx, None, &value.to_plain_date_time()),
                     Err(err) => self.temporal_throw(ctx, err),
                 }
             }
             "temporal.now.zonedDateTimeISO" => {
-                match Temporal::local_now().zoned_date_time_iso(self.temporal_now_time_zone(ctx, args.first())) {
+                let time_zone = self.temporal_now_time_zone(ctx, args.first());
+                match self.temporal_now(time_zone) {
                     Ok(value) => self.temporal_wrap_zoned_date_time(ctx, None, &value),
                     Err(err) => self.temporal_throw(ctx, err),
                 }
@@ -3114,6 +3135,7 @@ impl<'gc> VM<'gc> {
         }
     }
 
+    #[cfg(feature = "intl")]
     pub(super) fn temporal_value_kind(&self, value: &Value<'gc>) -> Option<String> {
         let Value::Object(obj) = value else {
             return None;
@@ -4644,6 +4666,25 @@ impl<'gc> VM<'gc> {
         }
     }
 
+    /// `Temporal.Now.instant()`: the host clock, or the virtual clock during
+    /// a deterministic run.
+    fn temporal_now_instant(&self) -> Result<Instant, TemporalError> {
+        if self.clock.is_deterministic() {
+            Instant::from_epoch_milliseconds(self.clock.epoch_ms() as i64)
+        } else {
+            Temporal::local_now().instant()
+        }
+    }
+
+    /// The current time in `time_zone`, or else in the local zone, which a
+    /// deterministic run pins to UTC.
+    fn temporal_now(&self, time_zone: Option<TimeZone>) -> Result<ZonedDateTime, TemporalError> {
+        if !self.clock.is_deterministic() {
+            return Temporal::local_now().zoned_date_time_iso(time_zone);
+        }
+        ZonedDateTime::try_new_iso_from_instant(self.temporal_now_instant()?, time_zone.unwrap_or_else(TimeZone::utc))
+    }
+
     fn temporal_now_time_zone(&mut self, ctx: &GcContext<'gc>, value: Option<&Value<'gc>>) -> Option<TimeZone> {
         let value = value?;
         if matches!(value, Value::Undefined) {
@@ -6766,6 +6807,7 @@ impl<'gc> VM<'gc> {
         }
     }
 
+    #[cfg(feature = "intl")]
     pub(super) fn temporal_parse_plain_year_month_repr(
         &mut self,
         ctx: &GcContext<'gc>,
@@ -6810,6 +6852,7 @@ impl<'gc> VM<'gc> {
         Some((year, month, day, calendar))
     }
 
+    #[cfg(feature = "intl")]
     pub(super) fn temporal_parse_plain_month_day_repr(
         &mut self,
         ctx: &GcContext<'gc>,
@@ -7215,6 +7258,7 @@ impl<'gc> VM<'gc> {
         }
     }
 
+    #[cfg(feature = "intl")]
     fn temporal_date_time_to_locale_string(
         &mut self,
         ctx: &GcContext<'gc>,
@@ -7247,6 +7291,7 @@ impl<'gc> VM<'gc> {
         self.call_host_fn(ctx, "intl.dateTimeFormat.format", Some(&formatter), &[receiver_value])
     }
 
+    #[cfg(feature = "intl")]
     fn temporal_instant_to_locale_string(
         &mut self,
         ctx: &GcContext<'gc>,
@@ -7269,6 +7314,7 @@ impl<'gc> VM<'gc> {
         self.call_host_fn(ctx, "date.toLocaleString", Some(&date_value), args)
     }
 
+    #[cfg(feature = "intl")]
     fn temporal_zoned_date_time_to_locale_string(
         &mut self,
         ctx: &GcContext<'gc>,
@@ -7345,6 +7391,7 @@ impl<'gc> VM<'gc> {
         self.call_host_fn(ctx, "intl.dateTimeFormat.format", Some(&formatter), &[date_value])
     }
 
+    #[cfg(feature = "intl")]
     fn temporal_date_time_format_args_for_locale_string(
         &mut self,
         ctx: &GcContext<'gc>,
@@ -7436,6 +7483,7 @@ impl<'gc> VM<'gc> {
         Ok(vec![locales, Value::Object(new_gc_cell_ptr(ctx, merged))])
     }
 
+    #[cfg(feature = "intl")]
     fn temporal_locale_default_options(&self, ctx: &GcContext<'gc>, kind: &str) -> ObjectHandle<'gc> {
         let mut defaults = IndexMap::new();
         for (key, value) in Self::temporal_locale_default_entries(kind) {
@@ -7444,6 +7492,7 @@ impl<'gc> VM<'gc> {
         new_gc_cell_ptr(ctx, defaults)
     }
 
+    #[cfg(feature = "intl")]
     fn temporal_locale_default_entries(kind: &str) -> Vec<(&'static str, Value<'gc>)> {
         match kind {
             "PlainTime" => vec![
@@ -7478,6 +7527,7 @@ impl<'gc> VM<'gc> {
         }
     }
 
+    #[cfg(feature = "intl")]
     fn temporal_validate_locale_calendar(
         &mut self,
         ctx: &GcContext<'gc>,
@@ -7528,6 +7578,7 @@ impl<'gc> VM<'gc> {
         Ok(())
     }
 
+    #[cfg(feature = "intl")]
     fn temporal_duration_to_locale_string(
         &mut self,
         ctx: &GcContext<'gc>,
@@ -257,6 +257,7 @@ impl<'gc> VM<'gc> {
         idx < arr.borrow().elements.len()
     }
 
+    #[cfg(feature = "shared-memory")]
     pub(super) fn shared_ta_info(&self, arr: &ArrayHandle<'gc>) -> Option<(u64, usize, usize, String)> {
         let borrow = arr.borrow();
         let Value::Object(buf_obj) = borrow.props.get("__typedarray_buffer__")?.clone() else {
@@ -283,6 +284,7 @@ impl<'gc> VM<'gc> {
         Some((shared_id, byte_offset, bpe, ta_name))
     }
 
+    #[cfg(feature = "shared-memory")]
     pub(super) fn ensure_shared_ta_info(&mut self, ctx: &GcContext<'gc>, arr: &ArrayHandle<'gc>) -> Option<(u64, usize, usize, String)> {
         let buf_obj = {
             let borrow = arr.borrow();
@@ -295,6 +297,7 @@ impl<'gc> VM<'gc> {
         self.shared_ta_info(arr)
     }
 
+    #[cfg(feature = "shared-memory")]
     pub(super) fn shared_ta_read_element(&self, arr: &ArrayHandle<'gc>, idx: usize) -> Option<Value<'gc>> {
         let (shared_id, byte_offset, bpe, ta_name) = self.shared_ta_info(arr)?;
         let base = byte_offset.checked_add(idx.checked_mul(bpe)?)?;
@@ -339,11 +342,13 @@ impl<'gc> VM<'gc> {
         Some(Self::decode_typed_element(&bb.elements, base, bpe, &ta_name))
     }
 
+    #[cfg(feature = "shared-memory")]
     pub(super) fn decode_typed_element_from_raw(&self, raw: &[u8], bpe: usize, ta_name: &str) -> Value<'gc> {
         let bytes: Vec<Value<'gc>> = raw.iter().map(|b| Value::Number(*b as f64)).collect();
         Self::decode_typed_element(&bytes, 0, bpe, ta_name)
     }
 
+    #[cfg(feature = "shared-memory")]
     pub(super) fn encode_typed_element_to_raw(&self, bpe: usize, ta_name: &str, value: &Value<'gc>) -> Option<Vec<u8>> {
         let mut bytes = vec![Value::Number(0.0); bpe];
         if ta_name == "BigInt64Array" || ta_name == "BigUint64Array" {
@@ -358,11 +363,15 @@ impl<'gc> VM<'gc> {
     }
 
     pub(super) fn shared_or_local_ta_element(&self, arr: &ArrayHandle<'gc>, idx: usize) -> Value<'gc> {
-        self.shared_ta_read_element(arr, idx)
-            .or_else(|| self.local_ta_read_element(arr, idx))
+        #[cfg(feature = "shared-memory")]
+        if let Some(value) = self.shared_ta_read_element(arr, idx) {
+            return value;
+        }
+        self.local_ta_read_element(arr, idx)
             .unwrap_or_else(|| arr.borrow().elements.get(idx).cloned().unwrap_or(Value::Undefined))
     }
 
+    #[cfg(feature = "shared-memory")]
     pub(super) fn shared_atomic_rmw(
         &mut self,
         ctx: &GcContext<'gc>,
@@ -432,6 +441,7 @@ impl<'gc> VM<'gc> {
         }
     }
 
+    #[cfg(feature = "typed-arrays")]
     pub(super) fn typedarray_handle_host_fn(
         &mut self,
         ctx: &GcContext<'gc>,
@@ -2696,6 +2706,7 @@ impl<'gc> VM<'gc> {
 
     /// Validate that a value is a TypedArray with a non-detached buffer.
     /// Returns true if valid. Sets pending_throw and returns false otherwise.
+    #[cfg(feature = "typed-arrays")]
     fn validate_typed_array(&mut self, ctx: &GcContext<'gc>, val: &Value<'gc>, method: &str) -> bool {
         match val {
             Value::Array(arr) => {
@@ -2756,6 +2767,7 @@ impl<'gc> VM<'gc> {
 
     /// Implements SpeciesConstructor(O, defaultConstructor) for TypedArrays.
     /// Returns the species constructor, or None if a throw was set.
+    #[cfg(feature = "typed-arrays")]
     fn typed_array_species_constructor(&mut self, ctx: &GcContext<'gc>, this_val: &Value<'gc>) -> Option<Value<'gc>> {
         let ta_name = if let Value::Array(arr) = this_val {
             arr.borrow()
@@ -2820,6 +2832,7 @@ impl<'gc> VM<'gc> {
 
     /// TypedArrayCreateSameType: creates a new TypedArray of the same type
     /// using the intrinsic constructor (ignores @@species).
+    #[cfg(feature = "typed-arrays")]
     fn typed_array_create_same_type(&mut self, ctx: &GcContext<'gc>, exemplar: &Value<'gc>, args: &[Value<'gc>]) -> Option<Value<'gc>> {
         let ta_name = match exemplar {
             Value::Array(arr) => match arr.borrow().props.get("__typedarray_name__") {
@@ -2845,6 +2858,7 @@ impl<'gc> VM<'gc> {
 
     /// TypedArraySpeciesCreate: use species constructor to create result,
     /// falling back to wrap_as_typed_array for default constructors.
+    #[cfg(feature = "typed-arrays")]
     fn typed_array_species_create(&mut self, ctx: &GcContext<'gc>, this_val: &Value<'gc>, args: &[Value<'gc>]) -> Option<Value<'gc>> {
         let ctor = self.typed_array_species_constructor(ctx, this_val)?;
 
@@ -2911,6 +2925,7 @@ impl<'gc> VM<'gc> {
     }
 
     /// Wrap a plain Array result as the same TypedArray type as `source`.
+    #[cfg(feature = "typed-arrays")]
     fn _wrap_as_typed_array(&mut self, ctx: &GcContext<'gc>, source: &Value<'gc>, result: &Value<'gc>) -> Value<'gc> {
         let (ta_name, bpe) = if let Value::Array(arr) = source {
             let a = arr.borrow();
@@ -3232,6 +3247,11 @@ impl<'gc> VM<'gc> {
             }
             let raw = raw_values.iter().map(|v| to_number(v) as u8).collect::<Vec<_>>();
 
+            #[cfg(feature = "shared-memory")]
+            if let Some(shared_id) = self.shared_buffer_id_from_obj(&buf_obj) {
+                let _ = crate::js_agent::shared_buffer_write(shared_id, base, &raw);
+            }
+
             if let Some(Value::Array(buf_bytes)) = buf_obj.borrow().get("__buffer_bytes__").cloned() {
                 let mut bb = buf_bytes.borrow_mut(ctx);
                 for (j, &b) in raw.iter().enumerate() {
@@ -3240,14 +3260,11 @@ impl<'gc> VM<'gc> {
                     }
                 }
             }
-
-            if let Some(shared_id) = self.shared_buffer_id_from_obj(&buf_obj) {
-                let _ = crate::js_agent::shared_buffer_write(shared_id, base, &raw);
-            }
         }
     }
 
     /// Sync elements array from the backing buffer bytes (for shared buffer scenarios)
+    #[cfg(feature = "typed-arrays")]
     fn sync_ta_elements_from_buffer(&self, ctx: &GcContext<'gc>, arr: &ArrayHandle<'gc>, ta_name: &str, bpe: usize, len: usize) {
         let (buffer, byte_offset) = {
             let a = arr.borrow();
@@ -3259,6 +3276,7 @@ impl<'gc> VM<'gc> {
                 .unwrap_or(0);
             (buffer, byte_offset)
         };
+        #[cfg(feature = "shared-memory")]
         if let Some(Value::Object(buf_obj)) = &buffer
             && let Some(shared_id) = self.shared_buffer_id_from_obj(buf_obj)
         {
@@ -3291,6 +3309,7 @@ impl<'gc> VM<'gc> {
         }
     }
 
+    #[cfg(feature = "typed-arrays")]
     fn create_ordinary_array_buffer(&mut self, ctx: &GcContext<'gc>, bytes: Vec<Value<'gc>>) -> Value<'gc> {
         let mut buf_map = IndexMap::new();
         buf_map.insert("__type__".to_string(), Value::from("ArrayBuffer"));
@@ -3349,6 +3368,7 @@ impl<'gc> VM<'gc> {
 
     /// Returns true if a resizable-backed TypedArray is out of bounds
     /// (buffer shrank below the TA's view). Non-resizable TAs always return false.
+    #[cfg(feature = "typed-arrays")]
     pub(super) fn is_typed_array_oob(&self, arr: &ArrayHandle<'gc>) -> bool {
         let b = arr.borrow();
         if !b.props.contains_key("__typedarray_name__") {
@@ -3482,6 +3502,7 @@ impl<'gc> VM<'gc> {
     }
 
     /// Convert a numeric value to the type-specific element value for a TypedArray.
+    #[cfg(feature = "typed-arrays")]
     pub(super) fn typed_array_coerce_value(num: f64, ta_name: &str) -> f64 {
         match ta_name {
             "Float16Array" => return f16round(num),
@@ -3545,6 +3566,7 @@ impl<'gc> VM<'gc> {
         }
     }
 
+    #[cfg(feature = "typed-arrays")]
     pub(super) fn typedarray_call_builtin(&mut self, ctx: &GcContext<'gc>, id: FunctionID, args: &[Value<'gc>]) -> Value<'gc> {
         // TypedArray constructors must be called with new
         if self.new_target_stack.is_empty() {
@@ -4319,6 +4341,7 @@ impl<'gc> VM<'gc> {
         Value::Array(new_gc_cell_ptr(ctx, data))
     }
 
+    #[cfg(feature = "typed-arrays")]
     pub(super) fn initialize_typed_arrays(&mut self, ctx: &GcContext<'gc>, array_to_string_fn_for_ta: Value<'gc>) {
         // ── %TypedArray% intrinsic and shared prototype ──
         // Spec: %TypedArray%.prototype holds all shared TypedArray methods.
@@ -4618,6 +4641,7 @@ impl<'gc> VM<'gc> {
     const BASE64_CHARS: &'static [u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
     const BASE64URL_CHARS: &'static [u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
 
+    #[cfg(feature = "typed-arrays")]
     fn base64_decode_char(c: u8, use_url: bool) -> Option<u8> {
         match c {
             b'A'..=b'Z' => Some(c - b'A'),
@@ -4631,6 +4655,7 @@ impl<'gc> VM<'gc> {
         }
     }
 
+    #[cfg(feature = "typed-arrays")]
     fn read_base64_alphabet_option(&mut self, ctx: &GcContext<'gc>, opts: Option<&Value<'gc>>) -> Option<bool> {
         let opts = match opts {
             Some(v) if !matches!(v, Value::Undefined) => v,
@@ -4662,6 +4687,7 @@ impl<'gc> VM<'gc> {
         }
     }
 
+    #[cfg(feature = "typed-arrays")]
     fn read_last_chunk_handling_option(&mut self, ctx: &GcContext<'gc>, opts: Option<&Value<'gc>>) -> Option<String> {
         let opts = match opts {
             Some(v) if !matches!(v, Value::Undefined) => v,
@@ -4702,6 +4728,7 @@ impl<'gc> VM<'gc> {
     /// Returns (decoded_bytes, chars_read, optional_error_message).
     /// Bytes/read always reflect valid data decoded so far.
     /// Caller writes bytes first, then throws error if present.
+    #[cfg(feature = "typed-arrays")]
     fn base64_decode_core(
         &self,
         input: &str,
@@ -4870,6 +4897,7 @@ impl<'gc> VM<'gc> {
 
     /// Hex decode per spec's FromHex.
     /// Returns (decoded_bytes, chars_read, optional_error_message).
+    #[cfg(feature = "typed-arrays")]
     fn hex_decode_core(input: &str, max_length: Option<usize>) -> (Vec<u8>, usize, Option<String>) {
         let bytes_in = input.as_bytes();
         // Spec step 2: odd length check FIRST (before any decoding)
@@ -4898,6 +4926,7 @@ impl<'gc> VM<'gc> {
         (result, i, None)
     }
 
+    #[cfg(feature = "typed-arrays")]
     fn hex_char(c: u8) -> Option<u8> {
         match c {
             b'0'..=b'9' => Some(c - b'0'),
@@ -4907,6 +4936,7 @@ impl<'gc> VM<'gc> {
         }
     }
 
+    #[cfg(feature = "typed-arrays")]
     fn validate_uint8array(&mut self, ctx: &GcContext<'gc>, val: &Value<'gc>, method: &str) -> bool {
         match val {
             Value::Array(arr) => {
@@ -4927,6 +4957,7 @@ impl<'gc> VM<'gc> {
         }
     }
 
+    #[cfg(feature = "typed-arrays")]
     fn check_uint8array_not_detached(&mut self, ctx: &GcContext<'gc>, val: &Value<'gc>) -> bool {
         if let Value::Array(arr) = val {
             let a = arr.borrow();
@@ -4942,6 +4973,7 @@ impl<'gc> VM<'gc> {
     }
 
     /// Read raw bytes from a Uint8Array's backing buffer.
+    #[cfg(feature = "typed-arrays")]
     fn get_uint8array_bytes(&self, val: &Value<'gc>) -> Vec<u8> {
         if let Value::Array(arr) = val {
             let a = arr.borrow();
@@ -4984,6 +5016,7 @@ impl<'gc> VM<'gc> {
     }
 
     /// Write bytes into a Uint8Array's backing buffer.
+    #[cfg(feature = "typed-arrays")]
     fn write_bytes_to_uint8array(&self, ctx: &GcContext<'gc>, ta: &Value<'gc>, bytes: &[u8]) {
         if let Value::Array(arr) = ta {
             let a = arr.borrow();
@@ -5024,6 +5057,7 @@ impl<'gc> VM<'gc> {
         }
     }
 
+    #[cfg(feature = "typed-arrays")]
     fn create_uint8array_from_bytes(&mut self, ctx: &GcContext<'gc>, bytes: Vec<u8>) -> Value<'gc> {
         let elements: Vec<Value<'gc>> = bytes.iter().map(|b| Value::Number(*b as f64)).collect();
         let len = elements.len();
@@ -5072,6 +5106,7 @@ impl<'gc> VM<'gc> {
 
     // ── Method implementations ──
 
+    #[cfg(feature = "typed-arrays")]
     fn uint8array_to_base64(&mut self, ctx: &GcContext<'gc>, receiver: Option<&Value<'gc>>, args: &[Value<'gc>]) -> Value<'gc> {
         let this = receiver.unwrap_or(&Value::Undefined);
         if !self.validate_uint8array(ctx, this, "Uint8Array.prototype.toBase64") {
@@ -5133,6 +5168,7 @@ impl<'gc> VM<'gc> {
         Value::from(&result)
     }
 
+    #[cfg(feature = "typed-arrays")]
     fn uint8array_to_hex(&mut self, ctx: &GcContext<'gc>, receiver: Option<&Value<'gc>>) -> Value<'gc> {
         let this = receiver.unwrap_or(&Value::Undefined);
         if !self.validate_uint8array(ctx, this, "Uint8Array.prototype.toHex") {
@@ -5149,6 +5185,7 @@ impl<'gc> VM<'gc> {
         Value::from(&result)
     }
 
+    #[cfg(feature = "typed-arrays")]
     fn uint8array_from_base64(&mut self, ctx: &GcContext<'gc>, args: &[Value<'gc>]) -> Value<'gc> {
         let input = args.first().cloned().unwrap_or(Value::Undefined);
         if !matches!(&input, Value::String(_)) {
@@ -5173,6 +5210,7 @@ impl<'gc> VM<'gc> {
         self.create_uint8array_from_bytes(ctx, bytes)
     }
 
+    #[cfg(feature = "typed-arrays")]
     fn uint8array_from_hex(&mut self, ctx: &GcContext<'gc>, args: &[Value<'gc>]) -> Value<'gc> {
         let input = args.first().cloned().unwrap_or(Value::Undefined);
         if !matches!(&input, Value::String(_)) {
@@ -5188,6 +5226,7 @@ impl<'gc> VM<'gc> {
         self.create_uint8array_from_bytes(ctx, bytes)
     }
 
+    #[cfg(feature = "typed-arrays")]
     fn uint8array_set_from_base64(&mut self, ctx: &GcContext<'gc>, receiver: Option<&Value<'gc>>, args: &[Value<'gc>]) -> Value<'gc> {
         let this = receiver.unwrap_or(&Value::Undefined);
         if !self.validate_uint8array(ctx, this, "Uint8Array.prototype.setFromBase64") {
@@ -5229,6 +5268,7 @@ impl<'gc> VM<'gc> {
         Value::Object(new_gc_cell_ptr(ctx, result_map))
     }
 
+    #[cfg(feature = "typed-arrays")]
     fn uint8array_set_from_hex(&mut self, ctx: &GcContext<'gc>, receiver: Option<&Value<'gc>>, args: &[Value<'gc>]) -> Value<'gc> {
         let this = receiver.unwrap_or(&Value::Undefined);
         if !self.validate_uint8array(ctx, this, "Uint8Array.prototype.setFromHex") {
@@ -1,3 +1,5 @@
+use crate::core::JsException;
+
 #[derive(thiserror::Error, Debug)]
 pub enum JSErrorKind {
     #[error("Tokenization failed: {message}")]
@@ -36,6 +38,12 @@ pub enum JSErrorKind {
     #[error("Thrown value: {0}")]
     Throw(String),
 
+    #[error("Script execution interrupted")]
+    Interrupted,
+
+    #[error("Out of memory: heap limit of {limit} bytes exceeded")]
+    OutOfMemory { limit: usize },
+
     #[error("std::io error: {0}")]
     IoError(#[from] std::io::Error),
 
@@ -49,6 +57,7 @@ pub enum JSErrorKind {
     RegExpError(#[from] regress::Error),
 }
 
+#[cfg(any(feature = "intl", feature = "temporal"))]
 impl From<temporal_rs::TemporalError> for JSErrorKind {
     fn from(err: temporal_rs::TemporalError) -> Self {
         let message = err.into_message().to_string();
@@ -75,6 +84,7 @@ pub struct JSErrorData {
     pub js_line: Option<usize>,
     pub js_column: Option<usize>,
     pub stack: Vec<String>,
+    pub exception: Option<JsException>,
 }
 
 #[derive(Debug)]
@@ -96,6 +106,7 @@ impl JSError {
                 js_line: None,
                 js_column: None,
                 stack: Vec::new(),
+                exception: None,
             }),
         }
     }
@@ -121,6 +132,17 @@ impl JSError {
         &self.inner.stack
     }
 
+    /// The name, message, stack frames, cause chain and value of what the
+    /// script threw. `None` for errors that did not come from a thrown value,
+    /// such as a syntax error in the source or an interrupted run.
+    pub fn exception(&self) -> Option<&JsException> {
+        self.inner.exception.as_ref()
+    }
+
+    pub(crate) fn set_exception(&mut self, exception: JsException) {
+        self.inner.exception = Some(exception);
+    }
+
     // convenience method to access the kind
     pub fn kind(&self) -> &JSErrorKind {
         &self.inner.kind
@@ -147,6 +169,8 @@ impl JSError {
             JSErrorKind::RuntimeError { message } => format!("Error: {message}"),
             JSErrorKind::URIError { message } => format!("URIError: {message}"),
             JSErrorKind::Throw(msg) => msg.clone(),
+            JSErrorKind::Interrupted => "Error: Script execution interrupted".to_string(),
+            JSErrorKind::OutOfMemory { limit } => format!("RangeError: Out of memory: heap limit of {limit} bytes exceeded"),
             JSErrorKind::IoError(e) => format!("IOError: {e}"),
             JSErrorKind::ParseIntError(e) => format!("ParseIntError: {e}"),
             JSErrorKind::ParseFloatError(e) => format!("ParseFloatError: {e}"),
@@ -231,6 +255,7 @@ impl From<std::num::ParseFloatError> for JSError {
     }
 }
 
+#[cfg(any(feature = "intl", feature = "temporal"))]
 impl From<temporal_rs::TemporalError> for JSError {
     fn from(err: temporal_rs::TemporalError) -> Self {
         JSError::new(JSErrorKind::from(err), "<unknown>".to_string(), "<unknown>".to_string(), None)
@@ -335,6 +360,22 @@ macro_rules! raise_infinite_loop_error {
     };
 }
 
+#[macro_export]
+#[doc(hidden)]
+macro_rules! raise_interrupted_error {
+    () => {
+        $crate::make_js_error!($crate::JSErrorKind::Interrupted)
+    };
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! raise_out_of_memory_error {
+    ($limit:expr) => {
+        $crate::make_js_error!($crate::JSErrorKind::OutOfMemory { limit: $limit })
+    };
+}
+
 #[macro_export]
 #[doc(hidden)]
 macro_rules! raise_variable_not_found_error {
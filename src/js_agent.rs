@@ -224,12 +224,21 @@ pub fn wait_for_broadcast() -> Option<BroadcastInfo> {
     BROADCAST_DATA.lock().unwrap().clone()
 }
 
-pub fn start_agent(script: String) {
+/// Run `script` on a new agent thread, limited to the starting VM's `permissions`.
+pub fn start_agent(script: String, permissions: crate::core::Permissions) {
     AGENT_COUNT.fetch_add(1, Ordering::SeqCst);
     let handle = thread::spawn(move || {
         IS_AGENT_THREAD.with(|c| c.set(true));
         LAST_BROADCAST_SEEN.with(|c| c.set(0));
-        let result = crate::core::evaluate_script_with_unwrap(script, false, Option::<&std::path::Path>::None, false);
+        let options = crate::core::EvalOptions::new().permissions(permissions);
+        let result = crate::core::evaluate_script_in_new_vm(
+            script,
+            false,
+            Option::<&std::path::Path>::None,
+            false,
+            &options,
+            crate::core::format_completion_value,
+        );
         if let Err(err) = result {
             push_report(format!("__agent_error__:{err}"));
         }
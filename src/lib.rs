@@ -4,6 +4,7 @@ pub(crate) mod core;
 
 #[macro_use]
 pub(crate) mod error;
+#[cfg(feature = "shared-memory")]
 pub(crate) mod js_agent;
 #[cfg(feature = "std")]
 pub(crate) mod js_std;
@@ -11,10 +12,19 @@ pub(crate) mod repl;
 pub(crate) mod unicode;
 
 pub use crate::core::{Token, TokenData};
-pub use core::{Value, evaluate_script, evaluate_script_with_unwrap, format_js_number, tokenize};
+pub use core::{CallTarget, CapturedConsole, ClassBuilder, ConsoleLevel, ConsoleSink, StdioConsoleSink};
+pub use core::{
+    DeterministicOptions, EvalOptions, HeapStats, InspectOptions, InterruptHandle, JsException, JsFunctionInfo, JsHandle, JsOwnedValue,
+    JsStackFrame, ModuleNamespaceHandle, Permissions, PromiseRejection, PromiseResolver, RejectionEvent, Snapshot, Value, format_js_number,
+    tokenize,
+};
+pub use core::{FsModuleLoader, ModuleLoader, ModuleSource};
+pub use core::{SerdeError, from_js_value, to_js_value};
+pub use core::{compile_to_bytes, evaluate_bytecode, is_bytecode};
+pub use core::{
+    evaluate_script, evaluate_script_value, evaluate_script_value_with_options, evaluate_script_with_options, evaluate_script_with_unwrap,
+};
 pub use core::{parse_object_destructuring_pattern, parse_simple_expression, parse_statement, parse_statements, read_script_file};
 pub use error::{JSError, JSErrorKind};
-// pub use js_promise::set_short_timer_threshold_ms;
-// pub use js_promise::set_wait_for_active_handles;
 pub use repl::Repl;
 pub use unicode::{utf8_to_utf16, utf16_to_utf8};
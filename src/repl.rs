@@ -1,7 +1,14 @@
 use crate::{
-    JSError,
-    core::{Chunk, JsArenaVm, VM, Value, value_to_compact_result_string, value_to_string},
+    JSError, JSErrorKind,
+    core::{
+        AsyncHostCallback, CallTarget, Chunk, ClassBuilder, ConsoleSink, DeterministicOptions, GcContext, HeapStats, HostClassSpec,
+        InspectOptions, InterruptHandle, JsArenaVm, JsHandle, JsOwnedValue, ModuleLoader, ModuleNamespaceHandle, Permissions,
+        PromiseRejection, PromiseResolver, RejectionCallback, RunSlice, Snapshot, VM, Value, value_to_compact_result_string,
+        value_to_string,
+    },
 };
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 /// A small persistent REPL environment wrapper.
 ///
@@ -25,33 +32,631 @@ impl Repl {
         Repl { arena }
     }
 
+    /// Create a REPL restored from `snapshot` instead of setting up the
+    /// builtins, starting with the globals of its prelude if it has one.
+    ///
+    /// ```
+    /// use javascript::{Repl, Snapshot};
+    ///
+    /// let snapshot = Snapshot::with_prelude("var config = { retries: 3 };").unwrap();
+    /// let mut repl = Repl::from_snapshot(&snapshot);
+    /// assert_eq!(repl.eval("config.retries").unwrap(), "3");
+    /// ```
+    pub fn from_snapshot(snapshot: &Snapshot) -> Self {
+        let arena = JsArenaVm::new(|ctx| VM::from_heap_image(snapshot.image(), ctx));
+        Repl { arena }
+    }
+
     /// Evaluate a script using the VM backend.
     /// Returns the evaluation result as a string or an error.
     pub fn eval<T: AsRef<str>>(&mut self, script: T) -> Result<String, JSError> {
         let script = script.as_ref();
-        // let mut vm = self.vm.borrow_mut();
-        let result = self.arena.mutate_root(|ctx, vm| {
-            let v = vm.eval_repl_snippet(ctx, script)?;
-
-            match v {
-                Value::String(s) => {
-                    let s_utf8 = crate::unicode::utf16_to_utf8(&s);
-                    match serde_json::to_string(&s_utf8) {
-                        Ok(quoted) => Ok(quoted),
-                        Err(_) => Ok(format!("\"{}\"", s_utf8)),
-                    }
+        self.collect_if_over_heap_limit();
+        let result = self.eval_snippet(script, |_ctx, _vm, v| match v {
+            Value::String(s) => {
+                let s_utf8 = crate::unicode::utf16_to_utf8(&s);
+                match serde_json::to_string(&s_utf8) {
+                    Ok(quoted) => Ok(quoted),
+                    Err(_) => Ok(format!("\"{}\"", s_utf8)),
                 }
-                Value::Array(_) | Value::Object(_) => Ok(value_to_compact_result_string(&v)),
-                _ => Ok(value_to_string(&v)),
             }
+            Value::Array(_) | Value::Object(_) => Ok(value_to_compact_result_string(&v)),
+            _ => Ok(value_to_string(&v)),
         });
 
         // Run incremental GC between REPL evaluations.
-        self.arena.collect_debt();
+        self.collect_after_eval(&result);
+
+        result
+    }
+
+    /// Evaluate a script like [`Repl::eval`], but return the completion value
+    /// as an owned [`JsOwnedValue`] instead of its string rendering.
+    pub fn eval_value<T: AsRef<str>>(&mut self, script: T) -> Result<JsOwnedValue, JSError> {
+        let script = script.as_ref();
+        self.collect_if_over_heap_limit();
+        let result = self.eval_snippet(script, |ctx, vm, v| vm.value_to_owned(ctx, &v));
+
+        self.collect_after_eval(&result);
+
+        result
+    }
+
+    /// Evaluate a script and deserialize its completion value into `T`
+    /// (see [`from_js_value`](crate::from_js_value)).
+    ///
+    /// ```
+    /// #[derive(serde::Deserialize)]
+    /// struct Point {
+    ///     x: i32,
+    ///     y: i32,
+    /// }
+    ///
+    /// let mut repl = javascript::Repl::new();
+    /// let p: Point = repl.eval_as("({ x: 1, y: 2 })").unwrap();
+    /// assert_eq!((p.x, p.y), (1, 2));
+    /// ```
+    pub fn eval_as<T: serde::de::DeserializeOwned, S: AsRef<str>>(&mut self, script: S) -> Result<T, JSError> {
+        let value = self.eval_value(script)?;
+        Ok(crate::from_js_value(&value)?)
+    }
+
+    /// Evaluate a script like [`Repl::eval`], but render the completion value
+    /// the way Node's REPL does (see [`InspectOptions`]).
+    pub fn eval_inspect<T: AsRef<str>>(&mut self, script: T) -> Result<String, JSError> {
+        let script = script.as_ref();
+        self.collect_if_over_heap_limit();
+        let result = self.eval_snippet(script, |ctx, vm, v| Ok(vm.console_inspect(ctx, &v)));
+
+        self.collect_after_eval(&result);
 
         result
     }
 
+    /// Call a JS function from Rust.
+    ///
+    /// `target` names a global function or a dotted path to one
+    /// (`"app.handlers.onEvent"`), or is a [`JsHandle`] to a function. `this` and `args` are converted to JS
+    /// values, and the completion value is returned as a [`JsOwnedValue`].
+    /// Microtasks and timers scheduled by the call run before it returns; a
+    /// returned promise is snapshotted as-is; use [`Repl::call_await`] to get
+    /// its settled value instead.
+    ///
+    /// ```
+    /// use javascript::{JsOwnedValue, Repl};
+    ///
+    /// let mut repl = Repl::new();
+    /// repl.eval("function add(a, b) { return a + b; }").unwrap();
+    /// assert_eq!(repl.call("add", (), &[2.into(), 40.into()]).unwrap(), JsOwnedValue::Number(42.0));
+    /// ```
+    pub fn call<'a>(
+        &mut self,
+        target: impl Into<CallTarget<'a>>,
+        this: impl Into<JsOwnedValue>,
+        args: &[JsOwnedValue],
+    ) -> Result<JsOwnedValue, JSError> {
+        self.call_inner(target.into(), this.into(), args, false)
+    }
+
+    /// Like [`Repl::call`], but when the function returns a promise the event
+    /// loop is driven until it settles. A fulfilled promise yields its value;
+    /// a rejection is returned as the error, and a promise that is still
+    /// pending once no work remains is reported as a `TypeError`.
+    pub fn call_await<'a>(
+        &mut self,
+        target: impl Into<CallTarget<'a>>,
+        this: impl Into<JsOwnedValue>,
+        args: &[JsOwnedValue],
+    ) -> Result<JsOwnedValue, JSError> {
+        self.call_inner(target.into(), this.into(), args, true)
+    }
+
+    /// Evaluate a script like [`Repl::eval`] and keep its result, which must
+    /// be an object or function, alive behind a [`JsHandle`].
+    ///
+    /// ```
+    /// use javascript::{JsOwnedValue, Repl};
+    ///
+    /// let mut repl = Repl::new();
+    /// let counter = repl.eval_handle("({ n: 0, inc() { return ++this.n; } })").unwrap();
+    /// repl.call_method(&counter, "inc", &[]).unwrap();
+    /// repl.set(&counter, "n", 41).unwrap();
+    /// assert_eq!(repl.call_method(&counter, "inc", &[]).unwrap(), JsOwnedValue::Number(42.0));
+    /// ```
+    pub fn eval_handle<T: AsRef<str>>(&mut self, script: T) -> Result<JsHandle, JSError> {
+        let script = script.as_ref();
+        self.collect_if_over_heap_limit();
+        let result = self.arena.mutate_root(|ctx, vm| vm.eval_handle(ctx, script));
+        self.collect_after_eval(&result);
+        result
+    }
+
+    /// Read property `key` of the object behind `handle` as an owned snapshot.
+    pub fn get(&mut self, handle: &JsHandle, key: &str) -> Result<JsOwnedValue, JSError> {
+        self.arena.mutate_root(|ctx, vm| {
+            let value = vm.handle_get(ctx, handle, key)?;
+            vm.value_to_owned(ctx, &value)
+        })
+    }
+
+    /// Read property `key` of the object behind `handle` and hold the result,
+    /// which must be an object or function, behind a new handle.
+    pub fn get_handle(&mut self, handle: &JsHandle, key: &str) -> Result<JsHandle, JSError> {
+        self.arena.mutate_root(|ctx, vm| {
+            let value = vm.handle_get(ctx, handle, key)?;
+            vm.make_handle(ctx, value)
+        })
+    }
+
+    /// Assign property `key` on the object behind `handle`. Setters run, and
+    /// an assignment the object rejects (e.g. a frozen object) is a `TypeError`.
+    pub fn set(&mut self, handle: &JsHandle, key: &str, value: impl Into<JsOwnedValue>) -> Result<(), JSError> {
+        let value = value.into();
+        self.arena.mutate_root(|ctx, vm| vm.handle_set(ctx, handle, key, &value))
+    }
+
+    /// Call method `name` of the object behind `handle` with the object as
+    /// `this`. Scheduled microtasks and timers run as with [`Repl::call`].
+    pub fn call_method(&mut self, handle: &JsHandle, name: &str, args: &[JsOwnedValue]) -> Result<JsOwnedValue, JSError> {
+        self.collect_if_over_heap_limit();
+        let result = self
+            .arena
+            .mutate_root(|ctx, vm| vm.call_method_from_host(ctx, handle, name, args, false));
+        self.collect_after_eval(&result);
+        result
+    }
+
+    /// Snapshot the object behind `handle` into a [`JsOwnedValue`].
+    pub fn snapshot(&mut self, handle: &JsHandle) -> Result<JsOwnedValue, JSError> {
+        self.arena.mutate_root(|ctx, vm| vm.handle_to_owned(ctx, handle))
+    }
+
+    /// Import a module and return its namespace.
+    ///
+    /// With the default loader `path_or_key` is a file path, relative paths
+    /// being taken from the current directory; with a custom loader (see
+    /// [`Repl::set_module_loader`]) it is resolved like `import()` in a
+    /// snippet. The module and its imports are linked and evaluated, and
+    /// top-level `await` is driven to completion before this returns. Every
+    /// import of the same module shares one instance, also with `import()`
+    /// in later snippets.
+    ///
+    /// ```
+    /// use javascript::{JsOwnedValue, JSError, ModuleLoader, ModuleSource, Repl};
+    ///
+    /// struct Memory;
+    ///
+    /// impl ModuleLoader for Memory {
+    ///     fn resolve(&self, specifier: &str, _referrer: &str) -> Result<String, JSError> {
+    ///         Ok(specifier.to_string())
+    ///     }
+    ///
+    ///     fn load(&self, _key: &str) -> Result<ModuleSource, JSError> {
+    ///         let source = "export const rate = await Promise.resolve(0.2); export function tax(x) { return x * rate; }";
+    ///         Ok(ModuleSource::JavaScript(source.to_string()))
+    ///     }
+    /// }
+    ///
+    /// let mut repl = Repl::new();
+    /// repl.set_module_loader(Memory);
+    /// let prices = repl.import_module("prices").unwrap();
+    /// assert_eq!(prices.export_names(), ["rate", "tax"]);
+    /// assert_eq!(repl.get_export(&prices, "rate").unwrap(), JsOwnedValue::Number(0.2));
+    /// assert_eq!(repl.call_export(&prices, "tax", &[50.into()]).unwrap(), JsOwnedValue::Number(10.0));
+    /// ```
+    pub fn import_module(&mut self, path_or_key: &str) -> Result<ModuleNamespaceHandle, JSError> {
+        self.collect_if_over_heap_limit();
+        let result = self.arena.mutate_root(|ctx, vm| vm.import_module_from_host(ctx, path_or_key));
+        self.collect_after_eval(&result);
+        result
+    }
+
+    /// Read the current value of export `name` of `module`.
+    pub fn get_export(&mut self, module: &ModuleNamespaceHandle, name: &str) -> Result<JsOwnedValue, JSError> {
+        self.get(module.as_handle(), name)
+    }
+
+    /// Call the exported function `name` of `module` with `this` undefined,
+    /// as a script calling an imported function would. Scheduled microtasks
+    /// and timers run as with [`Repl::call`].
+    pub fn call_export(&mut self, module: &ModuleNamespaceHandle, name: &str, args: &[JsOwnedValue]) -> Result<JsOwnedValue, JSError> {
+        let func = self.get_handle(module.as_handle(), name)?;
+        self.call(&func, (), args)
+    }
+
+    fn call_inner(
+        &mut self,
+        target: CallTarget<'_>,
+        this: JsOwnedValue,
+        args: &[JsOwnedValue],
+        settle: bool,
+    ) -> Result<JsOwnedValue, JSError> {
+        self.collect_if_over_heap_limit();
+        let result = self
+            .arena
+            .mutate_root(|ctx, vm| vm.call_from_host(ctx, target, &this, args, settle));
+        self.collect_after_eval(&result);
+        result
+    }
+
+    /// Set how [`Repl::eval_inspect`] and `console.*` render objects.
+    pub fn set_inspect_options(&mut self, options: InspectOptions) {
+        self.arena.mutate_root(|_ctx, vm| vm.set_inspect_options(options));
+    }
+
+    /// Cap the garbage-collected heap at `bytes` (`None` removes the cap).
+    /// The cap covers string contents, array elements and property tables as
+    /// well as the objects that hold them.
+    ///
+    /// When an evaluation crosses the cap it pauses for a full collection and
+    /// only fails if the objects still reachable exceed the cap, with an
+    /// uncatchable `JSErrorKind::OutOfMemory` (a `RangeError`). Code that runs
+    /// inside a callback of a builtin, and the timers and microtasks an
+    /// evaluation schedules, cannot pause, so garbage they create counts until
+    /// they return.
+    pub fn set_heap_limit(&mut self, bytes: Option<usize>) {
+        self.arena.mutate_root(|_ctx, vm| vm.set_heap_limit(bytes));
+    }
+
+    /// Current heap usage and the number of reachable objects.
+    pub fn heap_stats(&self) -> HeapStats {
+        self.arena.mutate(|ctx, vm| vm.heap_stats(ctx))
+    }
+
+    /// Evaluate one input and hand its completion value to `finish`,
+    /// collecting garbage whenever the run pauses at the heap limit.
+    fn eval_snippet<R>(
+        &mut self,
+        script: &str,
+        finish: impl for<'gc> FnOnce(&GcContext<'gc>, &mut VM<'gc>, Value<'gc>) -> Result<R, JSError>,
+    ) -> Result<R, JSError> {
+        let mut finish = Some(finish);
+        let mut started = false;
+        loop {
+            let done = self.arena.mutate_root(|ctx, vm| {
+                let slice = if started {
+                    vm.resume_repl_snippet(ctx, script)
+                } else {
+                    vm.start_repl_snippet(ctx, script)
+                };
+                match slice {
+                    RunSlice::Paused => None,
+                    RunSlice::Done(result) => {
+                        let finish = finish.take().expect("an evaluation finishes once");
+                        Some(result.and_then(|v| finish(ctx, vm, v)))
+                    }
+                }
+            });
+            if let Some(result) = done {
+                return result;
+            }
+            started = true;
+            self.arena.finish_cycle();
+            self.arena.mutate_root(|ctx, vm| vm.check_heap_after_collection(ctx));
+        }
+    }
+
+    fn collect_if_over_heap_limit(&mut self) {
+        // Unroot objects whose handles were dropped so this cycle can free them.
+        let limit = self.arena.mutate(|_ctx, vm| {
+            vm.release_dropped_handles();
+            vm.heap_limit()
+        });
+        if let Some(limit) = limit
+            && self.arena.metrics().total_allocation() > limit
+        {
+            self.arena.finish_cycle();
+            self.arena.mutate_root(|ctx, vm| vm.recount_external_storage(ctx));
+        }
+    }
+
+    fn collect_after_eval<R>(&mut self, result: &Result<R, JSError>) {
+        if matches!(result, Err(err) if matches!(err.kind(), JSErrorKind::OutOfMemory { .. })) {
+            self.arena.finish_cycle();
+            self.arena.mutate_root(|ctx, vm| vm.recount_external_storage(ctx));
+        } else {
+            self.arena.collect_debt();
+        }
+    }
+
+    /// Limit how many bytecode instructions each `eval`/`eval_value` call may
+    /// execute, including the timers and microtasks it schedules. `None`
+    /// removes the limit.
+    ///
+    /// Exhausting the budget aborts the evaluation with an uncatchable
+    /// `JSErrorKind::InfiniteLoopError`; the REPL remains usable afterwards.
+    pub fn set_instruction_budget(&mut self, budget: Option<u64>) {
+        self.arena.mutate_root(|_ctx, vm| vm.set_instruction_budget(budget));
+    }
+
+    /// Handle that cancels the evaluation currently running on this REPL.
+    ///
+    /// The handle is `Send + Sync` and may be used from another thread. An
+    /// interrupted evaluation fails with `JSErrorKind::Interrupted`; the REPL
+    /// keeps its state and accepts new input afterwards.
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.arena.mutate(|_ctx, vm| vm.interrupt_handle())
+    }
+
+    /// Route `console.*` output to `sink` instead of stdout/stderr.
+    ///
+    /// Use [`CapturedConsole`](crate::CapturedConsole) to buffer output in memory.
+    pub fn set_console_sink<S: ConsoleSink + 'static>(&mut self, sink: S) {
+        let sink: Rc<dyn ConsoleSink> = Rc::new(sink);
+        self.arena.mutate_root(|_ctx, vm| vm.set_console_sink(sink));
+    }
+
+    /// Resolve and fetch modules requested by `import()` through `loader`
+    /// instead of the filesystem. Snippets have no path of their own, so the
+    /// loader sees an empty referrer for imports made directly from the REPL.
+    pub fn set_module_loader<L: ModuleLoader + 'static>(&mut self, loader: L) {
+        let loader: Rc<dyn ModuleLoader> = Rc::new(loader);
+        self.arena.mutate_root(|_ctx, vm| vm.set_module_loader(loader));
+    }
+
+    /// Expose a Rust closure to scripts as a global function.
+    ///
+    /// `path` may be a plain name (`"greet"`) or a dotted namespace path
+    /// (`"app.db.query"`); missing namespace objects are created on demand.
+    /// Arguments are passed as [`JsOwnedValue`]s, and returning `Err` throws
+    /// the error into the calling script, where it can be caught.
+    /// The function's `length` is always 0, however many arguments `f`
+    /// reads; use [`Repl::register_function_with_length`] to declare an arity.
+    pub fn register_function<F>(&mut self, path: &str, f: F) -> Result<(), JSError>
+    where
+        F: Fn(&[JsOwnedValue]) -> Result<JsOwnedValue, JSError> + 'static,
+    {
+        self.register_function_with_length(path, 0, f)
+    }
+
+    /// Like [`Repl::register_function`], with an explicit `length` (declared arity).
+    pub fn register_function_with_length<F>(&mut self, path: &str, length: usize, f: F) -> Result<(), JSError>
+    where
+        F: Fn(&[JsOwnedValue]) -> Result<JsOwnedValue, JSError> + 'static,
+    {
+        let callback: Rc<dyn Fn(&[JsOwnedValue]) -> Result<JsOwnedValue, JSError>> = Rc::new(f);
+        self.arena
+            .mutate_root(|ctx, vm| vm.register_host_callback(ctx, path, length, callback))
+    }
+
+    /// Expose an asynchronous Rust operation to scripts as a function that
+    /// returns a promise.
+    ///
+    /// `f` receives the arguments and a [`PromiseResolver`] and should start
+    /// the work and return; the resolver can be settled later, from any
+    /// thread. Evaluations keep running the event loop until every promise
+    /// handed out this way has settled, so `await app.db.fetchRow(1)` works
+    /// in scripts and modules. Returning `Err` rejects the promise. The
+    /// function's `length` is always 0.
+    ///
+    /// ```
+    /// use javascript::Repl;
+    ///
+    /// let mut repl = Repl::new();
+    /// repl.register_async_function("double", |args, resolver| {
+    ///     let n = args.first().and_then(|v| v.as_f64()).unwrap_or(0.0);
+    ///     std::thread::spawn(move || resolver.resolve(n * 2.0));
+    ///     Ok(())
+    /// })
+    /// .unwrap();
+    /// repl.eval("var out; (async () => { out = await double(21); })();").unwrap();
+    /// assert_eq!(repl.eval("out").unwrap(), "42");
+    /// ```
+    pub fn register_async_function<F>(&mut self, path: &str, f: F) -> Result<(), JSError>
+    where
+        F: Fn(&[JsOwnedValue], PromiseResolver) -> Result<(), JSError> + 'static,
+    {
+        let callback: AsyncHostCallback = Rc::new(f);
+        self.arena
+            .mutate_root(|ctx, vm| vm.register_async_host_callback(ctx, path, 0, callback))
+    }
+
+    /// Start defining a global JavaScript class whose instances carry a Rust `T`.
+    ///
+    /// ```
+    /// use javascript::{JsOwnedValue, Repl};
+    ///
+    /// struct Counter(f64);
+    ///
+    /// let mut repl = Repl::new();
+    /// repl.define_class::<Counter>("Counter")
+    ///     .constructor(|args| Ok(Counter(args.first().and_then(JsOwnedValue::as_f64).unwrap_or(0.0))))
+    ///     .method("inc", |c, _| {
+    ///         c.0 += 1.0;
+    ///         Ok(c.0.into())
+    ///     })
+    ///     .getter("value", |c| Ok(c.0.into()))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(repl.eval("const c = new Counter(41); c.inc(); c.value").unwrap(), "42");
+    /// assert_eq!(repl.eval("c instanceof Counter").unwrap(), "true");
+    /// ```
+    pub fn define_class<T: 'static>(&mut self, name: &str) -> ClassBuilder<'_, T> {
+        ClassBuilder::new(self, name)
+    }
+
+    pub(crate) fn install_host_class(&mut self, spec: HostClassSpec) -> Result<(), JSError> {
+        self.arena.mutate_root(|ctx, vm| vm.define_host_class(ctx, spec))
+    }
+
+    /// Let the embedder drive the event loop instead of evaluation.
+    ///
+    /// By default `eval` and `call` keep running timers (see
+    /// [`Repl::set_wait_for_active_handles`]) and waiting for async host
+    /// functions until no work remains. Once enabled, they only
+    /// drain microtasks and return; timers and async settlements stay queued
+    /// until [`Repl::run_expired_timers`] and [`Repl::run_microtasks`] are
+    /// called, so a script's `setInterval` can tick under the host's own
+    /// scheduler:
+    ///
+    /// ```
+    /// use javascript::Repl;
+    /// use std::time::Instant;
+    ///
+    /// let mut repl = Repl::new();
+    /// repl.set_host_driven_event_loop(true);
+    /// repl.eval("var ticks = 0; const t = setInterval(() => { if (++ticks === 3) clearInterval(t); }, 1);")
+    ///     .unwrap();
+    /// while repl.has_pending_work() {
+    ///     if let Some(deadline) = repl.next_timer_deadline() {
+    ///         std::thread::sleep(deadline.saturating_duration_since(Instant::now()));
+    ///     }
+    ///     repl.run_expired_timers(Instant::now()).unwrap();
+    ///     repl.run_microtasks().unwrap();
+    /// }
+    /// assert_eq!(repl.eval("ticks").unwrap(), "3");
+    /// ```
+    pub fn set_host_driven_event_loop(&mut self, enabled: bool) {
+        self.arena.mutate_root(|_ctx, vm| vm.set_host_driven_event_loop(enabled));
+    }
+
+    /// Wait for timers with a delay of up to `ms` milliseconds before an
+    /// evaluation returns (20 by default). Longer timers stay queued and fire
+    /// during a later evaluation once due, unless
+    /// [`Repl::set_wait_for_active_handles`] is on.
+    pub fn set_short_timer_threshold_ms(&mut self, ms: u64) {
+        self.arena.mutate_root(|_ctx, vm| vm.set_short_timer_threshold_ms(ms));
+    }
+
+    /// Keep running the event loop, sleeping until each deadline, for as long
+    /// as any timer that was not released with `os.unrefTimer` is pending, as
+    /// Node does for a script. Off by default, since a `setInterval` that is
+    /// never cleared then keeps `eval` from returning.
+    pub fn set_wait_for_active_handles(&mut self, enabled: bool) {
+        self.arena.mutate_root(|_ctx, vm| vm.set_wait_for_active_handles(enabled));
+    }
+
+    /// Apply the settlements of async host functions that have arrived so
+    /// far, then run queued microtasks (promise reactions) to completion.
+    /// An exception escaping a reaction is returned as the error.
+    pub fn run_microtasks(&mut self) -> Result<(), JSError> {
+        let result = self.arena.mutate_root(|ctx, vm| vm.run_microtasks(ctx));
+        self.collect_after_eval(&result);
+        result
+    }
+
+    /// Fire the timers whose deadline is at or before `now`, earliest first,
+    /// running microtasks after each callback; returns how many fired.
+    /// Intervals are rescheduled one period after their previous deadline,
+    /// skipping any ticks already past `now`. If a callback throws, the
+    /// remaining due timers stay queued and the exception is returned.
+    pub fn run_expired_timers(&mut self, now: Instant) -> Result<usize, JSError> {
+        let result = self.arena.mutate_root(|ctx, vm| vm.run_expired_timers(ctx, now));
+        self.collect_after_eval(&result);
+        result
+    }
+
+    /// The earliest deadline among scheduled timers, if any.
+    pub fn next_timer_deadline(&self) -> Option<Instant> {
+        self.arena.mutate(|_ctx, vm| vm.next_timer_deadline())
+    }
+
+    /// Whether microtasks, timers or async host operations are still queued.
+    /// Timers released with `os.unrefTimer` do not count, though they still
+    /// fire once due.
+    pub fn has_pending_work(&self) -> bool {
+        self.arena.mutate(|_ctx, vm| vm.has_pending_work())
+    }
+
+    /// Make this REPL reproducible: `Math.random` is reseeded, and the time
+    /// scripts see comes from a virtual clock that starts over at the
+    /// configured time; see [`DeterministicOptions`].
+    ///
+    /// Evaluation no longer sleeps for the timers it waits for: once nothing
+    /// else is left to run, the clock jumps to the next deadline and the
+    /// timer fires. With a
+    /// host-driven event loop, deadlines from [`Repl::next_timer_deadline`]
+    /// are on the virtual clock, and [`Repl::run_expired_timers`] moves the
+    /// clock forward to the instant it is given.
+    ///
+    /// ```
+    /// use javascript::{DeterministicOptions, Repl};
+    ///
+    /// let run = || {
+    ///     let mut repl = Repl::new();
+    ///     repl.set_deterministic(DeterministicOptions::new().seed(42));
+    ///     repl.set_wait_for_active_handles(true);
+    ///     repl.eval("var start = Date.now(); var waited; setTimeout(() => { waited = Date.now() - start; }, 5000);")
+    ///         .unwrap();
+    ///     (repl.eval("waited").unwrap(), repl.eval("Math.random()").unwrap())
+    /// };
+    /// let first = run();
+    /// assert_eq!(first.0, "5000");
+    /// assert_eq!(first, run());
+    /// ```
+    pub fn set_deterministic(&mut self, options: DeterministicOptions) {
+        self.arena.mutate_root(|_ctx, vm| vm.set_deterministic(options));
+    }
+
+    /// Limit what scripts may do through the `os` and `std` namespaces; see
+    /// [`Permissions`]. A denied operation throws a `PermissionDenied` error.
+    ///
+    /// ```
+    /// use javascript::{Permissions, Repl};
+    ///
+    /// let mut repl = Repl::new();
+    /// repl.set_permissions(Permissions::none().allow_env("PATH"));
+    /// assert_eq!(repl.eval("typeof std.getenv('PATH')").unwrap(), "\"string\"");
+    /// let err = repl.eval("std.getenv('AWS_SECRET_ACCESS_KEY')").unwrap_err();
+    /// assert!(err.message().contains("Requires env access"));
+    /// ```
+    pub fn set_permissions(&mut self, permissions: Permissions) {
+        self.arena.mutate_root(|_ctx, vm| vm.set_permissions(permissions));
+    }
+
+    /// Move the virtual clock of a deterministic REPL forward by `by`,
+    /// firing each timer as the clock passes its deadline; returns how many
+    /// fired. Does nothing unless [`Repl::set_deterministic`] was called.
+    pub fn advance_clock(&mut self, by: Duration) -> Result<usize, JSError> {
+        let result = self.arena.mutate_root(|ctx, vm| vm.advance_clock(ctx, by));
+        self.collect_after_eval(&result);
+        result
+    }
+
+    /// Report promises still rejected without a handler once the microtask
+    /// queue drains, and reported ones that gain a handler later, to
+    /// `tracker`. Returning `Err` from it fails the running evaluation with
+    /// that error.
+    ///
+    /// Scripts see the same events through `globalThis.onunhandledrejection`
+    /// and `globalThis.onrejectionhandled`; calling `event.preventDefault()`
+    /// in the former keeps the rejection from reaching `tracker`.
+    ///
+    /// ```
+    /// use javascript::{RejectionEvent, Repl};
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// let events = Rc::new(RefCell::new(Vec::new()));
+    /// let log = events.clone();
+    /// let mut repl = Repl::new();
+    /// repl.set_rejection_tracker(move |rejection| {
+    ///     log.borrow_mut().push(rejection.event());
+    ///     Ok(())
+    /// });
+    /// repl.eval("var p = Promise.reject(new Error('lost'));").unwrap();
+    /// repl.eval("p.catch(() => {});").unwrap();
+    /// assert_eq!(*events.borrow(), [RejectionEvent::Unhandled, RejectionEvent::Handled]);
+    /// ```
+    pub fn set_rejection_tracker<F>(&mut self, tracker: F)
+    where
+        F: Fn(PromiseRejection) -> Result<(), JSError> + 'static,
+    {
+        let tracker: RejectionCallback = Rc::new(tracker);
+        self.arena.mutate_root(|_ctx, vm| vm.set_rejection_tracker(Some(tracker)));
+    }
+
+    /// Run a full garbage collection cycle now, then drop the Rust payloads
+    /// of host class instances that were collected.
+    pub fn collect_garbage(&mut self) {
+        self.arena.mutate(|_ctx, vm| vm.release_dropped_handles());
+        self.arena.finish_cycle();
+        self.arena.mutate_root(|ctx, vm| {
+            vm.collect_garbage(ctx);
+            vm.recount_external_storage(ctx);
+        });
+    }
+
     /// Returns true when the given `input` looks like a complete JavaScript
     /// top-level expression/program piece (i.e. brackets and template expressions
     /// are balanced, strings/comments/regex literals are properly closed).
@@ -53,9 +53,9 @@ fail_details=()
 for f in "${examples[@]}"; do
   name=$(basename "$f")
   if [[ "$f" == *.mjs ]] || [[ "$f" == *es6_module*.js ]]; then
-    cmd=(./target/release/js --module "$f")
+    cmd=(./target/release/js --allow-all --module "$f")
   else
-    cmd=(./target/release/js "$f")
+    cmd=(./target/release/js --allow-all "$f")
   fi
 
   echo "testing $f"
@@ -0,0 +1,142 @@
+use javascript::{
+    JSError, JsOwnedValue, ModuleLoader, ModuleSource, PromiseResolver, Repl, raise_eval_error, raise_range_error, to_js_value,
+};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Serialize)]
+struct Row {
+    id: f64,
+    name: String,
+}
+
+fn register_fetch_row(repl: &mut Repl) {
+    repl.register_async_function("host.fetchRow", |args, resolver| {
+        let id = args.first().and_then(JsOwnedValue::as_f64).unwrap_or(-1.0);
+        if id < 0.0 {
+            return Err(raise_range_error!("row id must not be negative"));
+        }
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(5));
+            if id == 0.0 {
+                resolver.reject(raise_eval_error!("row 0 is missing"));
+            } else {
+                let row = Row {
+                    id,
+                    name: format!("row{id}"),
+                };
+                resolver.resolve(to_js_value(&row).unwrap());
+            }
+        });
+        Ok(())
+    })
+    .unwrap();
+}
+
+#[test]
+fn awaits_values_resolved_on_other_threads() {
+    let mut repl = Repl::new();
+    register_fetch_row(&mut repl);
+    repl.eval("var names = []; (async () => { for (const id of [1, 2]) names.push((await host.fetchRow(id)).name); })();")
+        .unwrap();
+    assert_eq!(repl.eval("names.join()").unwrap(), "\"row1,row2\"");
+
+    let out = repl.call_await("host.fetchRow", (), &[3.into()]).unwrap();
+    assert_eq!(out.get("name").and_then(JsOwnedValue::as_str), Some("row3"));
+    assert_eq!(repl.eval("host.fetchRow(4) instanceof Promise").unwrap(), "true");
+}
+
+#[test]
+fn rejections_and_callback_errors_reject_the_promise() {
+    let mut repl = Repl::new();
+    register_fetch_row(&mut repl);
+    repl.eval(
+        r#"var seen = [];
+        host.fetchRow(0).catch(e => seen.push(`${e.name}:${e.message}`));
+        host.fetchRow(-1).catch(e => seen.push(`${e.name}:${e.message}`));"#,
+    )
+    .unwrap();
+    let out = repl.eval("seen.sort().join('|')").unwrap();
+    assert_eq!(out, "\"Error:row 0 is missing|RangeError:row id must not be negative\"");
+
+    let err = repl.call_await("host.fetchRow", (), &[0.into()]).unwrap_err();
+    assert!(err.message().contains("row 0 is missing"), "{}", err.message());
+}
+
+#[test]
+fn resolvers_can_be_settled_later_through_a_channel() {
+    let (tx, rx) = mpsc::channel::<(f64, PromiseResolver)>();
+    let worker = thread::spawn(move || {
+        for (n, resolver) in rx {
+            resolver.resolve(n * 10.0);
+        }
+    });
+
+    let mut repl = Repl::new();
+    repl.register_async_function("work", move |args, resolver| {
+        let n = args.first().and_then(JsOwnedValue::as_f64).unwrap_or(0.0);
+        tx.send((n, resolver)).map_err(|_| raise_eval_error!("worker has stopped"))
+    })
+    .unwrap();
+    repl.eval("Promise.all([work(1), work(2), work(3)]).then(v => { globalThis.total = v.reduce((a, b) => a + b); })")
+        .unwrap();
+    assert_eq!(repl.eval("total").unwrap(), "60");
+
+    // A resolver dropped without being settled rejects instead of hanging.
+    repl.register_async_function("forget", |_, resolver| {
+        drop(resolver);
+        Ok(())
+    })
+    .unwrap();
+    let err = repl.call_await("forget", (), &[]).unwrap_err();
+    assert!(err.message().contains("dropped without settling"), "{}", err.message());
+
+    drop(repl);
+    worker.join().unwrap();
+}
+
+#[test]
+fn modules_can_await_async_host_functions() {
+    struct Loader;
+    impl ModuleLoader for Loader {
+        fn resolve(&self, specifier: &str, _referrer: &str) -> Result<String, JSError> {
+            Ok(specifier.trim_start_matches("./").to_string())
+        }
+
+        fn load(&self, _key: &str) -> Result<ModuleSource, JSError> {
+            Ok(ModuleSource::JavaScript(
+                "export async function label(id) { const row = await host.fetchRow(id); return `#${row.id} ${row.name}`; }".to_string(),
+            ))
+        }
+    }
+
+    let mut repl = Repl::new();
+    register_fetch_row(&mut repl);
+    repl.set_module_loader(Loader);
+    repl.eval("var label; import('./rows.js').then(m => m.label(7)).then(v => { label = v; })")
+        .unwrap();
+    assert_eq!(repl.eval("label").unwrap(), "\"#7 row7\"");
+}
+
+#[test]
+fn resolvers_are_send() {
+    fn assert_send<T: Send + 'static>() {}
+    assert_send::<PromiseResolver>();
+    assert_send::<JsOwnedValue>();
+
+    let settled = Rc::new(RefCell::new(Vec::new()));
+    let mut repl = Repl::new();
+    let log = settled.clone();
+    repl.register_async_function("now", move |_, resolver| {
+        log.borrow_mut().push("started");
+        resolver.resolve("sync");
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(repl.call_await("now", (), &[]).unwrap(), JsOwnedValue::from("sync"));
+    assert_eq!(*settled.borrow(), vec!["started"]);
+}
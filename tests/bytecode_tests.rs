@@ -0,0 +1,172 @@
+use javascript::{EvalOptions, compile_to_bytes, evaluate_bytecode, evaluate_script, is_bytecode};
+
+fn run(bytes: &[u8]) -> Result<String, javascript::JSError> {
+    evaluate_bytecode(bytes, None::<&std::path::Path>, &EvalOptions::default())
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Offset of the LEB128 length of the code, which follows the magic, format
+/// version, engine version and flags.
+fn code_length_offset(bytes: &[u8]) -> usize {
+    8 + 1 + bytes[8] as usize + 1
+}
+
+/// Offset of the first instruction.
+fn code_offset(bytes: &[u8]) -> usize {
+    let mut pos = code_length_offset(bytes);
+    while bytes[pos] & 0x80 != 0 {
+        pos += 1;
+    }
+    pos + 1
+}
+
+/// Swap the compiled code for `code`, keeping the constants and metadata.
+fn with_code(bytes: &[u8], code: &[u8]) -> Vec<u8> {
+    let start = code_length_offset(bytes);
+    let mut pos = start;
+    let (mut len, mut shift) = (0, 0);
+    loop {
+        len |= ((bytes[pos] & 0x7f) as usize) << shift;
+        shift += 7;
+        pos += 1;
+        if bytes[pos - 1] & 0x80 == 0 {
+            break;
+        }
+    }
+    assert!(code.len() < 0x80);
+    let mut out = bytes[..start].to_vec();
+    out.push(code.len() as u8);
+    out.extend_from_slice(code);
+    out.extend_from_slice(&bytes[pos + len..]);
+    reseal(&mut out);
+    out
+}
+
+fn reseal(bytes: &mut Vec<u8>) {
+    let body_len = bytes.len() - 8;
+    let checksum = fnv1a(&bytes[..body_len]);
+    bytes.truncate(body_len);
+    bytes.extend_from_slice(&checksum.to_le_bytes());
+}
+
+#[test]
+fn bytecode_runs_like_the_source() {
+    let scripts = [
+        "1 + 2 * 3",
+        "'héllo, 世界 😀'.length",
+        "(2n ** 100n).toString(16) + typeof 10n",
+        "function fib(n) { return n < 2 ? n : fib(n - 1) + fib(n - 2); } fib(15)",
+        "let make = base => x => base + x; let add5 = make(5); add5(10)",
+        r#"
+        class Shape { #sides; constructor(n) { this.#sides = n; } get sides() { return this.#sides; } }
+        class Square extends Shape { constructor() { super(4); } toString() { return `square:${this.sides}`; } }
+        String(new Square())
+        "#,
+        "function* gen() { yield 1; yield* [2, 3]; } [...gen()].join()",
+        "try { null.x } catch (e) { e instanceof TypeError } finally { 'done' }",
+        "var order = []; Promise.resolve(1).then(v => order.push(v)); (async () => { await null; order.push(2); })(); order",
+        "const { a, ...rest } = { a: 1, b: 2, c: 3 }; JSON.stringify(rest)",
+        "(function named() { return named.name + ':' + arguments.length })(1, 2)",
+        "[/a(b+)/.exec('xabbb')[1], 0.1 + 0.2, -0, NaN, Infinity].join()",
+    ];
+    for script in scripts {
+        let bytes = compile_to_bytes(script).unwrap();
+        assert!(is_bytecode(&bytes));
+        let expected = evaluate_script(script, false, None::<&std::path::Path>).unwrap();
+        assert_eq!(run(&bytes).unwrap(), expected, "{script}");
+    }
+}
+
+#[test]
+fn strictness_and_source_text_survive() {
+    let scripts = [
+        (
+            "'use strict'; try { undeclared = 1; 'sloppy' } catch (e) { e.name }",
+            "\"ReferenceError\"",
+        ),
+        ("try { undeclared = 1; 'sloppy' } catch (e) { e.name }", "\"sloppy\""),
+        (
+            "function twice(x) { return x * 2; } twice.toString() === 'function twice(x) { return x * 2; }'",
+            "true",
+        ),
+    ];
+    for (script, expected) in scripts {
+        assert_eq!(
+            evaluate_script(script, false, None::<&std::path::Path>).unwrap(),
+            expected,
+            "{script}"
+        );
+        assert_eq!(run(&compile_to_bytes(script).unwrap()).unwrap(), expected, "{script}");
+    }
+}
+
+#[test]
+fn errors_keep_their_source_lines() {
+    let bytes = compile_to_bytes("var x = 1;\n\nthrow new RangeError('too big');").unwrap();
+    let err = run(&bytes).unwrap_err();
+    assert!(
+        matches!(err.kind(), javascript::JSErrorKind::RangeError { message } if message == "Uncaught: RangeError: too big"),
+        "{err:?}"
+    );
+    assert_eq!(err.js_line(), Some(3));
+
+    assert!(compile_to_bytes("let = ;").is_err());
+}
+
+#[test]
+fn encoding_is_reproducible() {
+    let script = "var a = 1, b = 2; function f() { let x = a; return () => x + b; } class C { m() {} } f()()";
+    assert_eq!(compile_to_bytes(script).unwrap(), compile_to_bytes(script).unwrap());
+}
+
+#[test]
+fn damaged_files_are_rejected() {
+    let bytes = compile_to_bytes("function f(s) { return s.repeat(3); } f('ab')").unwrap();
+    let rejects = |bytes: &[u8], reason: &str| {
+        let err = run(bytes).unwrap_err();
+        assert!(err.message().contains(reason), "expected {reason:?}, got {:?}", err.message());
+    };
+
+    rejects(b"console.log('source')", "not a bytecode file");
+    rejects(&bytes[..6], "truncated");
+    rejects(&bytes[..bytes.len() - 1], "checksum");
+
+    let mut flipped = bytes.clone();
+    let middle = flipped.len() / 2;
+    flipped[middle] ^= 0x40;
+    rejects(&flipped, "checksum");
+
+    let mut format = bytes.clone();
+    format[4] = 99;
+    rejects(&format, "format version 99");
+
+    let mut engine = bytes.clone();
+    engine[9] = b'~';
+    rejects(&engine, "engine version");
+
+    let mut opcode = bytes.clone();
+    let code = code_offset(&opcode);
+    opcode[code] = 0xee;
+    reseal(&mut opcode);
+    rejects(&opcode, "bad instruction at offset 0");
+
+    let mut trailing = bytes.clone();
+    trailing.splice(trailing.len() - 8..trailing.len() - 8, [0]);
+    reseal(&mut trailing);
+    rejects(&trailing, "unexpected data");
+}
+
+#[test]
+fn operands_missing_from_the_stack_are_an_error() {
+    // Opcodes: Return = 0, Constant = 1, Add = 2, Call = 15.
+    let bytes = compile_to_bytes("1 + 2").unwrap();
+    for code in [&[2, 0][..], &[1, 0, 0, 15, 3, 0], &[1, 0, 0, 2, 2, 0]] {
+        let err = run(&with_code(&bytes, code)).unwrap_err();
+        assert!(err.message().contains("operand stack underflow"), "{code:?}: {:?}", err.message());
+    }
+}
@@ -0,0 +1,181 @@
+use javascript::{CapturedConsole, ConsoleLevel, EvalOptions, evaluate_script_with_options};
+
+fn run_captured(script: &str) -> Vec<(ConsoleLevel, String)> {
+    let console = CapturedConsole::new();
+    let options = EvalOptions::new().console_sink(console.clone());
+    evaluate_script_with_options(script, false, None::<&std::path::Path>, &options).unwrap();
+    console.take()
+}
+
+fn messages(script: &str) -> Vec<String> {
+    run_captured(script).into_iter().map(|(_, message)| message).collect()
+}
+
+#[test]
+fn format_specifiers_are_substituted() {
+    assert_eq!(
+        messages(
+            r#"
+            console.log('%s is %d years', 'Bob', 42.9);
+            console.log('%i|%f|%o', '7.5', '1.25', 'x');
+            console.log('%c styled', 'color: red');
+            console.log('100%% done %s', 'now', 'extra', 3);
+            console.log('%d missing');
+            console.log('%d', 10n);
+            console.log('%d|%f|%i', -3.7, 2, 'x');
+            0
+            "#
+        ),
+        vec![
+            "Bob is 42 years",
+            "7|1.25|'x'",
+            " styled",
+            "100% done now extra 3",
+            "%d missing",
+            "10n",
+            "-3|2|NaN",
+        ]
+    );
+}
+
+#[test]
+fn info_and_debug_use_their_levels() {
+    assert_eq!(
+        run_captured("console.info('i'); console.debug('d'); 0"),
+        vec![(ConsoleLevel::Info, "i".to_string()), (ConsoleLevel::Debug, "d".to_string())]
+    );
+}
+
+#[test]
+fn groups_indent_nested_output() {
+    assert_eq!(
+        messages(
+            r#"
+            console.group('outer');
+            console.log('a');
+            console.groupCollapsed();
+            console.log('b\nc');
+            console.groupEnd();
+            console.groupEnd();
+            console.groupEnd();
+            console.log('d');
+            0
+            "#
+        ),
+        vec!["outer", "  a", "    b\n    c", "d"]
+    );
+}
+
+#[test]
+fn count_and_count_reset() {
+    let output = run_captured(
+        r#"
+        console.count();
+        console.count('x');
+        console.count();
+        console.countReset('x');
+        console.count('x');
+        console.countReset('nope');
+        0
+        "#,
+    );
+    assert_eq!(
+        output,
+        vec![
+            (ConsoleLevel::Info, "default: 1".to_string()),
+            (ConsoleLevel::Info, "x: 1".to_string()),
+            (ConsoleLevel::Info, "default: 2".to_string()),
+            (ConsoleLevel::Info, "x: 1".to_string()),
+            (ConsoleLevel::Warn, "Count for 'nope' does not exist".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn assert_only_reports_falsy_conditions() {
+    assert_eq!(
+        run_captured("console.assert(true, 'hidden'); console.assert(0); console.assert(false, 'n=%d', 5); 0"),
+        vec![
+            (ConsoleLevel::Error, "Assertion failed".to_string()),
+            (ConsoleLevel::Error, "Assertion failed: n=5".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn time_log_and_time_end() {
+    let output = messages(
+        r#"
+        console.time('t');
+        console.timeLog('t', 'step');
+        console.timeEnd('t');
+        console.timeEnd('t');
+        0
+        "#,
+    );
+    assert_eq!(output.len(), 3);
+    assert!(output[0].starts_with("t: ") && output[0].ends_with("ms step"), "{}", output[0]);
+    assert!(output[1].starts_with("t: ") && output[1].ends_with("ms"), "{}", output[1]);
+    assert_eq!(output[2], "Timer 't' does not exist");
+}
+
+#[test]
+fn table_renders_rows_and_columns() {
+    let output = messages("console.table([{ a: 1, b: 'x' }, { a: 2 }]); 0");
+    assert_eq!(
+        output,
+        vec![
+            [
+                "┌─────────┬───┬─────┐",
+                "│ (index) │ a │ b   │",
+                "├─────────┼───┼─────┤",
+                "│ 0       │ 1 │ 'x' │",
+                "│ 1       │ 2 │     │",
+                "└─────────┴───┴─────┘",
+            ]
+            .join("\n")
+        ]
+    );
+}
+
+#[test]
+fn table_of_primitives_uses_values_column() {
+    let output = messages("console.table(['p', 3]); console.table('plain'); 0");
+    assert!(output[0].contains("│ (index) │ Values │"), "{}", output[0]);
+    assert!(output[0].contains("│ 0       │ 'p'    │"), "{}", output[0]);
+    assert_eq!(output[1], "plain");
+}
+
+#[test]
+fn trace_prints_call_frames() {
+    let output = run_captured(
+        r#"
+        function inner() { console.trace('here %s', 'now'); }
+        function outer() { inner(); }
+        outer();
+        0
+        "#,
+    );
+    assert_eq!(output.len(), 1);
+    let (level, message) = &output[0];
+    assert_eq!(*level, ConsoleLevel::Trace);
+    let lines: Vec<&str> = message.lines().collect();
+    assert_eq!(lines[0], "Trace: here now");
+    assert!(lines[1].starts_with("    at inner ("), "{message}");
+    assert!(lines[2].starts_with("    at outer ("), "{message}");
+    // Each frame points at the line of its call site.
+    assert!(lines[1].contains(":2:"), "{message}");
+    assert!(lines[2].contains(":3:"), "{message}");
+    assert!(
+        lines[3].starts_with("    at <anonymous> (") && lines[3].contains(":4:"),
+        "{message}"
+    );
+}
+
+#[test]
+fn console_methods_are_functions() {
+    let output = messages(
+        "console.log(['info','debug','trace','dir','table','time','timeEnd','timeLog','count','countReset','group','groupEnd','assert'].every(k => typeof console[k] === 'function')); 0",
+    );
+    assert_eq!(output, vec!["true"]);
+}
@@ -0,0 +1,50 @@
+use javascript::{CapturedConsole, ConsoleLevel, ConsoleSink, EvalOptions, Repl, evaluate_script_with_options};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[test]
+fn captured_console_records_levels() {
+    let console = CapturedConsole::new();
+    let options = EvalOptions::new().console_sink(console.clone());
+    evaluate_script_with_options(
+        "console.log('a', 1); console.warn('careful'); console.error(new Error('boom').message); 0",
+        false,
+        None::<&std::path::Path>,
+        &options,
+    )
+    .unwrap();
+    assert_eq!(
+        console.take(),
+        vec![
+            (ConsoleLevel::Log, "a 1".to_string()),
+            (ConsoleLevel::Warn, "careful".to_string()),
+            (ConsoleLevel::Error, "boom".to_string()),
+        ]
+    );
+    assert!(console.take().is_empty());
+}
+
+#[test]
+fn custom_sink_sees_timer_and_eval_output() {
+    struct Collect(Rc<RefCell<Vec<String>>>);
+    impl ConsoleSink for Collect {
+        fn write(&self, level: ConsoleLevel, message: &str) {
+            self.0.borrow_mut().push(format!("{}:{}", level.as_str(), message));
+        }
+    }
+
+    let lines = Rc::new(RefCell::new(Vec::new()));
+    let mut repl = Repl::new();
+    repl.set_console_sink(Collect(lines.clone()));
+    repl.eval("setTimeout(() => console.log('later'), 0); console.log('now');").unwrap();
+    repl.eval("eval(\"console.warn('nested')\")").unwrap();
+    assert_eq!(lines.borrow().as_slice(), ["log:now", "log:later", "warn:nested"]);
+}
+
+#[test]
+fn diagnostic_levels_go_to_stderr_by_default() {
+    assert!(ConsoleLevel::Warn.is_diagnostic());
+    assert!(ConsoleLevel::Error.is_diagnostic());
+    assert!(!ConsoleLevel::Log.is_diagnostic());
+    assert!(!ConsoleLevel::Info.is_diagnostic());
+}
@@ -0,0 +1,138 @@
+use javascript::{DeterministicOptions, EvalOptions, Repl, evaluate_script_with_options};
+use std::time::{Duration, Instant};
+
+const NEW_YEAR_2024_MS: f64 = 1_704_067_200_000.0;
+
+fn deterministic(options: DeterministicOptions) -> Repl {
+    let mut repl = Repl::new();
+    repl.set_deterministic(options);
+    repl
+}
+
+fn new_year() -> Repl {
+    deterministic(DeterministicOptions::new().start_time_iso("2024-01-01T00:00:00Z").unwrap())
+}
+
+#[test]
+fn math_random_follows_the_seed() {
+    let sample = "Array.from({ length: 5 }, () => Math.random()).join()";
+    let first = deterministic(DeterministicOptions::new().seed(1)).eval(sample).unwrap();
+    let again = deterministic(DeterministicOptions::new().seed(1)).eval(sample).unwrap();
+    let other = deterministic(DeterministicOptions::new().seed(2)).eval(sample).unwrap();
+    assert_eq!(first, again);
+    assert_ne!(first, other);
+
+    let mut repl = deterministic(DeterministicOptions::new().seed(1));
+    assert_eq!(
+        repl.eval("var xs = Array.from({ length: 1000 }, Math.random); xs.every(x => x >= 0 && x < 1)")
+            .unwrap(),
+        "true"
+    );
+    assert_eq!(repl.eval("new Set(xs).size").unwrap(), "1000");
+}
+
+#[test]
+fn the_clock_stands_still_until_advanced() {
+    let mut repl = new_year();
+    assert_eq!(repl.eval("Date.now()").unwrap(), format!("{NEW_YEAR_2024_MS}"));
+    assert_eq!(
+        repl.eval("var t0 = Date.now(); for (let i = 0; i < 100000; i++) {} Date.now() - t0")
+            .unwrap(),
+        "0"
+    );
+    assert_eq!(repl.eval("new Date().toISOString()").unwrap(), "\"2024-01-01T00:00:00.000Z\"");
+    assert_eq!(repl.eval("performance.now()").unwrap(), "0");
+
+    assert_eq!(repl.advance_clock(Duration::from_millis(1500)).unwrap(), 0);
+    assert_eq!(repl.eval("Date.now() - t0").unwrap(), "1500");
+    assert_eq!(repl.eval("performance.now()").unwrap(), "1500");
+    #[cfg(feature = "temporal")]
+    assert_eq!(repl.eval("Temporal.Now.instant().epochMilliseconds - t0").unwrap(), "1500");
+}
+
+#[test]
+fn timers_fire_by_virtual_time_without_sleeping() {
+    let mut repl = new_year();
+    repl.set_wait_for_active_handles(true);
+    let started = Instant::now();
+    repl.eval(
+        r#"
+        var log = [];
+        var t0 = Date.now();
+        setTimeout(() => log.push('minute@' + (Date.now() - t0)), 60000);
+        setTimeout(() => log.push('second@' + (Date.now() - t0)), 1000);
+        const tick = setInterval(() => { log.push('tick@' + (Date.now() - t0)); if (log.length === 2) clearInterval(tick); }, 400);
+        "#,
+    )
+    .unwrap();
+    assert!(started.elapsed() < Duration::from_secs(5), "{:?}", started.elapsed());
+    assert_eq!(repl.eval("log.join()").unwrap(), "\"tick@400,tick@800,second@1000,minute@60000\"");
+}
+
+#[test]
+fn local_time_is_utc() {
+    let mut repl = new_year();
+    assert_eq!(
+        repl.eval("new Date(2024, 5, 1, 12).getTime() === Date.UTC(2024, 5, 1, 12)")
+            .unwrap(),
+        "true"
+    );
+    assert_eq!(repl.eval("new Date().getTimezoneOffset()").unwrap(), "0");
+    assert_eq!(repl.eval("new Date().getHours()").unwrap(), "0");
+    assert_eq!(
+        repl.eval("Date.parse('2024-06-01T12:00:00') === Date.UTC(2024, 5, 1, 12)").unwrap(),
+        "true"
+    );
+    #[cfg(feature = "temporal")]
+    assert_eq!(repl.eval("Temporal.Now.timeZoneId()").unwrap(), "\"UTC\"");
+    #[cfg(feature = "temporal")]
+    assert_eq!(repl.eval("Temporal.Now.plainDateISO().toString()").unwrap(), "\"2024-01-01\"");
+    #[cfg(feature = "intl")]
+    assert_eq!(
+        repl.eval("new Intl.DateTimeFormat().resolvedOptions().locale").unwrap(),
+        "\"en-US\""
+    );
+}
+
+#[test]
+fn host_driven_loops_step_the_virtual_clock() {
+    let mut repl = new_year();
+    repl.set_host_driven_event_loop(true);
+    repl.eval("var ticks = []; var t0 = Date.now(); setInterval(() => ticks.push(Date.now() - t0), 1000);")
+        .unwrap();
+    assert_eq!(repl.advance_clock(Duration::from_millis(3500)).unwrap(), 3);
+    assert_eq!(repl.eval("ticks.join()").unwrap(), "\"1000,2000,3000\"");
+    assert_eq!(repl.eval("Date.now() - t0").unwrap(), "3500");
+
+    let deadline = repl.next_timer_deadline().unwrap();
+    assert_eq!(repl.run_expired_timers(deadline).unwrap(), 1);
+    assert_eq!(repl.eval("ticks.join()").unwrap(), "\"1000,2000,3000,4000\"");
+}
+
+#[test]
+fn advancing_needs_a_virtual_clock() {
+    let mut repl = Repl::new();
+    repl.set_host_driven_event_loop(true);
+    repl.eval("var fired = false; setTimeout(() => { fired = true; }, 10);").unwrap();
+    assert_eq!(repl.advance_clock(Duration::from_secs(1)).unwrap(), 0);
+    assert_eq!(repl.eval("fired").unwrap(), "false");
+}
+
+#[test]
+fn eval_options_run_deterministically() {
+    let script = "setTimeout(() => {}, 250); [Math.random(), new Date().toISOString()].join()";
+    let options = EvalOptions::new().deterministic(DeterministicOptions::new().seed(9).start_time(NEW_YEAR_2024_MS));
+    let first = evaluate_script_with_options(script, false, None::<&std::path::Path>, &options).unwrap();
+    let again = evaluate_script_with_options(script, false, None::<&std::path::Path>, &options).unwrap();
+    assert_eq!(first, again);
+    assert!(first.ends_with(",2024-01-01T00:00:00.000Z\""), "{first}");
+}
+
+#[test]
+fn start_times_are_parsed_as_iso_dates() {
+    let options = DeterministicOptions::new().start_time_iso("2024-01-01").unwrap();
+    assert_eq!(options, DeterministicOptions::new().start_time(NEW_YEAR_2024_MS));
+    let offset = DeterministicOptions::new().start_time_iso("2024-01-01T02:00:00+02:00").unwrap();
+    assert_eq!(offset, options);
+    assert!(DeterministicOptions::new().start_time_iso("next tuesday").is_err());
+}
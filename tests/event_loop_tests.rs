@@ -0,0 +1,103 @@
+use javascript::Repl;
+use std::time::{Duration, Instant};
+
+fn host_driven() -> Repl {
+    let mut repl = Repl::new();
+    repl.set_host_driven_event_loop(true);
+    repl
+}
+
+#[test]
+fn timers_wait_for_the_embedder() {
+    let mut repl = host_driven();
+    let start = Instant::now();
+    repl.eval(
+        "var log = []; setTimeout(() => log.push('c'), 30); setTimeout(() => log.push('a'), 10); setTimeout(() => log.push('b'), 20);",
+    )
+    .unwrap();
+    assert_eq!(repl.eval("log.length").unwrap(), "0");
+    assert!(repl.has_pending_work());
+
+    let deadline = repl.next_timer_deadline().unwrap();
+    assert!(deadline >= start + Duration::from_millis(10), "{deadline:?}");
+    assert_eq!(repl.run_expired_timers(start).unwrap(), 0);
+
+    assert_eq!(repl.run_expired_timers(deadline).unwrap(), 1);
+    assert_eq!(repl.eval("log.join()").unwrap(), "\"a\"");
+    assert_eq!(repl.run_expired_timers(Instant::now() + Duration::from_secs(1)).unwrap(), 2);
+    assert_eq!(repl.eval("log.join()").unwrap(), "\"a,b,c\"");
+    assert!(!repl.has_pending_work());
+    assert_eq!(repl.next_timer_deadline(), None);
+}
+
+#[test]
+fn intervals_tick_once_per_step_and_can_be_cleared_later() {
+    let mut repl = host_driven();
+    repl.eval("var ticks = 0; var handle = setInterval(() => { ticks++; Promise.resolve().then(() => ticks += 10); }, 5);")
+        .unwrap();
+    let mut now = Instant::now();
+    for _ in 0..3 {
+        now += Duration::from_millis(5);
+        assert_eq!(repl.run_expired_timers(now).unwrap(), 1);
+    }
+    // Microtasks queued by a timer run before `run_expired_timers` returns.
+    assert_eq!(repl.eval("ticks").unwrap(), "33");
+
+    repl.eval("clearInterval(handle)").unwrap();
+    assert!(!repl.has_pending_work());
+    assert_eq!(repl.run_expired_timers(now + Duration::from_secs(1)).unwrap(), 0);
+}
+
+#[test]
+fn intervals_keep_their_period_when_run_late() {
+    let mut repl = host_driven();
+    repl.eval("var ticks = 0; setInterval(() => ticks++, 10);").unwrap();
+    let first = repl.next_timer_deadline().unwrap();
+
+    // Running 4ms late does not push the following deadline back.
+    assert_eq!(repl.run_expired_timers(first + Duration::from_millis(4)).unwrap(), 1);
+    assert_eq!(repl.next_timer_deadline().unwrap(), first + Duration::from_millis(10));
+
+    // Ticks missed altogether are skipped, keeping the original phase.
+    assert_eq!(repl.run_expired_timers(first + Duration::from_millis(35)).unwrap(), 1);
+    assert_eq!(repl.next_timer_deadline().unwrap(), first + Duration::from_millis(40));
+    assert_eq!(repl.eval("ticks").unwrap(), "2");
+}
+
+#[test]
+fn throwing_timers_leave_the_rest_queued() {
+    let mut repl = host_driven();
+    repl.eval("var ran = false; setTimeout(() => { throw new RangeError('boom'); }, 1); setTimeout(() => { ran = true; }, 2);")
+        .unwrap();
+    let later = Instant::now() + Duration::from_secs(1);
+    let err = repl.run_expired_timers(later).unwrap_err();
+    assert!(err.message().contains("boom"), "{}", err.message());
+    assert_eq!(repl.eval("ran").unwrap(), "false");
+    assert_eq!(repl.run_expired_timers(later).unwrap(), 1);
+    assert_eq!(repl.eval("ran").unwrap(), "true");
+}
+
+#[test]
+fn microtasks_and_async_host_settlements_run_on_request() {
+    let mut repl = host_driven();
+    repl.register_async_function("lookup", |_, resolver| {
+        resolver.resolve("found");
+        Ok(())
+    })
+    .unwrap();
+    repl.eval("var result = 'pending'; lookup().then(v => { result = v; });").unwrap();
+    assert_eq!(repl.eval("result").unwrap(), "\"pending\"");
+    assert!(repl.has_pending_work());
+
+    repl.run_microtasks().unwrap();
+    assert_eq!(repl.eval("result").unwrap(), "\"found\"");
+    assert!(!repl.has_pending_work());
+}
+
+#[test]
+fn evaluation_still_drains_timers_by_default() {
+    let mut repl = Repl::new();
+    repl.eval("var done = false; setTimeout(() => { done = true; }, 5);").unwrap();
+    assert_eq!(repl.eval("done").unwrap(), "true");
+    assert!(!repl.has_pending_work());
+}
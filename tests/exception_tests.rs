@@ -0,0 +1,122 @@
+use javascript::{JSError, JsOwnedValue, Permissions, Repl, evaluate_script};
+
+fn throw(script: &str) -> JSError {
+    evaluate_script(script, false, Some("some.js")).unwrap_err()
+}
+
+#[test]
+fn thrown_errors_keep_name_message_and_frames() {
+    let err = throw(
+        r#"
+        function doThirdThing() { throw new RangeError('boom'); }
+        doThirdThing();
+    "#,
+    );
+    let exception = err.exception().unwrap();
+    assert_eq!(exception.name, "RangeError");
+    assert_eq!(exception.message, "boom");
+    let frame = exception.stack.first().unwrap();
+    assert_eq!(
+        (frame.function.as_str(), frame.file.as_str(), frame.line, frame.column),
+        ("doThirdThing", "some.js", Some(2), Some(35))
+    );
+    assert_eq!(exception.stack.last().map(|frame| frame.function.as_str()), Some("<anonymous>"));
+    assert!(err.message().ends_with("RangeError: boom"), "{}", err.message());
+}
+
+#[test]
+fn cause_chains_and_aggregate_errors_are_kept() {
+    let err = throw(
+        r#"
+        const low = new Error('disk full');
+        const mid = new TypeError('write failed', { cause: low });
+        throw new AggregateError([mid, 'retry later'], 'save failed', { cause: mid });
+    "#,
+    );
+    let exception = err.exception().unwrap();
+    assert_eq!(exception.name, "AggregateError");
+    assert_eq!(exception.message, "save failed");
+
+    let cause = exception.cause.as_deref().unwrap();
+    assert_eq!((cause.name.as_str(), cause.message.as_str()), ("TypeError", "write failed"));
+    let root = cause.cause.as_deref().unwrap();
+    assert_eq!((root.name.as_str(), root.message.as_str()), ("Error", "disk full"));
+    assert!(root.cause.is_none());
+
+    let errors: Vec<_> = exception.errors.iter().map(|e| (e.name.as_str(), e.message.as_str())).collect();
+    assert_eq!(errors, [("TypeError", "write failed"), ("", "retry later")]);
+}
+
+#[test]
+fn non_error_values_are_copied_without_running_getters() {
+    let err = throw("throw { code: 42, detail: { path: '/tmp' } }");
+    let value = &err.exception().unwrap().value;
+    assert_eq!(value.get("code"), Some(&JsOwnedValue::Number(42.0)));
+    assert_eq!(
+        value.get("detail").and_then(|d| d.get("path")),
+        Some(&JsOwnedValue::String("/tmp".into()))
+    );
+
+    let err = throw("throw 'plain'");
+    let exception = err.exception().unwrap();
+    assert_eq!((exception.name.as_str(), exception.message.as_str()), ("", "plain"));
+    assert_eq!(exception.value, JsOwnedValue::String("plain".into()));
+
+    let mut repl = Repl::new();
+    let err = repl
+        .eval("var reads = 0; throw { get secret() { reads++; return 1; }, code: 7 }")
+        .unwrap_err();
+    assert_eq!(err.exception().unwrap().value.get("code"), Some(&JsOwnedValue::Number(7.0)));
+    assert_eq!(repl.eval("reads").unwrap(), "0");
+
+    // The name falls back to the constructor's, read as data only.
+    let err = repl
+        .eval("function Job() {} throw Object.create({ get constructor() { reads++; return Job; } })")
+        .unwrap_err();
+    assert_eq!(err.exception().unwrap().name, "Error");
+    assert_eq!(repl.eval("reads").unwrap(), "0");
+    let err = repl.eval("throw new Job()").unwrap_err();
+    assert_eq!(err.exception().unwrap().name, "Job");
+}
+
+#[test]
+fn names_set_on_the_error_win_over_its_class_and_kind() {
+    let err = throw(
+        r#"
+        class HttpError extends Error {
+            constructor(status) { super('bad status'); this.name = 'HttpError' + status; }
+        }
+        throw new HttpError(404);
+    "#,
+    );
+    assert_eq!(err.exception().unwrap().name, "HttpError404");
+
+    let err = throw("class AppError extends TypeError {} throw new AppError('nope')");
+    assert_eq!(err.exception().unwrap().name, "AppError");
+
+    let err = throw("const e = new Error('bad input'); e.name = 'ValidationError'; throw e");
+    assert_eq!(err.exception().unwrap().name, "ValidationError");
+
+    let mut repl = Repl::new();
+    repl.set_permissions(Permissions::none());
+    let err = repl.eval("os.getcwd()").unwrap_err();
+    let exception = err.exception().unwrap();
+    assert_eq!(exception.name, "PermissionDenied");
+    assert_eq!(exception.message, "Requires sys access to \"getcwd\"");
+}
+
+#[test]
+fn custom_error_fields_are_in_the_value() {
+    let err = throw("const e = new Error('denied'); e.status = 403; throw e");
+    let exception = err.exception().unwrap();
+    assert_eq!(exception.message, "denied");
+    assert_eq!(exception.value.get("status"), Some(&JsOwnedValue::Number(403.0)));
+    assert_eq!(exception.value.get("message"), None);
+}
+
+#[test]
+fn errors_without_a_thrown_value_have_no_exception() {
+    let err = throw("let = ;");
+    assert!(err.exception().is_none());
+    assert!(err.user_message().starts_with("SyntaxError"));
+}
@@ -0,0 +1,41 @@
+use javascript::evaluate_script;
+
+fn run(script: &str) -> String {
+    evaluate_script(script, false, None::<&std::path::Path>).unwrap()
+}
+
+fn type_if(enabled: bool, type_name: &str) -> &str {
+    if enabled { type_name } else { "undefined" }
+}
+
+#[test]
+fn optional_globals_follow_cargo_features() {
+    let expected = [
+        type_if(cfg!(feature = "intl"), "object"),
+        type_if(cfg!(feature = "temporal"), "object"),
+        type_if(cfg!(feature = "shared-memory"), "object"),
+        type_if(cfg!(feature = "shared-memory"), "function"),
+        type_if(cfg!(feature = "typed-arrays"), "function"),
+        type_if(cfg!(feature = "typed-arrays"), "function"),
+    ]
+    .join(",");
+    assert_eq!(
+        run("[typeof Intl, typeof Temporal, typeof Atomics, typeof SharedArrayBuffer, typeof Uint8Array, typeof DataView].join()"),
+        format!("\"{expected}\"")
+    );
+    assert_eq!(run("typeof ArrayBuffer"), "\"function\"");
+}
+
+#[test]
+fn unicode_regexps_work_in_every_build() {
+    assert_eq!(run("new RegExp('.', 'u').unicode"), "true");
+    assert_eq!(run("eval('/./v').unicodeSets"), "true");
+}
+
+#[test]
+fn locale_methods_work_without_intl() {
+    assert_eq!(run("'a'.localeCompare('b')"), "-1");
+    assert_eq!(run("'TITLE'.toLocaleLowerCase()"), "\"title\"");
+    assert_eq!(run("typeof (1234.5).toLocaleString()"), "\"string\"");
+    assert_eq!(run("typeof new Date(0).toLocaleDateString()"), "\"string\"");
+}
@@ -0,0 +1,191 @@
+use javascript::{EvalOptions, JSErrorKind, Repl, evaluate_script_with_options};
+
+const LIMIT: usize = 64 * 1024 * 1024;
+
+const RUNAWAY: &str = r#"
+    (() => {
+        try {
+            let a = [];
+            for (;;) a.push(new Array(1e6));
+        } catch (e) {
+            return 'caught';
+        }
+    })()
+"#;
+
+/// Grows only string contents and array elements, which the arena does not
+/// allocate itself.
+const RUNAWAY_STRINGS: &str = r#"
+    (() => {
+        try {
+            const chunks = [];
+            for (;;) chunks.push('x'.repeat(1 << 20) + chunks.length);
+        } catch (e) {
+            return 'caught';
+        }
+    })()
+"#;
+
+/// Allocates several times `LIMIT` in total while keeping almost nothing alive.
+const CHURN: &str = r#"
+    let total = 0;
+    for (let i = 0; i < 100000; i++) {
+        const garbage = { chunk: new Array(256).fill(i), tag: 'x' + i };
+        total += garbage.chunk.length;
+    }
+    total
+"#;
+
+#[test]
+fn runaway_allocation_hits_heap_limit() {
+    let options = EvalOptions::new().heap_limit(LIMIT);
+    let err = evaluate_script_with_options(RUNAWAY, false, None::<&std::path::Path>, &options).unwrap_err();
+    assert!(matches!(err.kind(), JSErrorKind::OutOfMemory { limit: LIMIT }));
+    assert!(err.message().starts_with("RangeError:"), "{}", err.message());
+}
+
+#[test]
+fn string_storage_counts_toward_heap_limit() {
+    let options = EvalOptions::new().heap_limit(LIMIT);
+    let err = evaluate_script_with_options(RUNAWAY_STRINGS, false, None::<&std::path::Path>, &options).unwrap_err();
+    assert!(matches!(err.kind(), JSErrorKind::OutOfMemory { limit: LIMIT }));
+}
+
+#[test]
+fn repl_recovers_after_out_of_memory() {
+    let mut repl = Repl::new();
+    repl.set_heap_limit(Some(LIMIT));
+    let err = repl.eval(RUNAWAY).unwrap_err();
+    assert!(matches!(err.kind(), JSErrorKind::OutOfMemory { .. }));
+
+    assert!(repl.heap_stats().allocated_bytes < LIMIT);
+    assert_eq!(repl.eval("[1, 2, 3].length").unwrap(), "3");
+}
+
+#[test]
+fn garbage_does_not_count_toward_heap_limit() {
+    let options = EvalOptions::new().heap_limit(LIMIT);
+    let out = evaluate_script_with_options(CHURN, false, None::<&std::path::Path>, &options).unwrap();
+    assert_eq!(out, "25600000");
+
+    let mut repl = Repl::new();
+    repl.set_heap_limit(Some(LIMIT));
+    assert_eq!(repl.eval(CHURN).unwrap(), "25600000");
+    assert!(repl.heap_stats().allocated_bytes < LIMIT);
+}
+
+#[test]
+fn heap_stats_reports_reachable_objects() {
+    let mut repl = Repl::new();
+    let before = repl.heap_stats();
+    assert!(before.allocated_bytes > 0);
+    assert_eq!(before.heap_limit, None);
+
+    repl.eval("var keep = { items: [[1], [2], [3]], lookup: new Map([[1, {}]]) };")
+        .unwrap();
+    let after = repl.heap_stats();
+    assert!(after.array_count >= before.array_count + 4);
+    assert!(after.object_count >= before.object_count + 2);
+    assert!(after.collection_count > before.collection_count);
+
+    repl.set_heap_limit(Some(LIMIT));
+    assert_eq!(repl.heap_stats().heap_limit, Some(LIMIT));
+}
+
+/// Creates several megabytes of garbage, so an evaluation that calls it under
+/// [`tight_limit`] pauses for full collections partway through.
+const CHURN_FN: &str = r#"
+    function churn() {
+        for (let i = 0; i < 20000; i++) ({ chunk: new Array(64).fill(i), tag: 'x' + i });
+    }
+"#;
+
+/// A limit a few megabytes above what `repl` holds right now.
+fn tight_limit(repl: &Repl) -> usize {
+    repl.heap_stats().allocated_bytes + 4 * 1024 * 1024
+}
+
+fn churning_repl() -> Repl {
+    let mut repl = Repl::new();
+    repl.eval(CHURN_FN).unwrap();
+    let limit = tight_limit(&repl);
+    repl.set_heap_limit(Some(limit));
+    repl
+}
+
+#[test]
+fn collection_mid_eval_keeps_suspended_generators() {
+    let mut repl = churning_repl();
+    let out = repl
+        .eval(
+            r#"
+            function* counter() {
+                const state = { n: 1 };
+                while (true) yield state.n++;
+            }
+            const it = counter();
+            it.next();
+            churn();
+            it.next().value
+            "#,
+        )
+        .unwrap();
+    assert_eq!(out, "2");
+}
+
+#[test]
+fn collection_mid_eval_keeps_pending_timers() {
+    let mut repl = churning_repl();
+    repl.eval(
+        r#"
+        var fired = [];
+        setTimeout(() => fired.push(payload.label), 0);
+        var payload = { label: 'timer' };
+        churn();
+        "#,
+    )
+    .unwrap();
+    assert_eq!(repl.eval("fired.join()").unwrap(), "\"timer\"");
+}
+
+#[test]
+fn collection_inside_direct_eval_keeps_the_caller_scope() {
+    let mut repl = churning_repl();
+    let out = repl
+        .eval("(function () { const local = { v: 'scope' }; return eval('churn(); local.v'); })()")
+        .unwrap();
+    assert_eq!(out, "\"scope\"");
+}
+
+#[test]
+fn collection_mid_eval_keeps_template_objects() {
+    let mut repl = churning_repl();
+    let out = repl
+        .eval(
+            r#"
+            function tag(strings) { return strings; }
+            function site() { return tag`a${1}b`; }
+            const first = site();
+            churn();
+            `${site() === first}:${first.raw.join('|')}`
+            "#,
+        )
+        .unwrap();
+    assert_eq!(out, "\"true:a|b\"");
+}
+
+#[test]
+fn collection_mid_eval_keeps_atomics_waiters() {
+    let mut repl = churning_repl();
+    repl.eval(
+        r#"
+        var status = 'pending';
+        const cell = new Int32Array(new SharedArrayBuffer(4));
+        Atomics.waitAsync(cell, 0, 0, 10000).value.then(v => { status = v; });
+        churn();
+        Atomics.notify(cell, 0);
+        "#,
+    )
+    .unwrap();
+    assert_eq!(repl.eval("status").unwrap(), "\"ok\"");
+}
@@ -0,0 +1,142 @@
+use javascript::{JSErrorKind, JsOwnedValue, Repl, raise_range_error};
+use std::cell::Cell;
+use std::rc::Rc;
+
+struct Account {
+    owner: String,
+    balance: f64,
+    _drops: Option<DropCounter>,
+}
+
+struct DropCounter(Rc<Cell<usize>>);
+
+impl Drop for DropCounter {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+fn define_account(repl: &mut Repl, drops: Option<Rc<Cell<usize>>>) {
+    repl.define_class::<Account>("Account")
+        .constructor(move |args| {
+            let owner = args.first().and_then(JsOwnedValue::as_str).unwrap_or("nobody").to_string();
+            Ok(Account {
+                owner,
+                balance: 0.0,
+                _drops: drops.clone().map(DropCounter),
+            })
+        })
+        .method("deposit", |account, args| {
+            let amount = args.first().and_then(JsOwnedValue::as_f64).unwrap_or(0.0);
+            if amount <= 0.0 {
+                return Err(raise_range_error!("deposit must be positive"));
+            }
+            account.balance += amount;
+            Ok(account.balance.into())
+        })
+        .getter("balance", |account| Ok(account.balance.into()))
+        .getter("owner", |account| Ok(account.owner.as_str().into()))
+        .setter("owner", |account, value| {
+            account.owner = value.as_str().unwrap_or_default().to_string();
+            Ok(())
+        })
+        .static_method("open", |args| Ok(format!("opened {}", args.len()).into()))
+        .static_value("currency", "EUR")
+        .build()
+        .unwrap();
+}
+
+#[test]
+fn instances_carry_rust_payloads() {
+    let mut repl = Repl::new();
+    define_account(&mut repl, None);
+    let out = repl
+        .eval("const a = new Account('ada'); a.deposit(5); a.deposit(2.5); a.owner = 'grace'; `${a.owner}:${a.balance}`")
+        .unwrap();
+    assert_eq!(out, "\"grace:7.5\"");
+    // Each instance has its own payload.
+    assert_eq!(repl.eval("new Account().balance + a.balance").unwrap(), "7.5");
+}
+
+#[test]
+fn classes_support_instanceof_tags_and_statics() {
+    let mut repl = Repl::new();
+    define_account(&mut repl, None);
+    assert_eq!(repl.eval("new Account() instanceof Account").unwrap(), "true");
+    assert_eq!(
+        repl.eval("Object.prototype.toString.call(new Account())").unwrap(),
+        "\"[object Account]\""
+    );
+    assert_eq!(repl.eval("Account.open(1, 2)").unwrap(), "\"opened 2\"");
+    assert_eq!(repl.eval("Account.currency").unwrap(), "\"EUR\"");
+    assert_eq!(repl.eval("Account.name").unwrap(), "\"Account\"");
+}
+
+#[test]
+fn classes_can_be_extended_from_scripts() {
+    let mut repl = Repl::new();
+    define_account(&mut repl, None);
+    let out = repl
+        .eval(
+            r#"
+            class Savings extends Account {
+                constructor(owner, rate) { super(owner); this.rate = rate; }
+                deposit(amount) { return super.deposit(amount * (1 + this.rate)); }
+            }
+            const s = new Savings('lin', 0.5);
+            s.deposit(10);
+            [s instanceof Savings, s instanceof Account, s.balance, s.owner].join()
+            "#,
+        )
+        .unwrap();
+    assert_eq!(out, "\"true,true,15,lin\"");
+}
+
+#[test]
+fn errors_are_thrown_into_scripts() {
+    let mut repl = Repl::new();
+    define_account(&mut repl, None);
+    let out = repl
+        .eval("try { new Account().deposit(-1); } catch (e) { `${e instanceof RangeError}:${e.message}` }")
+        .unwrap();
+    assert_eq!(out, "\"true:deposit must be positive\"");
+
+    let out = repl
+        .eval("try { Account.prototype.deposit.call({}, 1); } catch (e) { `${e.name}:${e.message}` }")
+        .unwrap();
+    assert_eq!(out, "\"TypeError:Account.prototype.deposit called on incompatible receiver\"");
+
+    let err = repl.eval("Account()").unwrap_err();
+    assert!(err.message().contains("TypeError"), "{}", err.message());
+}
+
+#[test]
+fn classes_without_a_constructor_cannot_be_instantiated() {
+    struct Token;
+    let mut repl = Repl::new();
+    repl.define_class::<Token>("Token")
+        .static_method("kind", |_| Ok("token".into()))
+        .build()
+        .unwrap();
+    assert_eq!(repl.eval("Token.kind()").unwrap(), "\"token\"");
+    let err = repl.eval("new Token()").unwrap_err();
+    assert!(err.message().contains("Illegal constructor"), "{}", err.message());
+
+    for name in ["not a name", "class", "function", "let", "eval"] {
+        let err = repl.define_class::<Token>(name).build().unwrap_err();
+        assert!(matches!(err.kind(), JSErrorKind::TypeError { .. }), "{name}: {err:?}");
+        assert!(err.message().contains("Invalid host class name"), "{}", err.message());
+    }
+}
+
+#[test]
+fn payloads_are_dropped_when_instances_are_collected() {
+    let drops = Rc::new(Cell::new(0));
+    let mut repl = Repl::new();
+    define_account(&mut repl, Some(drops.clone()));
+    repl.eval("var kept = new Account('kept'); (function () { for (let i = 0; i < 10; i++) new Account(); })(); 0")
+        .unwrap();
+    repl.collect_garbage();
+    assert!(drops.get() > 0, "no payload was dropped");
+    assert_eq!(repl.eval("kept.owner").unwrap(), "\"kept\"");
+}
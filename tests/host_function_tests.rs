@@ -0,0 +1,93 @@
+use javascript::{JsOwnedValue, Repl};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[test]
+fn registered_function_receives_converted_arguments() {
+    let mut repl = Repl::new();
+    repl.register_function_with_length("add", 2, |args| {
+        let a = args.first().and_then(JsOwnedValue::as_f64).unwrap_or(0.0);
+        let b = args.get(1).and_then(JsOwnedValue::as_f64).unwrap_or(0.0);
+        Ok(JsOwnedValue::Number(a + b))
+    })
+    .unwrap();
+    assert_eq!(repl.eval("add(2, 3)").unwrap(), "5");
+    assert_eq!(repl.eval("typeof add").unwrap(), "\"function\"");
+    assert_eq!(repl.eval("add.name").unwrap(), "\"add\"");
+    assert_eq!(repl.eval("add.length").unwrap(), "2");
+}
+
+#[test]
+fn registered_functions_have_length_zero_unless_declared() {
+    let mut repl = Repl::new();
+    repl.register_function("sum", |args| {
+        Ok(JsOwnedValue::Number(args.iter().filter_map(JsOwnedValue::as_f64).sum()))
+    })
+    .unwrap();
+    repl.register_async_function("later", |_, resolver| {
+        resolver.resolve(JsOwnedValue::Undefined);
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(repl.eval("sum(1, 2, 3)").unwrap(), "6");
+    assert_eq!(repl.eval("sum.length").unwrap(), "0");
+    assert_eq!(repl.eval("later.length").unwrap(), "0");
+}
+
+#[test]
+fn registered_function_can_return_structured_values() {
+    let mut repl = Repl::new();
+    repl.register_function("makeUser", |args| {
+        let name = args.first().and_then(JsOwnedValue::as_str).unwrap_or("anon").to_string();
+        let mut map = indexmap::IndexMap::new();
+        map.insert("name".to_string(), JsOwnedValue::String(name));
+        map.insert("roles".to_string(), vec!["admin", "dev"].into());
+        Ok(map.into())
+    })
+    .unwrap();
+    assert_eq!(
+        repl.eval("const u = makeUser('ada'); u.name + ':' + u.roles.join('|')").unwrap(),
+        "\"ada:admin|dev\""
+    );
+    assert_eq!(repl.eval("Object.getPrototypeOf(u) === Object.prototype").unwrap(), "true");
+}
+
+#[test]
+fn namespace_paths_create_nested_objects() {
+    let mut repl = Repl::new();
+    let calls = Rc::new(RefCell::new(Vec::new()));
+    let seen = calls.clone();
+    repl.register_function_with_length("app.db.query", 1, move |args| {
+        seen.borrow_mut().push(args.to_vec());
+        Ok(JsOwnedValue::Array(vec![]))
+    })
+    .unwrap();
+    repl.register_function("app.version", |_| Ok("1.0".into())).unwrap();
+
+    assert_eq!(repl.eval("app.db.query('select 1').length").unwrap(), "0");
+    assert_eq!(repl.eval("app.version()").unwrap(), "\"1.0\"");
+    assert_eq!(repl.eval("app.db.query.name").unwrap(), "\"query\"");
+    assert_eq!(calls.borrow().as_slice(), &[vec![JsOwnedValue::String("select 1".into())]]);
+}
+
+#[test]
+fn namespace_path_through_non_object_is_rejected() {
+    let mut repl = Repl::new();
+    repl.eval("var cfg = 42;").unwrap();
+    assert!(repl.register_function("cfg.load", |_| Ok(JsOwnedValue::Undefined)).is_err());
+    assert!(repl.register_function("bad..path", |_| Ok(JsOwnedValue::Undefined)).is_err());
+}
+
+#[test]
+fn registered_function_errors_are_catchable() {
+    let mut repl = Repl::new();
+    repl.register_function("fail", |_| Err(javascript::raise_type_error!("nope")))
+        .unwrap();
+    let out = repl
+        .eval("try { fail(); 'no' } catch (e) { (e instanceof TypeError) + ':' + e.message }")
+        .unwrap();
+    assert_eq!(out, "\"true:nope\"");
+
+    let err = repl.eval("fail()").unwrap_err();
+    assert!(err.message().contains("nope"), "{}", err.message());
+}
@@ -0,0 +1,135 @@
+use javascript::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Serves modules from memory under their specifiers and records each load.
+#[derive(Clone, Default)]
+struct MemoryLoader {
+    modules: Rc<HashMap<String, String>>,
+    loads: Rc<RefCell<Vec<String>>>,
+}
+
+impl MemoryLoader {
+    fn new(modules: &[(&str, &str)]) -> Self {
+        let modules = modules.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        MemoryLoader {
+            modules: Rc::new(modules),
+            loads: Rc::default(),
+        }
+    }
+}
+
+impl ModuleLoader for MemoryLoader {
+    fn resolve(&self, specifier: &str, _referrer: &str) -> Result<String, JSError> {
+        Ok(specifier.trim_start_matches("./").to_string())
+    }
+
+    fn load(&self, key: &str) -> Result<ModuleSource, JSError> {
+        self.loads.borrow_mut().push(key.to_string());
+        self.modules
+            .get(key)
+            .map(|source| ModuleSource::JavaScript(source.clone()))
+            .ok_or_else(|| raise_eval_error!(format!("No module '{key}'")))
+    }
+}
+
+fn repl_with(loader: &MemoryLoader) -> Repl {
+    let mut repl = Repl::new();
+    repl.set_module_loader(loader.clone());
+    repl
+}
+
+#[test]
+fn exports_can_be_listed_read_and_called() {
+    let loader = MemoryLoader::new(&[
+        ("consts.js", "export const two = 2;"),
+        (
+            "math.js",
+            "import { two } from './consts.js'; export const name = 'math'; export default function double(x) { return x * two; } export class Point {}",
+        ),
+    ]);
+    let mut repl = repl_with(&loader);
+    let math = repl.import_module("math.js").unwrap();
+
+    assert_eq!(math.key(), "math.js");
+    assert_eq!(math.export_names(), ["Point", "default", "name"]);
+    assert_eq!(repl.get_export(&math, "name").unwrap(), JsOwnedValue::String("math".into()));
+    assert_eq!(
+        repl.call_export(&math, "default", &[21.into()]).unwrap(),
+        JsOwnedValue::Number(42.0)
+    );
+    assert!(repl.get_export(&math, "missing").unwrap().is_undefined());
+    assert_eq!(*loader.loads.borrow(), ["math.js", "consts.js"]);
+}
+
+#[test]
+fn repeated_imports_share_one_instance() {
+    let loader = MemoryLoader::new(&[(
+        "counter.js",
+        "globalThis.evaluations = (globalThis.evaluations ?? 0) + 1; export let count = 0; export function bump() { return ++count; }",
+    )]);
+    let mut repl = repl_with(&loader);
+    let first = repl.import_module("counter.js").unwrap();
+    let second = repl.import_module("./counter.js").unwrap();
+
+    assert_eq!(repl.call_export(&first, "bump", &[]).unwrap(), JsOwnedValue::Number(1.0));
+    assert_eq!(repl.get_export(&second, "count").unwrap(), JsOwnedValue::Number(1.0));
+    assert_eq!(repl.eval("evaluations").unwrap(), "1");
+    assert_eq!(*loader.loads.borrow(), ["counter.js"]);
+
+    repl.eval("import('counter.js').then(ns => { globalThis.same = ns.count; })")
+        .unwrap();
+    assert_eq!(repl.eval("same").unwrap(), "1");
+}
+
+#[test]
+fn top_level_await_finishes_before_import_returns() {
+    let loader = MemoryLoader::new(&[
+        (
+            "config.js",
+            "export const port = await new Promise(resolve => setTimeout(() => resolve(8080), 5));",
+        ),
+        (
+            "app.js",
+            "import { port } from './config.js'; export const url = `http://localhost:${port}`;",
+        ),
+    ]);
+    let mut repl = repl_with(&loader);
+    let app = repl.import_module("app.js").unwrap();
+    assert_eq!(
+        repl.get_export(&app, "url").unwrap(),
+        JsOwnedValue::String("http://localhost:8080".into())
+    );
+}
+
+#[test]
+fn failed_modules_report_their_error_on_every_import() {
+    let loader = MemoryLoader::new(&[("broken.js", "export const ok = 1; throw new RangeError('bad config');")]);
+    let mut repl = repl_with(&loader);
+    for _ in 0..2 {
+        let err = repl.import_module("broken.js").unwrap_err();
+        let exception = err.exception().unwrap();
+        assert_eq!((exception.name.as_str(), exception.message.as_str()), ("RangeError", "bad config"));
+    }
+
+    let err = repl.import_module("missing.js").unwrap_err();
+    assert!(err.message().contains("No module 'missing.js'"), "{}", err.message());
+}
+
+#[test]
+fn default_loader_imports_files_by_path() {
+    let dir = std::env::temp_dir().join(format!("import_module_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("greet.mjs"), "export const greet = name => `hello, ${name}`;").unwrap();
+
+    let mut repl = Repl::new();
+    let greet = repl.import_module(dir.join("greet.mjs").to_str().unwrap()).unwrap();
+    let again = repl.import_module(dir.join(".").join("greet.mjs").to_str().unwrap()).unwrap();
+    assert_eq!(greet.key(), again.key());
+    assert_eq!(
+        repl.call_export(&again, "greet", &["world".into()]).unwrap(),
+        JsOwnedValue::String("hello, world".into())
+    );
+    std::fs::remove_dir_all(&dir).ok();
+}
@@ -0,0 +1,93 @@
+use javascript::{CapturedConsole, InspectOptions, Repl};
+
+fn inspect(script: &str) -> String {
+    let mut repl = Repl::new();
+    repl.eval_inspect(script).unwrap()
+}
+
+#[test]
+fn nested_objects_and_arrays() {
+    assert_eq!(inspect("({ a: [1, { b: 2 }], s: 'hi' })"), "{ a: [ 1, { b: 2 } ], s: 'hi' }");
+    assert_eq!(inspect("[]"), "[]");
+    assert_eq!(inspect("({})"), "{}");
+    assert_eq!(inspect("'it\\'s'"), "\"it's\"");
+    assert_eq!(inspect("-0"), "-0");
+    assert_eq!(inspect("10n"), "10n");
+    assert_eq!(inspect("({ 'a-b': undefined, c: null })"), "{ 'a-b': undefined, c: null }");
+}
+
+#[test]
+fn collections_and_typed_arrays() {
+    assert_eq!(
+        inspect("new Map([['a', 1], ['b', { c: 2 }]])"),
+        "Map(2) { 'a' => 1, 'b' => { c: 2 } }"
+    );
+    assert_eq!(inspect("new Set([1, 'two'])"), "Set(2) { 1, 'two' }");
+    #[cfg(feature = "typed-arrays")]
+    assert_eq!(inspect("new Uint8Array([1, 2, 3])"), "Uint8Array(3) [ 1, 2, 3 ]");
+    assert_eq!(inspect("new Date(0)"), "1970-01-01T00:00:00.000Z");
+    assert_eq!(inspect("/ab+c/gi"), "/ab+c/gi");
+}
+
+#[test]
+fn class_instances_functions_and_accessors() {
+    assert_eq!(
+        inspect("class Point { constructor() { this.x = 1; this.y = 2; } }; new Point()"),
+        "Point { x: 1, y: 2 }"
+    );
+    assert_eq!(inspect("[function foo() {}, class A {}]"), "[ [Function: foo], [class A] ]");
+    assert_eq!(
+        inspect("({ get g() { return 1; }, set s(v) {}, get gs() { return 1; }, set gs(v) {} })"),
+        "{ g: [Getter], s: [Setter], gs: [Getter/Setter] }"
+    );
+    assert_eq!(inspect("Object.create(null)"), "[Object: null prototype] {}");
+}
+
+#[test]
+fn symbols_are_shown_as_keys_and_values() {
+    assert_eq!(
+        inspect("({ [Symbol('k')]: 1, s: Symbol('v') })"),
+        "{ s: Symbol(v), [Symbol(k)]: 1 }"
+    );
+}
+
+#[test]
+fn circular_references_and_depth_limit() {
+    assert_eq!(
+        inspect("const o = { name: 'o' }; o.self = o; o"),
+        "<ref *1> { name: 'o', self: [Circular *1] }"
+    );
+    assert_eq!(inspect("({ a: { b: { c: { d: 1 } } } })"), "{ a: { b: { c: [Object] } } }");
+
+    let mut repl = Repl::new();
+    repl.set_inspect_options(InspectOptions::new().depth(None));
+    assert_eq!(
+        repl.eval_inspect("({ a: { b: { c: { d: [] } } } })").unwrap(),
+        "{ a: { b: { c: { d: [] } } } }"
+    );
+}
+
+#[test]
+fn long_objects_break_across_lines() {
+    let out = inspect("({ alpha: 'a'.repeat(30), beta: 'b'.repeat(30) })");
+    let expected = format!("{{\n  alpha: '{}',\n  beta: '{}'\n}}", "a".repeat(30), "b".repeat(30));
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn colors_wrap_values_in_ansi_escapes() {
+    let mut repl = Repl::new();
+    repl.set_inspect_options(InspectOptions::new().colors(true));
+    assert_eq!(repl.eval_inspect("1").unwrap(), "\x1b[33m1\x1b[39m");
+    assert_eq!(repl.eval_inspect("({ s: 'x' })").unwrap(), "{ s: \x1b[32m'x'\x1b[39m }");
+}
+
+#[test]
+fn console_log_inspects_non_string_arguments() {
+    let console = CapturedConsole::new();
+    let mut repl = Repl::new();
+    repl.set_console_sink(console.clone());
+    repl.eval("console.log({ a: [1, { b: 2 }] }, 'plain', [1, 'x']); console.dir({ n: null })")
+        .unwrap();
+    assert_eq!(console.messages(), vec!["{ a: [ 1, { b: 2 } ] } plain [ 1, 'x' ]", "{ n: null }"]);
+}
@@ -0,0 +1,55 @@
+use javascript::{EvalOptions, JSErrorKind, Repl, evaluate_script, evaluate_script_with_options};
+
+fn run_with_budget(script: &str, budget: u64) -> Result<String, javascript::JSError> {
+    let options = EvalOptions::new().instruction_budget(budget);
+    evaluate_script_with_options(script, false, None::<&std::path::Path>, &options)
+}
+
+#[test]
+fn infinite_loop_is_aborted() {
+    let err = run_with_budget("while (true) {}", 100_000).unwrap_err();
+    assert!(matches!(err.kind(), JSErrorKind::InfiniteLoopError { iterations: 100_000 }));
+}
+
+#[test]
+fn budget_cannot_be_caught_by_script() {
+    let script = r#"
+        let caught = false;
+        try { for (;;) {} } catch (e) { caught = true; } finally { while (true) {} }
+        caught
+    "#;
+    let err = run_with_budget(script, 50_000).unwrap_err();
+    assert!(matches!(err.kind(), JSErrorKind::InfiniteLoopError { .. }));
+}
+
+#[test]
+fn small_scripts_fit_in_budget() {
+    let out = run_with_budget("let s = 0; for (let i = 0; i < 100; i++) s += i; s", 100_000).unwrap();
+    assert_eq!(out, "4950");
+    // No budget: unchanged behaviour.
+    assert_eq!(evaluate_script("1 + 1", false, None::<&std::path::Path>).unwrap(), "2");
+}
+
+#[test]
+fn timers_and_microtasks_share_the_budget() {
+    let err = run_with_budget("setTimeout(() => { for (;;) {} }, 0); 1", 100_000).unwrap_err();
+    assert!(matches!(err.kind(), JSErrorKind::InfiniteLoopError { .. }));
+
+    let err = run_with_budget("Promise.resolve().then(() => { while (true) {} }); 1", 100_000).unwrap_err();
+    assert!(matches!(err.kind(), JSErrorKind::InfiniteLoopError { .. }));
+}
+
+#[test]
+fn repl_budget_applies_per_eval_and_repl_recovers() {
+    let mut repl = Repl::new();
+    repl.set_instruction_budget(Some(100_000));
+    repl.eval("let counter = 0;").unwrap();
+    let err = repl.eval("while (true) { counter++; }").unwrap_err();
+    assert!(matches!(err.kind(), JSErrorKind::InfiniteLoopError { .. }));
+
+    assert_eq!(repl.eval("typeof counter").unwrap(), "\"number\"");
+    assert_eq!(repl.eval("[1, 2, 3].map(x => x * 2).join(',')").unwrap(), "\"2,4,6\"");
+
+    repl.set_instruction_budget(None);
+    assert_eq!(repl.eval("let n = 0; for (let i = 0; i < 200000; i++) n++; n").unwrap(), "200000");
+}
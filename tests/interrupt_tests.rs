@@ -0,0 +1,78 @@
+use javascript::{EvalOptions, InterruptHandle, JSErrorKind, Repl, evaluate_script_with_options};
+use std::thread;
+use std::time::Duration;
+
+fn interrupt_after(handle: &InterruptHandle, delay_ms: u64) -> thread::JoinHandle<()> {
+    let handle = handle.clone();
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(delay_ms));
+        handle.interrupt();
+    })
+}
+
+#[test]
+fn interrupt_stops_running_script() {
+    let handle = InterruptHandle::new();
+    let options = EvalOptions::new().interrupt_handle(handle.clone());
+    let waker = interrupt_after(&handle, 50);
+    let err = evaluate_script_with_options("while (true) {}", false, None::<&std::path::Path>, &options).unwrap_err();
+    waker.join().unwrap();
+    assert!(matches!(err.kind(), JSErrorKind::Interrupted), "{}", err.message());
+}
+
+#[test]
+fn interrupt_is_not_catchable() {
+    let handle = InterruptHandle::new();
+    let options = EvalOptions::new().interrupt_handle(handle.clone());
+    let waker = interrupt_after(&handle, 50);
+    let script = "for (;;) { try { for (;;) {} } catch (e) {} finally { } }";
+    let err = evaluate_script_with_options(script, false, None::<&std::path::Path>, &options).unwrap_err();
+    waker.join().unwrap();
+    assert!(matches!(err.kind(), JSErrorKind::Interrupted));
+}
+
+#[test]
+fn interrupt_reaches_timer_callbacks() {
+    let handle = InterruptHandle::new();
+    let options = EvalOptions::new().interrupt_handle(handle.clone());
+    let waker = interrupt_after(&handle, 50);
+    let script = "setTimeout(() => { while (true) {} }, 0); 'scheduled'";
+    let err = evaluate_script_with_options(script, false, None::<&std::path::Path>, &options).unwrap_err();
+    waker.join().unwrap();
+    assert!(matches!(err.kind(), JSErrorKind::Interrupted));
+}
+
+#[test]
+fn repl_stays_usable_after_interrupt() {
+    let mut repl = Repl::new();
+    repl.eval("let total = 40;").unwrap();
+    let handle = repl.interrupt_handle();
+    let waker = interrupt_after(&handle, 50);
+    let err = repl.eval("while (true) {}").unwrap_err();
+    waker.join().unwrap();
+    assert!(matches!(err.kind(), JSErrorKind::Interrupted));
+
+    // The request was used up by the evaluation it stopped.
+    assert_eq!(repl.eval("total + 2").unwrap(), "42");
+}
+
+#[test]
+fn interrupts_do_not_outlive_the_run_they_were_meant_for() {
+    let handle = InterruptHandle::new();
+    handle.interrupt();
+    let options = EvalOptions::new().interrupt_handle(handle.clone());
+    let out = evaluate_script_with_options("1 + 1", false, None::<&std::path::Path>, &options).unwrap();
+    assert_eq!(out, "2");
+
+    let mut repl = Repl::new();
+    repl.eval("let total = 40;").unwrap();
+    let handle = repl.interrupt_handle();
+    handle.interrupt();
+    assert_eq!(repl.eval("total + 2").unwrap(), "42");
+
+    let waker = interrupt_after(&handle, 50);
+    let err = repl.eval("while (true) {}").unwrap_err();
+    waker.join().unwrap();
+    assert!(matches!(err.kind(), JSErrorKind::Interrupted));
+    assert_eq!(err.message(), "Error: Script execution interrupted");
+}
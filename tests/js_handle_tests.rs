@@ -0,0 +1,71 @@
+use javascript::{JsOwnedValue, Repl};
+
+#[test]
+fn handles_read_write_and_call_methods() {
+    let mut repl = Repl::new();
+    let counter = repl.eval_handle("({ n: 1, inc(by) { this.n += by; return this.n; } })").unwrap();
+    assert!(!counter.is_callable());
+
+    assert_eq!(repl.get(&counter, "n").unwrap(), JsOwnedValue::Number(1.0));
+    repl.set(&counter, "n", 10).unwrap();
+    assert_eq!(repl.call_method(&counter, "inc", &[5.into()]).unwrap(), JsOwnedValue::Number(15.0));
+    assert_eq!(repl.snapshot(&counter).unwrap().get("n"), Some(&JsOwnedValue::Number(15.0)));
+
+    let err = repl.call_method(&counter, "missing", &[]).unwrap_err();
+    assert!(err.message().contains("is not a function"), "{}", err.message());
+}
+
+#[test]
+fn handles_outlive_the_bindings_that_created_them() {
+    let mut repl = Repl::new();
+    repl.eval("var registry = { items: ['a'] };").unwrap();
+    let registry = repl.eval_handle("registry").unwrap();
+    let items = repl.get_handle(&registry, "items").unwrap();
+    repl.eval("delete globalThis.registry; for (let i = 0; i < 1000; i++) ({ junk: [i] });")
+        .unwrap();
+
+    repl.call_method(&items, "push", &["b".into()]).unwrap();
+    assert_eq!(repl.get(&registry, "items").unwrap(), JsOwnedValue::from(vec!["a", "b"]));
+}
+
+#[test]
+fn handles_are_call_targets_and_arguments() {
+    let mut repl = Repl::new();
+    let add = repl.eval_handle("(a, b) => a + b").unwrap();
+    assert!(add.is_callable());
+    assert_eq!(repl.call(&add, (), &[2.into(), 3.into()]).unwrap(), JsOwnedValue::Number(5.0));
+
+    let point = repl.eval_handle("({ x: 1 })").unwrap();
+    repl.eval("function bump(p) { p.x += 1; return p; }").unwrap();
+    let same = repl.call("bump", (), &[point.clone().into()]).unwrap();
+    assert_eq!(same.get("x"), Some(&JsOwnedValue::Number(2.0)));
+    assert_eq!(repl.get(&point, "x").unwrap(), JsOwnedValue::Number(2.0));
+    assert_eq!(JsOwnedValue::from(&point).type_of(), "object");
+}
+
+#[test]
+fn rejects_primitives_frozen_objects_and_foreign_handles() {
+    let mut repl = Repl::new();
+    let err = repl.eval_handle("42").unwrap_err();
+    assert!(err.message().contains("TypeError"), "{}", err.message());
+
+    let frozen = repl.eval_handle("Object.freeze({ a: 1 })").unwrap();
+    assert!(repl.set(&frozen, "a", 2).is_err());
+    assert_eq!(repl.get(&frozen, "a").unwrap(), JsOwnedValue::Number(1.0));
+
+    let mut other = Repl::new();
+    assert!(other.get(&frozen, "a").is_err());
+    assert!(other.call(&frozen, (), &[]).is_err());
+}
+
+#[test]
+fn dropped_handles_release_their_objects() {
+    let mut repl = Repl::new();
+    let handle = repl.eval_handle("({ payload: new Array(1000).fill(0) })").unwrap();
+    let copy = handle.clone();
+    assert_eq!(handle, copy);
+    drop(handle);
+    assert_eq!(repl.get(&copy, "payload").unwrap().as_array().map(|a| a.len()), Some(1000));
+    drop(copy);
+    assert_eq!(repl.eval("1 + 1").unwrap(), "2");
+}
@@ -0,0 +1,125 @@
+use javascript::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Serves modules from memory; keys are `/`-joined paths relative to the referrer.
+#[derive(Clone, Default)]
+struct MemoryLoader {
+    modules: Rc<HashMap<String, ModuleSource>>,
+    loads: Rc<RefCell<Vec<String>>>,
+}
+
+impl MemoryLoader {
+    fn new(modules: &[(&str, ModuleSource)]) -> Self {
+        let modules = modules.iter().map(|(k, v)| (k.to_string(), v.clone())).collect();
+        MemoryLoader {
+            modules: Rc::new(modules),
+            loads: Rc::default(),
+        }
+    }
+}
+
+impl ModuleLoader for MemoryLoader {
+    fn resolve(&self, specifier: &str, referrer: &str) -> Result<String, JSError> {
+        let Some(rest) = specifier.strip_prefix("./") else {
+            return Err(raise_type_error!(format!("Cannot resolve '{specifier}' from '{referrer}'")));
+        };
+        let dir = referrer.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+        Ok(if dir.is_empty() {
+            rest.to_string()
+        } else {
+            format!("{dir}/{rest}")
+        })
+    }
+
+    fn load(&self, key: &str) -> Result<ModuleSource, JSError> {
+        self.loads.borrow_mut().push(key.to_string());
+        self.modules
+            .get(key)
+            .cloned()
+            .ok_or_else(|| raise_eval_error!(format!("No module '{key}'")))
+    }
+}
+
+fn js(source: &str) -> ModuleSource {
+    ModuleSource::JavaScript(source.to_string())
+}
+
+fn run(loader: &MemoryLoader, script: &str) -> Result<String, JSError> {
+    let options = EvalOptions::new().module_loader(loader.clone());
+    evaluate_script_with_options(script, true, Some("main.js"), &options)
+}
+
+#[test]
+fn static_imports_resolve_relative_to_the_referrer() {
+    let loader = MemoryLoader::new(&[
+        (
+            "lib/math.js",
+            js("import { two } from './consts.js'; export const double = x => x * two;"),
+        ),
+        ("lib/consts.js", js("export const two = 2;")),
+    ]);
+    assert_eq!(run(&loader, "import { double } from './lib/math.js'; double(21)").unwrap(), "42");
+    assert_eq!(*loader.loads.borrow(), vec!["lib/math.js", "lib/consts.js"]);
+}
+
+#[test]
+fn each_module_is_loaded_once() {
+    let loader = MemoryLoader::new(&[
+        ("a.js", js("import { n } from './shared.js'; export const a = n;")),
+        ("b.js", js("import { n } from './shared.js'; export const b = n;")),
+        ("shared.js", js("globalThis.hits = (globalThis.hits || 0) + 1; export const n = 1;")),
+    ]);
+    let out = run(
+        &loader,
+        "import { a } from './a.js'; import { b } from './b.js'; `${a + b}:${globalThis.hits}`",
+    )
+    .unwrap();
+    assert_eq!(out, "\"2:1\"");
+    assert_eq!(loader.loads.borrow().iter().filter(|k| *k == "shared.js").count(), 1);
+}
+
+#[test]
+fn dynamic_import_uses_the_loader() {
+    let loader = MemoryLoader::new(&[("dyn.js", js("export default 'loaded';"))]);
+    assert_eq!(
+        run(&loader, "const ns = await import('./dyn.js'); ns.default").unwrap(),
+        "\"loaded\""
+    );
+}
+
+#[test]
+fn json_sources_become_default_exports() {
+    let loader = MemoryLoader::new(&[("data.json", ModuleSource::Json("{\"n\": 7}".to_string()))]);
+    assert_eq!(
+        run(&loader, "import data from './data.json' with { type: 'json' }; data.n").unwrap(),
+        "7"
+    );
+}
+
+#[test]
+fn deferred_imports_use_the_loader() {
+    let loader = MemoryLoader::new(&[("lazy.js", js("export const value = 'lazy';"))]);
+    assert_eq!(run(&loader, "import defer * as ns from './lazy.js'; ns.value").unwrap(), "\"lazy\"");
+}
+
+#[test]
+fn resolution_failures_reject_dynamic_imports() {
+    let loader = MemoryLoader::new(&[]);
+    let out = run(&loader, "let msg; try { await import('bare'); } catch (e) { msg = e.message; } msg").unwrap();
+    assert_eq!(out, "\"Cannot resolve 'bare' from 'main.js'\"");
+
+    let err = run(&loader, "import './missing.js'; 1").unwrap_err();
+    assert!(err.message().contains("No module 'missing.js'"), "{}", err.message());
+}
+
+#[test]
+fn repl_dynamic_import_uses_the_loader() {
+    let loader = MemoryLoader::new(&[("answer.js", js("export default 42;"))]);
+    let mut repl = Repl::new();
+    repl.set_module_loader(loader);
+    repl.eval("var answer; import('./answer.js').then(ns => { answer = ns.default; })")
+        .unwrap();
+    assert_eq!(repl.eval("answer").unwrap(), "42");
+}
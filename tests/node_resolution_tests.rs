@@ -0,0 +1,201 @@
+use javascript::*;
+use std::path::PathBuf;
+
+/// A scratch directory tree that is removed when dropped.
+struct Project(PathBuf);
+
+impl Project {
+    fn new(name: &str) -> Self {
+        let root = std::env::temp_dir().join(format!("node_resolution_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        Project(root)
+    }
+
+    fn file(self, path: &str, contents: &str) -> Self {
+        let path = self.0.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+        self
+    }
+
+    fn run_with(&self, entry: &str, options: &EvalOptions) -> Result<String, JSError> {
+        let path = self.0.join(entry);
+        let source = std::fs::read_to_string(&path).unwrap();
+        evaluate_script_with_options(&source, true, Some(&path), options)
+    }
+
+    fn run(&self, entry: &str) -> Result<String, JSError> {
+        self.run_with(entry, &EvalOptions::new())
+    }
+}
+
+impl Drop for Project {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn bare_specifiers_walk_up_node_modules() {
+    let project = Project::new("walk")
+        .file("node_modules/answer/package.json", r#"{ "name": "answer", "main": "lib/answer" }"#)
+        .file("node_modules/answer/lib/answer.js", "export default 42;")
+        .file("node_modules/plain/index.js", "export const plain = 'index';")
+        .file("node_modules/plain/extra.js", "export const extra = 'extra';")
+        .file(
+            "src/app/main.js",
+            "import answer from 'answer'; import { plain } from 'plain'; import { extra } from 'plain/extra.js'; `${answer} ${plain} ${extra}`",
+        );
+    assert_eq!(project.run("src/app/main.js").unwrap(), "\"42 index extra\"");
+}
+
+#[test]
+fn exports_conditions_select_the_import_target() {
+    let project = Project::new("conditions")
+        .file(
+            "node_modules/pkg/package.json",
+            r#"{ "name": "pkg", "exports": { "development": "./dev.js", "require": "./cjs.js", "import": "./esm.js" } }"#,
+        )
+        .file("node_modules/pkg/esm.js", "export default 'esm';")
+        .file("node_modules/pkg/dev.js", "export default 'dev';")
+        .file("node_modules/pkg/cjs.js", "module.exports = 'cjs';")
+        .file("main.js", "import v from 'pkg'; v");
+    assert_eq!(project.run("main.js").unwrap(), "\"esm\"");
+
+    let options = EvalOptions::new().module_loader(FsModuleLoader::new().condition("development"));
+    assert_eq!(project.run_with("main.js", &options).unwrap(), "\"dev\"");
+}
+
+#[test]
+fn node_is_a_default_condition() {
+    let project = Project::new("node-condition")
+        .file(
+            "node_modules/pkg/package.json",
+            r#"{ "name": "pkg", "exports": { "browser": "./browser.js", "node": "./node.js", "default": "./fallback.js" } }"#,
+        )
+        .file("node_modules/pkg/browser.js", "export default 'browser';")
+        .file("node_modules/pkg/node.js", "export default 'node';")
+        .file("node_modules/pkg/fallback.js", "export default 'fallback';")
+        .file("main.js", "import v from 'pkg'; v");
+    assert_eq!(project.run("main.js").unwrap(), "\"node\"");
+}
+
+#[test]
+fn null_condition_targets_exclude_the_path() {
+    let project = Project::new("null-condition")
+        .file(
+            "node_modules/pkg/package.json",
+            r#"{
+                "name": "pkg",
+                "exports": {
+                    ".": { "import": null, "default": "./index.js" },
+                    "./nested": { "node": { "import": null }, "default": "./index.js" },
+                    "./unmatched": { "node": { "browser": "./browser.js" }, "default": "./index.js" }
+                }
+            }"#,
+        )
+        .file("node_modules/pkg/index.js", "export default 'index';")
+        .file("node_modules/pkg/browser.js", "export default 'browser';")
+        .file("main.js", "import v from 'pkg'; v")
+        .file("nested.js", "import v from 'pkg/nested'; v")
+        .file("unmatched.js", "import v from 'pkg/unmatched'; v");
+    assert!(
+        project
+            .run("main.js")
+            .unwrap_err()
+            .message()
+            .contains("No \"exports\" main defined")
+    );
+    assert!(
+        project
+            .run("nested.js")
+            .unwrap_err()
+            .message()
+            .contains("Package subpath './nested' is not defined by \"exports\"")
+    );
+    assert_eq!(project.run("unmatched.js").unwrap(), "\"index\"");
+}
+
+#[test]
+fn exports_subpaths_and_patterns() {
+    let project = Project::new("subpaths")
+        .file(
+            "node_modules/@scope/kit/package.json",
+            r#"{
+                "name": "@scope/kit",
+                "exports": {
+                    ".": "./src/index.js",
+                    "./utils": { "default": "./src/utils.js" },
+                    "./features/*": "./src/features/*.js",
+                    "./features/private/*": null
+                }
+            }"#,
+        )
+        .file("node_modules/@scope/kit/src/index.js", "export default 'main';")
+        .file("node_modules/@scope/kit/src/utils.js", "export const util = 'utils';")
+        .file("node_modules/@scope/kit/src/features/a/b.js", "export const feature = 'a/b';")
+        .file(
+            "main.js",
+            "import main from '@scope/kit'; import { util } from '@scope/kit/utils'; import { feature } from '@scope/kit/features/a/b'; `${main} ${util} ${feature}`",
+        )
+        .file("hidden.js", "import x from '@scope/kit/features/private/x'; x")
+        .file("unexported.js", "import x from '@scope/kit/src/index.js'; x");
+    assert_eq!(project.run("main.js").unwrap(), "\"main utils a/b\"");
+
+    let err = project.run("hidden.js").unwrap_err();
+    assert!(
+        err.message()
+            .contains("Package subpath './features/private/x' is not defined by \"exports\""),
+        "{}",
+        err.message()
+    );
+    let err = project.run("unexported.js").unwrap_err();
+    assert!(
+        err.message()
+            .contains("Package subpath './src/index.js' is not defined by \"exports\""),
+        "{}",
+        err.message()
+    );
+}
+
+#[test]
+fn package_imports_and_self_reference() {
+    let project = Project::new("imports")
+        .file(
+            "package.json",
+            r##"{
+                "name": "my-app",
+                "exports": { "./greet": "./lib/greet.js" },
+                "imports": { "#config": "./config.js", "#dep": "dep", "#internal/*": "./internal/*.js" }
+            }"##,
+        )
+        .file("config.js", "export const config = 'config';")
+        .file("internal/x.js", "export const x = 'x';")
+        .file("lib/greet.js", "export const greet = 'hi';")
+        .file("node_modules/dep/index.js", "export const dep = 'dep';")
+        .file(
+            "src/main.js",
+            "import { config } from '#config'; import { dep } from '#dep'; import { x } from '#internal/x'; import { greet } from 'my-app/greet'; [config, dep, x, greet].join()",
+        )
+        .file("src/missing.js", "import m from '#missing'; m");
+    assert_eq!(project.run("src/main.js").unwrap(), "\"config,dep,x,hi\"");
+
+    let err = project.run("src/missing.js").unwrap_err();
+    assert!(
+        err.message().contains("Package import specifier \"#missing\" is not defined"),
+        "{}",
+        err.message()
+    );
+}
+
+#[test]
+fn missing_packages_report_the_importer() {
+    let project = Project::new("missing").file(
+        "main.js",
+        "let msg; try { await import('nope'); } catch (e) { msg = e.message; } msg",
+    );
+    let out = project.run("main.js").unwrap();
+    assert!(out.starts_with("\"Cannot find package 'nope' imported from "), "{out}");
+    assert!(out.contains("main.js"), "{out}");
+}
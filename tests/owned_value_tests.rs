@@ -0,0 +1,99 @@
+use javascript::{JsFunctionInfo, JsOwnedValue, Repl, evaluate_script_value};
+use num_bigint::BigInt;
+
+fn eval(script: &str) -> JsOwnedValue {
+    evaluate_script_value(script, false, None::<&std::path::Path>).unwrap()
+}
+
+#[test]
+fn primitives_convert_to_owned_values() {
+    assert_eq!(eval("undefined"), JsOwnedValue::Undefined);
+    assert_eq!(eval("null"), JsOwnedValue::Null);
+    assert_eq!(eval("1 + 2"), JsOwnedValue::Number(3.0));
+    assert_eq!(eval("'a' + 'b'"), JsOwnedValue::String("ab".into()));
+    assert_eq!(eval("3 > 2"), JsOwnedValue::Boolean(true));
+    assert_eq!(eval("2n ** 64n"), JsOwnedValue::BigInt(BigInt::from(1u128 << 64)));
+    assert_eq!(eval("Symbol('tag')"), JsOwnedValue::Symbol(Some("tag".into())));
+}
+
+#[test]
+fn objects_and_arrays_are_copied_recursively() {
+    let v = eval("({ name: 'cfg', ports: [80, 443], nested: { on: true } })");
+    assert_eq!(v.get("name").and_then(JsOwnedValue::as_str), Some("cfg"));
+    let ports = v.get("ports").and_then(JsOwnedValue::as_array).unwrap();
+    assert_eq!(ports, &[JsOwnedValue::Number(80.0), JsOwnedValue::Number(443.0)]);
+    assert_eq!(v.get("nested").and_then(|n| n.get("on")), Some(&JsOwnedValue::Boolean(true)));
+
+    let keys: Vec<_> = v.as_object().unwrap().keys().cloned().collect();
+    assert_eq!(keys, ["name", "ports", "nested"]);
+}
+
+#[test]
+fn functions_report_name_and_length() {
+    let v = eval("function add(a, b) { return a + b; } add");
+    assert_eq!(
+        v,
+        JsOwnedValue::Function(JsFunctionInfo {
+            name: "add".into(),
+            length: 2
+        })
+    );
+    assert_eq!(v.type_of(), "function");
+}
+
+#[test]
+fn cycles_are_a_conversion_error() {
+    let err = evaluate_script_value("const o = { a: 1 }; o.self = o; o", false, None::<&std::path::Path>).unwrap_err();
+    assert!(err.message().contains("Cannot convert a cyclic structure"), "{}", err.message());
+
+    // Shared references that do not loop are copied each time they appear.
+    let v = eval("const leaf = { n: 1 }; [leaf, { leaf }]");
+    let items = v.as_array().unwrap();
+    assert_eq!(items[0], items[1].get("leaf").cloned().unwrap());
+}
+
+#[test]
+fn sets_and_typed_arrays_are_copied() {
+    let v = eval("new Set([1, 'two', 1])");
+    assert_eq!(v.as_set(), Some(&[JsOwnedValue::Number(1.0), JsOwnedValue::from("two")][..]));
+    assert_eq!(
+        eval("new Int16Array([-1, 300])").as_array(),
+        Some(&[JsOwnedValue::Number(-1.0), JsOwnedValue::Number(300.0)][..])
+    );
+    assert_eq!(eval("new Uint8Array([1, 2])").as_bytes(), Some(&[1u8, 2][..]));
+
+    let mut repl = Repl::new();
+    repl.eval("function size(s) { return s instanceof Set ? s.size : -1; }").unwrap();
+    let set = JsOwnedValue::Set(vec![1.into(), 2.into()]);
+    assert_eq!(repl.call("size", (), &[set]).unwrap(), JsOwnedValue::Number(2.0));
+}
+
+#[test]
+fn weak_collections_are_a_conversion_error() {
+    for script in ["new WeakMap()", "new WeakSet()"] {
+        let err = evaluate_script_value(script, false, None::<&std::path::Path>).unwrap_err();
+        assert!(err.message().contains("TypeError"), "{script}: {}", err.message());
+    }
+}
+
+#[test]
+fn settled_top_level_promise_is_unwrapped() {
+    assert_eq!(eval("Promise.resolve(7)"), JsOwnedValue::Number(7.0));
+}
+
+#[test]
+fn repl_eval_value_persists_state() {
+    let mut repl = Repl::new();
+    assert_eq!(repl.eval_value("let xs = [1, 'two'];").unwrap(), JsOwnedValue::Undefined);
+    assert_eq!(
+        repl.eval_value("xs").unwrap(),
+        JsOwnedValue::Array(vec![JsOwnedValue::Number(1.0), JsOwnedValue::String("two".into())])
+    );
+}
+
+#[test]
+fn repl_eval_value_reports_errors() {
+    let mut repl = Repl::new();
+    let err = repl.eval_value("throw new TypeError('bad')").unwrap_err();
+    assert!(err.message().contains("bad"), "{}", err.message());
+}
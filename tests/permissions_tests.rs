@@ -0,0 +1,186 @@
+use javascript::{EvalOptions, Permissions, Repl, evaluate_script_with_options};
+use std::path::{Path, PathBuf};
+
+/// A fresh directory under the system temp dir, with `allowed/` and a
+/// sibling `allowed-not/` holding one file each.
+fn sandbox_dir(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("js_permissions_{}_{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    for dir in ["allowed", "allowed-not"] {
+        std::fs::create_dir_all(root.join(dir)).unwrap();
+        std::fs::write(root.join(dir).join("data.txt"), "secret").unwrap();
+    }
+    root
+}
+
+fn js_path(path: &Path) -> String {
+    serde_json::to_string(&path.to_string_lossy()).unwrap()
+}
+
+/// `name: message` of what `expr` throws, or `"ok"` if it does not.
+fn outcome(repl: &mut Repl, expr: &str) -> String {
+    repl.eval(format!("try {{ {expr}; 'ok' }} catch (e) {{ `${{e.name}}: ${{e.message}}` }}"))
+        .unwrap()
+}
+
+#[test]
+fn everything_is_allowed_by_default() {
+    let mut repl = Repl::new();
+    assert_eq!(repl.eval("typeof os.getpid()").unwrap(), "\"number\"");
+    assert_eq!(repl.eval("typeof std.getenv('PATH')").unwrap(), "\"string\"");
+    assert_eq!(repl.eval("typeof std.tmpfile()").unwrap(), "\"object\"");
+}
+
+#[test]
+fn denied_operations_throw_catchable_errors() {
+    let mut repl = Repl::new();
+    repl.set_permissions(Permissions::none());
+
+    assert_eq!(
+        outcome(&mut repl, "os.getcwd()"),
+        "\"PermissionDenied: Requires sys access to \\\"getcwd\\\"\""
+    );
+    assert_eq!(
+        outcome(&mut repl, "os.getpid()"),
+        "\"PermissionDenied: Requires sys access to \\\"getpid\\\"\""
+    );
+    assert_eq!(
+        outcome(&mut repl, "std.getenv('PATH')"),
+        "\"PermissionDenied: Requires env access to \\\"PATH\\\"\""
+    );
+    assert_eq!(outcome(&mut repl, "std.tmpfile()"), "\"PermissionDenied: Requires tmpfile access\"");
+    assert_eq!(
+        repl.eval("try { os.open('x.txt', 0) } catch (e) { e instanceof Error && !(e instanceof TypeError) }")
+            .unwrap(),
+        "true"
+    );
+
+    // What needs no permission keeps working.
+    assert_eq!(
+        repl.eval("os.path.join('a', 'b.js')").unwrap(),
+        serde_json::to_string(&Path::new("a").join("b.js")).unwrap()
+    );
+    assert_eq!(repl.eval("std.sprintf('%d', 42)").unwrap(), "\"42\"");
+}
+
+#[test]
+fn paths_are_allowed_by_prefix() {
+    let root = sandbox_dir("prefix");
+    let allowed = root.join("allowed");
+    let mut repl = Repl::new();
+    repl.set_permissions(Permissions::none().allow_read(&allowed));
+
+    let read = |path: &Path| format!("os.close(os.open({}, 0))", js_path(path));
+    let mut repl_outcome = |expr: String| outcome(&mut repl, &expr);
+    assert_eq!(repl_outcome(read(&allowed.join("data.txt"))), "\"ok\"");
+    assert!(repl_outcome(read(&root.join("allowed-not").join("data.txt"))).contains("Requires read access"));
+    assert!(repl_outcome(read(&allowed.join("..").join("allowed-not").join("data.txt"))).contains("Requires read access"));
+    // Reading is not writing.
+    let write = format!("os.open({}, 1)", js_path(&allowed.join("data.txt")));
+    assert!(repl_outcome(write).contains("Requires write access"));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn writes_stay_inside_the_allowed_directory() {
+    let root = sandbox_dir("write");
+    let allowed = root.join("allowed");
+    let mut repl = Repl::new();
+    repl.set_permissions(Permissions::none().allow_read(&allowed).allow_write(&allowed));
+
+    let target = js_path(&allowed.join("new.txt"));
+    let script = format!("var fd = os.open({target}, 578); os.write(fd, 'hello'); os.close(fd)");
+    assert_eq!(repl.eval(script).unwrap(), "0");
+    assert_eq!(std::fs::read_to_string(allowed.join("new.txt")).unwrap(), "hello");
+
+    let outside = format!("os.open({}, 577)", js_path(&root.join("escape.txt")));
+    assert!(outcome(&mut repl, &outside).contains("PermissionDenied: Requires write access"));
+    assert!(!root.join("escape.txt").exists());
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn env_access_is_limited_to_the_listed_names() {
+    let mut repl = Repl::new();
+    repl.set_permissions(Permissions::none().allow_env("PATH"));
+    assert_eq!(repl.eval("typeof std.getenv('PATH')").unwrap(), "\"string\"");
+    assert!(outcome(&mut repl, "std.getenv('HOME')").contains("Requires env access to \\\"HOME\\\""));
+}
+
+#[test]
+fn uncaught_denials_fail_the_evaluation() {
+    let options = EvalOptions::new().permissions(Permissions::none().allow_sys());
+    let run = |script: &str| evaluate_script_with_options(script, false, None::<&Path>, &options);
+
+    assert_eq!(run("typeof os.getpid()").unwrap(), "\"number\"");
+    let err = run("std.tmpfile()").unwrap_err();
+    assert!(err.message().contains("PermissionDenied"), "{}", err.message());
+    assert!(err.message().contains("Requires tmpfile access"), "{}", err.message());
+
+    let module = "import * as std from 'std'; std.getenv('PATH');";
+    let err = evaluate_script_with_options(module, true, None::<&Path>, &options).unwrap_err();
+    assert!(err.message().contains("Requires env access"), "{}", err.message());
+}
+
+#[test]
+fn new_realms_share_the_permissions() {
+    let mut repl = Repl::new();
+    repl.set_permissions(Permissions::none());
+    let out = repl
+        .eval("__createRealm__().global.eval(\"try { os.getpid(); 'escaped' } catch (e) { e.name }\")")
+        .unwrap();
+    assert_eq!(out, "\"PermissionDenied\"");
+}
+
+#[test]
+fn descriptors_belong_to_the_vm_that_opened_them() {
+    let root = sandbox_dir("descriptors");
+    let mut owner = Repl::new();
+    let open = format!("var fd = os.open({}, 0); fd", js_path(&root.join("allowed").join("data.txt")));
+    let fd = owner.eval(open).unwrap();
+
+    let mut other = Repl::new();
+    other.set_permissions(Permissions::none());
+    assert_eq!(other.eval(format!("os.read({fd}, 6)")).unwrap(), "\"\"");
+    assert_eq!(other.eval(format!("os.write({fd}, 'x')")).unwrap(), "-1");
+    assert_eq!(other.eval(format!("os.close({fd})")).unwrap(), "-1");
+
+    // Helper VMs, such as a new realm's, share the table.
+    let read = "__createRealm__().global.eval('os.read(' + fd + ', 6)')";
+    assert_eq!(owner.eval(read).unwrap(), "\"secret\"");
+    assert_eq!(owner.eval("os.close(fd)").unwrap(), "0");
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+#[cfg(unix)]
+fn symlinks_are_followed_before_parent_components() {
+    let root = sandbox_dir("symlink");
+    let allowed = root.join("allowed");
+    std::os::unix::fs::symlink(root.join("allowed-not"), allowed.join("link")).unwrap();
+    std::fs::write(root.join("data.txt"), "outside").unwrap();
+    let mut repl = Repl::new();
+    repl.set_permissions(Permissions::none().allow_read(&allowed));
+
+    // The link leads out of `allowed/`, and `..` after it goes on from
+    // where it leads, to the root, just as opening the path would.
+    for path in [
+        allowed.join("link").join("data.txt"),
+        allowed.join("link").join("..").join("data.txt"),
+    ] {
+        let script = format!("os.open({}, 0)", js_path(&path));
+        assert!(
+            outcome(&mut repl, &script).contains("PermissionDenied: Requires read access"),
+            "{}",
+            path.display()
+        );
+    }
+    let inside = js_path(&allowed.join("data.txt"));
+    let script = format!("var fd = os.open({inside}, 0); var text = os.read(fd, 16); os.close(fd); text");
+    assert_eq!(repl.eval(script).unwrap(), "\"secret\"");
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
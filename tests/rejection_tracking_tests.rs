@@ -0,0 +1,113 @@
+use javascript::{EvalOptions, PromiseRejection, RejectionEvent, Repl, evaluate_script_with_options};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+type Log = Rc<RefCell<Vec<(RejectionEvent, u64, String)>>>;
+
+fn tracked_repl() -> (Repl, Log) {
+    let log: Log = Rc::default();
+    let mut repl = Repl::new();
+    let sink = log.clone();
+    repl.set_rejection_tracker(move |rejection: PromiseRejection| {
+        sink.borrow_mut()
+            .push((rejection.event(), rejection.id(), rejection.error().message()));
+        Ok(())
+    });
+    (repl, log)
+}
+
+#[test]
+fn reports_rejections_left_without_a_handler() {
+    let (mut repl, log) = tracked_repl();
+    repl.eval("async function main() { throw new TypeError('bad config'); } main();")
+        .unwrap();
+    let log = log.borrow();
+    assert_eq!(log.len(), 1);
+    assert_eq!(log[0].0, RejectionEvent::Unhandled);
+    assert!(log[0].2.contains("bad config"), "{}", log[0].2);
+}
+
+#[test]
+fn handlers_attached_in_time_are_not_reported() {
+    let (mut repl, log) = tracked_repl();
+    repl.eval(
+        r#"
+        Promise.reject(1).catch(() => {});
+        const p = Promise.reject(2);
+        p.then(null, () => {});
+        (async () => { try { await Promise.reject(3); } catch {} })();
+        "#,
+    )
+    .unwrap();
+    assert!(log.borrow().is_empty(), "{:?}", log.borrow());
+}
+
+#[test]
+fn late_handlers_are_reported_as_handled() {
+    let (mut repl, log) = tracked_repl();
+    repl.eval("var late = Promise.reject(new Error('late'));").unwrap();
+    repl.eval("late.catch(() => {});").unwrap();
+    let log = log.borrow();
+    assert_eq!(log.len(), 2);
+    assert_eq!((log[0].0, log[1].0), (RejectionEvent::Unhandled, RejectionEvent::Handled));
+    assert_eq!(log[0].1, log[1].1);
+}
+
+#[test]
+fn rejections_from_timers_are_reported() {
+    let (mut repl, log) = tracked_repl();
+    repl.eval("setTimeout(() => { Promise.reject(new RangeError('tick')); }, 1);")
+        .unwrap();
+    let log = log.borrow();
+    assert_eq!(log.len(), 1);
+    assert!(log[0].2.contains("tick"), "{}", log[0].2);
+}
+
+#[test]
+fn scripts_can_observe_and_suppress_rejections() {
+    let (mut repl, log) = tracked_repl();
+    repl.eval(
+        r#"
+        var seen = [];
+        globalThis.onunhandledrejection = (event) => {
+            seen.push(event.type + ":" + event.reason);
+            if (event.reason === "quiet") event.preventDefault();
+        };
+        globalThis.onrejectionhandled = (event) => seen.push(event.type + ":" + event.reason);
+        var loud = Promise.reject("loud");
+        Promise.reject("quiet");
+        "#,
+    )
+    .unwrap();
+    repl.eval("loud.catch(() => {});").unwrap();
+    assert_eq!(
+        repl.eval("seen.join()").unwrap(),
+        "\"unhandledrejection:loud,unhandledrejection:quiet,rejectionhandled:loud\""
+    );
+    let events: Vec<RejectionEvent> = log.borrow().iter().map(|entry| entry.0).collect();
+    assert_eq!(events, [RejectionEvent::Unhandled, RejectionEvent::Handled]);
+}
+
+#[test]
+fn rejections_returned_to_the_host_are_handled() {
+    let (mut repl, log) = tracked_repl();
+    repl.eval("async function fail() { throw new Error('to host'); }").unwrap();
+    let err = repl.call_await("fail", (), &[]).unwrap_err();
+    assert!(err.message().contains("to host"), "{}", err.message());
+    assert!(log.borrow().is_empty(), "{:?}", log.borrow());
+}
+
+#[test]
+fn tracker_errors_fail_the_evaluation() {
+    let options = EvalOptions::new().rejection_tracker(|rejection| match rejection.event() {
+        RejectionEvent::Unhandled => Err(rejection.into_error()),
+        RejectionEvent::Handled => Ok(()),
+    });
+    let script = "(async () => { throw new Error('crashed'); })(); 1";
+    let err = evaluate_script_with_options(script, false, None::<&std::path::Path>, &options).unwrap_err();
+    assert!(err.message().contains("crashed"), "{}", err.message());
+
+    let handled = "(async () => { throw new Error('caught'); })().catch(() => {}); 1";
+    let out = evaluate_script_with_options(handled, false, None::<&std::path::Path>, &options).unwrap();
+    assert_eq!(out, "1");
+}
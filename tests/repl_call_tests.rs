@@ -0,0 +1,89 @@
+use indexmap::IndexMap;
+use javascript::{JSErrorKind, JsOwnedValue, Repl};
+
+fn repl_with(script: &str) -> Repl {
+    let mut repl = Repl::new();
+    repl.eval(script).unwrap();
+    repl
+}
+
+#[test]
+fn calls_global_functions_with_converted_arguments() {
+    let mut repl = repl_with("function describe(n, s, list) { return `${n + 1}:${s.toUpperCase()}:${list.length}`; }");
+    let out = repl.call("describe", (), &[41.into(), "ok".into(), vec![1, 2, 3].into()]).unwrap();
+    assert_eq!(out, JsOwnedValue::from("42:OK:3"));
+}
+
+#[test]
+fn passes_the_receiver_and_walks_dotted_paths() {
+    let mut repl = repl_with("var app = { handlers: { greet(greeting) { return `${greeting}, ${this.name}`; } } };");
+    let mut this = IndexMap::new();
+    this.insert("name".to_string(), JsOwnedValue::from("Ada"));
+    let out = repl
+        .call("app.handlers.greet", JsOwnedValue::Object(this), &["Hello".into()])
+        .unwrap();
+    assert_eq!(out.as_str(), Some("Hello, Ada"));
+}
+
+#[test]
+fn returns_structured_results() {
+    let mut repl = repl_with("const pair = (a, b) => ({ a, b, sum: a + b });");
+    let out = repl.call("pair", (), &[1.into(), 2.into()]).unwrap();
+    assert_eq!(out.get("sum"), Some(&JsOwnedValue::Number(3.0)));
+}
+
+#[test]
+fn reports_missing_targets_and_thrown_errors() {
+    let mut repl = repl_with("var notFn = 1; function boom() { throw new RangeError('bad input'); }");
+
+    let err = repl.call("nope", (), &[]).unwrap_err();
+    assert!(matches!(err.kind(), JSErrorKind::ReferenceError { .. }), "{err:?}");
+    let err = repl.call("notFn", (), &[]).unwrap_err();
+    assert!(err.message().contains("notFn is not a function"), "{}", err.message());
+    let err = repl.call("boom", (), &[]).unwrap_err();
+    assert!(err.message().contains("bad input"), "{}", err.message());
+
+    // The REPL stays usable after a failed call.
+    assert_eq!(repl.eval("1 + 1").unwrap(), "2");
+}
+
+#[test]
+fn call_await_drives_the_event_loop_until_the_promise_settles() {
+    let mut repl = repl_with(
+        r#"
+        async function double(x) { await null; return x * 2; }
+        function later(v) { return new Promise(resolve => setTimeout(() => resolve(v), 5)); }
+        async function fail() { await null; throw new Error('async failure'); }
+        "#,
+    );
+    assert_eq!(repl.call_await("double", (), &[21.into()]).unwrap(), JsOwnedValue::Number(42.0));
+    assert_eq!(repl.call_await("later", (), &["done".into()]).unwrap().as_str(), Some("done"));
+    let err = repl.call_await("fail", (), &[]).unwrap_err();
+    assert!(err.message().contains("async failure"), "{}", err.message());
+
+    // Without awaiting, the promise itself comes back and its callbacks still run.
+    repl.eval("var seen; function track() { return Promise.resolve(7).then(v => { seen = v; }); }")
+        .unwrap();
+    assert_eq!(repl.call("track", (), &[]).unwrap().type_of(), "object");
+    assert_eq!(repl.eval("seen").unwrap(), "7");
+}
+
+#[test]
+fn maps_dates_and_bytes_use_the_builtin_constructors() {
+    let mut repl = repl_with(
+        r#"
+        const Builtin = { Map, Date, Uint8Array };
+        Map = Date = Uint8Array = function () { throw new Error('patched'); };
+        function kinds(m, d, b) {
+            return [m instanceof Builtin.Map, d instanceof Builtin.Date, b instanceof Builtin.Uint8Array, m.get('k'), d.getTime(), b[1]].join();
+        }
+        "#,
+    );
+    let args = [
+        JsOwnedValue::Map(vec![("k".into(), 1.into())]),
+        JsOwnedValue::Date(5.0),
+        JsOwnedValue::Bytes(vec![7, 8]),
+    ];
+    let out = repl.call("kinds", (), &args).unwrap();
+    assert_eq!(out.as_str(), Some("true,true,true,1,5,8"));
+}
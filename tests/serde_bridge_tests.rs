@@ -0,0 +1,145 @@
+use javascript::{JsOwnedValue, Repl, from_js_value, to_js_value};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Server {
+    host: String,
+    port: u16,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Config {
+    name: String,
+    servers: Vec<Server>,
+    retries: Option<u32>,
+    mode: Mode,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum Mode {
+    Off,
+    Fixed(u32),
+    Range { min: u32, max: u32 },
+}
+
+fn sample_config() -> Config {
+    Config {
+        name: "edge".into(),
+        servers: vec![
+            Server {
+                host: "a".into(),
+                port: 80,
+                tags: vec!["primary".into()],
+            },
+            Server {
+                host: "b".into(),
+                port: 8080,
+                tags: vec![],
+            },
+        ],
+        retries: None,
+        mode: Mode::Range { min: 1, max: 3 },
+    }
+}
+
+#[test]
+fn structs_round_trip_through_scripts() {
+    let mut repl = Repl::new();
+    repl.eval("function touch(c) { c.servers[1].port += 1; c.retries = 2; c.mode = { Fixed: 7 }; return c; }")
+        .unwrap();
+    let config = to_js_value(&sample_config()).unwrap();
+    assert_eq!(config.get("retries"), Some(&JsOwnedValue::Null));
+
+    let out: Config = from_js_value(&repl.call("touch", (), &[config]).unwrap()).unwrap();
+    let mut expected = sample_config();
+    expected.servers[1].port = 8081;
+    expected.retries = Some(2);
+    expected.mode = Mode::Fixed(7);
+    assert_eq!(out, expected);
+
+    assert_eq!(repl.eval_as::<Mode, _>("'Off'").unwrap(), Mode::Off);
+    assert_eq!(
+        repl.eval_as::<Vec<Option<u8>>, _>("[1, undefined, null]").unwrap(),
+        vec![Some(1), None, None]
+    );
+}
+
+#[test]
+#[cfg(feature = "typed-arrays")]
+fn big_integers_maps_dates_and_bytes_keep_their_types() {
+    let mut repl = Repl::new();
+    repl.eval(
+        r#"function describe(v) {
+            return [typeof v.big, typeof v.small, v.lookup instanceof Map, v.lookup.get(2),
+                    v.bytes instanceof Uint8Array, v.bytes.length, v.bytes[2]].join();
+        }"#,
+    )
+    .unwrap();
+
+    #[derive(Serialize)]
+    struct Payload {
+        big: u64,
+        small: i64,
+        lookup: BTreeMap<u32, &'static str>,
+        #[serde(with = "bytes")]
+        bytes: Vec<u8>,
+    }
+    let payload = Payload {
+        big: u64::MAX,
+        small: -42,
+        lookup: BTreeMap::from([(1, "one"), (2, "two")]),
+        bytes: vec![7, 8, 9],
+    };
+    let out = repl.call("describe", (), &[to_js_value(&payload).unwrap()]).unwrap();
+    assert_eq!(out, JsOwnedValue::from("bigint,number,true,two,true,3,9"));
+
+    let big: u64 = repl.eval_as("18446744073709551615n").unwrap();
+    assert_eq!(big, u64::MAX);
+    let lookup: BTreeMap<u32, String> = repl.eval_as("new Map([[1, 'one'], [2, 'two']])").unwrap();
+    assert_eq!(lookup.get(&2).map(String::as_str), Some("two"));
+    let bytes: Vec<u8> = repl.eval_as("new Uint8Array([1, 2, 3])").unwrap();
+    assert_eq!(bytes, vec![1, 2, 3]);
+
+    let iso: String = repl.eval_as("new Date(Date.UTC(2024, 0, 2, 3, 4, 5))").unwrap();
+    assert_eq!(iso, "2024-01-02T03:04:05.000Z");
+    let ms: f64 = repl.eval_as("new Date(1000)").unwrap();
+    assert_eq!(ms, 1000.0);
+}
+
+#[test]
+fn errors_report_the_js_path() {
+    let mut repl = Repl::new();
+    let err = repl
+        .eval_as::<Config, _>(
+            "({ name: 'x', mode: 'Off', servers: [{ host: 'a', port: 1 }, { host: 'b', port: 2 }, { host: 'c', port: 70000 }] })",
+        )
+        .unwrap_err();
+    assert!(err.message().starts_with("TypeError: servers[2].port: "), "{}", err.message());
+
+    let value = repl.eval_value("({ config: { servers: [{ host: 'a' }] } })").unwrap();
+    #[derive(Debug, Deserialize)]
+    struct Outer {
+        #[allow(dead_code)]
+        config: Config,
+    }
+    let err = from_js_value::<Outer>(&value).unwrap_err();
+    assert_eq!(err.path(), "config.servers[0]");
+    assert!(err.message().contains("missing field `port`"), "{err}");
+
+    let value = repl.eval_value("({ 'odd key': [1, 'two'] })").unwrap();
+    let err = from_js_value::<BTreeMap<String, Vec<u8>>>(&value).unwrap_err();
+    assert_eq!(err.path(), "[\"odd key\"][1]");
+
+    let err = repl.eval_as::<String, _>("Symbol('s')").unwrap_err();
+    assert!(err.message().contains("symbol"), "{}", err.message());
+}
+
+#[cfg(feature = "typed-arrays")]
+mod bytes {
+    pub fn serialize<S: serde::Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(bytes)
+    }
+}
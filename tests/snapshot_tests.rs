@@ -0,0 +1,95 @@
+use javascript::{EvalOptions, Repl, Snapshot, evaluate_script, evaluate_script_with_options};
+
+fn run(script: &str, snapshot: &Snapshot) -> Result<String, javascript::JSError> {
+    let options = EvalOptions::new().snapshot(snapshot.clone());
+    evaluate_script_with_options(script, false, None::<&std::path::Path>, &options)
+}
+
+#[test]
+fn restored_vm_runs_like_a_fresh_one() {
+    let snapshot = Snapshot::new();
+    let scripts = [
+        "1 + 2 * 3",
+        "typeof globalThis.Promise + typeof Map + typeof Symbol.iterator",
+        "[3, 1, 2].sort().map(x => x * 2).join()",
+        "function* gen() { yield 1; yield* [2, 3]; } [...gen()].join()",
+        "class A { #x = 1; get x() { return this.#x; } } new A().x",
+        "var order = []; Promise.resolve(1).then(v => order.push(v)); (async () => { await null; order.push(2); })(); order",
+        "Symbol.for('app') === Symbol.for('app')",
+        "try { null.x } catch (e) { e instanceof TypeError }",
+        "JSON.stringify(Object.getOwnPropertyNames(Math).length > 10)",
+    ];
+    for script in scripts {
+        let expected = evaluate_script(script, false, None::<&std::path::Path>).unwrap();
+        assert_eq!(run(script, &snapshot).unwrap(), expected, "{script}");
+    }
+}
+
+#[test]
+fn prelude_globals_survive() {
+    let snapshot = Snapshot::with_prelude(
+        r#"
+        var counter = (() => { let n = 0; return () => ++n; })();
+        function twice(f) { return [f(), f()]; }
+        class Point { constructor(x, y) { this.x = x; this.y = y; } sum() { return this.x + this.y; } }
+        globalThis.Point = Point;
+        globalThis.appKey = Symbol.for('app');
+        var config = { retries: 3, tags: new Set(['a', 'b']) };
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(run("twice(counter).join()", &snapshot).unwrap(), "\"1,2\"");
+    // Every run starts from the captured state again.
+    assert_eq!(run("counter()", &snapshot).unwrap(), "1");
+    assert_eq!(run("new Point(2, 3).sum()", &snapshot).unwrap(), "5");
+    assert_eq!(run("appKey === Symbol.for('app')", &snapshot).unwrap(), "true");
+    assert_eq!(run("config.retries + config.tags.size", &snapshot).unwrap(), "5");
+}
+
+#[test]
+fn bytes_round_trip() {
+    let snapshot = Snapshot::with_prelude("function add(a, b) { return a + b; } var base = 40;").unwrap();
+    let bytes = snapshot.to_bytes();
+    assert_eq!(bytes, Snapshot::from_bytes(&bytes).unwrap().to_bytes());
+    assert_eq!(bytes, snapshot.to_bytes(), "capturing is deterministic");
+
+    let restored = Snapshot::from_bytes(&bytes).unwrap();
+    assert_eq!(run("add(base, 2)", &restored).unwrap(), "42");
+}
+
+#[test]
+fn damaged_or_foreign_bytes_are_rejected() {
+    let bytes = Snapshot::new().to_bytes();
+
+    let mut flipped = bytes.clone();
+    let middle = flipped.len() / 2;
+    flipped[middle] ^= 0xff;
+    let truncated = &bytes[..bytes.len() - 1];
+
+    for bad in [&flipped[..], truncated, b"not a snapshot", &[]] {
+        let err = Snapshot::from_bytes(bad).err().expect("rejected");
+        assert!(err.message().contains("Invalid snapshot"), "{}", err.message());
+    }
+}
+
+#[test]
+fn unfinished_preludes_cannot_be_captured() {
+    for prelude in [
+        "setTimeout(() => {}, 1000)",
+        "function* gen() { yield 1; } var it = gen(); it.next();",
+        "var pending = (async () => { await new Promise(() => {}); })();",
+    ] {
+        assert!(Snapshot::with_prelude(prelude).is_err(), "{prelude}");
+    }
+    assert!(Snapshot::with_prelude("throw new Error('boom')").is_err());
+}
+
+#[test]
+fn repl_starts_from_a_snapshot() {
+    let snapshot = Snapshot::with_prelude("var config = { retries: 3 }; function double(x) { return x * 2; }").unwrap();
+    let mut repl = Repl::from_snapshot(&snapshot);
+    assert_eq!(repl.eval("double(config.retries)").unwrap(), "6");
+    repl.eval("let seen = double(5);").unwrap();
+    assert_eq!(repl.eval("seen").unwrap(), "10");
+}
@@ -75,6 +75,7 @@ mod species_regression_tests {
     }
 
     #[test]
+    #[cfg(feature = "typed-arrays")]
     fn test_arraybuffer_and_typedarray_species_regressions() {
         let _guard = TEST_MUTEX.get_or_init(|| Mutex::new(())).lock().unwrap();
         let script = r#"
@@ -1,3 +1,5 @@
+#![cfg(feature = "temporal")]
+
 use javascript::*;
 
 #[test]
@@ -497,6 +499,7 @@ fn test_temporal_zoned_date_time_additional_getters_basic() {
 }
 
 #[test]
+#[cfg(feature = "intl")]
 fn test_temporal_to_locale_string_and_era_getters_basic() {
     let script = r#"
         const date = new Temporal.PlainDate(2000, 3, 6);
@@ -161,3 +161,93 @@ mod interval_tests {
         assert!(result.parse::<i32>().unwrap() > 0);
     }
 }
+
+#[cfg(test)]
+mod wall_clock_tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn evaluate_keeping_alive(script: &str) -> String {
+        let options = EvalOptions::new().wait_for_active_handles(true);
+        evaluate_script_with_options(script, false, None::<&std::path::Path>, &options).unwrap()
+    }
+
+    #[test]
+    fn timers_wait_for_their_deadline() {
+        let start = Instant::now();
+        let script = "new Promise((resolve) => setTimeout(() => resolve('late'), 30))";
+        let result = evaluate_keeping_alive(script);
+        assert_eq!(result, "\"late\"");
+        assert!(start.elapsed() >= Duration::from_millis(30), "{:?}", start.elapsed());
+    }
+
+    #[test]
+    fn timers_fire_in_deadline_order() {
+        // `c` is scheduled last with the shortest delay, but its deadline
+        // (about 15ms in) still falls before `b`'s at 40ms.
+        let script = r#"
+            let log = [];
+            new Promise((resolve) => {
+                setTimeout(() => {
+                    log.push("a");
+                    setTimeout(() => log.push("c"), 5);
+                }, 10);
+                setTimeout(() => {
+                    log.push("b");
+                    resolve(log.join());
+                }, 40);
+            })
+        "#;
+        let result = evaluate_keeping_alive(script);
+        assert_eq!(result, "\"a,c,b\"");
+    }
+
+    #[test]
+    #[cfg(feature = "os")]
+    fn unref_timers_do_not_keep_the_loop_alive() {
+        let mut repl = Repl::new();
+        let start = Instant::now();
+        repl.eval("var fired = false; os.unrefTimer(setTimeout(() => { fired = true; }, 30));")
+            .unwrap();
+        assert!(start.elapsed() < Duration::from_millis(30), "{:?}", start.elapsed());
+        assert!(!repl.has_pending_work());
+        assert!(repl.next_timer_deadline().is_some());
+
+        // Still fires once due, the next time the loop runs.
+        std::thread::sleep(Duration::from_millis(40));
+        repl.eval("0").unwrap();
+        assert_eq!(repl.eval("fired").unwrap(), "true");
+
+        repl.set_host_driven_event_loop(true);
+        repl.eval("var t = os.unrefTimer(setTimeout(() => {}, 1000));").unwrap();
+        assert!(!repl.has_pending_work());
+        repl.eval("os.refTimer(t)").unwrap();
+        assert!(repl.has_pending_work());
+    }
+
+    #[test]
+    #[cfg(feature = "os")]
+    fn unref_intervals_tick_while_other_timers_are_awaited() {
+        let script = r#"
+            let ticks = 0;
+            os.unrefTimer(setInterval(() => { ticks++; }, 5));
+            new Promise((resolve) => setTimeout(() => resolve(ticks), 40))
+        "#;
+        let result = evaluate_keeping_alive(script);
+        assert!(result.parse::<i32>().unwrap() > 0, "{result}");
+    }
+
+    #[test]
+    fn long_timers_do_not_keep_evaluation_waiting_by_default() {
+        let start = Instant::now();
+        let mut repl = Repl::new();
+        repl.eval("var ticks = 0; var id = setInterval(() => { ticks++; }, 1000);").unwrap();
+        assert!(start.elapsed() < Duration::from_millis(1000), "{:?}", start.elapsed());
+        assert_eq!(repl.eval("ticks").unwrap(), "0");
+
+        repl.set_short_timer_threshold_ms(50);
+        repl.eval("var done = false; setTimeout(() => { done = true; }, 30);").unwrap();
+        assert_eq!(repl.eval("done").unwrap(), "true");
+        assert!(start.elapsed() < Duration::from_millis(1000), "{:?}", start.elapsed());
+    }
+}
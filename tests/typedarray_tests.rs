@@ -20,6 +20,7 @@ fn test_js_arraybuffer_constructor_via_script() {
 }
 
 #[test]
+#[cfg(feature = "typed-arrays")]
 fn test_js_dataview_constructor_via_script() {
     // Test DataView constructor through JavaScript
     let script = r#"
@@ -34,6 +35,7 @@ fn test_js_dataview_constructor_via_script() {
 }
 
 #[test]
+#[cfg(feature = "typed-arrays")]
 fn test_js_typedarray_constructors_via_script() {
     // Test all TypedArray constructors through JavaScript
     let script = r#"
@@ -61,6 +63,7 @@ fn test_js_typedarray_constructors_via_script() {
 }
 
 #[test]
+#[cfg(feature = "typed-arrays")]
 fn test_js_typedarray_shared_buffer_via_script() {
     // Test that TypedArrays share the same underlying buffer
     let script = r#"
@@ -79,6 +82,7 @@ fn test_js_typedarray_shared_buffer_via_script() {
 }
 
 #[test]
+#[cfg(feature = "typed-arrays")]
 fn test_js_arraybuffer_dataview_integration_via_script() {
     // Test ArrayBuffer and DataView integration
     let script = r#"
@@ -95,6 +99,7 @@ fn test_js_arraybuffer_dataview_integration_via_script() {
 }
 
 #[test]
+#[cfg(feature = "typed-arrays")]
 fn test_js_typedarray_different_construction_patterns_via_script() {
     // Test different ways to construct TypedArrays
     let script = r#"
@@ -121,6 +126,7 @@ fn test_js_typedarray_different_construction_patterns_via_script() {
 }
 
 #[test]
+#[cfg(feature = "typed-arrays")]
 fn test_js_for_in_resizable_buffer_via_script() {
     // Regression test for for-in enumeration over TypedArrays backed by resizable ArrayBuffers
     let script = r#"
@@ -152,6 +158,7 @@ fn test_js_for_in_resizable_buffer_via_script() {
 }
 
 #[test]
+#[cfg(feature = "typed-arrays")]
 fn test_typedarray_destructuring_resizable_buffer_regression() {
     let path = std::path::Path::new("js-scripts/typedarray_destructuring_resizable_buffer_regression.js");
     let script = read_script_file(path).expect("failed to read regression script");